@@ -0,0 +1,87 @@
+//! cargo bench --bench build_vec
+//!
+//! Builds the trees of a million `Vec<u8>` leafs hashing the children of
+//! the nodes borrowed, against cloning them into `Algorithm::node`.
+
+extern crate merkle_light;
+extern crate sha2;
+
+use merkle_light::hash::Algorithm;
+use merkle_light::merkle::MerkleTree;
+use sha2::{Digest, Sha256};
+use std::hash::Hasher;
+use std::iter::FromIterator;
+use std::time::{Duration, Instant};
+
+const LEAFS: u64 = 1 << 20;
+const RUNS: usize = 5;
+
+/// SHA-256 over the hashes in vectors, which hashes the children borrowed
+/// if `BORROWS`, and clones them into `node` as of the default otherwise.
+#[derive(Clone, Default)]
+struct VecSha256<const BORROWS: bool>(Sha256);
+
+impl<const BORROWS: bool> Hasher for VecSha256<BORROWS> {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.update(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        unimplemented!()
+    }
+}
+
+impl<const BORROWS: bool> Algorithm<Vec<u8>> for VecSha256<BORROWS> {
+    #[inline]
+    fn hash(&mut self) -> Vec<u8> {
+        self.0.finalize_reset().to_vec()
+    }
+
+    #[inline]
+    fn node_ref(&mut self, left: &Vec<u8>, right: &Vec<u8>) -> Vec<u8> {
+        if !BORROWS {
+            return self.node(left.clone(), right.clone());
+        }
+        self.write(&[1u8]);
+        self.write(left);
+        self.write(right);
+        self.hash()
+    }
+}
+
+/// Returns the median time of building the tree, and its root.
+fn bench<const BORROWS: bool>(leafs: &[Vec<u8>]) -> (Duration, Vec<u8>) {
+    let mut root = Vec::new();
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let leafs = leafs.to_vec();
+            let start = Instant::now();
+            let mt: MerkleTree<Vec<u8>, VecSha256<BORROWS>> = MerkleTree::from_iter(leafs);
+            let time = start.elapsed();
+            root = mt.root();
+            time
+        })
+        .collect();
+    times.sort();
+    (times[RUNS / 2], root)
+}
+
+fn main() {
+    let leafs: Vec<Vec<u8>> = (0..LEAFS)
+        .map(|i| Sha256::digest(i.to_le_bytes()).to_vec())
+        .collect();
+
+    let (cloning, root) = bench::<false>(&leafs);
+    let (borrowing, same) = bench::<true>(&leafs);
+    assert_eq!(root, same);
+
+    println!("build {} Vec<u8> leafs, median of {} runs:", LEAFS, RUNS);
+    println!("  children cloned:   {:>10.1?}", cloning);
+    println!("  children borrowed: {:>10.1?}", borrowing);
+    println!(
+        "  speedup:           {:>10.2}x",
+        cloning.as_secs_f64() / borrowing.as_secs_f64()
+    );
+}
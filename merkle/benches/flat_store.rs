@@ -0,0 +1,138 @@
+//! cargo bench --bench flat_store
+//!
+//! Builds the trees of a million `Vec<u8>` leafs in `FlatStore`, against
+//! the default `VecStore` of a vector of the vectors, and generates their
+//! proofs, of the nodes read out of the stores and of the bytes borrowed.
+
+extern crate merkle_light;
+extern crate sha2;
+
+use merkle_light::hash::Algorithm;
+use merkle_light::merkle::MerkleTree;
+use merkle_light::store::{FlatStore, Store, VecStore};
+use sha2::{Digest, Sha256};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::hash::Hasher;
+use std::iter::FromIterator;
+use std::sync::atomic::{AtomicUsize, Ordering};
+use std::time::{Duration, Instant};
+
+const LEAFS: u64 = 1 << 20;
+const PROOFS: usize = 10_000;
+const RUNS: usize = 5;
+
+/// System allocator which counts the bytes allocated and not released.
+struct Counting;
+
+static LIVE: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        LIVE.fetch_add(layout.size(), Ordering::Relaxed);
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        LIVE.fetch_sub(layout.size(), Ordering::Relaxed);
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// SHA-256 over the hashes in vectors.
+#[derive(Clone, Default)]
+struct A(Sha256);
+
+impl Hasher for A {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.update(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        unimplemented!()
+    }
+}
+
+impl Algorithm<Vec<u8>> for A {
+    #[inline]
+    fn hash(&mut self) -> Vec<u8> {
+        self.0.finalize_reset().to_vec()
+    }
+
+    #[inline]
+    fn node_ref(&mut self, left: &Vec<u8>, right: &Vec<u8>) -> Vec<u8> {
+        self.write(&[1u8]);
+        self.write(left);
+        self.write(right);
+        self.hash()
+    }
+}
+
+/// Returns the median time of `f`.
+fn median<F: FnMut()>(mut f: F) -> Duration {
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    times[RUNS / 2]
+}
+
+/// Returns the median time of building the tree in the store `S`, the
+/// bytes the tree holds on the heap, and the tree.
+fn build<S: Store<Vec<u8>>>(leafs: &[Vec<u8>]) -> (Duration, usize, MerkleTree<Vec<u8>, A, S>) {
+    let time = median(|| {
+        let mt: MerkleTree<Vec<u8>, A, S> = MerkleTree::from_iter(leafs.iter().cloned());
+        assert_eq!(mt.leafs(), leafs.len());
+    });
+    let before = LIVE.load(Ordering::Relaxed);
+    let mt = MerkleTree::from_iter(leafs.iter().cloned());
+    (time, LIVE.load(Ordering::Relaxed) - before, mt)
+}
+
+fn main() {
+    let leafs: Vec<Vec<u8>> = (0..LEAFS)
+        .map(|i| Sha256::digest(i.to_le_bytes()).to_vec())
+        .collect();
+    let indices: Vec<usize> = (0..PROOFS)
+        .map(|k| k.wrapping_mul(2_654_435_761) % leafs.len())
+        .collect();
+
+    let (vec_time, vec_bytes, vec_tree) = build::<VecStore<Vec<u8>>>(&leafs);
+    let (flat_time, flat_bytes, flat_tree) = build::<FlatStore<Vec<u8>>>(&leafs);
+    assert_eq!(vec_tree.root(), flat_tree.root());
+
+    let vec_proofs = median(|| {
+        for &i in &indices {
+            assert_eq!(vec_tree.gen_proof(i).lemma().len(), 22);
+        }
+    });
+    let flat_proofs = median(|| {
+        for &i in &indices {
+            assert_eq!(flat_tree.gen_proof_bytes(i).lemma().len(), 22);
+        }
+    });
+
+    println!("tree of {} Vec<u8> leafs, median of {} runs:", LEAFS, RUNS);
+    println!(
+        "  VecStore:  build {:>8.1?}, heap {:>5} MiB, {} proofs {:>7.1?}",
+        vec_time,
+        vec_bytes >> 20,
+        PROOFS,
+        vec_proofs
+    );
+    println!(
+        "  FlatStore: build {:>8.1?}, heap {:>5} MiB, {} proofs {:>7.1?} (bytes borrowed)",
+        flat_time,
+        flat_bytes >> 20,
+        PROOFS,
+        flat_proofs
+    );
+}
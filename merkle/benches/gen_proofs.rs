@@ -0,0 +1,117 @@
+//! cargo bench --bench gen_proofs
+//!
+//! Generates ten thousand proofs of the tree of a million leafs in a batch,
+//! against generating them one by one, and the proofs of all the leafs of
+//! the tree of 65536 leafs at once, against generating them one by one.
+
+extern crate merkle_light;
+extern crate sha2;
+
+use merkle_light::hash::Algorithm;
+use merkle_light::merkle::MerkleTree;
+use sha2::{Digest, Sha256};
+use std::hash::Hasher;
+use std::iter::FromIterator;
+use std::time::{Duration, Instant};
+
+const LEAFS: u64 = 1 << 20;
+const PROOFS: usize = 10_000;
+const ALL_LEAFS: u64 = 1 << 16;
+const RUNS: usize = 5;
+
+/// SHA-256 over the hashes of 32 bytes.
+#[derive(Clone, Default)]
+struct A(Sha256);
+
+impl Hasher for A {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.update(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        unimplemented!()
+    }
+}
+
+impl Algorithm<[u8; 32]> for A {
+    #[inline]
+    fn hash(&mut self) -> [u8; 32] {
+        self.0.finalize_reset().into()
+    }
+}
+
+/// Returns the median time of `f`.
+fn bench<F: FnMut()>(mut f: F) -> Duration {
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    times[RUNS / 2]
+}
+
+fn main() {
+    let mt: MerkleTree<[u8; 32], A> =
+        MerkleTree::from_iter((0..LEAFS).map(|i| Sha256::digest(i.to_le_bytes()).into()));
+    // spread over the tree, out of order
+    let indices: Vec<usize> = (0..PROOFS)
+        .map(|k| k.wrapping_mul(2_654_435_761) % mt.leafs())
+        .collect();
+
+    let proofs = mt.gen_proofs(&indices);
+    assert!(indices
+        .iter()
+        .zip(&proofs)
+        .all(|(&i, p)| *p == mt.gen_proof(i)));
+
+    let naive = bench(|| {
+        let proofs: Vec<_> = indices.iter().map(|&i| mt.gen_proof(i)).collect();
+        assert_eq!(proofs.len(), PROOFS);
+    });
+    let batch = bench(|| assert_eq!(mt.gen_proofs(&indices).len(), PROOFS));
+
+    println!(
+        "{} proofs of {} leafs, median of {} runs:",
+        PROOFS, LEAFS, RUNS
+    );
+    println!("  gen_proof each: {:>10.1?}", naive);
+    println!("  gen_proofs:     {:>10.1?}", batch);
+    println!(
+        "  speedup:        {:>10.2}x",
+        naive.as_secs_f64() / batch.as_secs_f64()
+    );
+
+    let mt: MerkleTree<[u8; 32], A> =
+        MerkleTree::from_iter((0..ALL_LEAFS).map(|i| Sha256::digest(i.to_le_bytes()).into()));
+    let leafs = mt.leafs();
+    let naive: Vec<_> = (0..leafs).map(|i| mt.gen_proof(i)).collect();
+    assert!(mt.gen_all_proofs() == naive);
+
+    let naive = bench(|| {
+        let proofs: Vec<_> = (0..leafs).map(|i| mt.gen_proof(i)).collect();
+        assert_eq!(proofs.len(), leafs);
+    });
+    let all = bench(|| assert_eq!(mt.gen_all_proofs().len(), leafs));
+    let each = bench(|| {
+        let mut proofs = 0;
+        mt.for_each_proof(|_, _| proofs += 1);
+        assert_eq!(proofs, leafs);
+    });
+
+    println!(
+        "all proofs of {} leafs, median of {} runs:",
+        ALL_LEAFS, RUNS
+    );
+    println!("  gen_proof each: {:>10.1?}", naive);
+    println!("  gen_all_proofs: {:>10.1?}", all);
+    println!("  for_each_proof: {:>10.1?}", each);
+    println!(
+        "  speedup:        {:>10.2}x",
+        naive.as_secs_f64() / all.as_secs_f64()
+    );
+}
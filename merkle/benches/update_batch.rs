@@ -0,0 +1,101 @@
+//! cargo bench --bench update_batch
+//!
+//! Updates ten thousand leafs of the tree of a million leafs in a batch,
+//! against updating them one by one, of the leafs clustered in the runs of
+//! 64 and of the leafs spread over the tree.
+
+extern crate merkle_light;
+extern crate sha2;
+
+use merkle_light::hash::Algorithm;
+use merkle_light::merkle::MerkleTree;
+use sha2::{Digest, Sha256};
+use std::hash::Hasher;
+use std::iter::FromIterator;
+use std::time::{Duration, Instant};
+
+const LEAFS: u64 = 1 << 20;
+const UPDATES: usize = 10_000;
+const RUN: usize = 64;
+const RUNS: usize = 5;
+
+/// SHA-256 over the hashes of 32 bytes.
+#[derive(Clone, Default)]
+struct A(Sha256);
+
+impl Hasher for A {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.update(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        unimplemented!()
+    }
+}
+
+impl Algorithm<[u8; 32]> for A {
+    #[inline]
+    fn hash(&mut self) -> [u8; 32] {
+        self.0.finalize_reset().into()
+    }
+}
+
+/// Returns the median time of `f`.
+fn bench<F: FnMut()>(mut f: F) -> Duration {
+    let mut times: Vec<Duration> = (0..RUNS)
+        .map(|_| {
+            let start = Instant::now();
+            f();
+            start.elapsed()
+        })
+        .collect();
+    times.sort();
+    times[RUNS / 2]
+}
+
+fn main() {
+    let mt: MerkleTree<[u8; 32], A> =
+        MerkleTree::from_iter((0..LEAFS).map(|i| Sha256::digest(i.to_le_bytes()).into()));
+    let hash = |k: usize| Sha256::digest((k as u64 + LEAFS).to_le_bytes()).into();
+
+    // runs of the leafs next to each other, and the leafs out of order
+    let clustered: Vec<(usize, [u8; 32])> = (0..UPDATES)
+        .map(|k| {
+            let run = (k / RUN).wrapping_mul(2_654_435_761) % (mt.leafs() / RUN);
+            (run * RUN + k % RUN, hash(k))
+        })
+        .collect();
+    let spread: Vec<(usize, [u8; 32])> = (0..UPDATES)
+        .map(|k| (k.wrapping_mul(2_654_435_761) % mt.leafs(), hash(k)))
+        .collect();
+
+    for (name, updates) in [("clustered", &clustered), ("spread", &spread)] {
+        let mut each = mt.clone();
+        for &(i, h) in updates {
+            each.set_leaf(i, h).unwrap();
+        }
+        let mut batch = mt.clone();
+        batch.update_batch(updates).unwrap();
+        assert!(batch.as_slice() == each.as_slice());
+
+        let naive = bench(|| {
+            for &(i, h) in updates {
+                each.set_leaf(i, h).unwrap();
+            }
+        });
+        let batched = bench(|| batch.update_batch(updates).unwrap());
+
+        println!(
+            "{} updates {} of {} leafs, median of {} runs:",
+            UPDATES, name, LEAFS, RUNS
+        );
+        println!("  set_leaf each: {:>10.1?}", naive);
+        println!("  update_batch:  {:>10.1?}", batched);
+        println!(
+            "  speedup:       {:>10.2}x",
+            naive.as_secs_f64() / batched.as_secs_f64()
+        );
+    }
+}
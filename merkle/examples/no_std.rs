@@ -1,5 +0,0 @@
-// This is just for no_std testing
-#[allow(unused_imports)]
-use merkle_light;
-
-fn main() {}
@@ -0,0 +1,118 @@
+/* Light merkle tree, C ABI of the `ffi` feature. */
+
+#ifndef MERKLE_LIGHT_H
+#define MERKLE_LIGHT_H
+
+/* Generated with cbindgen, do not edit. */
+
+#include <stdarg.h>
+#include <stdbool.h>
+#include <stddef.h>
+#include <stdint.h>
+#include <stdlib.h>
+
+
+
+// Status the functions of the C ABI return.
+typedef enum MerkleStatus {
+  // Call succeeded, or the proof is valid.
+  MERKLE_STATUS_OK = 0,
+  // Pointer argument which must not be null is null.
+  MERKLE_STATUS_NULL_POINTER = 1,
+  // Arguments are out of their domain, such as too many leafs.
+  MERKLE_STATUS_INVALID_ARGUMENT = 2,
+  // Index is not less than the number of the leafs.
+  MERKLE_STATUS_INDEX_OUT_OF_BOUNDS = 3,
+  // Output buffer is shorter than the output, of which length is
+  // written to the length argument.
+  MERKLE_STATUS_BUFFER_TOO_SMALL = 4,
+  // Proof does not decode, or does not verify.
+  MERKLE_STATUS_INVALID_PROOF = 5,
+  // Call panicked, which is a bug of the crate.
+  MERKLE_STATUS_PANIC = 6,
+} MerkleStatus;
+
+// Merkle tree of the [`Sha256Algorithm`] owned by the C caller, opaque to
+// it, created with [`merkle_tree_new`] and released with
+// [`merkle_tree_free`].
+typedef struct MerkleTreeHandle MerkleTreeHandle;
+
+#ifdef __cplusplus
+extern "C" {
+#endif // __cplusplus
+
+// Builds the tree over the `count` records of `leaf_len` bytes each, laid
+// out one after another at `leaves`.
+//
+// Leaf of the record `x` is `SHA-256(0x00 || SHA-256(x))`, as of the tree
+// built with [`MerkleTree::from_data`] of the records as byte arrays.
+// `leaves` may be null if there are no bytes of the records.
+//
+// Returns the tree the caller owns and releases with [`merkle_tree_free`],
+// or null if `leaves` is null, the records are too many, or the call
+// panics.
+//
+// # Safety
+//
+// `leaves` must be valid for the reads of `leaf_len * count` bytes.
+struct MerkleTreeHandle *merkle_tree_new(const uint8_t *leaves, size_t leaf_len, size_t count);
+
+// Writes the root of the tree into the 32 bytes at `out`.
+//
+// # Safety
+//
+// `tree` must be null or a tree of [`merkle_tree_new`] not released yet,
+// and `out` must be null or valid for the writes of 32 bytes.
+enum MerkleStatus merkle_tree_root(const struct MerkleTreeHandle *tree, uint8_t *out);
+
+// Writes the inclusion proof of the leaf `index` into the buffer at `out`
+// of `*out_len` bytes, encoded as of [`Proof::to_bytes`], and sets
+// `*out_len` to the length of the proof.
+//
+// Returns [`MerkleStatus::BufferTooSmall`] if `out` is null or shorter than
+// the proof, having set `*out_len` to the length of the proof, so that the
+// caller calls again with the buffer of that length.
+//
+// # Safety
+//
+// `tree` must be null or a tree of [`merkle_tree_new`] not released yet,
+// `out_len` must be null or valid for the reads and writes of `size_t`,
+// and `out` must be null or valid for the writes of `*out_len` bytes.
+enum MerkleStatus merkle_tree_proof(const struct MerkleTreeHandle *tree,
+                                    size_t index,
+                                    uint8_t *out,
+                                    size_t *out_len);
+
+// Verifies the proof of `proof_len` bytes at `proof`, written by
+// [`merkle_tree_proof`], of the record of `leaf_len` bytes at `leaf`
+// against the trusted root of 32 bytes at `root`.
+//
+// Returns [`MerkleStatus::Ok`] if the proof is valid, and
+// [`MerkleStatus::InvalidProof`] if it does not decode or is not of the
+// record and the root. `leaf` and `proof` may be null if their lengths
+// are 0.
+//
+// # Safety
+//
+// `root` must be null or valid for the reads of 32 bytes, `leaf` for the
+// reads of `leaf_len` bytes and `proof` for the reads of `proof_len`
+// bytes.
+enum MerkleStatus merkle_proof_verify(const uint8_t *root,
+                                      const uint8_t *leaf,
+                                      size_t leaf_len,
+                                      const uint8_t *proof,
+                                      size_t proof_len);
+
+// Releases the tree of [`merkle_tree_new`]. Null is ignored.
+//
+// # Safety
+//
+// `tree` must be null or a tree of [`merkle_tree_new`] not released yet,
+// which is not used after.
+void merkle_tree_free(struct MerkleTreeHandle *tree);
+
+#ifdef __cplusplus
+}  // extern "C"
+#endif  // __cplusplus
+
+#endif  /* MERKLE_LIGHT_H */
@@ -0,0 +1,77 @@
+use core::hash::Hasher;
+use crate::fixed_hash::{FixedHash, Hash32};
+use crate::hash::Algorithm;
+
+/// BLAKE3 hash of the trees.
+pub type Blake3Hash = Hash32;
+
+/// Prefix of the leaf hashes, separating them from the interior nodes.
+const LEAF: u8 = 0x00;
+
+/// Prefix of the interior node hashes, separating them from the leafs.
+const INTERIOR: u8 = 0x01;
+
+/// BLAKE3 [`Algorithm`] of the trees over [`Blake3Hash`].
+///
+/// Semantics are frozen, so that the roots never change:
+///
+/// ```text
+/// hash()        = BLAKE3(everything written since reset)
+/// leaf(x)       = BLAKE3(0x00 || x)
+/// node(l, r)    = BLAKE3(0x01 || l || r)
+/// finish()      = first 8 bytes of hash() as big endian u64
+/// ```
+///
+/// Leafs and nodes are hashed with the distinct prefixes, so that no leaf
+/// can pass for a node. Root of the empty tree is `BLAKE3("")`.
+#[derive(Debug, Clone, Default)]
+pub struct Blake3Algorithm(::blake3::Hasher);
+
+impl Blake3Algorithm {
+    /// Creates new algorithm with nothing written.
+    pub fn new() -> Blake3Algorithm {
+        Blake3Algorithm(::blake3::Hasher::new())
+    }
+}
+
+impl Hasher for Blake3Algorithm {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.update(msg);
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        let h = self.0.finalize();
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&h.as_bytes()[..8]);
+        u64::from_be_bytes(bytes)
+    }
+}
+
+impl Algorithm<Blake3Hash> for Blake3Algorithm {
+    #[inline]
+    fn hash(&mut self) -> Blake3Hash {
+        FixedHash(self.0.finalize().into())
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.0 = ::blake3::Hasher::new();
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: Blake3Hash) -> Blake3Hash {
+        self.0.update(&[LEAF]);
+        self.0.update(leaf.as_ref());
+        self.hash()
+    }
+
+    #[inline]
+    fn node(&mut self, left: Blake3Hash, right: Blake3Hash) -> Blake3Hash {
+        self.0.update(&[INTERIOR]);
+        self.0.update(left.as_ref());
+        self.0.update(right.as_ref());
+        self.hash()
+    }
+}
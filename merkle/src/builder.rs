@@ -0,0 +1,153 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use crate::hash::Algorithm;
+use crate::merkle::{tree_layout, MerkleError, MerkleTree};
+
+/// Merkle tree builder which hashes the nodes as the leafs arrive.
+///
+/// Every node is hashed as soon as both of its children are there, so that
+/// only the right spine of the tree, which nodes wait for their right
+/// siblings, is left to hash by [`MerkleTreeBuilder::finish`]. The builder
+/// keeps all the nodes hashed so far, since the resulting tree holds them
+/// all, but it never needs the source of the leafs to be held at once.
+///
+/// ```
+/// extern crate merkle_light;
+///
+/// use merkle_light::builder::MerkleTreeBuilder;
+/// use merkle_light::hash::Algorithm;
+/// use merkle_light::merkle::MerkleTree;
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+///
+/// #[derive(Default)]
+/// struct Sip(DefaultHasher);
+///
+/// impl Hasher for Sip {
+///     fn write(&mut self, msg: &[u8]) {
+///         self.0.write(msg)
+///     }
+///
+///     fn finish(&self) -> u64 {
+///         self.0.finish()
+///     }
+/// }
+///
+/// impl Algorithm<[u8; 8]> for Sip {
+///     fn hash(&mut self) -> [u8; 8] {
+///         self.0.finish().to_le_bytes()
+///     }
+/// }
+///
+/// fn main() {
+///     let hashes = [[1; 8], [2; 8], [3; 8], [4; 8], [5; 8]];
+///
+///     let mut b: MerkleTreeBuilder<[u8; 8], Sip> = MerkleTreeBuilder::new();
+///     b.push(hashes[0]);
+///     b.push_chunk(&hashes[1..]);
+///     let t = b.finish();
+///
+///     assert_eq!(t.root(), MerkleTree::<[u8; 8], Sip>::new(hashes.iter().cloned()).root());
+/// }
+/// ```
+#[derive(Debug, Clone)]
+pub struct MerkleTreeBuilder<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> {
+    /// Nodes hashed so far, bottom-up level by level.
+    rows: Vec<Vec<T>>,
+    _a: PhantomData<A>,
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> Default for MerkleTreeBuilder<T, A> {
+    fn default() -> Self {
+        Self::new()
+    }
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> MerkleTreeBuilder<T, A> {
+    /// Creates new builder of an empty tree.
+    pub fn new() -> MerkleTreeBuilder<T, A> {
+        MerkleTreeBuilder {
+            rows: Vec::new(),
+            _a: PhantomData,
+        }
+    }
+
+    /// Returns number of the leafs pushed so far.
+    pub fn leafs(&self) -> usize {
+        self.rows.first().map_or(0, |row| row.len())
+    }
+
+    /// Appends a leaf hash, and hashes the nodes it completes.
+    pub fn push(&mut self, item: T) {
+        let mut a = A::default();
+        let leaf = a.leaf(item);
+        self.push_node(&mut a, 0, leaf);
+    }
+
+    /// Appends leaf hashes, and hashes the nodes they complete.
+    pub fn push_chunk(&mut self, items: &[T]) {
+        let mut a = A::default();
+        for item in items {
+            a.reset();
+            let leaf = a.leaf(item.clone());
+            self.push_node(&mut a, 0, leaf);
+        }
+    }
+
+    /// Hashes the right spine of the tree, and lays out the tree.
+    pub fn finish(self) -> MerkleTree<T, A> {
+        self.try_finish().unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Hashes the right spine of the tree, and lays out the tree.
+    ///
+    /// Fallible version of [`MerkleTreeBuilder::finish`].
+    pub fn try_finish(mut self) -> Result<MerkleTree<T, A>, MerkleError> {
+        let leafs = self.leafs();
+        let (size, height) = tree_layout(leafs)?;
+
+        let mut a = A::default();
+        let mut level = 0;
+        while level + 1 < height {
+            // if there is odd num of elements, fill in to the even
+            let row = &mut self.rows[level];
+            if row.len() & 1 == 1 {
+                let he = row[row.len() - 1].clone();
+                row.push(he);
+                self.push_pair(&mut a, level);
+            }
+            level += 1;
+        }
+
+        let mut data = Vec::with_capacity(size);
+        for row in self.rows {
+            data.extend(row);
+        }
+
+        MerkleTree::from_parts(data, leafs, height)
+    }
+
+    /// Appends the node to the level, and hashes the parent it completes.
+    fn push_node(&mut self, a: &mut A, level: usize, node: T) {
+        if self.rows.len() == level {
+            self.rows.push(Vec::new());
+        }
+
+        self.rows[level].push(node);
+        if self.rows[level].len() & 1 == 0 {
+            self.push_pair(a, level);
+        }
+    }
+
+    /// Hashes the last two nodes of the level into their parent.
+    fn push_pair(&mut self, a: &mut A, level: usize) {
+        let row = &self.rows[level];
+        let (left, right) = (row[row.len() - 2].clone(), row[row.len() - 1].clone());
+
+        a.reset();
+        let h = a.node(left, right);
+        self.push_node(a, level + 1, h);
+    }
+}
@@ -0,0 +1,293 @@
+use std::error;
+use std::fmt;
+use std::io::{self, Write};
+use crate::hash::Algorithm;
+use crate::merkle::{MerkleError, MerkleTree, PaddingStrategy, PairOrdering};
+use crate::store::Store;
+
+/// Magic bytes every encoded tree starts with.
+pub const MAGIC: [u8; 4] = *b"MKLT";
+
+/// Format version written by [`MerkleTree::encode`].
+pub const VERSION: u8 = 1;
+
+/// Format version written by [`MerkleTree::write_to`], which puts the root
+/// after the leafs.
+pub const STREAM_VERSION: u8 = 2;
+
+/// Bytes of leafs that [`MerkleTree::write_to`] writes and
+/// [`MerkleTree::read_from`] reads at a time. At least one leaf is moved.
+pub const CHUNK: usize = 64 * 1024;
+
+/// Error of decoding the tree with [`MerkleTree::decode`].
+#[derive(Debug)]
+pub enum DecodeError {
+    /// Stream failed or ended before the tree did.
+    Io(io::Error),
+    /// Stream does not start with [`MAGIC`].
+    Magic([u8; 4]),
+    /// Encoding version is not known to this crate.
+    Version(u8),
+    /// Padding strategy tag is not known to this crate.
+    Padding(u8),
+    /// Pair ordering tag is not known to this crate.
+    Ordering(u8),
+    /// Leaf hashes are not as long as the hash type.
+    DigestLength {
+        /// Length of the hash type.
+        expected: usize,
+        /// Length of the leaf hashes in the stream.
+        found: usize,
+    },
+    /// Leafs do not build a tree.
+    Tree(MerkleError),
+    /// Tree of the leafs is not of the root the stream ends with, such as
+    /// of the leafs changed, of the number of leafs other than written, or
+    /// of the other algorithm.
+    Root,
+}
+
+impl fmt::Display for DecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            DecodeError::Io(ref e) => write!(f, "failed to read encoded tree: {}", e),
+            DecodeError::Magic(magic) => {
+                write!(f, "{:02x?} is not the magic of encoded tree", magic)
+            }
+            DecodeError::Version(version) => {
+                write!(f, "unknown version {} of encoded tree", version)
+            }
+            DecodeError::Padding(tag) => {
+                write!(f, "unknown padding strategy {} of encoded tree", tag)
+            }
+            DecodeError::Ordering(tag) => {
+                write!(f, "unknown pair ordering {} of encoded tree", tag)
+            }
+            DecodeError::DigestLength { expected, found } => write!(
+                f,
+                "leaf hashes of {} bytes do not fit the hash type of {} bytes",
+                found, expected
+            ),
+            DecodeError::Tree(ref e) => write!(f, "{}", e),
+            DecodeError::Root => write!(f, "leafs do not hash to the root of encoded tree"),
+        }
+    }
+}
+
+impl error::Error for DecodeError {
+    fn source(&self) -> Option<&(dyn error::Error + 'static)> {
+        match *self {
+            DecodeError::Io(ref e) => Some(e),
+            DecodeError::Tree(ref e) => Some(e),
+            _ => None,
+        }
+    }
+}
+
+impl From<io::Error> for DecodeError {
+    fn from(e: io::Error) -> Self {
+        DecodeError::Io(e)
+    }
+}
+
+impl From<MerkleError> for DecodeError {
+    fn from(e: MerkleError) -> Self {
+        DecodeError::Tree(e)
+    }
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T>> MerkleTree<T, A, S> {
+    /// Writes the tree to `w` in the stable binary encoding, which does not
+    /// depend on the tree layout in memory:
+    ///
+    /// ```text
+    /// magic "MKLT" | version u8 | padding u8 | ordering u8 | digest length u32 | leafs u64 | leafs...
+    /// ```
+    ///
+    /// Numbers are little endian, and the leaf hashes follow one after
+    /// another. Padding strategy and pair ordering are tagged as they are
+    /// declared, from 0 of [`PaddingStrategy::HashWithSelf`] to 3 of
+    /// [`PaddingStrategy::DuplicateLast`], and 0 of
+    /// [`PairOrdering::Ordered`] and 1 of [`PairOrdering::Sorted`]. Nodes
+    /// above the leafs are rehashed on decoding, with the padding strategy
+    /// and pair ordering of the tree.
+    pub fn encode<W: Write>(&self, mut w: W) -> io::Result<()> {
+        let digest = self.root().as_ref().len();
+        w.write_all(&MAGIC)?;
+        w.write_all(&[VERSION])?;
+        w.write_all(&hashing_tags(self))?;
+        w.write_all(&(digest as u32).to_le_bytes())?;
+        w.write_all(&(self.leafs() as u64).to_le_bytes())?;
+
+        self.write_leafs(&mut w, digest)
+    }
+
+    /// Reads the tree written by [`MerkleTree::encode`] from `r`, and builds
+    /// the tree upon its leafs with the algorithm `alg`, such as the keyed
+    /// one the tree is built with.
+    pub fn decode<R: io::Read>(mut r: R, alg: A) -> Result<MerkleTree<T, A, S>, DecodeError>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        let mut magic = [0; 4];
+        r.read_exact(&mut magic)?;
+        if magic != MAGIC {
+            return Err(DecodeError::Magic(magic));
+        }
+
+        let mut version = [0; 1];
+        r.read_exact(&mut version)?;
+        if version[0] != VERSION {
+            return Err(DecodeError::Version(version[0]));
+        }
+        let (padding, ordering) = read_hashing(&mut r)?;
+
+        let mut digest = [0; 4];
+        r.read_exact(&mut digest)?;
+        let digest = u32::from_le_bytes(digest) as usize;
+        let expected = T::default().as_ref().len();
+        if digest != expected {
+            return Err(DecodeError::DigestLength {
+                expected,
+                found: digest,
+            });
+        }
+
+        let mut leafs = [0; 8];
+        r.read_exact(&mut leafs)?;
+        let leafs = u64::from_le_bytes(leafs);
+
+        Ok(Self::read_leafs(&mut r, leafs, alg, padding, ordering)??)
+    }
+
+    /// Writes the tree to `w` as [`MerkleTree::encode`] does, of the version
+    /// [`STREAM_VERSION`] and the root past the leafs:
+    ///
+    /// ```text
+    /// magic "MKLT" | version u8 | padding u8 | ordering u8 | digest length u32 | leafs u64 | leafs... | root
+    /// ```
+    ///
+    /// Leafs are written in the chunks of [`CHUNK`] bytes, so that the
+    /// memory other than the tree is of one chunk, however large the tree,
+    /// rather than of the whole encoding as of serde.
+    pub fn write_to<W: Write>(&self, w: &mut W) -> io::Result<()> {
+        let root = self.root();
+        let digest = root.as_ref().len();
+        let mut w = io::BufWriter::with_capacity(CHUNK, w);
+        w.write_all(&MAGIC)?;
+        w.write_all(&[STREAM_VERSION])?;
+        w.write_all(&hashing_tags(self))?;
+        w.write_all(&(digest as u32).to_le_bytes())?;
+        w.write_all(&(self.leafs() as u64).to_le_bytes())?;
+
+        self.write_leafs(&mut w, digest)?;
+        w.write_all(root.as_ref())?;
+        w.flush()
+    }
+
+    /// Reads the tree written by [`MerkleTree::write_to`] from `r`, in the
+    /// chunks of [`CHUNK`] bytes, and builds the tree upon its leafs with
+    /// the algorithm `alg`, such as the keyed one the tree is built with.
+    ///
+    /// Fails with [`DecodeError::Io`] of [`io::ErrorKind::UnexpectedEof`]
+    /// if the stream ends before the root does, and with
+    /// [`DecodeError::Root`] unless the tree is of the root, such as of the
+    /// number of leafs of the header other than of the leafs written. Bytes
+    /// past the root are not read.
+    pub fn read_from<R: io::Read>(r: &mut R, alg: A) -> Result<MerkleTree<T, A, S>, DecodeError>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        let mut magic = [0; 5];
+        r.read_exact(&mut magic)?;
+        let version = magic[4];
+        let magic = [magic[0], magic[1], magic[2], magic[3]];
+        if magic != MAGIC {
+            return Err(DecodeError::Magic(magic));
+        }
+        if version != STREAM_VERSION {
+            return Err(DecodeError::Version(version));
+        }
+        let (padding, ordering) = read_hashing(r)?;
+
+        let mut header = [0; 12];
+        r.read_exact(&mut header)?;
+        let digest = u32::from_le_bytes([header[0], header[1], header[2], header[3]]) as usize;
+        let expected = T::default().as_ref().len();
+        if digest != expected {
+            return Err(DecodeError::DigestLength {
+                expected,
+                found: digest,
+            });
+        }
+        let mut leafs = [0; 8];
+        leafs.copy_from_slice(&header[4..]);
+        let mut leafs = u64::from_le_bytes(leafs);
+
+        // leafs count is not trusted to allocate the store for
+        let mut data = S::new(0);
+        let per_chunk = (CHUNK / digest.max(1)).max(1) as u64;
+        let mut chunk = vec![0; CHUNK.max(digest)];
+        while leafs > 0 {
+            let n = leafs.min(per_chunk);
+            let bytes = &mut chunk[..n as usize * digest];
+            r.read_exact(bytes)?;
+            for k in 0..n as usize {
+                let at = k * digest;
+                let mut leaf = T::default();
+                leaf.as_mut().copy_from_slice(&bytes[at..at + digest]);
+                data.push(leaf);
+            }
+            leafs -= n;
+        }
+
+        let mut root = T::default();
+        r.read_exact(root.as_mut())?;
+        let mt = Self::from_leafs_with(data, alg, padding, ordering)?;
+        match mt.root() == root {
+            true => Ok(mt),
+            false => Err(DecodeError::Root),
+        }
+    }
+}
+
+/// Returns the tags of the padding strategy and pair ordering of `mt` in
+/// the header.
+fn hashing_tags<T, A, S>(mt: &MerkleTree<T, A, S>) -> [u8; 2]
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T>,
+{
+    let padding = match mt.padding_strategy() {
+        PaddingStrategy::HashWithSelf => 0,
+        PaddingStrategy::PromoteOdd => 1,
+        PaddingStrategy::EmptyHashPerLevel => 2,
+        PaddingStrategy::DuplicateLast => 3,
+    };
+    let ordering = match mt.pair_ordering() {
+        PairOrdering::Ordered => 0,
+        PairOrdering::Sorted => 1,
+    };
+    [padding, ordering]
+}
+
+/// Reads the padding strategy and pair ordering tagged in the header from
+/// `r`.
+fn read_hashing<R: io::Read>(r: &mut R) -> Result<(PaddingStrategy, PairOrdering), DecodeError> {
+    let mut tags = [0; 2];
+    r.read_exact(&mut tags)?;
+    let padding = match tags[0] {
+        0 => PaddingStrategy::HashWithSelf,
+        1 => PaddingStrategy::PromoteOdd,
+        2 => PaddingStrategy::EmptyHashPerLevel,
+        3 => PaddingStrategy::DuplicateLast,
+        tag => return Err(DecodeError::Padding(tag)),
+    };
+    let ordering = match tags[1] {
+        0 => PairOrdering::Ordered,
+        1 => PairOrdering::Sorted,
+        tag => return Err(DecodeError::Ordering(tag)),
+    };
+    Ok((padding, ordering))
+}
@@ -0,0 +1,167 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use crate::hash::Algorithm;
+use crate::merkle::{Levels, MerkleError};
+use crate::proof::{Lemma, Path, Proof};
+
+/// Merkle tree without the leaf level, as returned by
+/// [`crate::merkle::MerkleTree::compact`], for the leafs which are kept
+/// elsewhere anyway.
+///
+/// Leaf level holds about half of the tree nodes, so that the compact tree
+/// takes about half of the memory of the full one: `leafs - 1` nodes, plus
+/// the duplicates filling up odd levels, instead of `2 * leafs - 1`. Proofs
+/// are generated from the leafs supplied by the caller, which are checked
+/// to hash up to the parents kept in the tree.
+///
+/// Tree keeps the algorithm of the full tree, such as its key, which hashes
+/// the leafs supplied up to the parents.
+#[derive(Debug, Clone)]
+pub struct CompactTree<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> {
+    /// Nodes of the levels above the leafs, in memory layout.
+    data: Vec<T>,
+    leafs: usize,
+    height: usize,
+    root: T,
+    alg: A,
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> PartialEq for CompactTree<T, A> {
+    fn eq(&self, other: &Self) -> bool {
+        self.leafs == other.leafs
+            && self.height == other.height
+            && self.root == other.root
+            && self.data == other.data
+    }
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> Eq for CompactTree<T, A> {}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> CompactTree<T, A> {
+    pub(crate) fn new(
+        data: Vec<T>,
+        leafs: usize,
+        height: usize,
+        root: T,
+        alg: A,
+    ) -> CompactTree<T, A> {
+        CompactTree {
+            data,
+            leafs,
+            height,
+            root,
+            alg,
+        }
+    }
+
+    /// Returns merkle root of the full tree.
+    pub fn root(&self) -> T {
+        self.root.clone()
+    }
+
+    /// Returns number of leafs of the full tree.
+    pub fn leafs(&self) -> usize {
+        self.leafs
+    }
+
+    /// Returns height of the full tree.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns number of the nodes kept, above the leaf level.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if no nodes are kept, which is the case for the trees
+    /// over a single leaf or none.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`, given the leaf
+    /// hash `leaf` and the hash `sibling` of the leaf it is paired with.
+    ///
+    /// Sibling of the last leaf of an odd leaf level is the leaf itself.
+    /// Sibling is not used for the tree over a single leaf, which root is
+    /// the leaf.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds of the tree leafs, or if the leafs do
+    /// not hash up to the parent kept in the tree.
+    pub fn gen_proof_with_leaf(&self, i: usize, leaf: T, sibling: T) -> Proof<T>
+    where
+        A: Clone,
+    {
+        self.try_gen_proof_with_leaf(i, leaf, sibling)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`, given the leaf
+    /// and its sibling.
+    ///
+    /// Fallible version of [`CompactTree::gen_proof_with_leaf`]. Leafs are
+    /// hashed with the clone of the algorithm of the tree.
+    pub fn try_gen_proof_with_leaf(
+        &self,
+        i: usize,
+        leaf: T,
+        sibling: T,
+    ) -> Result<Proof<T>, MerkleError>
+    where
+        A: Clone,
+    {
+        // i in [0 .. self.leafs)
+        if i >= self.leafs {
+            return Err(MerkleError::IndexOutOfBounds {
+                index: i,
+                leafs: self.leafs,
+            });
+        }
+
+        let mut lemma = Lemma::with_capacity(self.height + 1); // path + root
+        let mut path = Path::with_capacity(self.height - 1); // path - 1
+
+        // tree over a single leaf has no level above it
+        if self.height == 1 {
+            if leaf != self.root {
+                return Err(MerkleError::LeafMismatch { index: i });
+            }
+            lemma.push(leaf);
+            lemma.push(self.root());
+            return Ok(Proof::new(lemma, path));
+        }
+
+        let mut a = self.alg.clone();
+        a.reset();
+        let h = match i & 1 {
+            0 => a.node(leaf.clone(), sibling.clone()),
+            _ => a.node(sibling.clone(), leaf.clone()),
+        };
+        if h != self.data[i >> 1] {
+            return Err(MerkleError::LeafMismatch { index: i });
+        }
+
+        lemma.push(leaf);
+        lemma.push(sibling);
+        path.push(i & 1 == 0);
+
+        // kept nodes start at the first level above the leafs
+        let levels = Levels::new(self.leafs, self.height).skip(1);
+        let split = self.leafs + (self.leafs & 1);
+
+        let mut j = i >> 1;
+        for (base, _) in levels.take(self.height - 2) {
+            lemma.push(self.data[base - split + (j ^ 1)].clone());
+            path.push(j & 1 == 0);
+            j >>= 1;
+        }
+
+        // root is final
+        lemma.push(self.root());
+        Ok(Proof::new(lemma, path))
+    }
+}
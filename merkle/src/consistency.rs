@@ -0,0 +1,133 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use crate::hash::Algorithm;
+use crate::merkle::{tree_layout, Levels};
+
+/// Proof that the tree over the first `old_size` leafs is a prefix of the
+/// tree over `new_size` leafs, as generated by
+/// [`crate::merkle::MerkleTree::gen_consistency_proof`] on the newer tree.
+///
+/// Both roots are hashed up from the nodes of the proof. Nodes of the old
+/// tree which cover a power of two aligned run of leafs are the same in the
+/// new tree, so the proof starts with the node over the last such run of
+/// the old tree, unless the old tree is a whole power of two and that node
+/// is its root. Then follows at most one node per level, bottom-up: the
+/// left sibling shared by both trees, or the right sibling which only the
+/// new tree has.
+///
+/// Trees fill up odd levels with the duplicate of the last node rather
+/// than splitting at the largest power of two, so that the proofs and roots
+/// match RFC 6962 only for the trees over a power of two leafs. See
+/// `crate::ct` of the `sha256` feature for the logs of RFC 6962.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ConsistencyProof<T: Eq + Clone + AsRef<[u8]>> {
+    nodes: Vec<T>,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> ConsistencyProof<T> {
+    pub(crate) fn new(nodes: Vec<T>) -> ConsistencyProof<T> {
+        ConsistencyProof { nodes }
+    }
+
+    /// Returns the nodes of the proof, bottom-up.
+    pub fn nodes(&self) -> &[T] {
+        &self.nodes
+    }
+
+    /// Verifies that the tree of root `old_root` over `old_size` leafs is a
+    /// prefix of the tree of root `new_root` over `new_size` leafs.
+    ///
+    /// Proof of the old tree which is the new one is empty, and validates if
+    /// the roots are equal. Empty tree is a prefix of any tree, its proof is
+    /// empty and validates if `old_root` is the root of the empty tree. Tree
+    /// is never a prefix of a smaller one.
+    pub fn validate<A: Algorithm<T>>(
+        &self,
+        old_root: &T,
+        old_size: usize,
+        new_root: &T,
+        new_size: usize,
+    ) -> bool {
+        self.validate_with(&mut A::default(), old_root, old_size, new_root, new_size)
+    }
+
+    /// Verifies that the tree of root `old_root` is a prefix of the tree of
+    /// root `new_root` with the algorithm `a`, such as the keyed one the
+    /// trees are built with, as [`ConsistencyProof::validate`].
+    pub fn validate_with<A: Algorithm<T>>(
+        &self,
+        a: &mut A,
+        old_root: &T,
+        old_size: usize,
+        new_root: &T,
+        new_size: usize,
+    ) -> bool {
+        if old_size > new_size {
+            return false;
+        }
+        if old_size == new_size {
+            return self.nodes.is_empty() && old_root == new_root;
+        }
+        if old_size == 0 {
+            a.reset();
+            return self.nodes.is_empty() && *old_root == a.hash();
+        }
+        let height = match tree_layout(new_size) {
+            Ok((_, height)) => height,
+            Err(_) => return false,
+        };
+
+        let mut nodes = self.nodes.iter().cloned();
+        let start = old_size.trailing_zeros() as usize;
+        let first = match old_size.is_power_of_two() {
+            true => Some(old_root.clone()),
+            false => nodes.next(),
+        };
+        let (mut old, mut new) = match first {
+            Some(h) => (h.clone(), h),
+            None => return false,
+        };
+
+        let levels = Levels::new(new_size, height).enumerate().skip(start);
+        for (level, (_, width)) in levels {
+            if width == 1 {
+                break;
+            }
+
+            // last node of the old tree, and of that run of the new one
+            let j = (old_size - 1) >> level;
+            if j & 1 == 1 {
+                let left = match nodes.next() {
+                    Some(left) => left,
+                    None => return false,
+                };
+                a.reset();
+                old = a.node(left.clone(), old);
+                a.reset();
+                new = a.node(left, new);
+                continue;
+            }
+
+            // old tree ends at the level of a single node, its root
+            if j > 0 {
+                a.reset();
+                old = a.node(old.clone(), old);
+            }
+            let right = match j + 1 < width {
+                true => nodes.next(),
+                // last node of an odd level is paired with its duplicate
+                false => Some(new.clone()),
+            };
+            match right {
+                Some(right) => {
+                    a.reset();
+                    new = a.node(new, right);
+                }
+                None => return false,
+            }
+        }
+
+        nodes.next().is_none() && old == *old_root && new == *new_root
+    }
+}
@@ -0,0 +1,358 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::hash::Hasher;
+use sha2::{Digest, Sha256};
+use crate::fixed_hash::FixedHash;
+use crate::hash::Algorithm;
+use crate::merkle::{MerkleError, MerkleTree, PaddingStrategy};
+use crate::proof::Proof;
+use crate::sha256::Sha256Hash;
+
+/// Prefix of the leaf hashes.
+const LEAF: u8 = 0x00;
+
+/// Prefix of the interior node hashes.
+const INTERIOR: u8 = 0x01;
+
+/// [`Algorithm`] of the Merkle Tree Hash of RFC 6962 over the leaf hashes,
+/// as the trees of [`CtTree`] hash.
+///
+/// Semantics are frozen, so that the roots never change:
+///
+/// ```text
+/// hash()        = SHA-256(everything written since reset)
+/// leaf(x)       = x
+/// node(l, r)    = SHA-256(0x01 || l || r)
+/// finish()      = first 8 bytes of hash() as big endian u64
+/// ```
+///
+/// Leafs of the tree are the leaf hashes `SHA-256(0x00 || d)` of the
+/// entries `d` of any length, see [`CtAlgorithm::leaf_hash`], rather than
+/// the leaf hashes of the hashes of the entries, as of
+/// [`crate::sha256::Sha256Algorithm`]. Root of the empty tree is
+/// `SHA-256("")`.
+#[derive(Debug, Clone, Default)]
+pub struct CtAlgorithm(Sha256);
+
+impl CtAlgorithm {
+    /// Creates new algorithm with nothing written.
+    pub fn new() -> CtAlgorithm {
+        CtAlgorithm(Sha256::new())
+    }
+
+    /// Returns the leaf hash `SHA-256(0x00 || entry)` of the log entry.
+    pub fn leaf_hash(entry: &[u8]) -> Sha256Hash {
+        let mut h = Sha256::new();
+        h.update([LEAF]);
+        h.update(entry);
+        FixedHash(h.finalize().into())
+    }
+}
+
+impl Hasher for CtAlgorithm {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.update(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        let h = self.0.clone().finalize();
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&h[..8]);
+        u64::from_be_bytes(bytes)
+    }
+}
+
+impl Algorithm<Sha256Hash> for CtAlgorithm {
+    #[inline]
+    fn hash(&mut self) -> Sha256Hash {
+        FixedHash(self.0.clone().finalize().into())
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.0 = Sha256::new();
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: Sha256Hash) -> Sha256Hash {
+        leaf
+    }
+
+    #[inline]
+    fn node(&mut self, left: Sha256Hash, right: Sha256Hash) -> Sha256Hash {
+        self.0.update([INTERIOR]);
+        self.0.update(left);
+        self.0.update(right);
+        self.hash()
+    }
+}
+
+/// Returns the node of `left` and `right`.
+fn node(left: &Sha256Hash, right: &Sha256Hash) -> Sha256Hash {
+    CtAlgorithm::new().node(*left, *right)
+}
+
+/// Returns the Merkle Tree Hash `MTH(D[n])` of RFC 6962 of the entries.
+pub fn mth<E: AsRef<[u8]>, I: IntoIterator<Item = E>>(entries: I) -> Sha256Hash {
+    CtTree::from_entries(entries).root()
+}
+
+/// Merkle tree of a Certificate Transparency log, as of RFC 6962: the tree
+/// over the leaf hashes of the entries, which splits at the largest power
+/// of two less than the number of the leafs, with the audit paths and the
+/// consistency proofs as the RFC defines them.
+///
+/// Tree is the [`MerkleTree`] of the [`CtAlgorithm`] of the
+/// [`PaddingStrategy::PromoteOdd`] padding, so that its nodes are laid out
+/// and stored as of any other tree, and the inclusion proofs it generates
+/// validate as any other proof too.
+///
+/// ```
+/// use merkle_light::ct::{verify_audit_path, verify_consistency, CtAlgorithm, CtTree};
+///
+/// let mut log = CtTree::new();
+/// for entry in [&b"a"[..], b"b", b"c"] {
+///     log.push(entry);
+/// }
+/// let old = log.root();
+/// log.push(b"d");
+///
+/// let path = log.audit_path(2).unwrap();
+/// let leaf = CtAlgorithm::leaf_hash(b"c");
+/// assert!(verify_audit_path(&leaf, 2, 4, &path, &log.root()));
+///
+/// let proof = log.consistency_proof(3).unwrap();
+/// assert!(verify_consistency(3, 4, &old, &log.root(), &proof));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct CtTree {
+    tree: MerkleTree<Sha256Hash, CtAlgorithm>,
+}
+
+impl CtTree {
+    /// Creates new empty log.
+    pub fn new() -> CtTree {
+        CtTree {
+            tree: MerkleTree::new_padded(Vec::new(), PaddingStrategy::PromoteOdd),
+        }
+    }
+
+    /// Creates new log of the entries, in order.
+    pub fn from_entries<E: AsRef<[u8]>, I: IntoIterator<Item = E>>(entries: I) -> CtTree {
+        let leafs = entries
+            .into_iter()
+            .map(|e| CtAlgorithm::leaf_hash(e.as_ref()));
+        CtTree {
+            tree: MerkleTree::new_padded(leafs, PaddingStrategy::PromoteOdd),
+        }
+    }
+
+    /// Appends the entry to the log.
+    pub fn push(&mut self, entry: &[u8]) {
+        self.tree.push(CtAlgorithm::leaf_hash(entry))
+    }
+
+    /// Returns the number of the entries of the log.
+    pub fn size(&self) -> usize {
+        self.tree.leafs()
+    }
+
+    /// Returns the Merkle Tree Hash of the log.
+    pub fn root(&self) -> Sha256Hash {
+        self.tree.root()
+    }
+
+    /// Returns the tree of the log.
+    pub fn tree(&self) -> &MerkleTree<Sha256Hash, CtAlgorithm> {
+        &self.tree
+    }
+
+    /// Returns the audit path `PATH(m, D[n])` of the entry `m`, bottom-up.
+    ///
+    /// Fails with [`MerkleError::IndexOutOfBounds`] unless `m < size()`.
+    pub fn audit_path(&self, m: usize) -> Result<Vec<Sha256Hash>, MerkleError> {
+        let proof = self.gen_proof(m)?;
+        let lemma = proof.lemma();
+        Ok(lemma[1..lemma.len() - 1].to_vec())
+    }
+
+    /// Returns the inclusion proof of the entry `m`, of the audit path as
+    /// its lemma.
+    ///
+    /// Fails with [`MerkleError::IndexOutOfBounds`] unless `m < size()`.
+    pub fn gen_proof(&self, m: usize) -> Result<Proof<Sha256Hash>, MerkleError> {
+        // padding of the odd levels is of the tree, not of the log
+        self.tree.try_gen_proof(m).map_err(|e| match e {
+            MerkleError::PaddingIndex { index, leafs } => {
+                MerkleError::IndexOutOfBounds { index, leafs }
+            }
+            e => e,
+        })
+    }
+
+    /// Returns the consistency proof `PROOF(m, D[n])` of the log of the
+    /// first `m` entries, bottom-up. Proofs of the empty log and of this
+    /// log itself are empty.
+    ///
+    /// Fails with [`MerkleError::InvalidRange`] if `m` is larger than the
+    /// size of the log.
+    pub fn consistency_proof(&self, m: usize) -> Result<Vec<Sha256Hash>, MerkleError> {
+        let n = self.size();
+        if m > n {
+            return Err(MerkleError::InvalidRange {
+                start: 0,
+                end: m,
+                leafs: n,
+            });
+        }
+
+        let mut proof = Vec::new();
+        if m > 0 && m < n {
+            self.subproof(m, 0, n, true, &mut proof);
+        }
+        Ok(proof)
+    }
+
+    /// Pushes `SUBPROOF(m, D[start:start + n], complete)` onto `proof`.
+    fn subproof(
+        &self,
+        m: usize,
+        start: usize,
+        n: usize,
+        complete: bool,
+        proof: &mut Vec<Sha256Hash>,
+    ) {
+        if m == n {
+            if !complete {
+                proof.push(self.subtree_root(start, n));
+            }
+            return;
+        }
+
+        let k = split(n);
+        if m <= k {
+            self.subproof(m, start, k, complete, proof);
+            proof.push(self.subtree_root(start + k, n - k));
+        } else {
+            self.subproof(m - k, start + k, n - k, false, proof);
+            proof.push(self.subtree_root(start, k));
+        }
+    }
+
+    /// Returns `MTH(D[start:start + n])` of the subtree the RFC splits the
+    /// log into, which is the node of the tree over these leafs.
+    fn subtree_root(&self, start: usize, n: usize) -> Sha256Hash {
+        let level = n.next_power_of_two().trailing_zeros() as usize;
+        *self
+            .tree
+            .node(level, start >> level)
+            .expect("subtree is of the tree")
+    }
+}
+
+impl Default for CtTree {
+    fn default() -> CtTree {
+        CtTree::new()
+    }
+}
+
+/// Returns the largest power of two less than `n > 1`.
+pub(crate) fn split(n: usize) -> usize {
+    1 << (usize::BITS - 1 - (n - 1).leading_zeros())
+}
+
+/// Verifies the audit path of the leaf hash `leaf` of the entry `m` of the
+/// log of `size` entries against the trusted root `root`, as in RFC 9162
+/// section 2.1.3.2.
+pub fn verify_audit_path(
+    leaf: &Sha256Hash,
+    m: usize,
+    size: usize,
+    path: &[Sha256Hash],
+    root: &Sha256Hash,
+) -> bool {
+    if m >= size {
+        return false;
+    }
+
+    let (mut f, mut s) = (m, size - 1);
+    let mut r = *leaf;
+    for p in path {
+        if s == 0 {
+            return false;
+        }
+        if f & 1 == 1 || f == s {
+            r = node(p, &r);
+            while f & 1 == 0 && f != 0 {
+                f >>= 1;
+                s >>= 1;
+            }
+        } else {
+            r = node(&r, p);
+        }
+        f >>= 1;
+        s >>= 1;
+    }
+    s == 0 && r == *root
+}
+
+/// Verifies the consistency proof of the log of root `old_root` of
+/// `old_size` entries and the log of root `new_root` of `new_size` entries
+/// against the trusted roots, as in RFC 9162 section 2.1.4.2.
+///
+/// Proof of the log and itself is empty, and validates if the roots are
+/// equal. Empty log is a prefix of any log, its proof is empty and
+/// validates if `old_root` is the root of the empty log.
+pub fn verify_consistency(
+    old_size: usize,
+    new_size: usize,
+    old_root: &Sha256Hash,
+    new_root: &Sha256Hash,
+    proof: &[Sha256Hash],
+) -> bool {
+    if old_size > new_size {
+        return false;
+    }
+    if old_size == new_size {
+        return proof.is_empty() && old_root == new_root;
+    }
+    if old_size == 0 {
+        return proof.is_empty() && *old_root == CtAlgorithm::new().hash();
+    }
+
+    let (first, rest) = match old_size.is_power_of_two() {
+        true => (old_root, proof),
+        false => match proof.split_first() {
+            Some(split) => split,
+            None => return false,
+        },
+    };
+
+    let (mut f, mut s) = (old_size - 1, new_size - 1);
+    while f & 1 == 1 {
+        f >>= 1;
+        s >>= 1;
+    }
+    let (mut fr, mut sr) = (*first, *first);
+    for c in rest {
+        if s == 0 {
+            return false;
+        }
+        if f & 1 == 1 || f == s {
+            fr = node(c, &fr);
+            sr = node(c, &sr);
+            while f & 1 == 0 && f != 0 {
+                f >>= 1;
+                s >>= 1;
+            }
+        } else {
+            sr = node(&sr, c);
+        }
+        f >>= 1;
+        s >>= 1;
+    }
+    s == 0 && fr == *old_root && sr == *new_root
+}
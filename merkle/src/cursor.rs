@@ -0,0 +1,192 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::ops;
+use crate::hash::Algorithm;
+use crate::merkle::MerkleTree;
+use crate::store::{Store, VecStore};
+
+/// Cursor at the node of the tree, as returned by
+/// [`MerkleTree::cursor`] at the root, which moves to the children, the
+/// parent and the sibling of the node.
+///
+/// Moves return `None` where there is no such node: below the leafs, above
+/// the root, and into the duplicate filling up an odd level. Sibling is the
+/// node the inclusion proofs hash the node with, as of
+/// [`MerkleTree::gen_proof`], which is the duplicate of the last node of an
+/// odd level unless the padding strategy of the tree promotes it.
+///
+/// ```
+/// # use merkle_light::merkle::MerkleTree;
+/// # use std::collections::hash_map::DefaultHasher;
+/// # use merkle_light::hash::Algorithm;
+/// # use std::hash::Hasher;
+/// # #[derive(Clone, Default)]
+/// # struct Sip(DefaultHasher);
+/// # impl Hasher for Sip {
+/// #     fn write(&mut self, msg: &[u8]) { self.0.write(msg) }
+/// #     fn finish(&self) -> u64 { self.0.finish() }
+/// # }
+/// # impl Algorithm<[u8; 8]> for Sip {
+/// #     fn hash(&mut self) -> [u8; 8] { self.finish().to_le_bytes() }
+/// # }
+/// let mt: MerkleTree<[u8; 8], Sip> = MerkleTree::from_iter([[1; 8], [2; 8], [3; 8]]);
+/// let root = mt.cursor().unwrap();
+/// assert_eq!(root.hash(), &mt.root());
+///
+/// let leaf = root.right().and_then(|c| c.left()).unwrap();
+/// assert_eq!(leaf.leaf_index(), Some(2));
+/// assert!(root.right().and_then(|c| c.right()).is_none());
+/// assert_eq!(leaf.path_from_root(), vec![false, true]);
+/// assert_eq!(leaf.sibling().map(|c| c.is_padding()), Some(true));
+/// ```
+#[derive(Debug)]
+pub struct Cursor<'a, T, A, S = VecStore<T>>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T>,
+{
+    tree: &'a MerkleTree<T, A, S>,
+    index: usize,
+}
+
+impl<T, A, S> Clone for Cursor<'_, T, A, S>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T>,
+{
+    fn clone(&self) -> Self {
+        *self
+    }
+}
+
+impl<T, A, S> Copy for Cursor<'_, T, A, S>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T>,
+{
+}
+
+impl<T, A, S> PartialEq for Cursor<'_, T, A, S>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        core::ptr::eq(self.tree, other.tree) && self.index == other.index
+    }
+}
+
+impl<T, A, S> Eq for Cursor<'_, T, A, S>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T>,
+{
+}
+
+impl<'a, T, A, S> Cursor<'a, T, A, S>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T> + ops::Deref<Target = [T]>,
+{
+    pub(crate) fn new(tree: &'a MerkleTree<T, A, S>, index: usize) -> Self {
+        Cursor { tree, index }
+    }
+
+    fn at(&self, index: usize) -> Self {
+        Cursor::new(self.tree, index)
+    }
+
+    /// Returns the memory layout index of the node, as of
+    /// [`MerkleTree::level_of`].
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the level of the node, 0 of the leafs.
+    pub fn level(&self) -> usize {
+        self.locate().0
+    }
+
+    /// Returns the node.
+    pub fn hash(&self) -> &'a T {
+        &self.tree[self.index]
+    }
+
+    /// Returns `true` if the node is of the leafs row, the leafs and the
+    /// duplicate filling it up.
+    pub fn is_leaf(&self) -> bool {
+        self.level() == 0
+    }
+
+    /// Returns `true` if the node is the duplicate filling up an odd level.
+    pub fn is_padding(&self) -> bool {
+        let (_, base, width) = self.locate();
+        self.index - base >= width
+    }
+
+    /// Returns the index of the leaf, or `None` if the node is not a leaf.
+    pub fn leaf_index(&self) -> Option<usize> {
+        match self.is_leaf() && !self.is_padding() {
+            true => Some(self.index),
+            false => None,
+        }
+    }
+
+    /// Moves to the left child, or returns `None` of the leafs.
+    pub fn left(&self) -> Option<Self> {
+        let (left, _) = self.tree.children_indices(self.index)?;
+        Some(self.at(left))
+    }
+
+    /// Moves to the right child, or returns `None` of the leafs and if it
+    /// is the duplicate filling up the odd level of the left one.
+    pub fn right(&self) -> Option<Self> {
+        let (_, right) = self.tree.children_indices(self.index)?;
+        let right = self.at(right);
+        match right.is_padding() {
+            true => None,
+            false => Some(right),
+        }
+    }
+
+    /// Moves to the parent, or returns `None` of the root.
+    pub fn parent(&self) -> Option<Self> {
+        self.tree.parent_index(self.index).map(|i| self.at(i))
+    }
+
+    /// Moves to the sibling the proofs hash the node with, or returns
+    /// `None` of the root and of the node promoted with no sibling.
+    pub fn sibling(&self) -> Option<Self> {
+        self.tree.proof_sibling(self.index).map(|i| self.at(i))
+    }
+
+    /// Returns the path from the root down to the node, `true` where the
+    /// node is the left child, the reverse of [`crate::proof::Proof::path`]
+    /// of the proofs of the leafs, which leaves out the levels where the
+    /// node is promoted with no sibling.
+    pub fn path_from_root(&self) -> Vec<bool> {
+        let mut path = Vec::new();
+        let mut j = self.index;
+        while let Some(parent) = self.tree.parent_index(j) {
+            if let Some(sibling) = self.tree.proof_sibling(j) {
+                path.push(j < sibling);
+            }
+            j = parent;
+        }
+        path.reverse();
+        path
+    }
+
+    fn locate(&self) -> (usize, usize, usize) {
+        self.tree
+            .locate(self.index)
+            .expect("cursor is at the node of the tree")
+    }
+}
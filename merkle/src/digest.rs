@@ -0,0 +1,148 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::hash::Hasher;
+use ::digest::typenum::Unsigned;
+use ::digest::Digest;
+use crate::fixed_hash::FixedHash;
+use crate::hash::Algorithm;
+
+/// Default prefix of the leaf hashes.
+const LEAF: u8 = 0x00;
+
+/// Default prefix of the interior node hashes.
+const INTERIOR: u8 = 0x01;
+
+/// [`Algorithm`] of the trees over [`FixedHash<N>`] of any RustCrypto
+/// [`Digest`] `D` of the output of `N` bytes, such as SHA-3, RIPEMD-160 or
+/// Whirlpool.
+///
+/// ```text
+/// hash()        = D(everything written since reset)
+/// leaf(x)       = D(leaf prefix || x)
+/// node(l, r)    = D(node prefix || l || r)
+/// finish()      = first 8 bytes of hash() as big endian u64
+/// ```
+///
+/// Prefixes are 0x00 and 0x01 by default, as in RFC 6962, and are chosen
+/// with [`DigestAlgorithm::with_prefixes`]. Prefixes are kept over `reset`,
+/// so that the algorithm of other prefixes is passed to the tree, such as
+/// with [`crate::merkle::MerkleTree::new_with`], and to the proofs, such as
+/// with [`crate::proof::Proof::validate_with`], rather than instantiated as
+/// `A::default()`.
+///
+/// Using the algorithm with `N` other than the output size of `D` fails to
+/// compile:
+///
+/// ```compile_fail
+/// # extern crate merkle_light;
+/// # extern crate sha2;
+/// # use merkle_light::digest::DigestAlgorithm;
+/// let a = DigestAlgorithm::<sha2::Sha512, 32>::new();
+/// ```
+///
+/// ```
+/// extern crate merkle_light;
+/// extern crate sha2;
+///
+/// use merkle_light::digest::DigestAlgorithm;
+/// use merkle_light::fixed_hash::Hash64;
+/// use merkle_light::merkle::MerkleTree;
+///
+/// type Sha512Algorithm = DigestAlgorithm<sha2::Sha512, 64>;
+///
+/// let t: MerkleTree<Hash64, Sha512Algorithm> = MerkleTree::from_data(&[1u64, 2, 3]);
+/// assert!(t.gen_proof(1).validate::<Sha512Algorithm>());
+/// ```
+#[derive(Debug, Clone)]
+pub struct DigestAlgorithm<D, const N: usize> {
+    digest: D,
+    leaf_prefix: Vec<u8>,
+    node_prefix: Vec<u8>,
+}
+
+impl<D: Digest, const N: usize> DigestAlgorithm<D, N> {
+    /// Creates new algorithm with nothing written, and the default prefixes.
+    pub fn new() -> DigestAlgorithm<D, N> {
+        Self::with_prefixes(&[LEAF], &[INTERIOR])
+    }
+
+    /// Creates new algorithm with nothing written, which prefixes the
+    /// leafs with `leaf` and the interior nodes with `node`.
+    ///
+    /// Prefixes which are empty, or of which one is a prefix of the other,
+    /// do not separate the leafs from the nodes.
+    pub fn with_prefixes(leaf: &[u8], node: &[u8]) -> DigestAlgorithm<D, N> {
+        const {
+            assert!(
+                <D::OutputSize as Unsigned>::USIZE == N,
+                "output size of the digest is not the size of the hash"
+            )
+        };
+        DigestAlgorithm {
+            digest: D::new(),
+            leaf_prefix: leaf.to_vec(),
+            node_prefix: node.to_vec(),
+        }
+    }
+
+    /// Returns the prefix of the leafs.
+    pub fn leaf_prefix(&self) -> &[u8] {
+        &self.leaf_prefix
+    }
+
+    /// Returns the prefix of the interior nodes.
+    pub fn node_prefix(&self) -> &[u8] {
+        &self.node_prefix
+    }
+}
+
+impl<D: Digest, const N: usize> Default for DigestAlgorithm<D, N> {
+    fn default() -> DigestAlgorithm<D, N> {
+        DigestAlgorithm::new()
+    }
+}
+
+impl<D: Digest + Clone, const N: usize> Hasher for DigestAlgorithm<D, N> {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.digest.update(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        let h = self.digest.clone().finalize();
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&h[..8]);
+        u64::from_be_bytes(bytes)
+    }
+}
+
+impl<D: Digest + Clone, const N: usize> Algorithm<FixedHash<N>> for DigestAlgorithm<D, N> {
+    #[inline]
+    fn hash(&mut self) -> FixedHash<N> {
+        let mut h = FixedHash::default();
+        h.0.copy_from_slice(&self.digest.clone().finalize());
+        h
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.digest = D::new();
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: FixedHash<N>) -> FixedHash<N> {
+        self.digest.update(&self.leaf_prefix);
+        self.digest.update(leaf);
+        self.hash()
+    }
+
+    #[inline]
+    fn node(&mut self, left: FixedHash<N>, right: FixedHash<N>) -> FixedHash<N> {
+        self.digest.update(&self.node_prefix);
+        self.digest.update(left);
+        self.digest.update(right);
+        self.hash()
+    }
+}
@@ -0,0 +1,214 @@
+// functions of the C ABI take raw pointers, and are of unsafe code
+#![allow(unsafe_code)]
+
+use std::hash::Hasher;
+use std::panic::{self, AssertUnwindSafe};
+use std::ptr;
+use std::slice;
+use crate::hash::Algorithm;
+use crate::merkle::{MerkleError, MerkleTree};
+use crate::proof::Proof;
+use crate::sha256::{Sha256Algorithm, Sha256Hash};
+
+/// Status the functions of the C ABI return.
+#[repr(C)]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MerkleStatus {
+    /// Call succeeded, or the proof is valid.
+    Ok = 0,
+    /// Pointer argument which must not be null is null.
+    NullPointer = 1,
+    /// Arguments are out of their domain, such as too many leafs.
+    InvalidArgument = 2,
+    /// Index is not less than the number of the leafs.
+    IndexOutOfBounds = 3,
+    /// Output buffer is shorter than the output, of which length is
+    /// written to the length argument.
+    BufferTooSmall = 4,
+    /// Proof does not decode, or does not verify.
+    InvalidProof = 5,
+    /// Call panicked, which is a bug of the crate.
+    Panic = 6,
+}
+
+/// Merkle tree of the [`Sha256Algorithm`] owned by the C caller, opaque to
+/// it, created with [`merkle_tree_new`] and released with
+/// [`merkle_tree_free`].
+#[derive(Debug)]
+pub struct MerkleTreeHandle {
+    tree: MerkleTree<Sha256Hash, Sha256Algorithm>,
+}
+
+/// Returns the bytes of `len` at `data`, which may be null if `len` is 0.
+unsafe fn bytes<'a>(data: *const u8, len: usize) -> Option<&'a [u8]> {
+    match (data.is_null(), len) {
+        (_, 0) => Some(&[]),
+        (true, _) => None,
+        (false, _) => Some(slice::from_raw_parts(data, len)),
+    }
+}
+
+/// Returns the hash of the record the tree hashes into its leaf, as of the
+/// tree over the records built with [`MerkleTree::from_data`].
+fn record_hash(record: &[u8]) -> Sha256Hash {
+    let mut a = Sha256Algorithm::new();
+    a.write(record);
+    a.hash()
+}
+
+/// Runs `f`, returning [`MerkleStatus::Panic`] if it panics.
+fn guard<F: FnOnce() -> MerkleStatus>(f: F) -> MerkleStatus {
+    panic::catch_unwind(AssertUnwindSafe(f)).unwrap_or(MerkleStatus::Panic)
+}
+
+/// Builds the tree over the `count` records of `leaf_len` bytes each, laid
+/// out one after another at `leaves`.
+///
+/// Leaf of the record `x` is `SHA-256(0x00 || SHA-256(x))`, as of the tree
+/// built with [`MerkleTree::from_data`] of the records as byte arrays.
+/// `leaves` may be null if there are no bytes of the records.
+///
+/// Returns the tree the caller owns and releases with [`merkle_tree_free`],
+/// or null if `leaves` is null, the records are too many, or the call
+/// panics.
+///
+/// # Safety
+///
+/// `leaves` must be valid for the reads of `leaf_len * count` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn merkle_tree_new(
+    leaves: *const u8,
+    leaf_len: usize,
+    count: usize,
+) -> *mut MerkleTreeHandle {
+    let build = || {
+        let len = leaf_len.checked_mul(count)?;
+        let data = unsafe { bytes(leaves, len)? };
+        let hashes = match leaf_len {
+            0 => vec![record_hash(&[]); count],
+            _ => data.chunks(leaf_len).map(record_hash).collect(),
+        };
+        let tree = MerkleTree::try_new(hashes).ok()?;
+        Some(Box::into_raw(Box::new(MerkleTreeHandle { tree })))
+    };
+    match panic::catch_unwind(AssertUnwindSafe(build)) {
+        Ok(Some(tree)) => tree,
+        _ => ptr::null_mut(),
+    }
+}
+
+/// Writes the root of the tree into the 32 bytes at `out`.
+///
+/// # Safety
+///
+/// `tree` must be null or a tree of [`merkle_tree_new`] not released yet,
+/// and `out` must be null or valid for the writes of 32 bytes.
+#[no_mangle]
+pub unsafe extern "C" fn merkle_tree_root(
+    tree: *const MerkleTreeHandle,
+    out: *mut u8,
+) -> MerkleStatus {
+    guard(|| {
+        if tree.is_null() || out.is_null() {
+            return MerkleStatus::NullPointer;
+        }
+        let root = unsafe { (*tree).tree.root() };
+        unsafe { ptr::copy_nonoverlapping(root.0.as_ptr(), out, root.0.len()) };
+        MerkleStatus::Ok
+    })
+}
+
+/// Writes the inclusion proof of the leaf `index` into the buffer at `out`
+/// of `*out_len` bytes, encoded as of [`Proof::to_bytes`], and sets
+/// `*out_len` to the length of the proof.
+///
+/// Returns [`MerkleStatus::BufferTooSmall`] if `out` is null or shorter than
+/// the proof, having set `*out_len` to the length of the proof, so that the
+/// caller calls again with the buffer of that length.
+///
+/// # Safety
+///
+/// `tree` must be null or a tree of [`merkle_tree_new`] not released yet,
+/// `out_len` must be null or valid for the reads and writes of `size_t`,
+/// and `out` must be null or valid for the writes of `*out_len` bytes.
+#[no_mangle]
+pub unsafe extern "C" fn merkle_tree_proof(
+    tree: *const MerkleTreeHandle,
+    index: usize,
+    out: *mut u8,
+    out_len: *mut usize,
+) -> MerkleStatus {
+    guard(|| {
+        if tree.is_null() || out_len.is_null() {
+            return MerkleStatus::NullPointer;
+        }
+        let proof = match unsafe { (*tree).tree.try_gen_proof(index) } {
+            Ok(proof) => proof.to_bytes(),
+            Err(MerkleError::IndexOutOfBounds { .. } | MerkleError::PaddingIndex { .. }) => {
+                return MerkleStatus::IndexOutOfBounds
+            }
+            Err(_) => return MerkleStatus::InvalidArgument,
+        };
+
+        let capacity = unsafe { *out_len };
+        unsafe { *out_len = proof.len() };
+        if out.is_null() || capacity < proof.len() {
+            return MerkleStatus::BufferTooSmall;
+        }
+        unsafe { ptr::copy_nonoverlapping(proof.as_ptr(), out, proof.len()) };
+        MerkleStatus::Ok
+    })
+}
+
+/// Verifies the proof of `proof_len` bytes at `proof`, written by
+/// [`merkle_tree_proof`], of the record of `leaf_len` bytes at `leaf`
+/// against the trusted root of 32 bytes at `root`.
+///
+/// Returns [`MerkleStatus::Ok`] if the proof is valid, and
+/// [`MerkleStatus::InvalidProof`] if it does not decode or is not of the
+/// record and the root. `leaf` and `proof` may be null if their lengths
+/// are 0.
+///
+/// # Safety
+///
+/// `root` must be null or valid for the reads of 32 bytes, `leaf` for the
+/// reads of `leaf_len` bytes and `proof` for the reads of `proof_len`
+/// bytes.
+#[no_mangle]
+pub unsafe extern "C" fn merkle_proof_verify(
+    root: *const u8,
+    leaf: *const u8,
+    leaf_len: usize,
+    proof: *const u8,
+    proof_len: usize,
+) -> MerkleStatus {
+    guard(|| {
+        let (leaf, proof) = match unsafe { (bytes(leaf, leaf_len), bytes(proof, proof_len)) } {
+            (Some(leaf), Some(proof)) if !root.is_null() => (leaf, proof),
+            _ => return MerkleStatus::NullPointer,
+        };
+        let mut trusted = Sha256Hash::default();
+        unsafe { ptr::copy_nonoverlapping(root, trusted.0.as_mut_ptr(), trusted.0.len()) };
+
+        let leaf = Sha256Algorithm::new().leaf(record_hash(leaf));
+        match Proof::<Sha256Hash>::from_bytes(proof) {
+            Ok(proof) if proof.validate_leaf_against::<Sha256Algorithm>(&leaf, &trusted) => {
+                MerkleStatus::Ok
+            }
+            _ => MerkleStatus::InvalidProof,
+        }
+    })
+}
+
+/// Releases the tree of [`merkle_tree_new`]. Null is ignored.
+///
+/// # Safety
+///
+/// `tree` must be null or a tree of [`merkle_tree_new`] not released yet,
+/// which is not used after.
+#[no_mangle]
+pub unsafe extern "C" fn merkle_tree_free(tree: *mut MerkleTreeHandle) {
+    if !tree.is_null() {
+        let _ = panic::catch_unwind(AssertUnwindSafe(|| drop(unsafe { Box::from_raw(tree) })));
+    }
+}
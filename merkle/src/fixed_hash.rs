@@ -0,0 +1,253 @@
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::Hasher;
+use core::str::FromStr;
+use crate::hash::Hashable;
+
+/// Hash of `N` bytes, the element type of the trees of the built-in
+/// algorithms, with the traits the trees and their users need.
+///
+/// Default hash is all zeros. Hash displays, parses and serializes to the
+/// human readable formats as the lowercase hex of its bytes, and to the
+/// binary ones as the bytes. Hash is [`Hashable`] as its bytes, just as
+/// `[u8; N]`.
+///
+/// ```
+/// use merkle_light::fixed_hash::Hash32;
+///
+/// let h: Hash32 = "0x0101010101010101010101010101010101010101010101010101010101010101"
+///     .parse()
+///     .unwrap();
+/// assert_eq!(h, Hash32::from([1; 32]));
+/// assert_eq!(h.to_string(), "01".repeat(32));
+/// assert_eq!(Hash32::try_from(&[1; 32][..]), Ok(h));
+/// assert!(Hash32::try_from(vec![1; 31]).is_err());
+/// ```
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
+pub struct FixedHash<const N: usize>(pub [u8; N]);
+
+/// Hash of 32 bytes, such as of SHA-256, BLAKE3 and Keccak-256.
+pub type Hash32 = FixedHash<32>;
+
+/// Hash of 64 bytes, such as of SHA-512.
+pub type Hash64 = FixedHash<64>;
+
+/// Error of building [`FixedHash`] of bytes or hex.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FixedHashError {
+    /// Bytes are not as many as the hash has.
+    Length {
+        /// Length of the hash.
+        expected: usize,
+        /// Length of the bytes.
+        found: usize,
+    },
+    /// Hex has a character other than a hex digit at `index`.
+    Hex {
+        /// Index of the character in the hex, past the `0x` prefix.
+        index: usize,
+    },
+}
+
+impl fmt::Display for FixedHashError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FixedHashError::Length { expected, found } => {
+                write!(f, "hash of {} bytes is built of {} bytes", expected, found)
+            }
+            FixedHashError::Hex { index } => write!(f, "invalid hex digit at {}", index),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FixedHashError {}
+
+impl<const N: usize> FixedHash<N> {
+    /// Returns the bytes of the hash.
+    pub fn as_bytes(&self) -> &[u8; N] {
+        &self.0
+    }
+
+    /// Returns the bytes of the hash in a vector.
+    #[cfg(feature = "alloc")]
+    pub fn to_vec(&self) -> Vec<u8> {
+        self.0.to_vec()
+    }
+}
+
+impl<const N: usize> Default for FixedHash<N> {
+    fn default() -> FixedHash<N> {
+        FixedHash([0; N])
+    }
+}
+
+impl<const N: usize> AsRef<[u8]> for FixedHash<N> {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+impl<const N: usize> AsMut<[u8]> for FixedHash<N> {
+    fn as_mut(&mut self) -> &mut [u8] {
+        &mut self.0
+    }
+}
+
+impl<const N: usize> From<[u8; N]> for FixedHash<N> {
+    fn from(bytes: [u8; N]) -> FixedHash<N> {
+        FixedHash(bytes)
+    }
+}
+
+impl<const N: usize> From<FixedHash<N>> for [u8; N] {
+    fn from(h: FixedHash<N>) -> [u8; N] {
+        h.0
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> From<FixedHash<N>> for Vec<u8> {
+    fn from(h: FixedHash<N>) -> Vec<u8> {
+        h.to_vec()
+    }
+}
+
+impl<const N: usize> TryFrom<&[u8]> for FixedHash<N> {
+    type Error = FixedHashError;
+
+    fn try_from(bytes: &[u8]) -> Result<FixedHash<N>, FixedHashError> {
+        bytes
+            .try_into()
+            .map(FixedHash)
+            .map_err(|_| FixedHashError::Length {
+                expected: N,
+                found: bytes.len(),
+            })
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<const N: usize> TryFrom<Vec<u8>> for FixedHash<N> {
+    type Error = FixedHashError;
+
+    fn try_from(bytes: Vec<u8>) -> Result<FixedHash<N>, FixedHashError> {
+        FixedHash::try_from(bytes.as_slice())
+    }
+}
+
+/// Displays as the lowercase hex of the bytes, `0x` prefixed in the
+/// alternate form.
+impl<const N: usize> fmt::Display for FixedHash<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        for b in &self.0 {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+impl<const N: usize> fmt::Debug for FixedHash<N> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FixedHash({:#})", self)
+    }
+}
+
+/// Parses the hex of exactly `N` bytes, with or without the `0x` prefix,
+/// in any case.
+impl<const N: usize> FromStr for FixedHash<N> {
+    type Err = FixedHashError;
+
+    fn from_str(s: &str) -> Result<FixedHash<N>, FixedHashError> {
+        let digits = s.strip_prefix("0x").unwrap_or(s).as_bytes();
+        if digits.len() != 2 * N {
+            return Err(FixedHashError::Length {
+                expected: N,
+                found: digits.len() / 2,
+            });
+        }
+
+        let hex = |index: usize| {
+            (digits[index] as char)
+                .to_digit(16)
+                .ok_or(FixedHashError::Hex { index })
+        };
+        let mut h = FixedHash::default();
+        for (i, b) in h.0.iter_mut().enumerate() {
+            *b = (hex(2 * i)? << 4 | hex(2 * i + 1)?) as u8;
+        }
+        Ok(h)
+    }
+}
+
+impl<H: Hasher, const N: usize> Hashable<H> for FixedHash<N> {
+    fn hash(&self, state: &mut H) {
+        state.write(&self.0)
+    }
+}
+
+#[cfg(feature = "serde")]
+mod serde_impl {
+    use core::fmt;
+    use serde::de::{self, Deserialize, Deserializer, SeqAccess, Visitor};
+    use serde::ser::{Serialize, Serializer};
+    use super::FixedHash;
+
+    impl<const N: usize> Serialize for FixedHash<N> {
+        fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+            if serializer.is_human_readable() {
+                serializer.collect_str(&format_args!("{:#}", self))
+            } else {
+                serializer.serialize_bytes(&self.0)
+            }
+        }
+    }
+
+    impl<'de, const N: usize> Deserialize<'de> for FixedHash<N> {
+        fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+            if deserializer.is_human_readable() {
+                deserializer.deserialize_str(FixedHashVisitor)
+            } else {
+                deserializer.deserialize_bytes(FixedHashVisitor)
+            }
+        }
+    }
+
+    struct FixedHashVisitor<const N: usize>;
+
+    impl<'de, const N: usize> Visitor<'de> for FixedHashVisitor<N> {
+        type Value = FixedHash<N>;
+
+        fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+            write!(f, "hash of {} bytes", N)
+        }
+
+        fn visit_str<E: de::Error>(self, s: &str) -> Result<FixedHash<N>, E> {
+            s.parse().map_err(E::custom)
+        }
+
+        fn visit_bytes<E: de::Error>(self, bytes: &[u8]) -> Result<FixedHash<N>, E> {
+            FixedHash::try_from(bytes).map_err(|_| E::invalid_length(bytes.len(), &self))
+        }
+
+        fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<FixedHash<N>, S::Error> {
+            let mut h = FixedHash::default();
+            for (i, b) in h.0.iter_mut().enumerate() {
+                *b = seq
+                    .next_element()?
+                    .ok_or_else(|| de::Error::invalid_length(i, &self))?;
+            }
+            if seq.next_element::<u8>()?.is_some() {
+                return Err(de::Error::invalid_length(N + 1, &self));
+            }
+            Ok(h)
+        }
+    }
+}
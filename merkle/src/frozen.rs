@@ -0,0 +1,76 @@
+use core::ops;
+use crate::hash::Algorithm;
+use crate::merkle::MerkleTree;
+use crate::store::{BoxStore, VecStore};
+
+/// Merkle tree frozen with [`MerkleTree::freeze`], of its nodes in the
+/// boxed slice of exactly the nodes, with no room reserved past them, as
+/// the services holding many small trees which do not grow any more want.
+///
+/// Tree dereferences to the tree of [`BoxStore`], so that it reads as the
+/// tree does, of the roots, the proofs and the nodes, but is not mutated.
+/// Tree to grow again is thawed with [`FrozenTree::thaw`], of the nodes
+/// moved back without copying them.
+///
+/// ```
+/// use merkle_light::hash::Algorithm;
+/// use merkle_light::merkle::MerkleTree;
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+/// use std::iter::FromIterator;
+///
+/// #[derive(Clone, Default)]
+/// struct Sip(DefaultHasher);
+///
+/// impl Hasher for Sip {
+///     fn write(&mut self, msg: &[u8]) {
+///         self.0.write(msg)
+///     }
+///
+///     fn finish(&self) -> u64 {
+///         self.0.finish()
+///     }
+/// }
+///
+/// impl Algorithm<[u8; 8]> for Sip {
+///     fn hash(&mut self) -> [u8; 8] {
+///         self.finish().to_le_bytes()
+///     }
+///
+///     fn reset(&mut self) {
+///         *self = Sip::default();
+///     }
+/// }
+///
+/// let mut t: MerkleTree<[u8; 8], Sip> = MerkleTree::from_iter((0..5u64).map(u64::to_le_bytes));
+/// t.push(5u64.to_le_bytes());
+/// assert!(t.heap_size_estimate() >= t.node_count() * size_of::<[u8; 8]>());
+///
+/// let frozen = t.clone().freeze();
+/// assert_eq!(frozen.heap_size_estimate(), t.node_count() * size_of::<[u8; 8]>());
+/// assert_eq!(frozen.root(), t.root());
+/// assert_eq!(frozen.gen_proof(3), t.gen_proof(3));
+/// assert_eq!(frozen.thaw().into_vec(), t.into_vec());
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct FrozenTree<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>>(MerkleTree<T, A, BoxStore<T>>);
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> FrozenTree<T, A> {
+    pub(crate) fn new(tree: MerkleTree<T, A, BoxStore<T>>) -> FrozenTree<T, A> {
+        FrozenTree(tree)
+    }
+
+    /// Thaws the tree back into the tree of [`VecStore`], which grows and is
+    /// mutated again, of the nodes moved without copying them.
+    pub fn thaw(self) -> MerkleTree<T, A> {
+        self.0.map_store(|data| VecStore::from(data.into_vec()))
+    }
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> ops::Deref for FrozenTree<T, A> {
+    type Target = MerkleTree<T, A, BoxStore<T>>;
+
+    fn deref(&self) -> &MerkleTree<T, A, BoxStore<T>> {
+        &self.0
+    }
+}
@@ -1,7 +1,17 @@
 //! Hash infrastructure for items in Merkle Tree.
 
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
+use core::fmt;
 use core::hash::Hasher;
 
+#[cfg(feature = "derive")]
+pub use merkle_light_derive::Hashable;
+
 /// A hashable type.
 ///
 /// Types implementing `Hashable` are able to be [`hash`]ed with an instance of
@@ -9,23 +19,19 @@ use core::hash::Hasher;
 ///
 /// ## Implementing `Hashable`
 ///
-/// You can derive `Hashable` with `#[derive(Hashable)]` if all fields implement `Hashable`.
-/// The resulting hash will be the combination of the values from calling
-/// [`hash`] on each field.
+/// You can derive `Hashable` with `#[derive(Hashable)]` of the `derive` feature
+/// if all fields implement `Hashable`. The resulting hash will be the combination
+/// of the values from calling [`hash`] on each field in order, and enums hash
+/// the index of the variant as u32 first. Fields are left out of the hash with
+/// `#[merkle(skip)]`, or hashed with a function with `#[merkle(with = "path")]`.
 ///
 /// ```text
-/// #[macro_use]
-/// extern crate merkle_light_derive;
-/// extern crate merkle_light;
-///
 /// use merkle_light::hash::Hashable;
 ///
-/// fn main() {
-///     #[derive(Hashable)]
-///     struct Foo {
-///         name: String,
-///         country: String,
-///     }
+/// #[derive(Hashable)]
+/// struct Foo {
+///     name: String,
+///     country: String,
 /// }
 /// ```
 ///
@@ -62,10 +68,31 @@ use core::hash::Hasher;
 ///
 ///    let mut hr = DefaultHasher::new();
 ///    foo.hash(&mut hr);
-///    assert_eq!(hr.finish(), 7101638158313343130)
+///    assert_eq!(hr.finish(), 16409760087465598165)
 /// }
 /// ```
 ///
+/// ## Encodings
+///
+/// Implementations for the common types are frozen, so that the hashes of
+/// the values never change, and are the same on all platforms:
+///
+/// ```text
+/// u8 .. u128, i8 .. i128   little endian, fixed width
+/// usize, isize             as u64, i64
+/// bool                     u8, 0 or 1
+/// char                     u32 of the code point
+/// str, String              u64 length in bytes, then the UTF-8 bytes
+/// [T], Vec<T>              u64 length in items, then each item
+/// [u8; N]                  the N bytes, no length
+/// Option<T>                u8 0 for None, u8 1 then the value for Some
+/// (A, B, ..)               each item in order
+/// ```
+///
+/// Variable width values are prefixed with their length, so that the
+/// concatenations of them are unambiguous, e.g. `("ab", "c")` and
+/// `("a", "bc")` hash apart.
+///
 /// ## `Hashable` and `Eq`
 ///
 /// When implementing both `Hashable` and [`Eq`], it is important that the following
@@ -111,6 +138,14 @@ const INTERIOR: u8 = 0x01;
 /// [`Algorithm`] breaks the [`Hasher`] contract at `finish()`, but that is intended.
 /// This trait extends [`Hasher`] with `hash -> T` and `reset` state methods,
 /// plus implements default behavior of evaluation of MT interior nodes.
+///
+/// Trees reset the algorithm before every leaf, node and object they hash,
+/// and do not rely on the state it is left in, so that the algorithm passed
+/// to the tree hashes the same whatever was written to it before. `reset`
+/// brings the algorithm back to the state of no bytes written, as of
+/// `Self::default()` unless it is keyed, and `leaf` and `node` hash from the
+/// state they are called in. [`ResetChecked`] checks that of the algorithm
+/// in the tests.
 pub trait Algorithm<T>: Hasher + Default
 where
     T: Clone + AsRef<[u8]>,
@@ -118,7 +153,7 @@ where
     /// Returns the hash value for the data stream written so far.
     fn hash(&mut self) -> T;
 
-    /// Reset Hasher state.
+    /// Reset Hasher state, dropping the bytes written so far.
     #[inline]
     fn reset(&mut self) {
         *self = Self::default();
@@ -140,4 +175,335 @@ where
         self.write(right.as_ref());
         self.hash()
     }
+
+    /// Returns hash value for MT interior node of the children borrowed,
+    /// which the trees build their levels with.
+    ///
+    /// Default clones the children into [`Algorithm::node`], which is the
+    /// same node. Algorithms of the hashes costly to clone, such as
+    /// `Vec<u8>`, override it to write the children as they are, with the
+    /// same output as `node`.
+    #[inline]
+    fn node_ref(&mut self, left: &T, right: &T) -> T {
+        self.node(left.clone(), right.clone())
+    }
+
+    /// Returns hash value for MT interior node of many `children`, as of
+    /// the k-ary trees, see [`crate::kary::KaryMerkleTree`].
+    ///
+    /// Default folds the children pairwise from the left with `node`, so
+    /// that the node of two children is the binary one.
+    ///
+    /// # Panics
+    ///
+    /// Panics if there are no children.
+    #[inline]
+    fn multi_node(&mut self, children: &[T]) -> T {
+        let (first, rest) = children.split_first().expect("node has children");
+        rest.iter().fold(first.clone(), |h, child| {
+            self.reset();
+            self.node(h, child.clone())
+        })
+    }
+}
+
+/// [`Algorithm`] which separates the leafs from the interior nodes of the
+/// inner algorithm `A`, as in RFC 6962, whatever `A` does itself:
+///
+/// ```text
+/// leaf(x)       = A(0x00 || x)
+/// node(l, r)    = A(0x01 || l || r)
+/// ```
+///
+/// Without the prefixes, interior node of the tree can pass for a leaf, so
+/// that the proof of the node proves the "leaf", such as with the
+/// algorithms which take the leafs as they are. `hash` and the bytes
+/// written are those of `A`, so that the objects are hashed into the leafs
+/// the same.
+#[derive(Debug, Clone, Default)]
+pub struct DomainSeparated<A>(pub A);
+
+impl<A: Hasher> Hasher for DomainSeparated<A> {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl<T: Clone + AsRef<[u8]>, A: Algorithm<T>> Algorithm<T> for DomainSeparated<A> {
+    #[inline]
+    fn hash(&mut self) -> T {
+        self.0.hash()
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.0.reset()
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: T) -> T {
+        self.0.write(&[LEAF]);
+        self.0.write(leaf.as_ref());
+        self.0.hash()
+    }
+
+    #[inline]
+    fn node(&mut self, left: T, right: T) -> T {
+        self.0.write(&[INTERIOR]);
+        self.0.write(left.as_ref());
+        self.0.write(right.as_ref());
+        self.0.hash()
+    }
+
+    #[inline]
+    fn node_ref(&mut self, left: &T, right: &T) -> T {
+        self.0.write(&[INTERIOR]);
+        self.0.write(left.as_ref());
+        self.0.write(right.as_ref());
+        self.0.hash()
+    }
+}
+
+/// [`Algorithm`] checking that the inner algorithm `A` is reset between the
+/// hashes, meant for the tests of the implementations: every leaf and node
+/// is hashed twice with `A` reset in between, and the hashes are asserted
+/// equal, which they are not if `reset` leaves over the bytes written.
+/// Hashes and the bytes written are those of `A` otherwise, so that the
+/// trees and proofs of either are the same.
+///
+/// ```
+/// # #[cfg(feature = "sha256")] {
+/// use merkle_light::hash::ResetChecked;
+/// use merkle_light::merkle::MerkleTree;
+/// use merkle_light::sha256::{Sha256Algorithm, Sha256Hash};
+///
+/// let checked: MerkleTree<Sha256Hash, ResetChecked<Sha256Algorithm>> =
+///     MerkleTree::from_data(0..5u64);
+/// let mt: MerkleTree<Sha256Hash, Sha256Algorithm> = MerkleTree::from_data(0..5u64);
+/// assert_eq!(checked.root(), mt.root());
+/// # }
+/// ```
+///
+/// # Panics
+///
+/// Panics if the hashes of the same leaf or node differ.
+#[derive(Debug, Clone, Default)]
+pub struct ResetChecked<A>(pub A);
+
+impl<A> ResetChecked<A> {
+    /// Hashes with `f` twice, resetting `A` in between, and asserts that the
+    /// hashes of `what` are equal.
+    fn twice<T, F>(&mut self, what: &str, mut f: F) -> T
+    where
+        T: Clone + AsRef<[u8]>,
+        A: Algorithm<T>,
+        F: FnMut(&mut A) -> T,
+    {
+        let h = f(&mut self.0);
+        self.0.reset();
+        let again = f(&mut self.0);
+        assert!(
+            h.as_ref() == again.as_ref(),
+            "{} hashes differ, algorithm is not reset between them",
+            what
+        );
+        h
+    }
+}
+
+impl<A: Hasher> Hasher for ResetChecked<A> {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl<T: Clone + AsRef<[u8]>, A: Algorithm<T>> Algorithm<T> for ResetChecked<A> {
+    #[inline]
+    fn hash(&mut self) -> T {
+        self.0.hash()
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.0.reset()
+    }
+
+    fn leaf(&mut self, leaf: T) -> T {
+        self.twice("leaf", |a| a.leaf(leaf.clone()))
+    }
+
+    fn node(&mut self, left: T, right: T) -> T {
+        self.twice("node", |a| a.node(left.clone(), right.clone()))
+    }
+
+    fn node_ref(&mut self, left: &T, right: &T) -> T {
+        self.twice("node", |a| a.node_ref(left, right))
+    }
+}
+
+/// [`Algorithm`] keyed with the secret `key`, which the inner algorithm `A`
+/// hashes ahead of every stream, leaf and node, separated as in
+/// [`DomainSeparated`]:
+///
+/// ```text
+/// hash()        = A(key || bytes written since reset)
+/// leaf(x)       = A(key || 0x00 || x)
+/// node(l, r)    = A(key || 0x01 || l || r)
+/// ```
+///
+/// Trees with the same leafs but different keys have unrelated roots.
+/// Without the key, low entropy leafs cannot be guessed from the root, and
+/// empty trees (root `A(key)`) cannot be told apart.
+///
+/// Key survives `reset`. Pass the algorithm to the tree, such as with
+/// [`crate::merkle::MerkleTree::new_with`], and to the proofs, such as with
+/// [`crate::proof::Proof::validate_with`]. `A::default()` has the empty
+/// key. Objects are hashed into leafs with the key, and the keyed leaf
+/// hashes them again.
+///
+/// Keyed prefix makes a MAC of the hashes resistant to length extension,
+/// such as SHA-3 or BLAKE3, but not of SHA-256.
+#[cfg(feature = "alloc")]
+#[derive(Clone, Default)]
+pub struct KeyedAlgorithm<A> {
+    key: Vec<u8>,
+    inner: A,
+}
+
+#[cfg(feature = "alloc")]
+impl<A: Default + Hasher> KeyedAlgorithm<A> {
+    /// Creates new algorithm keyed with `key`.
+    pub fn new(key: &[u8]) -> KeyedAlgorithm<A> {
+        let mut inner = A::default();
+        inner.write(key);
+        KeyedAlgorithm {
+            key: key.to_vec(),
+            inner,
+        }
+    }
+}
+
+/// Key is never printed.
+#[cfg(feature = "alloc")]
+impl<A: fmt::Debug> fmt::Debug for KeyedAlgorithm<A> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("KeyedAlgorithm")
+            .field("key", &"..")
+            .field("inner", &self.inner)
+            .finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<A: Hasher> Hasher for KeyedAlgorithm<A> {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.inner.write(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.inner.finish()
+    }
+}
+
+#[cfg(feature = "alloc")]
+impl<T: Clone + AsRef<[u8]>, A: Algorithm<T>> Algorithm<T> for KeyedAlgorithm<A> {
+    #[inline]
+    fn hash(&mut self) -> T {
+        self.inner.hash()
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.inner.reset();
+        self.inner.write(&self.key);
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: T) -> T {
+        self.inner.write(&[LEAF]);
+        self.inner.write(leaf.as_ref());
+        self.inner.hash()
+    }
+
+    #[inline]
+    fn node(&mut self, left: T, right: T) -> T {
+        self.inner.write(&[INTERIOR]);
+        self.inner.write(left.as_ref());
+        self.inner.write(right.as_ref());
+        self.inner.hash()
+    }
+
+    #[inline]
+    fn node_ref(&mut self, left: &T, right: &T) -> T {
+        self.inner.write(&[INTERIOR]);
+        self.inner.write(left.as_ref());
+        self.inner.write(right.as_ref());
+        self.inner.hash()
+    }
+}
+
+/// Returns whether the hashes `a` and `b` are equal, comparing every byte
+/// of them whatever the first one differing is, so that the time it takes
+/// does not tell where the hashes differ. Lengths of the hashes are not
+/// secret, and the hashes of different lengths differ at once.
+///
+/// ```
+/// use merkle_light::hash::ct_eq;
+///
+/// assert!(ct_eq(&[1, 2, 3], &[1, 2, 3]));
+/// assert!(!ct_eq(&[1, 2, 3], &[0, 2, 3]));
+/// assert!(!ct_eq(&[1, 2, 3], &[1, 2]));
+/// ```
+pub fn ct_eq(a: &[u8], b: &[u8]) -> bool {
+    if a.len() != b.len() {
+        return false;
+    }
+    let diff = a.iter().zip(b).fold(0, |diff, (x, y)| diff | (x ^ y));
+    // kept from being optimized into the comparison exiting early
+    core::hint::black_box(diff) == 0
+}
+
+/// Returns the hash of the object `x`, which the trees built from the
+/// hashable objects hash into their leafs: the object alone is fed into
+/// the reset algorithm `a`.
+#[cfg(feature = "alloc")]
+pub(crate) fn hash_data<T, A, O>(a: &mut A, x: &O) -> T
+where
+    T: Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    O: Hashable<A>,
+{
+    a.reset();
+    x.hash(a);
+    a.hash()
+}
+
+/// Returns the root `root` of the tree over `leafs` leafs, committed to the
+/// number of leafs with the reset algorithm `a`: the hash of the root
+/// followed by the number of leafs as little endian `u64`, `A(root ||
+/// leafs)`.
+#[cfg(feature = "alloc")]
+pub(crate) fn commit_leafs<T, A>(a: &mut A, root: &T, leafs: usize) -> T
+where
+    T: Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+{
+    a.reset();
+    a.write(root.as_ref());
+    a.write(&(leafs as u64).to_le_bytes());
+    a.hash()
 }
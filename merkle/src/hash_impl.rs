@@ -1,42 +1,60 @@
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
+#[cfg(feature = "alloc")]
 use alloc::string::String;
 use core::hash::Hasher;
 use core::mem;
 use core::slice;
 use crate::hash::Hashable;
 
+/// Feeds the length of a variable width value as u64 little endian.
+#[inline]
+fn write_len<H: Hasher>(len: usize, state: &mut H) {
+    state.write(&(len as u64).to_le_bytes())
+}
+
 macro_rules! impl_write {
-    ($(($ty:ident, $meth:ident),)*) => {$(
+    ($(($ty:ident, $le:ident),)*) => {$(
         impl<H: Hasher> Hashable<H> for $ty {
+            #[allow(trivial_numeric_casts)]
             fn hash(&self, state: &mut H) {
-                state.$meth(*self)
+                state.write(&(*self as $le).to_le_bytes())
             }
 
             #[allow(trivial_casts, unsafe_code)]
             fn hash_slice(data: &[$ty], state: &mut H) {
-                let newlen = data.len() * mem::size_of::<$ty>();
-                let ptr = data.as_ptr() as *const u8;
-                state.write(unsafe { slice::from_raw_parts(ptr, newlen) })
+                if cfg!(target_endian = "little") && mem::size_of::<$ty>() == mem::size_of::<$le>() {
+                    let newlen = data.len() * mem::size_of::<$ty>();
+                    let ptr = data.as_ptr() as *const u8;
+                    state.write(unsafe { slice::from_raw_parts(ptr, newlen) })
+                } else {
+                    for piece in data {
+                        piece.hash(state);
+                    }
+                }
             }
         }
     )*}
 }
 
+// usize and isize are fed as 64 bit, so that the hashes do not depend on
+// the platform
 impl_write! {
-    (u8, write_u8),
-    (u16, write_u16),
-    (u32, write_u32),
-    (u64, write_u64),
-    (usize, write_usize),
-    (i8, write_i8),
-    (i16, write_i16),
-    (i32, write_i32),
-    (i64, write_i64),
-    (isize, write_isize),
-    // unstable: (u128, write_u128),
-    // unstable: (i128, write_i128),
+    (u8, u8),
+    (u16, u16),
+    (u32, u32),
+    (u64, u64),
+    (u128, u128),
+    (usize, u64),
+    (i8, i8),
+    (i16, i16),
+    (i32, i32),
+    (i64, i64),
+    (i128, i128),
+    (isize, i64),
 }
 
 macro_rules! impl_array {
@@ -66,35 +84,48 @@ impl_array! { u8
  61 62 63 64
 }
 
-impl<H: Hasher> Hashable<H> for Vec<u8> {
+#[cfg(feature = "alloc")]
+impl<H: Hasher, T: Hashable<H>> Hashable<H> for Vec<T> {
     fn hash(&self, state: &mut H) {
-        state.write(self.as_ref());
+        self.as_slice().hash(state)
     }
 }
 
 impl<H: Hasher> Hashable<H> for bool {
     fn hash(&self, state: &mut H) {
-        state.write_u8(*self as u8)
+        (*self as u8).hash(state)
     }
 }
 
 impl<H: Hasher> Hashable<H> for char {
     fn hash(&self, state: &mut H) {
-        state.write_u32(*self as u32)
+        (*self as u32).hash(state)
     }
 }
 
 impl<H: Hasher> Hashable<H> for str {
     fn hash(&self, state: &mut H) {
+        write_len(self.len(), state);
         state.write(self.as_bytes());
-        // empty str nope: state.write_u8(0xff)
     }
 }
 
+#[cfg(feature = "alloc")]
 impl<H: Hasher> Hashable<H> for String {
     fn hash(&self, state: &mut H) {
-        state.write(self.as_bytes());
-        // empty str nope: state.write_u8(0xff)
+        self.as_str().hash(state)
+    }
+}
+
+impl<H: Hasher, T: Hashable<H>> Hashable<H> for Option<T> {
+    fn hash(&self, state: &mut H) {
+        match self {
+            None => 0u8.hash(state),
+            Some(x) => {
+                1u8.hash(state);
+                x.hash(state);
+            }
+        }
     }
 }
 
@@ -140,18 +171,18 @@ impl_hash_tuple! { A B C D E F G H I J K L }
 
 impl<H: Hasher, T: Hashable<H>> Hashable<H> for [T] {
     fn hash(&self, state: &mut H) {
-        self.len().hash(state);
+        write_len(self.len(), state);
         Hashable::hash_slice(self, state)
     }
 }
 
-impl<'a, H: Hasher, T: ?Sized + Hashable<H>> Hashable<H> for &'a T {
+impl<H: Hasher, T: ?Sized + Hashable<H>> Hashable<H> for &T {
     fn hash(&self, state: &mut H) {
         (**self).hash(state);
     }
 }
 
-impl<'a, H: Hasher, T: ?Sized + Hashable<H>> Hashable<H> for &'a mut T {
+impl<H: Hasher, T: ?Sized + Hashable<H>> Hashable<H> for &mut T {
     fn hash(&self, state: &mut H) {
         (**self).hash(state);
     }
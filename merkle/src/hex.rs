@@ -0,0 +1,196 @@
+#[cfg(feature = "alloc")]
+extern crate alloc;
+
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
+use alloc::vec::Vec;
+use core::fmt;
+#[cfg(feature = "alloc")]
+use core::fmt::Write;
+
+/// Displays the bytes as their lowercase hex, `0x` prefixed in the
+/// alternate form as [`crate::fixed_hash::FixedHash`] displays, with no
+/// string allocated, such as of the hashes of the trees in `format!` and
+/// the log lines.
+///
+/// ```
+/// use merkle_light::hex::HexDisplay;
+///
+/// let h = [0xab, 0x01, 0xff];
+/// assert_eq!(format!("{}", HexDisplay::new(&h)), "ab01ff");
+/// assert_eq!(format!("root {:#}", HexDisplay::new(&h)), "root 0xab01ff");
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct HexDisplay<'a>(&'a [u8]);
+
+impl<'a> HexDisplay<'a> {
+    /// Creates new display of the bytes of the hash `h`.
+    pub fn new<T: AsRef<[u8]> + ?Sized>(h: &'a T) -> HexDisplay<'a> {
+        HexDisplay(h.as_ref())
+    }
+}
+
+impl fmt::Display for HexDisplay<'_> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        if f.alternate() {
+            f.write_str("0x")?;
+        }
+        for b in self.0 {
+            write!(f, "{:02x}", b)?;
+        }
+        Ok(())
+    }
+}
+
+/// Error of parsing the hex of [`from_hex`] and [`parse_hash`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum HexError {
+    /// Hex is of the odd number of digits, of no whole bytes.
+    OddLength {
+        /// Number of the digits, past the `0x` prefix.
+        digits: usize,
+    },
+    /// Hex is not of the bytes of the hash.
+    Length {
+        /// Length of the hash.
+        expected: usize,
+        /// Length of the bytes of the hex.
+        found: usize,
+    },
+    /// Hex has a character other than a hex digit at `index`.
+    Digit {
+        /// Index of the character in the hex, past the `0x` prefix.
+        index: usize,
+    },
+}
+
+impl fmt::Display for HexError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            HexError::OddLength { digits } => {
+                write!(f, "hex of {} digits is of no whole bytes", digits)
+            }
+            HexError::Length { expected, found } => {
+                write!(f, "hash of {} bytes is parsed of {} bytes", expected, found)
+            }
+            HexError::Digit { index } => write!(f, "invalid hex digit at {}", index),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HexError {}
+
+/// Error of parsing the hashes of [`parse_hashes`], of the line the hash of
+/// which fails to parse.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub struct HexLineError {
+    /// Number of the line, from 1.
+    pub line: usize,
+    /// Error of the hash of the line.
+    pub error: HexError,
+}
+
+impl fmt::Display for HexLineError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.error)
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for HexLineError {}
+
+/// Returns the `0x` prefixed lowercase hex of the bytes, as
+/// [`crate::proof::Proof::to_hex`] encodes them.
+#[cfg(feature = "alloc")]
+pub fn to_hex(bytes: &[u8]) -> String {
+    let mut s = String::with_capacity(2 + 2 * bytes.len());
+    let _ = write!(s, "{:#}", HexDisplay(bytes));
+    s
+}
+
+/// Parses the bytes of the hex, with or without the `0x` prefix, in any
+/// case.
+#[cfg(feature = "alloc")]
+pub fn from_hex(s: &str) -> Result<Vec<u8>, HexError> {
+    let digits = digits(s)?;
+    let mut bytes = alloc::vec![0; digits.len() / 2];
+    decode(digits, &mut bytes)?;
+    Ok(bytes)
+}
+
+/// Parses the hash of the hex of exactly the bytes of `T`, with or without
+/// the `0x` prefix, in any case, such as of the leaf received as text.
+///
+/// ```
+/// use merkle_light::hex::{parse_hash, HexError};
+///
+/// assert_eq!(parse_hash::<[u8; 2]>("0xAB01"), Ok([0xab, 0x01]));
+/// assert_eq!(
+///     parse_hash::<[u8; 2]>("ab01ff"),
+///     Err(HexError::Length { expected: 2, found: 3 })
+/// );
+/// assert_eq!(parse_hash::<[u8; 2]>("ab0"), Err(HexError::OddLength { digits: 3 }));
+/// assert_eq!(parse_hash::<[u8; 2]>("ab0g"), Err(HexError::Digit { index: 3 }));
+/// ```
+pub fn parse_hash<T: Default + AsMut<[u8]>>(s: &str) -> Result<T, HexError> {
+    let digits = digits(s)?;
+    let mut h = T::default();
+    let (expected, found) = (h.as_mut().len(), digits.len() / 2);
+    if expected != found {
+        return Err(HexError::Length { expected, found });
+    }
+    decode(digits, h.as_mut())?;
+    Ok(h)
+}
+
+/// Parses the hashes of the lines of `text`, one per line, as
+/// [`parse_hash`] does, such as of the leafs exported one per line. Spaces
+/// around the hashes and the blank lines are skipped.
+///
+/// Fails with the first line which does not parse.
+///
+/// ```
+/// use merkle_light::hex::parse_hashes;
+///
+/// let leafs: Vec<[u8; 2]> = parse_hashes("0x0001\n\n  0002\r\n0003\n").unwrap();
+/// assert_eq!(leafs, [[0, 1], [0, 2], [0, 3]]);
+///
+/// let err = parse_hashes::<[u8; 2]>("0001\nxyz\n").unwrap_err();
+/// assert_eq!(err.line, 2);
+/// assert_eq!(err.to_string(), "line 2: hex of 3 digits is of no whole bytes");
+/// ```
+#[cfg(feature = "alloc")]
+pub fn parse_hashes<T: Default + AsMut<[u8]>>(text: &str) -> Result<Vec<T>, HexLineError> {
+    text.lines()
+        .enumerate()
+        .map(|(i, line)| (i + 1, line.trim()))
+        .filter(|(_, line)| !line.is_empty())
+        .map(|(line, s)| parse_hash(s).map_err(|error| HexLineError { line, error }))
+        .collect()
+}
+
+/// Returns the digits of the hex past the `0x` prefix, of whole bytes.
+fn digits(s: &str) -> Result<&[u8], HexError> {
+    let digits = s.strip_prefix("0x").unwrap_or(s).as_bytes();
+    match digits.len() & 1 {
+        0 => Ok(digits),
+        _ => Err(HexError::OddLength {
+            digits: digits.len(),
+        }),
+    }
+}
+
+/// Decodes the pairs of `digits` into the bytes of `out`, as many.
+fn decode(digits: &[u8], out: &mut [u8]) -> Result<(), HexError> {
+    let hex = |index: usize| {
+        (digits[index] as char)
+            .to_digit(16)
+            .ok_or(HexError::Digit { index })
+    };
+    for (i, b) in out.iter_mut().enumerate() {
+        *b = (hex(2 * i)? << 4 | hex(2 * i + 1)?) as u8;
+    }
+    Ok(())
+}
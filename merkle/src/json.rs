@@ -0,0 +1,199 @@
+use std::fmt::{self, Write};
+use std::marker::PhantomData;
+use serde::de::{self, Deserialize, Deserializer, Visitor};
+use serde::ser::{Serialize, Serializer};
+use crate::hash::Algorithm;
+use crate::merkle::{MerkleTree, PaddingStrategy, PairOrdering};
+use crate::proof::{Lemma, Proof};
+
+/// Hash serializing as the `0x` prefixed lowercase hex string of its bytes.
+///
+/// Deserializes from the hex string with or without the prefix, of exactly
+/// as many bytes as `T` has, in any case.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Default, Hash)]
+pub struct Hex<T>(pub T);
+
+impl<T: AsRef<[u8]>> Serialize for Hex<T> {
+    fn serialize<S: Serializer>(&self, serializer: S) -> Result<S::Ok, S::Error> {
+        let bytes = self.0.as_ref();
+        let mut s = String::with_capacity(2 + 2 * bytes.len());
+        s.push_str("0x");
+        for b in bytes {
+            let _ = write!(s, "{:02x}", b);
+        }
+        serializer.serialize_str(&s)
+    }
+}
+
+impl<'de, T: Default + AsMut<[u8]>> Deserialize<'de> for Hex<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        deserializer.deserialize_str(HexVisitor(PhantomData))
+    }
+}
+
+struct HexVisitor<T>(PhantomData<T>);
+
+impl<T: Default + AsMut<[u8]>> Visitor<'_> for HexVisitor<T> {
+    type Value = Hex<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "hex string of {} bytes", T::default().as_mut().len())
+    }
+
+    fn visit_str<E: de::Error>(self, s: &str) -> Result<Hex<T>, E> {
+        let digits = s.strip_prefix("0x").unwrap_or(s).as_bytes();
+        if digits.len() & 1 == 1 {
+            return Err(E::custom(format!("odd number of hex digits in {:?}", s)));
+        }
+
+        let mut x = T::default();
+        let bytes = x.as_mut();
+        if digits.len() != 2 * bytes.len() {
+            return Err(E::invalid_length(digits.len() / 2, &self));
+        }
+
+        for (b, pair) in bytes.iter_mut().zip(digits.chunks(2)) {
+            let hex = |d: u8| {
+                (d as char)
+                    .to_digit(16)
+                    .ok_or_else(|| E::custom(format!("invalid hex digit in {:?}", s)))
+            };
+            *b = (hex(pair[0])? << 4 | hex(pair[1])?) as u8;
+        }
+        Ok(Hex(x))
+    }
+}
+
+/// Tree in JSON, as the nodes in memory layout.
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "T: AsRef<[u8]>", deserialize = "T: Default + AsMut<[u8]>"))]
+struct TreeJson<T> {
+    leafs: usize,
+    height: usize,
+    // missing of the trees exported before they were
+    #[serde(default)]
+    padding: PaddingStrategy,
+    #[serde(default)]
+    ordering: PairOrdering,
+    root: Hex<T>,
+    nodes: Vec<Hex<T>>,
+}
+
+/// Proof in JSON, as the lemma and path of [`Proof`].
+#[derive(serde::Serialize, serde::Deserialize)]
+#[serde(bound(serialize = "T: AsRef<[u8]>", deserialize = "T: Default + AsMut<[u8]>"))]
+struct ProofJson<T> {
+    lemma: Vec<Hex<T>>,
+    path: Vec<bool>,
+    #[serde(default)]
+    index: Option<usize>,
+    #[serde(default)]
+    depth: Option<usize>,
+    #[serde(default)]
+    ordering: PairOrdering,
+}
+
+/// Step of the proof in JSON, as merkletreejs has it.
+#[derive(serde::Serialize)]
+#[serde(bound(serialize = "T: AsRef<[u8]>"))]
+struct JsProofNodeJson<T> {
+    position: &'static str,
+    data: Hex<T>,
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> MerkleTree<T, A> {
+    /// Returns the tree as JSON object of number of leafs, height, padding
+    /// strategy, pair ordering, root and all the nodes in memory layout,
+    /// every hash in hex:
+    ///
+    /// ```text
+    /// {"leafs":3,"height":3,"padding":"HashWithSelf","ordering":"Ordered","root":"0x…","nodes":["0x…",…]}
+    /// ```
+    pub fn to_json(&self) -> String {
+        let tree = TreeJson {
+            leafs: self.leafs(),
+            height: self.height(),
+            padding: self.padding_strategy(),
+            ordering: self.pair_ordering(),
+            root: Hex(self.root()),
+            nodes: self.as_slice().iter().cloned().map(Hex).collect(),
+        };
+        serde_json::to_string(&tree).expect("hex strings serialize")
+    }
+
+    /// Parses the tree from JSON as written by [`MerkleTree::to_json`].
+    ///
+    /// Only the layout of the nodes is validated, as in
+    /// [`MerkleTree::from_parts`], and the root against the nodes.
+    pub fn from_json(s: &str) -> Result<MerkleTree<T, A>, serde_json::Error>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        let tree: TreeJson<T> = serde_json::from_str(s)?;
+        let nodes = tree.nodes.into_iter().map(|h| h.0).collect();
+        let mt = MerkleTree::from_parts(nodes, tree.leafs, tree.height);
+        let mt = mt.map_err(de::Error::custom)?.with_padding(tree.padding);
+        let mt = mt.with_pair_ordering(tree.ordering);
+        if mt.root() != tree.root.0 {
+            return Err(de::Error::custom("root does not match the tree nodes"));
+        }
+        Ok(mt)
+    }
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> Proof<T> {
+    /// Returns the proof as JSON object of the lemma, every hash in hex, the
+    /// path, the leaf index, the tree depth and the pair ordering:
+    ///
+    /// ```text
+    /// {"lemma":["0x…",…],"path":[true,…],"index":4,"depth":3,"ordering":"Ordered"}
+    /// ```
+    pub fn to_json(&self) -> String {
+        let proof = ProofJson {
+            lemma: self.lemma().iter().cloned().map(Hex).collect(),
+            path: self.path().to_vec(),
+            index: Some(self.index()),
+            depth: Some(self.depth()),
+            ordering: self.pair_ordering(),
+        };
+        serde_json::to_string(&proof).expect("hex strings serialize")
+    }
+
+    /// Returns the steps of the proof of [`Proof::to_js_proof`] as JSON
+    /// array of the objects of the position of the sibling and the sibling
+    /// in hex, as `getProof` of merkletreejs returns them and
+    /// `MerkleTree.verify` takes them:
+    ///
+    /// ```text
+    /// [{"position":"left","data":"0x…"},{"position":"right","data":"0x…"},…]
+    /// ```
+    pub fn to_js_json(&self) -> String {
+        let proof: Vec<JsProofNodeJson<T>> = self
+            .to_js_proof()
+            .into_iter()
+            .map(|node| JsProofNodeJson {
+                position: node.position.as_str(),
+                data: Hex(node.data),
+            })
+            .collect();
+        serde_json::to_string(&proof).expect("hex strings serialize")
+    }
+
+    /// Parses the proof from JSON as written by [`Proof::to_json`]. Index and
+    /// depth left out are the ones the path tells, and the ordering left
+    /// out is the default.
+    pub fn from_json(s: &str) -> Result<Proof<T>, serde_json::Error>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        let json: ProofJson<T> = serde_json::from_str(s)?;
+        let lemma: Lemma<T> = json.lemma.into_iter().map(|h| h.0).collect();
+        let proof = Proof::try_new(lemma, json.path).map_err(de::Error::custom)?;
+        let index = json.index.unwrap_or(proof.index());
+        let depth = json.depth.unwrap_or(proof.depth());
+        proof
+            .with_pair_ordering(json.ordering)
+            .try_with_position(index, depth)
+            .map_err(de::Error::custom)
+    }
+}
@@ -0,0 +1,237 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use crate::hash::Algorithm;
+use crate::merkle::MerkleError;
+
+/// Merkle tree of `arity` children per node, which proofs hold `arity - 1`
+/// siblings per level, of fewer levels than the binary tree has.
+///
+/// Levels are laid out bottom-up as in [`crate::merkle::MerkleTree`], and
+/// the last node of a level of width not divisible by the arity is repeated
+/// to fill up the children of its parent, which generalizes the duplicate of
+/// the binary tree. Interior nodes are hashed with
+/// [`Algorithm::multi_node`], so that the tree of arity 2 is the binary one.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct KaryMerkleTree<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> {
+    data: Vec<T>,
+    leafs: usize,
+    arity: usize,
+    _a: PhantomData<A>,
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> KaryMerkleTree<T, A> {
+    /// Creates new merkle tree of `arity` children per node from a sequence
+    /// of hashes.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the arity is less than 2.
+    pub fn new<I: IntoIterator<Item = T>>(data: I, arity: usize) -> KaryMerkleTree<T, A> {
+        Self::try_new(data, arity).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new merkle tree of `arity` children per node.
+    ///
+    /// Fallible version of [`KaryMerkleTree::new`].
+    pub fn try_new<I: IntoIterator<Item = T>>(
+        data: I,
+        arity: usize,
+    ) -> Result<KaryMerkleTree<T, A>, MerkleError> {
+        if arity < 2 {
+            return Err(MerkleError::InvalidArity { arity });
+        }
+
+        let mut a = A::default();
+        let mut data: Vec<T> = data
+            .into_iter()
+            .map(|item| {
+                a.reset();
+                a.leaf(item)
+            })
+            .collect();
+        let leafs = data.len();
+
+        let mut children = Vec::with_capacity(arity);
+        let (mut base, mut width) = (0, leafs);
+        while width > 1 {
+            for i in (base..base + width).step_by(arity) {
+                children.clear();
+                children.extend((i..i + arity).map(|j| data[j.min(base + width - 1)].clone()));
+                a.reset();
+                let h = a.multi_node(&children);
+                data.push(h);
+            }
+            base += width;
+            width = parent_width(width, arity);
+        }
+
+        Ok(KaryMerkleTree {
+            data,
+            leafs,
+            arity,
+            _a: PhantomData,
+        })
+    }
+
+    /// Returns merkle root.
+    pub fn root(&self) -> T {
+        match self.data.last() {
+            Some(root) => root.clone(),
+            None => A::default().hash(),
+        }
+    }
+
+    /// Returns number of leafs.
+    pub fn leafs(&self) -> usize {
+        self.leafs
+    }
+
+    /// Returns number of children per node.
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Returns height of the tree, which is 0 for an empty tree.
+    pub fn height(&self) -> usize {
+        let mut width = self.leafs;
+        let mut height = (width > 0) as usize;
+        while width > 1 {
+            width = parent_width(width, self.arity);
+            height += 1;
+        }
+        height
+    }
+
+    /// Returns all the tree nodes in memory layout, bottom-up. Repeated
+    /// nodes filling up the levels are not stored.
+    pub fn as_slice(&self) -> &[T] {
+        &self.data
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`.
+    pub fn gen_proof(&self, i: usize) -> KaryProof<T> {
+        self.try_gen_proof(i).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`.
+    ///
+    /// Fallible version of [`KaryMerkleTree::gen_proof`].
+    pub fn try_gen_proof(&self, i: usize) -> Result<KaryProof<T>, MerkleError> {
+        if i >= self.leafs {
+            return Err(MerkleError::IndexOutOfBounds {
+                index: i,
+                leafs: self.leafs,
+            });
+        }
+
+        let mut lemma = Vec::with_capacity(self.height() * (self.arity - 1) + 1);
+        lemma.push(self.data[i].clone());
+        let mut path = Vec::new();
+        let (mut base, mut width, mut j) = (0, self.leafs, i);
+        while width > 1 {
+            let first = j - j % self.arity;
+            for k in first..first + self.arity {
+                if k != j {
+                    lemma.push(self.data[base + k.min(width - 1)].clone());
+                }
+            }
+            path.push(j - first);
+
+            base += width;
+            width = parent_width(width, self.arity);
+            j /= self.arity;
+        }
+        lemma.push(self.root());
+
+        Ok(KaryProof::new(lemma, path, self.arity))
+    }
+}
+
+/// Merkle inclusion proof of the leaf of [`KaryMerkleTree`].
+///
+/// Lemma layout is as of [`crate::proof::Proof`], with `arity - 1` siblings
+/// per level in order, and the position of the node among its siblings per
+/// level in place of the direction:
+///
+/// ```text
+/// [ item s11 .. s1(k-1) s21 .. s2(k-1) ... root ]
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct KaryProof<T: Eq + Clone + AsRef<[u8]>> {
+    lemma: Vec<T>,
+    path: Vec<usize>,
+    arity: usize,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> KaryProof<T> {
+    /// Creates new k-ary proof of the lemma, the positions of the nodes on
+    /// the path among their siblings, bottom-up, and the arity of the tree.
+    pub fn new(lemma: Vec<T>, path: Vec<usize>, arity: usize) -> KaryProof<T> {
+        KaryProof { lemma, path, arity }
+    }
+
+    /// Returns the lemma, the item, the siblings bottom-up, and the root.
+    pub fn lemma(&self) -> &[T] {
+        &self.lemma
+    }
+
+    /// Returns the positions of the nodes on the path among their siblings,
+    /// bottom-up.
+    pub fn path(&self) -> &[usize] {
+        &self.path
+    }
+
+    /// Returns number of children per node.
+    pub fn arity(&self) -> usize {
+        self.arity
+    }
+
+    /// Returns the leaf hash the proof is of.
+    pub fn item(&self) -> T {
+        self.lemma.first().unwrap().clone()
+    }
+
+    /// Returns the root the proof carries.
+    pub fn root(&self) -> T {
+        self.lemma.last().unwrap().clone()
+    }
+
+    /// Verifies the proof against the root it carries.
+    pub fn validate<A: Algorithm<T>>(&self) -> bool {
+        !self.lemma.is_empty() && self.validate_against::<A>(&self.root())
+    }
+
+    /// Verifies the proof against the trusted root `root`.
+    pub fn validate_against<A: Algorithm<T>>(&self, root: &T) -> bool {
+        let siblings = self.arity.saturating_sub(1);
+        if self.arity < 2 || self.lemma.len() != self.path.len() * siblings + 2 {
+            return false;
+        }
+
+        let mut a = A::default();
+        let mut children = Vec::with_capacity(self.arity);
+        let mut h = self.item();
+        for (level, &position) in self.path.iter().enumerate() {
+            if position >= self.arity {
+                return false;
+            }
+
+            let start = 1 + level * siblings;
+            let row = &self.lemma[start..start + siblings];
+            children.clear();
+            children.extend_from_slice(&row[..position]);
+            children.push(h);
+            children.extend_from_slice(&row[position..]);
+            a.reset();
+            h = a.multi_node(&children);
+        }
+        h == *root
+    }
+}
+
+/// Returns the width of the level above the level of `width` nodes.
+fn parent_width(width: usize, arity: usize) -> usize {
+    width.div_ceil(arity)
+}
@@ -0,0 +1,80 @@
+use core::hash::Hasher;
+use sha3::{Digest, Keccak256};
+use crate::fixed_hash::{FixedHash, Hash32};
+use crate::hash::Algorithm;
+
+/// Keccak-256 hash of the trees.
+pub type Keccak256Hash = Hash32;
+
+/// Keccak-256 [`Algorithm`] of the trees over [`Keccak256Hash`], which
+/// hashes as the Solidity contracts do with
+/// `keccak256(abi.encodePacked(left, right))`.
+///
+/// Semantics are frozen, so that the roots never change:
+///
+/// ```text
+/// hash()        = Keccak-256(everything written since reset)
+/// leaf(x)       = x, or Keccak-256(x) if HASH_LEAFS
+/// node(l, r)    = Keccak-256(l || r)
+/// finish()      = first 8 bytes of hash() as big endian u64
+/// ```
+///
+/// Nodes have no prefix, as there is none on chain, so that an interior
+/// node can pass for a leaf unless the verifier fixes the depth of the
+/// proofs, see [`crate::hash::DomainSeparated`]. Default algorithm takes the leafs pre-hashed by the caller as
+/// they are, see [`Keccak256LeafAlgorithm`] for the one which hashes them.
+#[derive(Debug, Clone, Default)]
+pub struct Keccak256Algorithm<const HASH_LEAFS: bool = false>(Keccak256);
+
+/// Keccak-256 algorithm which hashes the leafs, `leaf(x) = Keccak-256(x)`.
+pub type Keccak256LeafAlgorithm = Keccak256Algorithm<true>;
+
+impl<const HASH_LEAFS: bool> Keccak256Algorithm<HASH_LEAFS> {
+    /// Creates new algorithm with nothing written.
+    pub fn new() -> Keccak256Algorithm<HASH_LEAFS> {
+        Keccak256Algorithm(Keccak256::new())
+    }
+}
+
+impl<const HASH_LEAFS: bool> Hasher for Keccak256Algorithm<HASH_LEAFS> {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.update(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        let h = self.0.clone().finalize();
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&h[..8]);
+        u64::from_be_bytes(bytes)
+    }
+}
+
+impl<const HASH_LEAFS: bool> Algorithm<Keccak256Hash> for Keccak256Algorithm<HASH_LEAFS> {
+    #[inline]
+    fn hash(&mut self) -> Keccak256Hash {
+        FixedHash(self.0.clone().finalize().into())
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.0 = Keccak256::new();
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: Keccak256Hash) -> Keccak256Hash {
+        if !HASH_LEAFS {
+            return leaf;
+        }
+        self.0.update(leaf);
+        self.hash()
+    }
+
+    #[inline]
+    fn node(&mut self, left: Keccak256Hash, right: Keccak256Hash) -> Keccak256Hash {
+        self.0.update(left);
+        self.0.update(right);
+        self.hash()
+    }
+}
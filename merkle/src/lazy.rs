@@ -0,0 +1,188 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::cell::RefCell;
+use core::cmp;
+use core::iter::FromIterator;
+use core::marker::PhantomData;
+use crate::hash::{hash_data, Hashable, Algorithm};
+use crate::merkle::{leaf_index, tree_layout, MerkleError, MerkleTree};
+use crate::proof::{Lemma, Path, Proof};
+
+/// Merkle tree which stores the leafs only and computes the nodes on demand.
+///
+/// Nodes are computed when first requested by [`LazyMerkleTree::root`],
+/// [`LazyMerkleTree::node`] or [`LazyMerkleTree::gen_proof`], and memoized
+/// for later requests. Tree levels, nodes, roots and proofs are the same as
+/// of [`MerkleTree`] built upon the same leafs.
+///
+/// Memoized nodes live in a `RefCell`, so the tree is `Send` but not `Sync`:
+/// it can be moved to another thread, but sharing it between threads takes
+/// a lock, such as a `Mutex`, around it.
+#[derive(Debug, Clone)]
+pub struct LazyMerkleTree<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> {
+    leafs: Vec<T>,
+    nodes: RefCell<Vec<Option<T>>>,
+    /// Index of the first node of each level above the leafs in `nodes`.
+    offsets: Vec<usize>,
+    height: usize,
+    _a: PhantomData<A>,
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> LazyMerkleTree<T, A> {
+    /// Creates new lazy merkle tree from a sequence of hashes.
+    pub fn new<I: IntoIterator<Item = T>>(data: I) -> LazyMerkleTree<T, A> {
+        Self::try_new(data).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new lazy merkle tree from a sequence of hashes.
+    ///
+    /// Fallible version of [`LazyMerkleTree::new`].
+    pub fn try_new<I: IntoIterator<Item = T>>(
+        data: I,
+    ) -> Result<LazyMerkleTree<T, A>, MerkleError> {
+        let mut a = A::default();
+        let leafs: Vec<T> = data
+            .into_iter()
+            .map(|item| {
+                a.reset();
+                a.leaf(item)
+            })
+            .collect();
+
+        let (_, height) = tree_layout(leafs.len())?;
+
+        // levels above the leafs, without the duplicates filling up odd ones
+        let mut offsets = Vec::with_capacity(height);
+        let mut size = 0;
+        let mut width = leafs.len();
+        for _ in 1..height {
+            width = (width + 1) >> 1;
+            offsets.push(size);
+            size += width;
+        }
+
+        let mut nodes = Vec::with_capacity(size);
+        nodes.resize(size, None);
+
+        Ok(LazyMerkleTree {
+            leafs,
+            nodes: RefCell::new(nodes),
+            offsets,
+            height,
+            _a: PhantomData,
+        })
+    }
+
+    /// Creates new lazy merkle tree from a list of hashable objects.
+    pub fn from_data<O: Hashable<A>, I: IntoIterator<Item = O>>(data: I) -> LazyMerkleTree<T, A> {
+        let mut a = A::default();
+        Self::new(data.into_iter().map(|x| hash_data(&mut a, &x)))
+    }
+
+    /// Returns merkle root, computing the whole tree on the first call.
+    ///
+    /// Root of an empty tree is the hash of the empty data stream.
+    pub fn root(&self) -> T {
+        if self.height == 0 {
+            return A::default().hash();
+        }
+        self.compute(self.height - 1, 0)
+    }
+
+    /// Returns height of the tree, which is 0 for an empty tree.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns original number of elements the tree was built upon.
+    pub fn leafs(&self) -> usize {
+        self.leafs.len()
+    }
+
+    /// Returns hash of the leaf `i`, or `None` if `i >= leafs()`.
+    pub fn leaf(&self, i: usize) -> Option<&T> {
+        self.leafs.get(i)
+    }
+
+    /// Returns node `index` of the tree level `level`, as in
+    /// [`MerkleTree::node`], computing it if it is not yet.
+    pub fn node(&self, level: usize, index: usize) -> Option<T> {
+        if level >= self.height || index >= self.width(level) {
+            return None;
+        }
+        Some(self.compute(level, index))
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`
+    pub fn gen_proof(&self, i: usize) -> Proof<T> {
+        self.try_gen_proof(i).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`
+    ///
+    /// Fallible version of [`LazyMerkleTree::gen_proof`].
+    pub fn try_gen_proof(&self, i: usize) -> Result<Proof<T>, MerkleError> {
+        leaf_index(i, self.leafs.len())?;
+
+        let mut lemma = Lemma::with_capacity(self.height + 1); // path + root
+        let mut path = Path::with_capacity(self.height - 1); // path - 1
+
+        let mut j = i;
+        lemma.push(self.leafs[j].clone());
+        for level in 0..self.height - 1 {
+            lemma.push(self.compute(level, j ^ 1));
+            path.push(j & 1 == 0);
+            j >>= 1;
+        }
+
+        // root is final
+        lemma.push(self.root());
+        Ok(Proof::new(lemma, path))
+    }
+
+    /// Builds the regular merkle tree upon the leafs.
+    pub fn into_tree(self) -> MerkleTree<T, A> {
+        MerkleTree::from_leafs(self.leafs.into()).expect("tree layout is valid")
+    }
+
+    /// Returns the number of nodes of the level, not counting the node
+    /// duplicated to fill up odd levels.
+    fn width(&self, level: usize) -> usize {
+        let mut width = self.leafs.len();
+        for _ in 0..level {
+            width = (width + 1) >> 1;
+        }
+        width
+    }
+
+    /// Returns node `index` of the level `level`, which is the last node of
+    /// the level for the index of the duplicate filling up an odd level.
+    fn compute(&self, level: usize, index: usize) -> T {
+        let index = cmp::min(index, self.width(level) - 1);
+        if level == 0 {
+            return self.leafs[index].clone();
+        }
+
+        let slot = self.offsets[level - 1] + index;
+        if let Some(ref h) = self.nodes.borrow()[slot] {
+            return h.clone();
+        }
+
+        // borrow is released while the children are computed
+        let left = self.compute(level - 1, 2 * index);
+        let right = self.compute(level - 1, 2 * index + 1);
+        let mut a = A::default();
+        let h = a.node(left, right);
+
+        self.nodes.borrow_mut()[slot] = Some(h.clone());
+        h
+    }
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> FromIterator<T> for LazyMerkleTree<T, A> {
+    /// Creates new lazy merkle tree from an iterator over hashes.
+    fn from_iter<I: IntoIterator<Item = T>>(into: I) -> Self {
+        Self::new(into)
+    }
+}
@@ -59,6 +59,17 @@
 //! - validate_proof (proof, leaf, root) -> bool
 //! ```
 //!
+//! # `no_std`
+//!
+//! Crate is `no_std` without the default `std` feature. Trees, their
+//! stores and the proofs of [`proof::Proof`] allocate, and need the
+//! `alloc` feature. Paths of the proofs verify without `alloc` too, of the
+//! hashes given as slices, with [`verify`] and [`proof::verify_path`]:
+//!
+//! ```toml
+//! merkle_light = { version = "0.4", default-features = false, features = ["sha256"] }
+//! ```
+//!
 //! # Examples
 //!
 //! [`test_sip.rs`]: algorithm implementation example for std sip hasher, u64 hash items
@@ -162,12 +173,139 @@ pub mod hash;
 /// Common implementations for [`Hashable`].
 mod hash_impl;
 
+/// Fixed size hashes, the elements of the trees of the built-in algorithms.
+pub mod fixed_hash;
+
+/// Hex of the hashes, displayed with no allocation and parsed of the
+/// bytes of the hash.
+pub mod hex;
+
+/// [`Hashable`](hash::Hashable) of the serde serializable types.
+#[cfg(feature = "serde")]
+pub mod serde_hashable;
+
 /// Merkle tree inclusion proof
 pub mod proof;
 
+pub use proof::{verify, verify_raw_leaf};
+
 /// Merkle tree abstractions, implementation and algorithms.
+#[cfg(feature = "alloc")]
 pub mod merkle;
 
+/// Storage of the merkle tree nodes.
+#[cfg(feature = "alloc")]
+pub mod store;
+
+/// Storage of the merkle tree nodes in memory mapped files.
+#[cfg(feature = "mmap")]
+pub mod mmap;
+
+/// Merkle tree computing its nodes on demand.
+#[cfg(feature = "alloc")]
+pub mod lazy;
+
+/// Merkle tree builder hashing the leafs as they arrive.
+#[cfg(feature = "alloc")]
+pub mod builder;
+
+/// Cursor navigating the nodes of the merkle tree.
+#[cfg(feature = "alloc")]
+pub mod cursor;
+
+/// Merkle tree frozen into the boxed slice of its nodes.
+#[cfg(feature = "alloc")]
+pub mod frozen;
+
+/// Merkle tree pruned down to the chosen inclusion proofs.
+#[cfg(feature = "alloc")]
+pub mod pruned;
+
+/// Merkle tree without the leaf level.
+#[cfg(feature = "alloc")]
+pub mod compact;
+
+/// Combined inclusion proof of several leafs.
+#[cfg(feature = "alloc")]
+pub mod multiproof;
+
+/// Inclusion proof of a run of leafs.
+#[cfg(feature = "alloc")]
+pub mod range;
+
+/// Proof of one tree being a prefix of another.
+#[cfg(feature = "alloc")]
+pub mod consistency;
+
+/// Merkle tree over the sorted items, with the proofs of their absence.
+#[cfg(feature = "alloc")]
+pub mod sorted;
+
+/// Merkle trees of many children per node, with their proofs.
+#[cfg(feature = "alloc")]
+pub mod kary;
+
+/// Merkle mountain ranges, the append-only accumulators of perfect trees.
+#[cfg(feature = "alloc")]
+pub mod mmr;
+
+/// Sparse merkle trees of the values at the keys of the bits of the hashes.
+#[cfg(feature = "std")]
+pub mod sparse;
+
+/// Trees and proofs of merkletreejs.
+#[cfg(feature = "alloc")]
+pub mod merkletreejs;
+
+/// Stable binary encoding of the trees.
+#[cfg(feature = "std")]
+pub mod codec;
+
+/// JSON export of the trees and proofs with hashes in hex.
+#[cfg(feature = "json")]
+pub mod json;
+
+/// SHA-256 algorithms of the trees, plain and as in Bitcoin.
+#[cfg(feature = "sha256")]
+pub mod sha256;
+
+/// Certificate Transparency logs of RFC 6962.
+#[cfg(all(feature = "sha256", feature = "alloc"))]
+pub mod ct;
+
+/// Simple merkle trees and proofs of Tendermint and CometBFT.
+#[cfg(all(feature = "sha256", feature = "alloc"))]
+pub mod tendermint;
+
+/// BLAKE3 algorithm of the trees.
+#[cfg(feature = "blake3")]
+pub mod blake3;
+
+/// Keccak-256 algorithm of the trees, as in Solidity.
+#[cfg(feature = "keccak")]
+pub mod keccak;
+
+/// Poseidon algorithm of the BN254 field elements, of the circomlib
+/// parameters of the circuits.
+#[cfg(feature = "poseidon")]
+pub mod poseidon;
+
+/// Algorithm of the trees of any RustCrypto digest.
+#[cfg(feature = "digest")]
+pub mod digest;
+
+/// JavaScript bindings of the SHA-256 trees for WebAssembly.
+#[cfg(feature = "wasm")]
+pub mod wasm;
+
+/// C ABI of the SHA-256 trees and proofs.
+#[cfg(feature = "ffi")]
+pub mod ffi;
+
+/// Proptest strategies of the random trees and proofs.
+#[cfg(feature = "proptest")]
+pub mod strategy;
+
 /// Tests data.
 #[cfg(test)]
 mod test_item;
@@ -183,3 +321,111 @@ mod test_sip;
 /// Tests for Merkle Hasher Customization
 #[cfg(test)]
 mod test_cmh;
+
+/// Tests stores.
+#[cfg(test)]
+mod test_store;
+
+/// Tests lazy trees.
+#[cfg(test)]
+mod test_lazy;
+
+/// Tests builders.
+#[cfg(test)]
+mod test_builder;
+
+/// Tests cursors.
+#[cfg(test)]
+mod test_cursor;
+
+/// Tests frozen trees.
+#[cfg(test)]
+mod test_frozen;
+
+/// Tests pruned trees.
+#[cfg(test)]
+mod test_pruned;
+
+/// Tests compact trees.
+#[cfg(test)]
+mod test_compact;
+
+/// Tests multiproofs.
+#[cfg(test)]
+mod test_multiproof;
+
+/// Tests range proofs.
+#[cfg(test)]
+mod test_range;
+
+/// Tests consistency proofs.
+#[cfg(test)]
+mod test_consistency;
+
+/// Tests sorted trees.
+#[cfg(test)]
+mod test_sorted;
+
+/// Tests k-ary trees.
+#[cfg(test)]
+mod test_kary;
+
+/// Tests MMR.
+#[cfg(test)]
+mod test_mmr;
+
+/// Tests sparse trees.
+#[cfg(test)]
+mod test_sparse;
+
+/// Tests binary codec.
+#[cfg(test)]
+mod test_codec;
+
+/// Tests hex.
+#[cfg(test)]
+mod test_hex;
+
+/// Tests memory mapped stores.
+#[cfg(all(test, feature = "mmap"))]
+mod test_mmap;
+
+/// Tests JSON.
+#[cfg(all(test, feature = "json"))]
+mod test_json;
+
+/// Tests serde hashing.
+#[cfg(all(test, feature = "serde"))]
+mod test_serde_hashable;
+
+/// Tests SHA-256.
+#[cfg(all(test, feature = "sha256"))]
+mod test_sha256;
+
+/// Tests Certificate Transparency.
+#[cfg(all(test, feature = "sha256", feature = "alloc"))]
+mod test_ct;
+
+/// Tests merkletreejs.
+#[cfg(all(test, feature = "sha256", feature = "alloc"))]
+mod test_merkletreejs;
+
+/// Tests Tendermint.
+#[cfg(all(test, feature = "sha256", feature = "alloc"))]
+mod test_tendermint;
+
+/// Tests BLAKE3.
+#[cfg(all(test, feature = "blake3"))]
+mod test_blake3;
+
+/// Tests Keccak-256.
+#[cfg(all(test, feature = "keccak"))]
+mod test_keccak;
+
+/// Tests Poseidon.
+#[cfg(all(test, feature = "poseidon"))]
+mod test_poseidon;
+
+/// Tests RustCrypto digests.
+#[cfg(all(test, feature = "digest"))]
+mod test_digest;
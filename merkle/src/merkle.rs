@@ -1,15 +1,40 @@
 extern crate alloc;
 
-use alloc::vec::Vec;
-use core::iter::FromIterator;
+use alloc::collections::BTreeMap;
+use alloc::string::String;
+use alloc::vec::{self, Vec};
+use core::cmp;
+use core::fmt::{self, Write};
+use core::iter::{self, FromIterator};
 use core::marker::PhantomData;
 use core::ops;
-use crate::hash::{Hashable, Algorithm};
-use crate::proof::Proof;
+use core::slice::{self, SliceIndex};
+use crate::hash::{commit_leafs, hash_data, Hashable, Algorithm};
+use crate::hex::to_hex;
+use crate::proof::{Lemma, Path, Proof, ProofRef};
+#[cfg(feature = "serde")]
+use crate::proof::Bounded;
+use crate::compact::CompactTree;
+use crate::consistency::ConsistencyProof;
+use crate::cursor::Cursor;
+use crate::frozen::FrozenTree;
+use crate::multiproof::{decommitments, MultiProof};
+use crate::range::RangeProof;
+use crate::pruned::PrunedTree;
+use crate::store::{BoxStore, FlatStore, Store, VecStore};
+#[cfg(feature = "rayon")]
+use rayon::prelude::*;
+#[cfg(feature = "serde")]
+use serde::ser::SerializeStruct;
+#[cfg(feature = "serde")]
+use serde::{de, Deserialize, Deserializer, Serialize, Serializer};
+#[cfg(feature = "std")]
+use std::io;
 
 /// Merkle Tree.
 ///
-/// All leafs and nodes are stored in a linear array (vec).
+/// All leafs and nodes are stored in a linear array, the [`Store`] of the
+/// tree, which is [`VecStore`] vector by default.
 ///
 /// A merkle tree is a tree in which every non-leaf node is the hash of its
 /// children nodes. A diagram depicting how it works:
@@ -28,7 +53,8 @@ use crate::proof::Proof;
 ///     [h1 h2 h3 h4 h12 h34 root]
 /// ```
 ///
-/// Merkle root is always the last element in the array.
+/// Merkle root is always the last element in the array. Nodes can be
+/// accessed by their position in this layout with `tree[i]`.
 ///
 /// The number of inputs is not always a power of two which results in a
 /// balanced tree structure as above.  In that case, parent nodes with no
@@ -37,123 +63,2529 @@ use crate::proof::Proof;
 /// Since this function uses nodes that are pointers to the hashes, empty nodes
 /// will be nil.
 ///
+/// An empty tree has no leafs and no nodes at all, its root is the hash of
+/// the empty data stream of the algorithm (`MTH({}) = ALG()` in RFC 6962),
+/// and no inclusion proof can be generated for it.
+///
+/// A tree built upon a single leaf consists of that leaf alone, which is then
+/// also the merkle root (as `MTH({d(0)})` in RFC 6962). The only inclusion proof
+/// of such a tree has an empty path.
+///
+/// Tree keeps the algorithm it is built with, `A::default()` unless given,
+/// such as the keyed one, and hashes the nodes it changes with it, see
+/// [`MerkleTree::new_with`]. Trees are equal if their nodes are.
+///
+/// Leafs are counted and indexed as the tree is built upon them, with no
+/// padding: [`MerkleTree::leaf_count`] of them, of the indices below it, of
+/// every function taking or returning the index of a leaf, such as
+/// [`MerkleTree::gen_proof`], [`MerkleTree::set_leaf`] or
+/// [`MerkleTree::enumerate_leaves`], and of the leafs the tree is encoded
+/// of, such as of [`MerkleTree::encode`]. None takes the index of the
+/// padding. Only `tree[i]` and the functions of the memory layout, such as
+/// [`MerkleTree::level_of`], take the index of a node, of the nodes
+/// [`MerkleTree::node_count`] counts.
+///
+/// ```
+/// # use merkle_light::hash::Algorithm;
+/// # use merkle_light::merkle::{MerkleError, MerkleTree};
+/// # use std::collections::hash_map::DefaultHasher;
+/// # use std::hash::Hasher;
+/// # #[derive(Default)]
+/// # struct Sip(DefaultHasher);
+/// # impl Hasher for Sip {
+/// #     fn write(&mut self, msg: &[u8]) { self.0.write(msg) }
+/// #     fn finish(&self) -> u64 { self.0.finish() }
+/// # }
+/// # impl Algorithm<[u8; 8]> for Sip {
+/// #     fn hash(&mut self) -> [u8; 8] { self.finish().to_le_bytes() }
+/// #     fn reset(&mut self) { *self = Sip::default() }
+/// # }
+/// let mt: MerkleTree<[u8; 8], Sip> = MerkleTree::new((0..5u64).map(u64::to_le_bytes));
+/// assert_eq!(mt.leaf_count(), 5);
+/// // leafs are padded up to 8, indices 5, 6 and 7 are of the padding
+/// assert_eq!(mt.padded_leaf_count(), 8);
+/// assert_eq!(mt.padding_leaf_count(), 3);
+/// assert!(!mt.is_balanced());
+/// for i in 5..8 {
+///     assert_eq!(
+///         mt.try_gen_proof(i).unwrap_err(),
+///         MerkleError::PaddingIndex { index: i, leafs: 5 }
+///     );
+/// }
+/// // of which only the duplicate of the last leaf is stored, at 5
+/// assert_eq!(mt.padded_len(), 6);
+/// assert!(mt.is_padded_slot(5) && !mt.is_padded_slot(6));
+/// // rows of 6, 4 and 2 nodes, and the root
+/// assert_eq!(mt.node_count(), 13);
+/// ```
+///
 /// TODO: Ord
-#[derive(Debug, Clone, Eq, PartialEq)]
-pub struct MerkleTree<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> {
-    data: Vec<T>,
+#[derive(Debug, Clone)]
+pub struct MerkleTree<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T> = VecStore<T>> {
+    data: S,
     leafs: usize,
     height: usize,
-    _a: PhantomData<A>,
+    alg: A,
+    // root of no leafs, the hash of the empty data stream of `alg`
+    empty: T,
+    padding: PaddingStrategy,
+    ordering: PairOrdering,
+    // leafs sorted with their indices, of `build_index`
+    index: Option<Vec<(T, usize)>>,
+    _t: PhantomData<T>,
+}
+
+impl<T, A, S> PartialEq for MerkleTree<T, A, S>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T> + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.leafs == other.leafs
+            && self.height == other.height
+            && self.padding == other.padding
+            && self.ordering == other.ordering
+            && self.data == other.data
+    }
+}
+
+impl<T, A, S> Eq for MerkleTree<T, A, S>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T> + Eq,
+{
+}
+
+/// Merkle tree construction and inclusion proof errors.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum MerkleError {
+    /// Leaf index is out of bounds of the tree leafs.
+    IndexOutOfBounds {
+        /// Requested leaf index.
+        index: usize,
+        /// Number of leafs in the tree.
+        leafs: usize,
+    },
+    /// Leaf index is of the padding of the leafs rather than of a leaf,
+    /// `leafs <= index < padded_leaf_count()` of [`MerkleTree`].
+    ///
+    /// Leafs are padded up to the power of two, the leafs of the complete
+    /// tree of the height of the tree. Of the padding, only the duplicate of
+    /// the last of the odd many leafs is stored, see
+    /// [`MerkleTree::is_padded_slot`].
+    PaddingIndex {
+        /// Requested leaf index.
+        index: usize,
+        /// Number of leafs in the tree.
+        leafs: usize,
+    },
+    /// Tree over that many leafs does not fit into the address space of
+    /// the target, of its pointer width.
+    CapacityOverflow {
+        /// Number of leafs the tree was requested for.
+        leafs: usize,
+    },
+    /// Nodes do not lay out a tree over that many leafs of that height.
+    InvalidLayout {
+        /// Number of nodes.
+        len: usize,
+        /// Number of leafs.
+        leafs: usize,
+        /// Height of the tree.
+        height: usize,
+    },
+    /// Trees to merge are not over the same power of two number of leafs.
+    MergeMismatch {
+        /// Number of leafs in the left tree.
+        left: usize,
+        /// Number of leafs in the right tree.
+        right: usize,
+    },
+    /// Lemma of the proof is not one hash longer than the path plus the
+    /// root.
+    ProofShape {
+        /// Number of the lemma hashes.
+        lemma: usize,
+        /// Number of the path steps.
+        path: usize,
+    },
+    /// Leaf is pruned from the tree, so no proof can be generated for it.
+    Pruned {
+        /// Requested leaf index.
+        index: usize,
+    },
+    /// Node is not the hash of its children, or not the duplicate of the
+    /// last node of an odd level.
+    InvalidNode {
+        /// Index of the node in memory layout.
+        index: usize,
+        /// Level of the node.
+        level: usize,
+    },
+    /// Leaf supplied for the proof does not hash up to its parent.
+    LeafMismatch {
+        /// Requested leaf index.
+        index: usize,
+    },
+    /// Leaf range is empty, or reaches past the tree leafs.
+    InvalidRange {
+        /// Start of the requested range.
+        start: usize,
+        /// End of the requested range, exclusive.
+        end: usize,
+        /// Number of leafs in the tree.
+        leafs: usize,
+    },
+    /// Items of the sorted tree are out of order, or are not unique.
+    Unsorted {
+        /// Index of the first item not greater than the one before it.
+        index: usize,
+    },
+    /// Item is in the sorted tree, so its absence cannot be proven.
+    Present {
+        /// Leaf index of the item.
+        index: usize,
+    },
+    /// Node is out of bounds of the tree levels, or of its level.
+    NodeOutOfBounds {
+        /// Requested level.
+        level: usize,
+        /// Requested index in the level.
+        index: usize,
+    },
+    /// Tree nodes have less than two children.
+    InvalidArity {
+        /// Requested number of children of the nodes.
+        arity: usize,
+    },
+    /// Operation is only supported by the trees of the default padding.
+    UnsupportedPadding {
+        /// Padding strategy of the tree.
+        padding: PaddingStrategy,
+    },
+    /// Operation is only supported by the trees of the default pair
+    /// ordering.
+    UnsupportedOrdering {
+        /// Pair ordering of the tree.
+        ordering: PairOrdering,
+    },
+    /// Position is out of bounds of the nodes of the merkle mountain range.
+    PositionOutOfBounds {
+        /// Requested position.
+        position: usize,
+        /// Number of nodes of the range.
+        size: usize,
+    },
+    /// Number of nodes is not of any merkle mountain range.
+    InvalidMmrSize {
+        /// Number of nodes.
+        size: usize,
+    },
+    /// Key is not of the bytes of the hashes of the sparse merkle tree.
+    KeyLength {
+        /// Bytes of the key.
+        len: usize,
+        /// Bytes of the hashes of the tree.
+        expected: usize,
+    },
+}
+
+impl fmt::Display for MerkleError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            MerkleError::IndexOutOfBounds { index, leafs } => write!(
+                f,
+                "leaf index {} is out of bounds of the tree with {} leafs",
+                index, leafs
+            ),
+            MerkleError::PaddingIndex { index, leafs } => write!(
+                f,
+                "leaf index {} is of the padding of the tree with {} leafs, not of a leaf",
+                index, leafs
+            ),
+            MerkleError::CapacityOverflow { leafs } => write!(
+                f,
+                "merkle tree over {} leafs overflows capacity of {}-bit target",
+                leafs,
+                usize::BITS
+            ),
+            MerkleError::InvalidLayout { len, leafs, height } => write!(
+                f,
+                "{} nodes of height {} do not lay out a merkle tree over {} leafs",
+                len, height, leafs
+            ),
+            MerkleError::MergeMismatch { left, right } => write!(
+                f,
+                "cannot merge merkle trees over {} and {} leafs, expected the same power of two",
+                left, right
+            ),
+            MerkleError::ProofShape { lemma, path } => write!(
+                f,
+                "lemma of {} hashes does not fit the path of {} steps",
+                lemma, path
+            ),
+            MerkleError::Pruned { index } => write!(f, "leaf {} is pruned from the tree", index),
+            MerkleError::InvalidNode { index, level } => write!(
+                f,
+                "node {} of level {} does not match its children",
+                index, level
+            ),
+            MerkleError::LeafMismatch { index } => write!(
+                f,
+                "leaf {} does not hash up to its parent in the tree",
+                index
+            ),
+            MerkleError::InvalidRange { start, end, leafs } => write!(
+                f,
+                "leaf range {}..{} is empty or out of bounds of the tree with {} leafs",
+                start, end, leafs
+            ),
+            MerkleError::Unsorted { index } => {
+                write!(f, "item {} is not greater than the item before it", index)
+            }
+            MerkleError::Present { index } => write!(f, "item is leaf {} of the tree", index),
+            MerkleError::NodeOutOfBounds { level, index } => write!(
+                f,
+                "node {} of level {} is out of bounds of the tree",
+                index, level
+            ),
+            MerkleError::InvalidArity { arity } => write!(
+                f,
+                "merkle tree nodes of {} children, expected at least 2",
+                arity
+            ),
+            MerkleError::UnsupportedPadding { padding } => write!(
+                f,
+                "operation is not supported by the merkle trees of {:?} padding",
+                padding
+            ),
+            MerkleError::UnsupportedOrdering { ordering } => write!(
+                f,
+                "operation is not supported by the merkle trees of {:?} pair ordering",
+                ordering
+            ),
+            MerkleError::PositionOutOfBounds { position, size } => write!(
+                f,
+                "position {} is out of bounds of the merkle mountain range of {} nodes",
+                position, size
+            ),
+            MerkleError::InvalidMmrSize { size } => {
+                write!(f, "{} nodes are not of any merkle mountain range", size)
+            }
+            MerkleError::KeyLength { len, expected } => write!(
+                f,
+                "key of {} bytes is not of the {} bytes of the hashes of the tree",
+                len, expected
+            ),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for MerkleError {}
+
+/// Rule of filling up the levels of odd number of nodes, which decides the
+/// roots of the trees over the number of leafs other than a power of two.
+///
+/// Every odd level of the tree is followed by the pad node in memory layout
+/// whichever the strategy, which is the duplicate of the last node of the
+/// level, unless stated otherwise. Roots of 5 leafs `a b c d e`, where
+/// `(xy)` is the node of `x` and `y`:
+///
+/// ```text
+/// HashWithSelf        ((ab)(cd))((ee)(ee))
+/// PromoteOdd          ((ab)(cd))e
+/// EmptyHashPerLevel   ((ab)(cd))((eE0)E1)
+/// DuplicateLast       ((ab)(cd))(ee)
+/// ```
+///
+/// Inclusion proofs only skip the levels where the node is promoted, so
+/// that they validate as any other proof. Multi, range and consistency
+/// proofs, pruning, subtrees, merging and compacting assume the default
+/// strategy, and fail with [`MerkleError::UnsupportedPadding`] otherwise.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PaddingStrategy {
+    /// Last node of the odd level is hashed with itself, as the merkle
+    /// roots of the Bitcoin blocks are. Default.
+    #[default]
+    HashWithSelf,
+    /// Last node of the odd level is promoted to the level above as it is,
+    /// so that the tree splits at the largest power of two less than the
+    /// number of leafs, as the Merkle Tree Hash of RFC 6962 (Certificate
+    /// Transparency) does, and as merkletreejs does by default.
+    PromoteOdd,
+    /// Last node of the odd level `k` is hashed with the root of the empty
+    /// tree of height `k`: `E0` is the hash of the empty data stream and
+    /// `E(k+1) = H(Ek Ek)`, so that the tree is the one over the leafs
+    /// filled up to the power of two with `E0`, as of the trees of fixed
+    /// depth, such as the deposit contract of Ethereum, with `E0` of theirs.
+    EmptyHashPerLevel,
+    /// Odd leafs row is filled up with the duplicate of the last leaf, and
+    /// the last node of the odd levels above it is promoted.
+    DuplicateLast,
+}
+
+impl PaddingStrategy {
+    /// Returns `true` if the last node of the odd level `level` is promoted
+    /// rather than hashed with the pad node.
+    pub(crate) fn promotes(self, level: usize) -> bool {
+        match self {
+            PaddingStrategy::PromoteOdd => true,
+            PaddingStrategy::DuplicateLast => level > 0,
+            _ => false,
+        }
+    }
+
+    /// Returns the pad node of the odd level `level` of the last node `last`.
+    fn pad<T: Clone + AsRef<[u8]>, A: Algorithm<T>>(self, a: &mut A, level: usize, last: T) -> T {
+        if self != PaddingStrategy::EmptyHashPerLevel {
+            return last;
+        }
+
+        a.reset();
+        let mut h = a.hash();
+        for _ in 0..level {
+            a.reset();
+            h = a.node(h.clone(), h);
+        }
+        h
+    }
+
+    /// Returns the parent of `left` and `right` of level `level` hashed in
+    /// `ordering`, where `right` is the pad node if `padded`.
+    fn parent<T: Clone + AsRef<[u8]>, A: Algorithm<T>>(
+        self,
+        a: &mut A,
+        ordering: PairOrdering,
+        level: usize,
+        left: &T,
+        right: &T,
+        padded: bool,
+    ) -> T {
+        if padded && self.promotes(level) {
+            return left.clone();
+        }
+        ordering.node_ref(a, left, right)
+    }
+}
+
+/// Order the children of the node are hashed in.
+///
+/// Sorted pairs make the node commutative, `H(min(l, r) max(l, r))` of the
+/// bytes of the children, as OpenZeppelin `MerkleProof` and merkletreejs
+/// with `sortPairs` hash, so that the proofs need no directions: the path
+/// of the proof of the tree of sorted pairs still tells the index of the
+/// leaf, and is ignored as the proof is validated. Pad nodes hash in the
+/// same order as the rest.
+///
+/// Pair ordering is encoded into the proofs, and the proofs decoded or
+/// deserialized validate in the order of the tree they are of. Multi,
+/// range and consistency proofs, pruning, subtrees, merging and compacting
+/// assume the default ordering, and fail with
+/// [`MerkleError::UnsupportedOrdering`] otherwise.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+#[cfg_attr(feature = "serde", derive(Serialize, Deserialize))]
+pub enum PairOrdering {
+    /// Left child is hashed first, as the path of the proof tells. Default.
+    #[default]
+    Ordered,
+    /// Lesser child is hashed first, whichever side of the node it is.
+    Sorted,
+}
+
+impl PairOrdering {
+    /// Returns the node of `left` and `right` hashed with `a` in this order,
+    /// sorted by their bytes as the Solidity `bytes32` compare.
+    pub(crate) fn node<T: Clone + AsRef<[u8]>, A: Algorithm<T>>(
+        self,
+        a: &mut A,
+        left: T,
+        right: T,
+    ) -> T {
+        a.reset();
+        match self {
+            PairOrdering::Sorted if right.as_ref() < left.as_ref() => a.node(right, left),
+            _ => a.node(left, right),
+        }
+    }
+
+    /// Returns the node of the children borrowed, as [`PairOrdering::node`]
+    /// does, with [`Algorithm::node_ref`].
+    pub(crate) fn node_ref<T: Clone + AsRef<[u8]>, A: Algorithm<T>>(
+        self,
+        a: &mut A,
+        left: &T,
+        right: &T,
+    ) -> T {
+        a.reset();
+        match self {
+            PairOrdering::Sorted if right.as_ref() < left.as_ref() => a.node_ref(right, left),
+            _ => a.node_ref(left, right),
+        }
+    }
 }
 
-impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> MerkleTree<T, A> {
-    /// Creates new merkle from a sequence of hashes.
-    pub fn new<I: IntoIterator<Item = T>>(data: I) -> MerkleTree<T, A> {
-        Self::from_iter(data)
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T>> MerkleTree<T, A, S> {
+    /// Creates new merkle from a sequence of hashes.
+    pub fn new<I: IntoIterator<Item = T>>(data: I) -> MerkleTree<T, A, S> {
+        Self::from_iter(data)
+    }
+
+    /// Creates new merkle from a sequence of hashes.
+    ///
+    /// Fallible version of [`MerkleTree::new`].
+    pub fn try_new<I: IntoIterator<Item = T>>(data: I) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        Self::try_from_iter(data)
+    }
+
+    /// Creates new merkle tree from a sequence of hashes, hashing with the
+    /// algorithm `alg`, such as [`crate::hash::KeyedAlgorithm`], rather than
+    /// `A::default()`. Tree keeps the algorithm for the nodes it rehashes.
+    pub fn new_with<I: IntoIterator<Item = T>>(data: I, alg: A) -> MerkleTree<T, A, S> {
+        Self::try_new_with(data, alg).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new merkle tree from a sequence of hashes with the algorithm
+    /// `alg`.
+    ///
+    /// Fallible version of [`MerkleTree::new_with`].
+    pub fn try_new_with<I: IntoIterator<Item = T>>(
+        data: I,
+        alg: A,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        let iter = data.into_iter();
+        let data = Self::store_for(&iter)?;
+        Self::build_in(
+            data,
+            iter,
+            alg,
+            PaddingStrategy::default(),
+            PairOrdering::default(),
+        )
+    }
+
+    /// Creates new merkle tree from a sequence of hashes, filling up the odd
+    /// levels with `padding` rather than the default strategy.
+    pub fn new_padded<I: IntoIterator<Item = T>>(
+        data: I,
+        padding: PaddingStrategy,
+    ) -> MerkleTree<T, A, S> {
+        Self::try_new_padded(data, padding).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new merkle tree from a sequence of hashes with `padding`.
+    ///
+    /// Fallible version of [`MerkleTree::new_padded`].
+    pub fn try_new_padded<I: IntoIterator<Item = T>>(
+        data: I,
+        padding: PaddingStrategy,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        let iter = data.into_iter();
+        let data = Self::store_for(&iter)?;
+        Self::build_in(data, iter, A::default(), padding, PairOrdering::default())
+    }
+
+    /// Creates new merkle tree from a sequence of hashes with `padding` and
+    /// `ordering`.
+    pub(crate) fn try_build<I: IntoIterator<Item = T>>(
+        data: I,
+        padding: PaddingStrategy,
+        ordering: PairOrdering,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        let iter = data.into_iter();
+        let data = Self::store_for(&iter)?;
+        Self::build_in(data, iter, A::default(), padding, ordering)
+    }
+
+    /// Creates new merkle tree from a sequence of hashes, hashing the
+    /// children of the nodes in `ordering` rather than the default order,
+    /// such as [`PairOrdering::Sorted`] for the trees OpenZeppelin
+    /// `MerkleProof` verifies.
+    pub fn new_ordered<I: IntoIterator<Item = T>>(
+        data: I,
+        ordering: PairOrdering,
+    ) -> MerkleTree<T, A, S> {
+        Self::try_new_ordered(data, ordering).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new merkle tree from a sequence of hashes with `ordering`.
+    ///
+    /// Fallible version of [`MerkleTree::new_ordered`].
+    pub fn try_new_ordered<I: IntoIterator<Item = T>>(
+        data: I,
+        ordering: PairOrdering,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        let iter = data.into_iter();
+        let data = Self::store_for(&iter)?;
+        Self::build_in(
+            data,
+            iter,
+            A::default(),
+            PaddingStrategy::default(),
+            ordering,
+        )
+    }
+
+    /// Creates new empty merkle tree.
+    pub fn empty() -> MerkleTree<T, A, S> {
+        Self::from_iter(iter::empty())
+    }
+
+    /// Creates new merkle tree from a list of hashable objects.
+    pub fn from_data<O: Hashable<A>, I: IntoIterator<Item = O>>(data: I) -> MerkleTree<T, A, S> {
+        Self::try_from_data(data).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new merkle tree from a list of hashable objects.
+    ///
+    /// Fallible version of [`MerkleTree::from_data`].
+    pub fn try_from_data<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        let mut a = A::default();
+        Self::try_from_iter(data.into_iter().map(|x| hash_data(&mut a, &x)))
+    }
+
+    /// Creates new merkle tree from a list of hashable objects, hashing with
+    /// the algorithm `alg`, as [`MerkleTree::new_with`] does.
+    pub fn from_data_with<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+        alg: A,
+    ) -> MerkleTree<T, A, S> {
+        Self::try_from_data_with(data, alg).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new merkle tree from a list of hashable objects with the
+    /// algorithm `alg`.
+    ///
+    /// Fallible version of [`MerkleTree::from_data_with`].
+    pub fn try_from_data_with<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+        mut alg: A,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        let hashes: Vec<T> = data.into_iter().map(|x| hash_data(&mut alg, &x)).collect();
+        Self::try_new_with(hashes, alg)
+    }
+
+    /// Creates new merkle tree from a list of hashable objects, filling up
+    /// the odd levels with `padding`, as [`MerkleTree::new_padded`] does.
+    pub fn from_data_padded<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+        padding: PaddingStrategy,
+    ) -> MerkleTree<T, A, S> {
+        Self::try_from_data_padded(data, padding).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new merkle tree from a list of hashable objects with
+    /// `padding`.
+    ///
+    /// Fallible version of [`MerkleTree::from_data_padded`].
+    pub fn try_from_data_padded<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+        padding: PaddingStrategy,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        let mut a = A::default();
+        let hashes: Vec<T> = data.into_iter().map(|x| hash_data(&mut a, &x)).collect();
+        Self::try_new_padded(hashes, padding)
+    }
+
+    /// Creates new merkle tree from a list of hashable objects, hashing the
+    /// children of the nodes in `ordering`, as [`MerkleTree::new_ordered`]
+    /// does.
+    pub fn from_data_ordered<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+        ordering: PairOrdering,
+    ) -> MerkleTree<T, A, S> {
+        Self::try_from_data_ordered(data, ordering).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new merkle tree from a list of hashable objects with
+    /// `ordering`.
+    ///
+    /// Fallible version of [`MerkleTree::from_data_ordered`].
+    pub fn try_from_data_ordered<O: Hashable<A>, I: IntoIterator<Item = O>>(
+        data: I,
+        ordering: PairOrdering,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        let mut a = A::default();
+        let hashes: Vec<T> = data.into_iter().map(|x| hash_data(&mut a, &x)).collect();
+        Self::try_new_ordered(hashes, ordering)
+    }
+
+    /// Creates new merkle tree from an iterator over hashable objects.
+    ///
+    /// Fallible version of [`MerkleTree::from_iter`].
+    pub fn try_from_iter<I: IntoIterator<Item = T>>(
+        into: I,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        let iter = into.into_iter();
+        let data = Self::store_for(&iter)?;
+        Self::try_from_iter_in(data, iter)
+    }
+
+    /// Creates new store with the room for the leafs `iter` yields at least,
+    /// or fails with [`MerkleError::CapacityOverflow`] before the room is
+    /// reserved if the tree over them does not fit into memory.
+    fn store_for<I: Iterator<Item = T>>(iter: &I) -> Result<S, MerkleError> {
+        // size hint is only a guess on the number of leafs, the tree layout
+        // is computed upon the number of items the iterator actually yields,
+        // but the tree over no fewer items than the lower bound overflows
+        // just as well.
+        let (lower, _) = iter.size_hint();
+        tree_layout(lower)?;
+        Ok(S::new(lower))
+    }
+
+    /// Creates new merkle tree from an iterator over hashable objects, in
+    /// the store `data`, such as the one backed by a file. Nodes the store
+    /// holds are dropped first.
+    pub fn try_from_iter_in<I: IntoIterator<Item = T>>(
+        data: S,
+        into: I,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        Self::build_in(
+            data,
+            into,
+            A::default(),
+            PaddingStrategy::default(),
+            PairOrdering::default(),
+        )
+    }
+
+    /// Creates new merkle tree upon the leafs `leafs` as they are, not
+    /// hashed with [`Algorithm::leaf`], such as upon the roots of the trees
+    /// of the shards, so that the proof of the leaf of a shard composed
+    /// with the proof of its root with [`Proof::compose`] validates against
+    /// the root of this tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tree over the leafs does not fit into memory.
+    pub fn from_leaf_hashes<I: IntoIterator<Item = T>>(leafs: I) -> MerkleTree<T, A, S> {
+        Self::try_from_leaf_hashes(leafs).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new merkle tree upon the leafs `leafs` as they are.
+    ///
+    /// Fallible version of [`MerkleTree::from_leaf_hashes`].
+    pub fn try_from_leaf_hashes<I: IntoIterator<Item = T>>(
+        leafs: I,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        let iter = leafs.into_iter();
+        let mut data = Self::store_for(&iter)?;
+        for leaf in iter {
+            data.push(leaf);
+        }
+        Self::from_leafs(data)
+    }
+
+    /// Builds the tree upon the leafs hashed with `alg` in the store `data`,
+    /// filling up the odd levels with `padding` and hashing the nodes in
+    /// `ordering`.
+    fn build_in<I: IntoIterator<Item = T>>(
+        mut data: S,
+        into: I,
+        mut alg: A,
+        padding: PaddingStrategy,
+        ordering: PairOrdering,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        data.truncate(0);
+
+        // leafs
+        for item in into {
+            alg.reset();
+            data.push(alg.leaf(item));
+        }
+
+        Self::from_leafs_with(data, alg, padding, ordering)
+    }
+
+    /// Creates merkle tree from the store of its nodes in memory layout,
+    /// number of leafs and height, as returned by [`MerkleTree::into_store`].
+    ///
+    /// Only the layout is validated, so that the number of nodes and the
+    /// height match the number of leafs. Nodes are not rehashed.
+    pub fn from_store(
+        data: S,
+        leafs: usize,
+        height: usize,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        // number of nodes is the end of the last level
+        let size = match tree_layout(leafs) {
+            Ok((_, h)) if h == height => Some(
+                Levels::new(leafs, height)
+                    .last()
+                    .map_or(0, |(base, width)| base + width),
+            ),
+            _ => None,
+        };
+
+        if size != Some(data.len()) {
+            return Err(MerkleError::InvalidLayout {
+                len: data.len(),
+                leafs,
+                height,
+            });
+        }
+
+        Ok(MerkleTree {
+            data,
+            leafs,
+            height,
+            alg: A::default(),
+            empty: A::default().hash(),
+            padding: PaddingStrategy::default(),
+            ordering: PairOrdering::default(),
+            index: None,
+            _t: PhantomData,
+        })
+    }
+
+    /// Replaces the padding strategy of the tree with `padding`, which the
+    /// nodes the tree changes from now on are hashed with. Nodes are not
+    /// rehashed, as with [`MerkleTree::with_algorithm`].
+    pub fn with_padding(mut self, padding: PaddingStrategy) -> MerkleTree<T, A, S> {
+        self.padding = padding;
+        self
+    }
+
+    /// Returns the padding strategy of the tree.
+    pub fn padding_strategy(&self) -> PaddingStrategy {
+        self.padding
+    }
+
+    /// Replaces the pair ordering of the tree with `ordering`, which the
+    /// nodes the tree changes from now on are hashed in, and which the
+    /// proofs the tree generates carry. Nodes are not rehashed, as with
+    /// [`MerkleTree::with_padding`].
+    pub fn with_pair_ordering(mut self, ordering: PairOrdering) -> MerkleTree<T, A, S> {
+        self.ordering = ordering;
+        self
+    }
+
+    /// Returns the pair ordering of the tree.
+    pub fn pair_ordering(&self) -> PairOrdering {
+        self.ordering
+    }
+
+    /// Fails with [`MerkleError::UnsupportedPadding`] or
+    /// [`MerkleError::UnsupportedOrdering`] unless the tree has the default
+    /// padding strategy and pair ordering.
+    fn default_hashing(&self) -> Result<(), MerkleError> {
+        match (self.padding, self.ordering) {
+            (PaddingStrategy::HashWithSelf, PairOrdering::Ordered) => Ok(()),
+            (PaddingStrategy::HashWithSelf, ordering) => {
+                Err(MerkleError::UnsupportedOrdering { ordering })
+            }
+            (padding, _) => Err(MerkleError::UnsupportedPadding { padding }),
+        }
+    }
+
+    /// Replaces the algorithm of the tree with `alg`, which the nodes the
+    /// tree changes from now on are hashed with. Nodes are not rehashed, so
+    /// that the tree put together from its nodes, such as with
+    /// [`MerkleTree::from_store`], gets back the algorithm it was built with.
+    pub fn with_algorithm(mut self, alg: A) -> MerkleTree<T, A, S> {
+        self.alg = alg;
+        self.empty = empty_root(&mut self.alg);
+        self
+    }
+
+    /// Returns the algorithm of the tree.
+    pub fn algorithm(&self) -> &A {
+        &self.alg
+    }
+
+    /// Builds the tree upon the leafs row `data`.
+    pub(crate) fn from_leafs(data: S) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        Self::from_leafs_with(
+            data,
+            A::default(),
+            PaddingStrategy::default(),
+            PairOrdering::default(),
+        )
+    }
+
+    /// Builds the tree upon the leafs row `data`, hashing with `alg` in
+    /// `ordering` and filling up the odd levels with `padding`.
+    pub(crate) fn from_leafs_with(
+        data: S,
+        mut alg: A,
+        padding: PaddingStrategy,
+        ordering: PairOrdering,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        let layout = tree_layout(data.len())?;
+
+        // leafs row of the empty tree grows into the whole tree
+        let empty = empty_root(&mut alg);
+        let mut mt: MerkleTree<T, A, S> = MerkleTree {
+            data,
+            leafs: 0,
+            height: 0,
+            alg,
+            empty,
+            padding,
+            ordering,
+            index: None,
+            _t: PhantomData,
+        };
+
+        mt.rebuild(Vec::new(), 0, layout);
+        // leafs of no size hint leave the room of the row grown past the
+        // tree, which lives as long as the tree does
+        mt.data.shrink_to_fit();
+        Ok(mt)
+    }
+
+    /// Rebuilds the levels above the new leafs row, which `data` is cut
+    /// down to, into the tree of `layout`. Nodes of the old levels `upper`
+    /// which only depend on the leafs before `from` are reused, the rest
+    /// are recomputed.
+    fn rebuild(&mut self, upper: Vec<T>, from: usize, layout: (usize, usize)) {
+        let (size, height) = layout;
+        let old: Vec<(usize, usize)> = self.levels().collect();
+        let split = self.leafs;
+        self.index = None;
+
+        self.leafs = self.data.len();
+        self.height = height;
+        self.data.reserve(size - self.leafs);
+
+        let mut levels = self.levels();
+        let (mut base, mut width) = match levels.next() {
+            Some(level) => level,
+            None => return,
+        };
+        let (a, padding, ordering) = (&mut self.alg, self.padding, self.ordering);
+
+        for (level, (next, next_width)) in levels.enumerate() {
+            let level = level + 1;
+
+            // if there is odd num of elements, fill in to the even
+            if width & 1 == 1 {
+                let he = self.data.read(base + width - 1);
+                self.data.push(padding.pad(a, level - 1, he));
+            }
+
+            // children are borrowed from the store where it lends them, and
+            // read into the same two nodes otherwise, and the level is
+            // pushed once it is hashed
+            let data = &self.data;
+            let mut read: Option<(T, T)> = None;
+            let row: Vec<T> = (0..next_width)
+                .map(|i| match old.get(level) {
+                    Some(&(b, w)) if i < w && i < from >> level => upper[b - split + i].clone(),
+                    _ => {
+                        let (l, r) = (base + 2 * i, base + 2 * i + 1);
+                        let padded = 2 * i + 1 == width;
+                        if let (Some(left), Some(right)) = (data.read_ref(l), data.read_ref(r)) {
+                            return padding.parent(a, ordering, level - 1, left, right, padded);
+                        }
+                        match read {
+                            Some((ref mut left, ref mut right)) => {
+                                data.read_into(l, left);
+                                data.read_into(r, right);
+                            }
+                            None => read = Some((data.read(l), data.read(r))),
+                        }
+                        let (left, right) = read.as_ref().expect("children are read");
+                        padding.parent(a, ordering, level - 1, left, right, padded)
+                    }
+                })
+                .collect();
+            for h in row {
+                self.data.push(h);
+            }
+
+            base = next;
+            width = next_width;
+        }
+    }
+
+    /// Appends a leaf to the tree.
+    ///
+    /// Only the nodes along the path of the new leaf to the root are
+    /// rehashed, in place. Levels above the leafs row are moved up within
+    /// the store as far as the rows below them grow, and the tree is laid
+    /// out anew only as its height grows, when the number of leafs exceeds
+    /// the power of two.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the tree overflows capacity.
+    pub fn push(&mut self, item: T) {
+        let leafs = self.leafs;
+        let layout = tree_layout(leafs + 1).unwrap_or_else(|e| panic!("{}", e));
+        self.alg.reset();
+        let leaf = self.alg.leaf(item);
+
+        if layout.1 != self.height {
+            let upper = self.data.split_off(leafs);
+            self.data.push(leaf);
+            self.rebuild(upper, leafs, layout);
+            return;
+        }
+
+        // levels are moved top down, each one from its last node, so that
+        // no node is overwritten before it is moved
+        let old: Vec<(usize, usize)> = self.levels().collect();
+        let new: Vec<(usize, usize)> = Levels::new(leafs + 1, self.height).collect();
+        let (root, _) = new[new.len() - 1];
+        while self.data.len() <= root {
+            self.data.push(leaf.clone());
+        }
+        for (&(from, width), &(to, _)) in old.iter().zip(&new).skip(1).rev() {
+            if from != to {
+                for i in (0..width).rev() {
+                    let h = self.data.read(from + i);
+                    self.data.write(to + i, h);
+                }
+            }
+        }
+
+        self.index = None;
+        self.leafs = leafs + 1;
+        self.data.write(leafs, leaf);
+        self.update_path(leafs);
+    }
+
+    /// Merges two trees into the parent tree, which root is the node over
+    /// `left` and `right` roots, and which leafs are `left` leafs followed
+    /// by `right` leafs.
+    ///
+    /// Both trees must be built upon the same power of two number of leafs,
+    /// so that no node of the parent tree pairs nodes across the seam other
+    /// than the root. Nodes of both trees are copied to the parent tree and
+    /// the root is the only one hashed.
+    pub fn merge(
+        left: MerkleTree<T, A, S>,
+        right: MerkleTree<T, A, S>,
+    ) -> Result<MerkleTree<T, A, S>, MerkleError> {
+        left.default_hashing()?;
+        right.default_hashing()?;
+        if left.leafs != right.leafs || !left.leafs.is_power_of_two() {
+            return Err(MerkleError::MergeMismatch {
+                left: left.leafs,
+                right: right.leafs,
+            });
+        }
+
+        let (size, height) = tree_layout(left.leafs + right.leafs)?;
+        let mut data = S::new(size);
+        for ((l, w), (r, _)) in left.levels().zip(right.levels()) {
+            for i in 0..w {
+                data.push(left.data.read(l + i));
+            }
+            for i in 0..w {
+                data.push(right.data.read(r + i));
+            }
+        }
+
+        let (l, r) = (left.root(), right.root());
+        let mut alg = left.alg;
+        alg.reset();
+        data.push(alg.node(l, r));
+
+        Ok(MerkleTree {
+            data,
+            leafs: left.leafs + right.leafs,
+            height,
+            alg,
+            empty: left.empty,
+            padding: PaddingStrategy::default(),
+            ordering: PairOrdering::default(),
+            index: None,
+            _t: PhantomData,
+        })
+    }
+
+    /// Shortens the tree, keeping the first `n` leafs and dropping the rest.
+    ///
+    /// If `n` is greater or equal to the number of leafs, this has no effect.
+    /// Nodes which only depend on the kept leafs are not rehashed, and the
+    /// tree height shrinks with the number of leafs. Truncating to 0 leafs
+    /// leaves an empty tree.
+    pub fn truncate(&mut self, n: usize) {
+        let leafs = self.leafs;
+        if n >= leafs {
+            return;
+        }
+
+        let layout = tree_layout(n).expect("smaller tree fits capacity");
+        let upper = self.data.split_off(leafs);
+        self.data.truncate(n);
+        self.rebuild(upper, n, layout);
+    }
+
+    /// Replaces leaf `i` with the leaf hash of `value`.
+    ///
+    /// Only the ancestors of the leaf up to the root are recomputed.
+    pub fn set_leaf(&mut self, i: usize, value: T) -> Result<(), MerkleError> {
+        // i in [0 .. self.leafs)
+        if i >= self.leafs {
+            return Err(MerkleError::IndexOutOfBounds {
+                index: i,
+                leafs: self.leafs,
+            });
+        }
+
+        self.index = None;
+        self.alg.reset();
+        let leaf = self.alg.leaf(value);
+        self.data.write(i, leaf);
+        self.update_path(i);
+
+        Ok(())
+    }
+
+    /// Recomputes the ancestors of leaf `i` up to the root, and the
+    /// duplicates filling in the odd levels along the path.
+    fn update_path(&mut self, i: usize) {
+        let mut j = i;
+        let mut levels = self.levels();
+        let (mut base, mut width) = levels.next().expect("tree has leafs");
+        let (a, padding, ordering) = (&mut self.alg, self.padding, self.ordering);
+
+        for (level, (next, next_width)) in levels.enumerate() {
+            // last node of the odd level is duplicated to fill in to the even
+            if width & 1 == 1 && j == width - 1 {
+                let he = self.data.read(base + j);
+                self.data.write(base + width, padding.pad(a, level, he));
+            }
+
+            let h = padding.parent(
+                a,
+                ordering,
+                level,
+                &self.data.read(base + (j & !1)),
+                &self.data.read(base + (j | 1)),
+                (j | 1) == width,
+            );
+            j >>= 1;
+            self.data.write(next + j, h);
+
+            base = next;
+            width = next_width;
+        }
+    }
+
+    /// Replaces the leafs of `updates` with the leaf hashes of their values,
+    /// as [`MerkleTree::set_leaf`] of every update does, of the last value
+    /// of the index which is updated more than once.
+    ///
+    /// Leafs are written first, then every ancestor of the leafs updated is
+    /// recomputed once, level by level, rather than once per update, so that
+    /// the updates under the same subtree share the hashing of the nodes
+    /// above it. Fails with [`MerkleError::IndexOutOfBounds`] of the first
+    /// update past the tree leafs, and no leaf is updated.
+    pub fn update_batch(&mut self, updates: &[(usize, T)]) -> Result<(), MerkleError> {
+        if let Some(&(index, _)) = updates.iter().find(|&&(i, _)| i >= self.leafs) {
+            return Err(MerkleError::IndexOutOfBounds {
+                index,
+                leafs: self.leafs,
+            });
+        }
+        if updates.is_empty() {
+            return Ok(());
+        }
+
+        self.index = None;
+        // sort is stable, so that the last update of the index is written
+        // last
+        let mut order: Vec<usize> = (0..updates.len()).collect();
+        order.sort_by_key(|&k| updates[k].0);
+
+        let mut levels = self.levels();
+        let (mut base, mut width) = levels.next().expect("tree has leafs");
+        let (a, padding, ordering) = (&mut self.alg, self.padding, self.ordering);
+        let mut dirty: Vec<usize> = Vec::with_capacity(updates.len());
+        for &k in &order {
+            let (i, ref value) = updates[k];
+            a.reset();
+            self.data.write(i, a.leaf(value.clone()));
+            if dirty.last() != Some(&i) {
+                dirty.push(i);
+            }
+        }
+
+        for (level, (next, next_width)) in levels.enumerate() {
+            // last node of the odd level is duplicated to fill in to the even
+            if width & 1 == 1 && dirty.last() == Some(&(width - 1)) {
+                let he = self.data.read(base + width - 1);
+                self.data.write(base + width, padding.pad(a, level, he));
+            }
+
+            // nodes are sorted, so that the siblings are next to each other
+            let mut parents: Vec<usize> = Vec::with_capacity(dirty.len());
+            for &j in &dirty {
+                if parents.last() == Some(&(j >> 1)) {
+                    continue;
+                }
+                let h = padding.parent(
+                    a,
+                    ordering,
+                    level,
+                    &self.data.read(base + (j & !1)),
+                    &self.data.read(base + (j | 1)),
+                    (j | 1) == width,
+                );
+                self.data.write(next + (j >> 1), h);
+                parents.push(j >> 1);
+            }
+
+            dirty = parents;
+            base = next;
+            width = next_width;
+        }
+
+        Ok(())
+    }
+
+    /// Returns `true` if every node is the hash of its children, and every
+    /// odd level is filled up with the duplicate of its last node.
+    ///
+    /// All the nodes are rehashed, as when the tree is built, to check the
+    /// tree put together from a store. See [`MerkleTree::validate`].
+    pub fn verify(&self) -> bool
+    where
+        A: Clone,
+    {
+        self.validate().is_ok()
+    }
+
+    /// Checks that every node is the hash of its children, and every odd
+    /// level is filled up with the duplicate of its last node, bottom-up.
+    ///
+    /// Layout of the nodes is checked as the tree is put together, so this
+    /// is left to check of the tree from an untrusted store. Fails with
+    /// [`MerkleError::InvalidNode`] of the first node which does not match.
+    ///
+    /// Nodes are rehashed with the clone of the algorithm of the tree.
+    pub fn validate(&self) -> Result<(), MerkleError>
+    where
+        A: Clone,
+    {
+        let mut a = self.alg.clone();
+        let mut levels = self.levels().enumerate();
+        let (mut base, mut width) = match levels.next() {
+            Some((_, level)) => level,
+            None => return Ok(()),
+        };
+
+        for (level, (next, next_width)) in levels {
+            let last = self.data.read(base + width - 1);
+            if width & 1 == 1
+                && self.data.read(base + width) != self.padding.pad(&mut a, level - 1, last)
+            {
+                return Err(MerkleError::InvalidNode {
+                    index: base + width,
+                    level: level - 1,
+                });
+            }
+
+            for i in 0..next_width {
+                let h = self.padding.parent(
+                    &mut a,
+                    self.ordering,
+                    level - 1,
+                    &self.data.read(base + 2 * i),
+                    &self.data.read(base + 2 * i + 1),
+                    2 * i + 1 == width,
+                );
+                if h != self.data.read(next + i) {
+                    return Err(MerkleError::InvalidNode {
+                        index: next + i,
+                        level,
+                    });
+                }
+            }
+
+            base = next;
+            width = next_width;
+        }
+
+        Ok(())
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`
+    pub fn gen_proof(&self, i: usize) -> Proof<T> {
+        self.try_gen_proof(i).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate merkle tree inclusion proof for leaf `i`
+    ///
+    /// Fallible version of [`MerkleTree::gen_proof`]. Fails with
+    /// [`MerkleError::PaddingIndex`] if `i` is of the padding of the leafs,
+    /// below [`MerkleTree::padded_leaf_count`], and with
+    /// [`MerkleError::IndexOutOfBounds`] if it is past it.
+    pub fn try_gen_proof(&self, i: usize) -> Result<Proof<T>, MerkleError> {
+        leaf_index(i, self.leafs)?;
+
+        Ok(self.proof_of(i))
+    }
+
+    /// Returns the index of the leaf `hash`, the leaf hash as
+    /// [`MerkleTree::leaf`] returns it, or `None` if no leaf is of it. Of
+    /// the duplicate leafs, the first index is returned.
+    ///
+    /// Leafs are scanned one by one, unless the index of
+    /// [`MerkleTree::build_index`] is built, which the leaf is searched in.
+    pub fn find_leaf(&self, hash: &T) -> Option<usize> {
+        if let Some(index) = &self.index {
+            let k = index.partition_point(|(h, _)| h < hash);
+            return index.get(k).filter(|(h, _)| h == hash).map(|&(_, i)| i);
+        }
+        (0..self.leafs).find(|&i| match self.data.read_ref(i) {
+            Some(leaf) => leaf == hash,
+            None => self.data.read(i) == *hash,
+        })
+    }
+
+    /// Builds the index of the leafs, sorted, which [`MerkleTree::find_leaf`]
+    /// searches in `O(log n)` rather than scanning the leafs.
+    ///
+    /// Index holds the copy of every leaf, and is dropped as the leafs
+    /// change, by [`MerkleTree::set_leaf`], [`MerkleTree::push`],
+    /// [`MerkleTree::truncate`] or [`Extend::extend`], to be built again.
+    pub fn build_index(&mut self) {
+        let mut index: Vec<(T, usize)> = (0..self.leafs).map(|i| (self.data.read(i), i)).collect();
+        index.sort_unstable();
+        self.index = Some(index);
+    }
+
+    /// Returns `true` if the index of [`MerkleTree::build_index`] is built,
+    /// and not dropped since.
+    pub fn is_indexed(&self) -> bool {
+        self.index.is_some()
+    }
+
+    /// Generate merkle tree inclusion proof for the leaf `hash`, of its
+    /// first index as [`MerkleTree::find_leaf`] returns it, or `None` if no
+    /// leaf is of it.
+    pub fn gen_proof_for_hash(&self, hash: &T) -> Option<Proof<T>> {
+        self.find_leaf(hash).map(|i| self.proof_of(i))
+    }
+
+    /// Generate merkle tree inclusion proofs for the leafs `indices`, in
+    /// their order, each the same as [`MerkleTree::gen_proof`] generates.
+    ///
+    /// Tree is walked once per level for all the proofs, rather than once
+    /// per proof, and the nodes the proofs share are read once.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of `indices` is out of bounds of the tree leafs.
+    pub fn gen_proofs(&self, indices: &[usize]) -> Vec<Proof<T>> {
+        self.try_gen_proofs(indices)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate merkle tree inclusion proofs for the leafs `indices`.
+    ///
+    /// Fallible version of [`MerkleTree::gen_proofs`]. Fails for the whole
+    /// batch with the first index out of bounds, and no proof is generated.
+    pub fn try_gen_proofs(&self, indices: &[usize]) -> Result<Vec<Proof<T>>, MerkleError> {
+        for &i in indices {
+            leaf_index(i, self.leafs)?;
+        }
+
+        // proofs sorted by their nodes in the level, which the runs of the
+        // proofs of the same node share the sibling of
+        let mut order: Vec<usize> = (0..indices.len()).collect();
+        order.sort_by_key(|&k| indices[k]);
+        let mut nodes: Vec<usize> = order.iter().map(|&k| indices[k]).collect();
+        let mut lemmas: Vec<Lemma<T>> = indices
+            .iter()
+            .map(|_| Lemma::with_capacity(self.height + 1))
+            .collect();
+        let mut paths: Vec<Path> = indices
+            .iter()
+            .map(|_| Path::with_capacity(self.height.saturating_sub(1)))
+            .collect();
+
+        each_run(&nodes, &order, |j, proofs| {
+            let leaf = self.data.read(j);
+            for &k in proofs {
+                lemmas[k].push(leaf.clone());
+            }
+        });
+
+        let levels = self.levels().take(self.height.saturating_sub(1));
+        for (level, (base, width)) in levels.enumerate() {
+            each_run(&nodes, &order, |j, proofs| {
+                // promoted node has no sibling
+                let sibling = j ^ 1;
+                if sibling == width && self.padding.promotes(level) {
+                    return;
+                }
+                let node = self.data.read(base + sibling);
+                for &k in proofs {
+                    lemmas[k].push(node.clone());
+                    // j is left
+                    paths[k].push(j < sibling);
+                }
+            });
+            for j in nodes.iter_mut() {
+                *j >>= 1;
+            }
+        }
+
+        // root is final
+        let root = self.root();
+        Ok(lemmas
+            .into_iter()
+            .zip(paths)
+            .zip(indices)
+            .map(|((mut lemma, path), &i)| {
+                lemma.push(root.clone());
+                Proof::new(lemma, path)
+                    .with_pair_ordering(self.ordering)
+                    .with_position(i, self.height - 1)
+            })
+            .collect())
+    }
+
+    /// Generate merkle tree inclusion proofs for all the leafs, in their
+    /// order, each the same as [`MerkleTree::gen_proof`] generates, such as
+    /// of the airdrop published with the proof of every leaf.
+    ///
+    /// Proofs are of [`MerkleTree::for_each_proof`], which reads every node
+    /// of the tree once for all of them. All the proofs are held at once,
+    /// of `leafs * (height + 1)` hashes: use [`MerkleTree::for_each_proof`]
+    /// to handle the proofs of the huge trees one by one instead.
+    pub fn gen_all_proofs(&self) -> Vec<Proof<T>> {
+        let mut proofs = Vec::with_capacity(self.leafs);
+        self.for_each_proof(|_, proof| proofs.push(proof));
+        proofs
+    }
+
+    /// Calls `f` with the index and the inclusion proof of every leaf, in
+    /// their order, each the same as [`MerkleTree::gen_proof`] generates.
+    ///
+    /// Siblings of the proof of every level are read once for the run of
+    /// the leafs below the node they are the siblings of, rather than once
+    /// per proof, so that every node is read once. Only the proof passed to
+    /// `f` and a sibling per level are held at once.
+    ///
+    /// ```
+    /// # use merkle_light::hash::Algorithm;
+    /// # use merkle_light::merkle::MerkleTree;
+    /// # use std::collections::hash_map::DefaultHasher;
+    /// # use std::hash::Hasher;
+    /// # #[derive(Default)]
+    /// # struct Sip(DefaultHasher);
+    /// # impl Hasher for Sip {
+    /// #     fn write(&mut self, msg: &[u8]) { self.0.write(msg) }
+    /// #     fn finish(&self) -> u64 { self.0.finish() }
+    /// # }
+    /// # impl Algorithm<[u8; 8]> for Sip {
+    /// #     fn hash(&mut self) -> [u8; 8] { self.finish().to_le_bytes() }
+    /// #     fn reset(&mut self) { *self = Sip::default() }
+    /// # }
+    /// let mt: MerkleTree<[u8; 8], Sip> = MerkleTree::new((0..5u64).map(u64::to_le_bytes));
+    /// let mut leafs = 0;
+    /// mt.for_each_proof(|i, proof| {
+    ///     assert!(proof == mt.gen_proof(i));
+    ///     assert!(proof.validate_against::<Sip>(&mt.root()));
+    ///     leafs += 1;
+    /// });
+    /// assert_eq!(leafs, 5);
+    /// ```
+    pub fn for_each_proof<F: FnMut(usize, Proof<T>)>(&self, mut f: F) {
+        let levels: Vec<(usize, usize)> =
+            self.levels().take(self.height.saturating_sub(1)).collect();
+        // sibling of every level, of the node of the level it is read for,
+        // or None of the promoted node
+        let mut siblings: Vec<Option<(usize, Option<T>)>> = levels.iter().map(|_| None).collect();
+
+        // root is final
+        let root = self.root();
+        for i in 0..self.leafs {
+            let mut lemma = Lemma::with_capacity(self.height + 1);
+            let mut path = Path::with_capacity(self.height.saturating_sub(1));
+            lemma.push(self.data.read(i));
+            for (level, &(base, width)) in levels.iter().enumerate() {
+                let j = i >> level;
+                let sibling = match &siblings[level] {
+                    Some((node, sibling)) if *node == j => sibling,
+                    _ => {
+                        let sibling = j ^ 1;
+                        let promoted = sibling == width && self.padding.promotes(level);
+                        let read = (!promoted).then(|| self.data.read(base + sibling));
+                        &siblings[level].insert((j, read)).1
+                    }
+                };
+                if let Some(sibling) = sibling {
+                    lemma.push(sibling.clone());
+                    // j is left
+                    path.push(j & 1 == 0);
+                }
+            }
+            lemma.push(root.clone());
+            let proof = Proof::new(lemma, path).with_pair_ordering(self.ordering);
+            f(i, proof.with_position(i, self.height - 1));
+        }
+    }
+
+    /// Generate merkle tree inclusion proof for the node `index` of `level`,
+    /// the root of the subtree over the leafs below it, rather than a leaf.
+    ///
+    /// Proof of the leaf of the subtree built on its own, composed with the
+    /// proof of the node with [`Proof::compose`], is the proof of the leaf
+    /// in the whole tree. Subtrees built on their own are the same as the
+    /// ones in the tree over a power of two aligned runs of leafs.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the level is not of the tree, or `index` is out of bounds
+    /// of the level, not counting the duplicate filling it up.
+    pub fn gen_node_proof(&self, level: usize, index: usize) -> Proof<T> {
+        self.try_gen_node_proof(level, index)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate merkle tree inclusion proof for the node `index` of `level`.
+    ///
+    /// Fallible version of [`MerkleTree::gen_node_proof`].
+    pub fn try_gen_node_proof(&self, level: usize, index: usize) -> Result<Proof<T>, MerkleError> {
+        match self.levels().nth(level) {
+            Some((base, width)) if index < width => Ok(self.proof_of(base + index)),
+            _ => Err(MerkleError::NodeOutOfBounds { level, index }),
+        }
+    }
+
+    /// Returns inclusion proof for the node at memory layout index `i`.
+    fn proof_of(&self, i: usize) -> Proof<T> {
+        self.proof_with(i, |j| self.data.read(j))
+    }
+
+    /// Returns inclusion proof for the node at memory layout index `i` of
+    /// the nodes `node` returns of their indices.
+    fn proof_with<U, F>(&self, i: usize, node: F) -> Proof<U>
+    where
+        U: Eq + Clone + AsRef<[u8]>,
+        F: Fn(usize) -> U,
+    {
+        let mut lemma = Lemma::with_capacity(self.height + 1); // path + root
+        let mut path = Path::with_capacity(self.height - 1); // path - 1
+
+        let mut j = i;
+        lemma.push(node(j));
+        while let Some(parent) = self.parent_index(j) {
+            // promoted node has no sibling
+            if let Some(sibling) = self.proof_sibling(j) {
+                lemma.push(node(sibling));
+                // j is left
+                path.push(j < sibling);
+            }
+            j = parent;
+        }
+
+        // root is final
+        lemma.push(node(self.data.len() - 1));
+        let (level, base, _) = self.locate(i).expect("node is of the tree");
+        Proof::new(lemma, path)
+            .with_pair_ordering(self.ordering)
+            .with_position(i - base, self.height - 1 - level)
+    }
+
+    /// Prunes the tree down to the nodes the proofs of the leafs `indices`
+    /// are made of: the leafs themselves, their siblings along the paths to
+    /// the root, and the root.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of `indices` is out of bounds of the tree leafs.
+    ///
+    /// Panics with [`MerkleError::UnsupportedPadding`] or
+    /// [`MerkleError::UnsupportedOrdering`] unless the tree has the default
+    /// padding strategy and pair ordering.
+    pub fn prune(&self, indices: &[usize]) -> PrunedTree<T, A> {
+        self.try_prune(indices).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Prunes the tree down to the nodes of the proofs of the leafs `indices`.
+    ///
+    /// Fallible version of [`MerkleTree::prune`].
+    pub fn try_prune(&self, indices: &[usize]) -> Result<PrunedTree<T, A>, MerkleError> {
+        self.default_hashing()?;
+        let mut nodes = BTreeMap::new();
+        for &i in indices {
+            if i >= self.leafs {
+                return Err(MerkleError::IndexOutOfBounds {
+                    index: i,
+                    leafs: self.leafs,
+                });
+            }
+
+            let mut j = i;
+            nodes.insert(j, self.data.read(j));
+            while let Some(sibling) = self.sibling_index(j) {
+                nodes
+                    .entry(sibling)
+                    .or_insert_with(|| self.data.read(sibling));
+                j = self.parent_index(j).expect("non-root node has a parent");
+            }
+        }
+
+        Ok(PrunedTree::new(
+            indices.iter().cloned().collect(),
+            nodes,
+            self.leafs,
+            self.height,
+            self.root(),
+        ))
+    }
+
+    /// Generate combined inclusion proof for the leafs `indices`, which
+    /// holds every node the proofs of the leafs need once, and none of the
+    /// nodes which are hashed from the proven leafs themselves.
+    ///
+    /// Indices are sorted and deduplicated.
+    ///
+    /// # Panics
+    ///
+    /// Panics if any of `indices` is out of bounds of the tree leafs.
+    ///
+    /// Panics with [`MerkleError::UnsupportedPadding`] or
+    /// [`MerkleError::UnsupportedOrdering`] unless the tree has the default
+    /// padding strategy and pair ordering.
+    pub fn gen_multiproof(&self, indices: &[usize]) -> MultiProof<T> {
+        self.try_gen_multiproof(indices)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate combined inclusion proof for the leafs `indices`.
+    ///
+    /// Fallible version of [`MerkleTree::gen_multiproof`].
+    pub fn try_gen_multiproof(&self, indices: &[usize]) -> Result<MultiProof<T>, MerkleError> {
+        self.default_hashing()?;
+        let mut known = indices.to_vec();
+        known.sort_unstable();
+        known.dedup();
+        if let Some(&i) = known.iter().find(|&&i| i >= self.leafs) {
+            return Err(MerkleError::IndexOutOfBounds {
+                index: i,
+                leafs: self.leafs,
+            });
+        }
+
+        let leaves = known.iter().map(|&i| self.data.read(i)).collect();
+
+        let bases: Vec<usize> = Levels::new(self.leafs, self.height)
+            .map(|(base, _)| base)
+            .collect();
+        let mut nodes = Vec::new();
+        decommitments(self.leafs, self.height, &known, &mut |level, index| {
+            nodes.push(self.data.read(bases[level] + index))
+        });
+
+        Ok(MultiProof::new(
+            known,
+            leaves,
+            nodes,
+            self.leafs,
+            self.height,
+        ))
+    }
+
+    /// Generate inclusion proof for the run of leafs `range`, which holds
+    /// only the siblings left of the start of the range and right of its
+    /// end, `O(log n)` nodes however long the range is.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `range` is empty or reaches past the tree leafs.
+    ///
+    /// Panics with [`MerkleError::UnsupportedPadding`] or
+    /// [`MerkleError::UnsupportedOrdering`] unless the tree has the default
+    /// padding strategy and pair ordering.
+    pub fn gen_range_proof(&self, range: ops::Range<usize>) -> RangeProof<T> {
+        self.try_gen_range_proof(range)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate inclusion proof for the run of leafs `range`.
+    ///
+    /// Fallible version of [`MerkleTree::gen_range_proof`].
+    pub fn try_gen_range_proof(
+        &self,
+        range: ops::Range<usize>,
+    ) -> Result<RangeProof<T>, MerkleError> {
+        self.default_hashing()?;
+        if range.start >= range.end || range.end > self.leafs {
+            return Err(MerkleError::InvalidRange {
+                start: range.start,
+                end: range.end,
+                leafs: self.leafs,
+            });
+        }
+
+        let (mut lo, mut hi) = (range.start, range.end);
+        let mut nodes = Vec::new();
+        let levels = Levels::new(self.leafs, self.height);
+        for (base, width) in levels.take(self.height - 1) {
+            if lo & 1 == 1 {
+                nodes.push(self.data.read(base + lo - 1));
+            }
+            if hi & 1 == 1 && hi != width {
+                nodes.push(self.data.read(base + hi));
+            }
+            lo >>= 1;
+            hi = (hi + 1) >> 1;
+        }
+
+        Ok(RangeProof::new(range, nodes, self.leafs, self.height))
+    }
+
+    /// Generate proof that the tree over the first `old_size` leafs of this
+    /// tree is its prefix, see [`ConsistencyProof`].
+    ///
+    /// Proofs of the empty tree and of this tree itself are empty.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `old_size` is larger than the number of the tree leafs.
+    ///
+    /// Panics with [`MerkleError::UnsupportedPadding`] or
+    /// [`MerkleError::UnsupportedOrdering`] unless the tree has the default
+    /// padding strategy and pair ordering.
+    pub fn gen_consistency_proof(&self, old_size: usize) -> ConsistencyProof<T> {
+        self.try_gen_consistency_proof(old_size)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate proof that the tree over the first `old_size` leafs of this
+    /// tree is its prefix.
+    ///
+    /// Fallible version of [`MerkleTree::gen_consistency_proof`].
+    pub fn try_gen_consistency_proof(
+        &self,
+        old_size: usize,
+    ) -> Result<ConsistencyProof<T>, MerkleError> {
+        self.default_hashing()?;
+        if old_size > self.leafs {
+            return Err(MerkleError::InvalidRange {
+                start: 0,
+                end: old_size,
+                leafs: self.leafs,
+            });
+        }
+
+        let mut nodes = Vec::new();
+        if old_size == 0 || old_size == self.leafs {
+            return Ok(ConsistencyProof::new(nodes));
+        }
+
+        let start = old_size.trailing_zeros() as usize;
+        let levels = Levels::new(self.leafs, self.height).enumerate().skip(start);
+        for (level, (base, width)) in levels {
+            // subtree over the last power of two aligned run of the old leafs
+            if level == start && !old_size.is_power_of_two() {
+                nodes.push(self.data.read(base + (old_size >> start) - 1));
+            }
+            if width == 1 {
+                break;
+            }
+
+            let j = (old_size - 1) >> level;
+            if j & 1 == 1 {
+                nodes.push(self.data.read(base + j - 1));
+            } else if j + 1 < width {
+                nodes.push(self.data.read(base + j + 1));
+            }
+        }
+
+        Ok(ConsistencyProof::new(nodes))
+    }
+
+    /// Drops the leaf level of the tree, keeping the levels above it, for the
+    /// leafs which are kept elsewhere anyway. Proofs are then generated from
+    /// the leafs supplied by the caller, see [`CompactTree`].
+    ///
+    /// # Panics
+    ///
+    /// Panics with [`MerkleError::UnsupportedPadding`] or
+    /// [`MerkleError::UnsupportedOrdering`] unless the tree has the default
+    /// padding strategy and pair ordering.
+    pub fn compact(self) -> CompactTree<T, A> {
+        self.default_hashing().unwrap_or_else(|e| panic!("{}", e));
+        let root = self.root();
+        let split = match self.height {
+            0 | 1 => self.data.len(),
+            _ => self.leafs + (self.leafs & 1),
+        };
+        let data = self.data.read_range(split..self.data.len());
+        CompactTree::new(data, self.leafs, self.height, root, self.alg)
+    }
+
+    /// Rebuilds the tree under the algorithm `alg`, mapping the leaf hashes
+    /// into the leafs of the new tree with `map`, without hashing the
+    /// original data again.
+    ///
+    /// Only the leafs are mapped, the duplicates filling up odd levels and
+    /// the nodes above the leafs are hashed by `alg` anew, which the new tree
+    /// keeps, such as the algorithm of the other key of the same type.
+    pub fn rehash<U, B, F>(&self, map: F, alg: B) -> MerkleTree<U, B>
+    where
+        U: Ord + Clone + AsRef<[u8]>,
+        B: Algorithm<U>,
+        F: Fn(&T) -> U,
+    {
+        let leafs: Vec<U> = (0..self.leafs).map(|i| map(&self.data.read(i))).collect();
+        // same number of leafs lays out the same as this tree
+        MerkleTree::from_leafs_with(leafs.into(), alg, self.padding, self.ordering)
+            .expect("layout of the existing tree")
+    }
+
+    /// Returns indices of the leafs which differ between the trees, in order,
+    /// followed by the indices of the leafs only the larger tree has.
+    ///
+    /// Trees are descended from the top, and only the nodes which hashes
+    /// differ are descended into, so that only `O(d log n)` nodes are read
+    /// for `d` differing leafs out of `n`. Trees of different heights are
+    /// compared over the leafs they share, from the top level of the lower
+    /// tree, and the nodes covering the leafs past the shared ones are
+    /// descended into, since their hashes differ anyway.
+    pub fn diff<R: Store<T>>(&self, other: &MerkleTree<T, A, R>) -> Vec<usize> {
+        let leafs = cmp::min(self.leafs, other.leafs);
+        let mut diff = Vec::new();
+
+        if leafs > 0 {
+            let ours: Vec<usize> = self.levels().map(|(base, _)| base).collect();
+            let theirs: Vec<usize> = other.levels().map(|(base, _)| base).collect();
+
+            // nodes to compare as (level, index), left first
+            let mut stack = Vec::with_capacity(2 * self.height);
+            stack.push((cmp::min(self.height, other.height) - 1, 0));
+            while let Some((level, index)) = stack.pop() {
+                if index << level >= leafs {
+                    continue;
+                }
+
+                // nodes over the shared leafs alone are equal if the leafs are
+                if (index + 1) << level <= leafs {
+                    let a = self.data.read(ours[level] + index);
+                    if a == other.data.read(theirs[level] + index) {
+                        continue;
+                    }
+                    if level == 0 {
+                        diff.push(index);
+                        continue;
+                    }
+                }
+
+                stack.push((level - 1, 2 * index + 1));
+                stack.push((level - 1, 2 * index));
+            }
+        }
+
+        diff.extend(leafs..cmp::max(self.leafs, other.leafs));
+        diff
+    }
+
+    /// Returns merkle root
+    ///
+    /// Root of an empty tree is the hash of the empty data stream, of the
+    /// algorithm of the tree, such as of its key.
+    pub fn root(&self) -> T {
+        match self.data.len() {
+            0 => self.empty.clone(),
+            len => self.data.read(len - 1),
+        }
+    }
+
+    /// Returns merkle root as the `0x` prefixed lowercase hex of its bytes,
+    /// as [`Proof::to_hex_lemma`] returns the hashes of the proofs. Root is
+    /// formatted with no string allocated with
+    /// [`crate::hex::HexDisplay`].
+    pub fn root_hex(&self) -> String {
+        to_hex(self.root().as_ref())
+    }
+
+    /// Returns merkle root committed to the number of leafs, the hash of the
+    /// root followed by the number of leafs as little endian `u64`.
+    ///
+    /// Trees fill up odd levels with the duplicate of the last node, so that
+    /// the trees over `a b c` and `a b c c` have the same root, as the
+    /// blocks of CVE-2012-2459 do. Committed roots of such trees differ,
+    /// and the proofs against them are checked to be of one of the leafs,
+    /// see [`Proof::validate_committed`].
+    ///
+    /// Root is committed with the clone of the algorithm of the tree.
+    pub fn committed_root(&self) -> T
+    where
+        A: Clone,
+    {
+        commit_leafs(&mut self.alg.clone(), &self.root(), self.leafs)
+    }
+
+    /// Returns number of elements (nodes) in the tree.
+    ///
+    /// Alias of [`MerkleTree::node_count`].
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns the number of the nodes of the tree in memory layout, of the
+    /// leafs, the nodes above them and the duplicates filling up the odd
+    /// levels, the indices of `tree[i]`.
+    pub fn node_count(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if the tree contains no elements, i.e. it was built
+    /// upon no leafs.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Returns height of the tree, which is 0 for an empty tree.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns original number of elements the tree was built upon.
+    ///
+    /// Alias of [`MerkleTree::leaf_count`].
+    pub fn leafs(&self) -> usize {
+        self.leafs
+    }
+
+    /// Returns number of leafs, without padding. Leaf indices are below it.
+    pub fn leaf_count(&self) -> usize {
+        self.leafs
+    }
+
+    /// Returns number of leafs padded up to a power of two. Empty tree has
+    /// none. See [`MerkleError::PaddingIndex`].
+    pub fn padded_leaf_count(&self) -> usize {
+        padded_leafs(self.leafs)
+    }
+
+    /// Returns number of padding leafs, `padded_leaf_count() - leaf_count()`.
+    pub fn padding_leaf_count(&self) -> usize {
+        self.padded_leaf_count() - self.leafs
+    }
+
+    /// Returns `true` if the tree has no padding: its number of leafs is a
+    /// power of two, or zero.
+    pub fn is_balanced(&self) -> bool {
+        self.padding_leaf_count() == 0
+    }
+
+    /// Returns number of stored nodes in the leaf row. It is the number of
+    /// leafs, plus one for the duplicate of the last leaf when the number is
+    /// odd and above 1. No other padding is stored.
+    pub fn padded_len(&self) -> usize {
+        match self.height {
+            0 | 1 => self.leafs,
+            _ => self.leafs + (self.leafs & 1),
+        }
+    }
+
+    /// Returns `true` if `i` is the stored duplicate of the last leaf, that
+    /// is `leafs() <= i < padded_len()`.
+    pub fn is_padded_slot(&self, i: usize) -> bool {
+        self.leafs <= i && i < self.padded_len()
+    }
+
+    /// Returns the tree under node `index` of the tree level `level`, which
+    /// root is that node and which leafs are the leafs it covers.
+    ///
+    /// Returns `None` if there is no such node, or if the node right child
+    /// is the duplicate filling up an odd level, since the tree over the
+    /// node leafs would not grow as high as the node, and unless the tree
+    /// has the default padding strategy.
+    pub fn subtree(&self, level: usize, index: usize) -> Option<MerkleTree<T, A, S>>
+    where
+        A: Clone,
+    {
+        if level >= self.height || index >= self.level(level).1 {
+            return None;
+        }
+        self.default_hashing().ok()?;
+
+        let leafs = cmp::min(self.leafs - (index << level), 1 << level);
+        if level > 0 && leafs <= 1 << (level - 1) {
+            return None;
+        }
+
+        let (size, height) = tree_layout(leafs).expect("subtree fits capacity");
+        let mut data = S::new(size);
+        for (j, (base, width)) in self.levels().take(level + 1).enumerate() {
+            let start = index << (level - j);
+            let end = cmp::min(start + (1 << (level - j)), width);
+            for i in base + start..base + end {
+                data.push(self.data.read(i));
+            }
+
+            // if there is odd num of elements, fill in to the even
+            if end - start > 1 && (end - start) & 1 == 1 {
+                data.push(self.data.read(base + end - 1));
+            }
+        }
+
+        Some(MerkleTree {
+            data,
+            leafs,
+            height,
+            alg: self.alg.clone(),
+            empty: self.empty.clone(),
+            padding: self.padding,
+            ordering: self.ordering,
+            index: None,
+            _t: PhantomData,
+        })
+    }
+
+    /// Returns the level of the node at memory layout index `i`, or `None`
+    /// if `i >= len()`.
+    ///
+    /// Levels are laid out bottom-up one after another, and every odd level
+    /// but the root is followed by the duplicate of its last node. A tree
+    /// upon 5 leafs is laid out as:
+    ///
+    /// ```text
+    ///     level: 0                  1                 2           3
+    ///            [h1 h2 h3 h4 h5 h5 h12 h34 h55 h55 h1234 h5555 root]
+    /// ```
+    pub fn level_of(&self, i: usize) -> Option<usize> {
+        self.locate(i).map(|(level, _, _)| level)
+    }
+
+    /// Returns memory layout index of the parent of the node at index `i`,
+    /// or `None` for the root and for `i >= len()`.
+    ///
+    /// The duplicate filling up an odd level shares the parent with the
+    /// node it duplicates. See [`MerkleTree::level_of`] for the layout.
+    pub fn parent_index(&self, i: usize) -> Option<usize> {
+        let (level, base, width) = self.locate(i)?;
+        if level + 1 == self.height {
+            return None;
+        }
+
+        // next level starts past the filled up to the even current one
+        Some(base + ((width + 1) & !1) + ((i - base) >> 1))
+    }
+
+    /// Returns memory layout index of the node hashed together with the
+    /// node at index `i` into their parent, or `None` for the root and for
+    /// `i >= len()`.
+    ///
+    /// Sibling of the last node of an odd level is its duplicate and vice
+    /// versa. See [`MerkleTree::level_of`] for the layout.
+    pub fn sibling_index(&self, i: usize) -> Option<usize> {
+        let (level, base, _) = self.locate(i)?;
+        if level + 1 == self.height {
+            return None;
+        }
+
+        Some(base + ((i - base) ^ 1))
+    }
+
+    /// Returns memory layout index of the sibling of the node at index `i`
+    /// in the inclusion proofs of the tree, or `None` for the root and for
+    /// the last node of an odd level which the padding strategy promotes
+    /// with no sibling.
+    pub(crate) fn proof_sibling(&self, i: usize) -> Option<usize> {
+        let sibling = self.sibling_index(i)?;
+        let (level, base, width) = self.locate(i)?;
+        match sibling == base + width && self.padding.promotes(level) {
+            true => None,
+            false => Some(sibling),
+        }
+    }
+
+    /// Returns memory layout indices of the left and right children of the
+    /// node at index `i`, or `None` for the leafs, the duplicates filling
+    /// up odd levels and for `i >= len()`.
+    ///
+    /// See [`MerkleTree::level_of`] for the layout.
+    pub fn children_indices(&self, i: usize) -> Option<(usize, usize)> {
+        let (level, base, width) = self.locate(i)?;
+        if level == 0 || i - base >= width {
+            return None;
+        }
+
+        let (below, _) = self.level(level - 1);
+        let left = below + ((i - base) << 1);
+        Some((left, left + 1))
+    }
+
+    /// Returns the level of the node at memory layout index `i`, the index
+    /// of the first node of that level and the number of its nodes, not
+    /// counting the duplicate filling up odd levels.
+    pub(crate) fn locate(&self, i: usize) -> Option<(usize, usize, usize)> {
+        let mut levels = self.levels().enumerate();
+        levels.find_map(|(level, (base, width))| {
+            // odd levels are followed by the duplicate of the last node
+            let end = if width > 1 {
+                base + ((width + 1) & !1)
+            } else {
+                base + width
+            };
+            if i < end {
+                Some((level, base, width))
+            } else {
+                None
+            }
+        })
+    }
+
+    /// Returns index of the first node of the level in memory layout and
+    /// the number of nodes of the level, not counting the node duplicated
+    /// to fill up odd levels.
+    fn level(&self, level: usize) -> (usize, usize) {
+        self.levels()
+            .nth(level)
+            .expect("level is out of bounds of the tree")
+    }
+
+    /// Returns an iterator over the tree levels bottom-up as in
+    /// [`MerkleTree::level`].
+    fn levels(&self) -> Levels {
+        Levels::new(self.leafs, self.height)
+    }
+
+    /// Decomposes the tree into the store of its nodes in memory layout,
+    /// number of leafs and height, to be put back together with
+    /// [`MerkleTree::from_store`].
+    pub fn into_store(self) -> (S, usize, usize) {
+        (self.data, self.leafs, self.height)
+    }
+
+    /// Moves the nodes of the tree into the store `f` makes of its store.
+    pub(crate) fn map_store<R: Store<T>, F: FnOnce(S) -> R>(self, f: F) -> MerkleTree<T, A, R> {
+        MerkleTree {
+            data: f(self.data),
+            leafs: self.leafs,
+            height: self.height,
+            alg: self.alg,
+            empty: self.empty,
+            padding: self.padding,
+            ordering: self.ordering,
+            index: self.index,
+            _t: PhantomData,
+        }
+    }
+
+    /// Drops the room reserved past the nodes of the tree, and past the
+    /// leafs index of [`MerkleTree::build_index`], where the store can, such
+    /// as the room [`MerkleTree::push`] and [`MerkleTree::extend`] grow the
+    /// store by.
+    ///
+    /// Trees are shrunk once they are built, so that only the trees which
+    /// grew after need it. Trees which do not grow any more are better off
+    /// [`MerkleTree::freeze`]d.
+    pub fn shrink_to_fit(&mut self) {
+        self.data.shrink_to_fit();
+        if let Some(index) = self.index.as_mut() {
+            index.shrink_to_fit();
+        }
+    }
+
+    /// Returns the estimate of the bytes of the heap the tree holds, of its
+    /// store as [`Store::heap_size`] estimates it and of the leafs index,
+    /// with the room reserved past them, but not of the heap the nodes own
+    /// themselves, such as of `Vec<u8>` nodes in [`VecStore`].
+    pub fn heap_size_estimate(&self) -> usize {
+        let index = match self.index {
+            Some(ref index) => index.capacity() * size_of::<(T, usize)>(),
+            None => 0,
+        };
+        self.data.heap_size() + index
+    }
+}
+
+#[cfg(feature = "std")]
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T>> MerkleTree<T, A, S> {
+    /// Writes the leaf hashes of the tree to `w`, behind the header of the
+    /// number of leafs (8 bytes) and the length of a leaf hash (4 bytes),
+    /// both little endian, to be read back by
+    /// [`MerkleTree::deserialize_leaves`].
+    ///
+    /// Nodes above the leafs are not written, since they are rehashed on
+    /// load, so that the tree takes about half the space of its nodes.
+    ///
+    /// Fails with [`io::ErrorKind::InvalidInput`] unless the tree has the
+    /// default padding strategy and pair ordering, which are not written,
+    /// see [`MerkleTree::encode`] of them.
+    pub fn serialize_leaves<W: io::Write>(&self, mut w: W) -> io::Result<()> {
+        self.default_hashing()
+            .map_err(|e| io::Error::new(io::ErrorKind::InvalidInput, e))?;
+        let width = match self.leafs {
+            0 => 0,
+            _ => self.data.read(0).as_ref().len(),
+        };
+        w.write_all(&(self.leafs as u64).to_le_bytes())?;
+        w.write_all(&(width as u32).to_le_bytes())?;
+        self.write_leafs(&mut w, width)
+    }
+
+    /// Reads the leaf hashes written by [`MerkleTree::serialize_leaves`]
+    /// from `r`, and builds the tree upon them.
+    ///
+    /// Fails with [`io::ErrorKind::UnexpectedEof`] if the stream ends before
+    /// the last leaf does, and with [`io::ErrorKind::InvalidData`] if the
+    /// leafs are not as long as `T` or do not fit into the tree.
+    pub fn deserialize_leaves<R: io::Read>(mut r: R) -> io::Result<MerkleTree<T, A, S>>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        let mut leafs = [0; 8];
+        r.read_exact(&mut leafs)?;
+        let leafs = u64::from_le_bytes(leafs);
+        let mut width = [0; 4];
+        r.read_exact(&mut width)?;
+        let width = u32::from_le_bytes(width) as usize;
+
+        if leafs > 0 && width != T::default().as_ref().len() {
+            return Err(io::Error::new(
+                io::ErrorKind::InvalidData,
+                format!("leaf hashes of {} bytes do not fit the hash type", width),
+            ));
+        }
+
+        let (padding, ordering) = (PaddingStrategy::default(), PairOrdering::default());
+        Self::read_leafs(&mut r, leafs, A::default(), padding, ordering)
+            .and_then(|mt| mt.map_err(|e| io::Error::new(io::ErrorKind::InvalidData, e)))
     }
 
-    /// Creates new merkle tree from a list of hashable objects.
-    pub fn from_data<O: Hashable<A>, I: IntoIterator<Item = O>>(data: I) -> MerkleTree<T, A> {
-        let mut a = A::default();
-        Self::from_iter(data.into_iter().map(|x| {
-            a.reset();
-            x.hash(&mut a);
-            a.hash()
-        }))
+    /// Writes the leaf hashes one after another to `w`, each of `width`
+    /// bytes.
+    pub(crate) fn write_leafs<W: io::Write>(&self, w: &mut W, width: usize) -> io::Result<()> {
+        for i in 0..self.leafs {
+            let leaf = self.data.read(i);
+            if leaf.as_ref().len() != width {
+                return Err(io::Error::new(
+                    io::ErrorKind::InvalidInput,
+                    "leaf hashes differ in length",
+                ));
+            }
+            w.write_all(leaf.as_ref())?;
+        }
+        Ok(())
     }
 
-    fn build(&mut self) {
-        let mut a = A::default();
-        let mut width = self.leafs;
+    /// Reads `leafs` leaf hashes from `r` as written by
+    /// [`MerkleTree::write_leafs`], and builds the tree upon them with the
+    /// algorithm `alg`, the padding strategy `padding` and the pair ordering
+    /// `ordering`.
+    pub(crate) fn read_leafs<R: io::Read>(
+        r: &mut R,
+        leafs: u64,
+        alg: A,
+        padding: PaddingStrategy,
+        ordering: PairOrdering,
+    ) -> io::Result<Result<MerkleTree<T, A, S>, MerkleError>>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        // leafs count is not trusted to allocate the store for
+        let mut data = S::new(0);
+        for _ in 0..leafs {
+            let mut leaf = T::default();
+            r.read_exact(leaf.as_mut())?;
+            data.push(leaf);
+        }
+        Ok(Self::from_leafs_with(data, alg, padding, ordering))
+    }
 
-        // build tree
-        let mut i: usize = 0;
-        let mut j: usize = width;
-        while width > 1 {
-            // if there is odd num of elements, fill in to the even
-            if width & 1 == 1 {
-                let he = self.data[self.len() - 1].clone();
-                self.data.push(he);
-                width += 1;
-                j += 1;
+    /// Writes the tree as a Graphviz DOT digraph to `w`, for debugging.
+    ///
+    /// Every node is labeled with the first 4 bytes of its hash in hex, and
+    /// has the edges to its children. Root is drawn bold, and the duplicates
+    /// filling up odd levels are drawn dashed. If `highlight` is the index of
+    /// a leaf, the path from the leaf to the root is colored red and the
+    /// siblings along it, which make up the leaf proof, are colored blue.
+    ///
+    /// ```text
+    /// digraph merkle {
+    ///     node [shape=box, fontname=monospace];
+    ///     n0 [label="9e107d9d"];
+    ///     ...
+    ///     n12 [label="2fd4e1c6", style=bold];
+    ///     n6 -> n0;
+    ///     ...
+    /// }
+    /// ```
+    pub fn to_dot<W: io::Write>(&self, w: &mut W, highlight: Option<usize>) -> io::Result<()> {
+        let mut path = Vec::new();
+        let mut siblings = Vec::new();
+        if let Some(i) = highlight.filter(|&i| i < self.leafs) {
+            let mut j = Some(i);
+            while let Some(i) = j {
+                path.push(i);
+                siblings.extend(self.sibling_index(i));
+                j = self.parent_index(i);
             }
+        }
 
-            // next shift
-            while i < j {
-                a.reset();
-                let h = a.node(self.data[i].clone(), self.data[i + 1].clone());
-                self.data.push(h);
-                i += 2;
+        writeln!(w, "digraph merkle {{")?;
+        writeln!(w, "    node [shape=box, fontname=monospace];")?;
+        for i in 0..self.data.len() {
+            write!(w, "    n{} [label=\"", i)?;
+            for b in self.data.read(i).as_ref().iter().take(4) {
+                write!(w, "{:02x}", b)?;
             }
+            write!(w, "\"")?;
+
+            let (_, base, width) = self.locate(i).expect("node is in the tree");
+            if i + 1 == self.data.len() {
+                write!(w, ", style=bold")?;
+            } else if i - base >= width {
+                write!(w, ", style=dashed")?;
+            }
+            if path.contains(&i) {
+                write!(w, ", color=red")?;
+            } else if siblings.contains(&i) {
+                write!(w, ", color=blue")?;
+            }
+            writeln!(w, "];")?;
+        }
 
-            width >>= 1;
-            j += width;
+        for i in 0..self.data.len() {
+            if let Some((left, right)) = self.children_indices(i) {
+                writeln!(w, "    n{} -> n{};", i, left)?;
+                writeln!(w, "    n{} -> n{};", i, right)?;
+            }
         }
+        writeln!(w, "}}")
     }
+}
 
-    /// Generate merkle tree inclusion proof for leaf `i`
-    pub fn gen_proof(&self, i: usize) -> Proof<T> {
-        assert!(i < self.leafs); // i in [0 .. self.leafs)
+/// Trees over [`FlatStore`] give out the bytes of the nodes, borrowed out
+/// of the buffer of the store.
+impl<T, A> MerkleTree<T, A, FlatStore<T>>
+where
+    T: Ord + Clone + AsRef<[u8]> + AsMut<[u8]> + for<'a> From<&'a [u8]>,
+    A: Algorithm<T>,
+{
+    /// Returns the bytes of the node at memory layout index `i`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i >= len()`.
+    pub fn node_bytes(&self, i: usize) -> &[u8] {
+        self.data.get(i)
+    }
 
-        let mut lemma: Vec<T> = Vec::with_capacity(self.height + 1); // path + root
-        let mut path: Vec<bool> = Vec::with_capacity(self.height - 1); // path - 1
+    /// Generate merkle tree inclusion proof for leaf `i` of the bytes of
+    /// the nodes borrowed out of the store, rather than of the nodes read
+    /// out of it, otherwise the same as [`MerkleTree::gen_proof`] generates.
+    ///
+    /// Proof encodes and serializes as the proof of the nodes does, such as
+    /// with [`Proof::to_bytes`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds of the tree leafs.
+    pub fn gen_proof_bytes(&self, i: usize) -> Proof<&[u8]> {
+        self.try_gen_proof_bytes(i)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        let mut base = 0;
-        let mut j = i;
+    /// Generate merkle tree inclusion proof for leaf `i` of the bytes of
+    /// the nodes borrowed out of the store.
+    ///
+    /// Fallible version of [`MerkleTree::gen_proof_bytes`].
+    pub fn try_gen_proof_bytes(&self, i: usize) -> Result<Proof<&[u8]>, MerkleError> {
+        leaf_index(i, self.leafs)?;
 
-        // level 1 width
-        let mut width = self.leafs;
-        if width & 1 == 1 {
-            width += 1;
-        }
+        Ok(self.proof_with(i, |j| self.data.get(j)))
+    }
+}
 
-        lemma.push(self.data[j].clone());
-        while base + 1 < self.len() {
-            lemma.push(if j & 1 == 0 {
-                // j is left
-                self.data[base + j + 1].clone()
-            } else {
-                // j is right
-                self.data[base + j - 1].clone()
-            });
-            path.push(j & 1 == 0);
+/// Trees over the stores which dereference to the slice of the nodes, such
+/// as the default [`VecStore`], give out the references to the nodes.
+impl<T, A, S> MerkleTree<T, A, S>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T> + ops::Deref<Target = [T]>,
+{
+    /// Returns the cursor at the root of the tree, which navigates the
+    /// nodes down to the leafs and back up, or `None` of the empty tree.
+    pub fn cursor(&self) -> Option<Cursor<'_, T, A, S>> {
+        let root = self.data.len().checked_sub(1)?;
+        Some(Cursor::new(self, root))
+    }
 
-            base += width;
-            width >>= 1;
-            if width & 1 == 1 {
-                width += 1;
-            }
-            j >>= 1;
-        }
+    /// Generate merkle tree inclusion proof for leaf `i` of the references
+    /// to the nodes of the tree, rather than of their clones, otherwise the
+    /// same as [`MerkleTree::gen_proof`] generates. See [`ProofRef`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `i` is out of bounds of the tree leafs.
+    pub fn gen_proof_ref(&self, i: usize) -> ProofRef<'_, T> {
+        self.try_gen_proof_ref(i)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        // root is final
-        lemma.push(self.root());
-        Proof::new(lemma, path)
+    /// Generate merkle tree inclusion proof for leaf `i` of the references
+    /// to the nodes of the tree.
+    ///
+    /// Fallible version of [`MerkleTree::gen_proof_ref`].
+    pub fn try_gen_proof_ref(&self, i: usize) -> Result<ProofRef<'_, T>, MerkleError> {
+        leaf_index(i, self.leafs)?;
+
+        Ok(ProofRef::new(self.proof_with(i, |j| &self.data[j])))
     }
 
-    /// Returns merkle root
-    pub fn root(&self) -> T {
-        self.data[self.data.len() - 1].clone()
+    /// Returns an iterator over the leafs hashes of the tree.
+    ///
+    /// Only the `leafs()` hashes the tree was built upon are yielded, nodes
+    /// filling up odd levels are not. Iterating the tree as a slice, such as
+    /// with `tree.iter()`, visits all the nodes instead.
+    pub fn leaves(&self) -> slice::Iter<'_, T> {
+        self.data[..self.leafs].iter()
     }
 
-    /// Returns number of elements in the tree.
-    pub fn len(&self) -> usize {
-        self.data.len()
+    /// Returns an iterator over the leafs hashes of the tree with their
+    /// indices, the `leafs()` of them [`MerkleTree::leaves`] yields, which
+    /// the proofs of [`MerkleTree::gen_proof`] are generated of.
+    ///
+    /// Slot of the duplicate filling up the odd leafs row, of
+    /// [`MerkleTree::is_padded_slot`], is not yielded, as it is by the slice
+    /// of the nodes up to [`MerkleTree::padded_len`].
+    ///
+    /// ```
+    /// # use merkle_light::merkle::MerkleTree;
+    /// # use std::collections::hash_map::DefaultHasher;
+    /// # use merkle_light::hash::Algorithm;
+    /// # use std::hash::Hasher;
+    /// # #[derive(Clone, Default)]
+    /// # struct Sip(DefaultHasher);
+    /// # impl Hasher for Sip {
+    /// #     fn write(&mut self, msg: &[u8]) { self.0.write(msg) }
+    /// #     fn finish(&self) -> u64 { self.0.finish() }
+    /// # }
+    /// # impl Algorithm<[u8; 8]> for Sip {
+    /// #     fn hash(&mut self) -> [u8; 8] { self.finish().to_le_bytes() }
+    /// # }
+    /// let mt: MerkleTree<[u8; 8], Sip> = MerkleTree::from_iter([[1; 8], [2; 8], [3; 8]]);
+    /// assert_eq!(mt.padded_len(), 4);
+    /// assert!(mt.is_padded_slot(3));
+    ///
+    /// let leaves: Vec<(usize, &[u8; 8])> = mt.enumerate_leaves().collect();
+    /// assert_eq!(leaves.len(), mt.leafs());
+    /// assert_eq!(leaves[2], (2, &mt[2]));
+    /// assert_eq!(mt[3], mt[2]);
+    /// ```
+    pub fn enumerate_leaves(&self) -> impl Iterator<Item = (usize, &T)> {
+        self.leaves().enumerate()
     }
 
-    /// Returns `true` if the vector contains no elements.
-    pub fn is_empty(&self) -> bool {
-        self.data.is_empty()
+    /// Returns nodes of the tree level `level` bottom-up, where level 0 is
+    /// the leafs row and level `height() - 1` holds the root alone.
+    ///
+    /// The node duplicated to fill up an odd level is not a part of the row.
+    ///
+    /// # Panics
+    ///
+    /// Panics if `level >= height()`.
+    pub fn row(&self, level: usize) -> &[T] {
+        assert!(
+            level < self.height,
+            "level {} is out of bounds of the tree of height {}",
+            level,
+            self.height
+        );
+
+        let (base, width) = self.level(level);
+        &self.data[base..base + width]
     }
 
-    /// Returns height of the tree
-    pub fn height(&self) -> usize {
-        self.height
+    /// Returns an iterator over all the tree levels bottom-up, from the
+    /// leafs row to the root, as in [`MerkleTree::row`].
+    pub fn rows(&self) -> impl Iterator<Item = &[T]> {
+        (0..self.height).map(move |level| self.row(level))
     }
 
-    /// Returns original number of elements the tree was built upon.
-    pub fn leafs(&self) -> usize {
-        self.leafs
+    /// Renders the tree top-down level by level, each node labeled with the
+    /// first 2 bytes of its hash in hex, and the duplicates filling up odd
+    /// levels as dots. Every node is centered over its children:
+    ///
+    /// ```text
+    ///                   e57c
+    ///         7bdb                90b7
+    ///    83bb      1ff7      8c71      ....
+    /// 8f1f 17eb 60d5 99a1 83b3 ....
+    /// ```
+    ///
+    /// Trees over more than 16 leafs are too wide for that, and are listed
+    /// one level per line instead, as `level: nodes...`.
+    pub fn pretty(&self) -> String {
+        // label of 2 bytes in hex, and space between the leafs
+        const CELL: usize = 5;
+        const LABEL: usize = 4;
+
+        let triangle = self.height <= 5;
+        let mut out = String::new();
+        for level in (0..self.height).rev() {
+            let row = self.row(level);
+            let mut labels: Vec<String> = row
+                .iter()
+                .map(|node| {
+                    let mut label = String::new();
+                    for b in node.as_ref().iter().take(LABEL / 2) {
+                        let _ = write!(label, "{:02x}", b);
+                    }
+                    label
+                })
+                .collect();
+            if row.len() > 1 && row.len() & 1 == 1 {
+                labels.push(".".repeat(LABEL));
+            }
+
+            if triangle {
+                let span = CELL << level;
+                let mut line = String::new();
+                for (k, label) in labels.iter().enumerate() {
+                    let start = k * span + (span - LABEL) / 2;
+                    while line.len() < start {
+                        line.push(' ');
+                    }
+                    line.push_str(label);
+                }
+                out.push_str(&line);
+            } else {
+                let _ = write!(out, "{}:", level);
+                for label in &labels {
+                    out.push(' ');
+                    out.push_str(label);
+                }
+            }
+            out.push('\n');
+        }
+        out
+    }
+
+    /// Returns hash of the leaf `i`, or `None` if `i >= leafs()`.
+    pub fn leaf(&self, i: usize) -> Option<&T> {
+        self.node(0, i)
+    }
+
+    /// Returns node `index` of the tree level `level`, numbered as in
+    /// [`MerkleTree::row`], or `None` if there is no such node.
+    ///
+    /// The node duplicated to fill up an odd level is not addressable.
+    pub fn node(&self, level: usize, index: usize) -> Option<&T> {
+        if level >= self.height {
+            return None;
+        }
+
+        let (base, width) = self.level(level);
+        if index < width {
+            Some(&self.data[base + index])
+        } else {
+            None
+        }
     }
 
     /// Extracts a slice containing the entire vector.
@@ -164,45 +2596,310 @@ impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> MerkleTree<T, A> {
     }
 }
 
-impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> FromIterator<T> for MerkleTree<T, A> {
-    /// Creates new merkle tree from an iterator over hashable objects.
-    fn from_iter<I: IntoIterator<Item = T>>(into: I) -> Self {
-        let iter = into.into_iter();
-        let mut data: Vec<T> = match iter.size_hint().1 {
-            Some(e) => {
-                let pow = next_pow2(e);
-                let size = 2 * pow - 1;
-                Vec::with_capacity(size)
-            }
-            None => Vec::new(),
-        };
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> MerkleTree<T, A> {
+    /// Creates new merkle tree from a vector of hashes, hashing the leafs in
+    /// place of the vector storage without cloning them.
+    pub fn from_vec(data: Vec<T>) -> MerkleTree<T, A> {
+        Self::try_from_vec(data).unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        // leafs
+    /// Creates new merkle tree from a vector of hashes.
+    ///
+    /// Fallible version of [`MerkleTree::from_vec`].
+    pub fn try_from_vec(data: Vec<T>) -> Result<MerkleTree<T, A>, MerkleError> {
         let mut a = A::default();
-        for item in iter {
-            a.reset();
-            data.push(a.leaf(item));
-        }
+        // collecting a vector into itself reuses its allocation
+        let data: Vec<T> = data
+            .into_iter()
+            .map(|item| {
+                a.reset();
+                a.leaf(item)
+            })
+            .collect();
+
+        Self::from_leafs(data.into())
+    }
 
-        let leafs = data.len();
-        let pow = next_pow2(leafs);
-        let size = 2 * pow - 1;
+    /// Creates merkle tree from its nodes in memory layout, number of leafs
+    /// and height, as returned by [`MerkleTree::into_parts`].
+    ///
+    /// Only the layout is validated, as in [`MerkleTree::from_store`].
+    pub fn from_parts(
+        data: Vec<T>,
+        leafs: usize,
+        height: usize,
+    ) -> Result<MerkleTree<T, A>, MerkleError> {
+        Self::from_store(data.into(), leafs, height)
+    }
+
+    /// Returns all the tree nodes in memory layout, without cloning them.
+    pub fn into_vec(self) -> Vec<T> {
+        self.data.into_vec()
+    }
+
+    /// Decomposes the tree into its nodes in memory layout, number of leafs
+    /// and height, to be put back together with [`MerkleTree::from_parts`].
+    pub fn into_parts(self) -> (Vec<T>, usize, usize) {
+        (self.data.into_vec(), self.leafs, self.height)
+    }
+
+    /// Freezes the tree into [`FrozenTree`], of its nodes moved into the
+    /// boxed slice of exactly the nodes, with no room reserved past them,
+    /// which reads as the tree does but is not mutated any more.
+    pub fn freeze(mut self) -> FrozenTree<T, A> {
+        self.shrink_to_fit();
+        FrozenTree::new(self.map_store(BoxStore::from))
+    }
+}
+
+#[cfg(feature = "rayon")]
+impl<T: Ord + Clone + AsRef<[u8]> + Send + Sync, A: Algorithm<T>> MerkleTree<T, A> {
+    /// Creates new merkle tree from a sequence of hashes, hashing the leafs
+    /// and the nodes of each tree level in parallel.
+    ///
+    /// Resulting tree is identical to the one built by [`MerkleTree::new`].
+    pub fn from_iter_parallel<I: IntoIterator<Item = T>>(data: I) -> MerkleTree<T, A> {
+        Self::try_from_iter_parallel(data).unwrap_or_else(|e| panic!("{}", e))
+    }
 
-        assert!(leafs > 1);
+    /// Creates new merkle tree from a sequence of hashes, hashing in parallel.
+    ///
+    /// Fallible version of [`MerkleTree::from_iter_parallel`].
+    pub fn try_from_iter_parallel<I: IntoIterator<Item = T>>(
+        data: I,
+    ) -> Result<MerkleTree<T, A>, MerkleError> {
+        let data: Vec<T> = data.into_iter().collect();
+        let (size, height) = tree_layout(data.len())?;
+
+        // every worker hashes with its own algorithm instance
+        let mut leafs: Vec<T> = Vec::with_capacity(size);
+        data.into_par_iter()
+            .map_init(A::default, |a, item| {
+                a.reset();
+                a.leaf(item)
+            })
+            .collect_into_vec(&mut leafs);
 
         let mut mt: MerkleTree<T, A> = MerkleTree {
-            data,
-            leafs,
-            height: log2_pow2(size + 1),
-            _a: PhantomData,
+            leafs: leafs.len(),
+            data: leafs.into(),
+            height,
+            alg: A::default(),
+            empty: A::default().hash(),
+            padding: PaddingStrategy::default(),
+            ordering: PairOrdering::default(),
+            index: None,
+            _t: PhantomData,
+        };
+        mt.build_parallel();
+        Ok(mt)
+    }
+
+    /// Creates new merkle tree from a list of hashable objects, hashing the
+    /// objects in parallel.
+    ///
+    /// Leafs keep the order of the objects, and the resulting tree is
+    /// identical to the one built by [`MerkleTree::from_data`].
+    pub fn from_data_parallel<O, I>(data: I) -> MerkleTree<T, A>
+    where
+        O: Hashable<A>,
+        I: IntoParallelIterator<Item = O>,
+    {
+        Self::try_from_data_parallel(data).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new merkle tree from a list of hashable objects, hashing in
+    /// parallel.
+    ///
+    /// Fallible version of [`MerkleTree::from_data_parallel`].
+    pub fn try_from_data_parallel<O, I>(data: I) -> Result<MerkleTree<T, A>, MerkleError>
+    where
+        O: Hashable<A>,
+        I: IntoParallelIterator<Item = O>,
+    {
+        // every worker hashes with its own algorithm instance
+        let hashes: Vec<T> = data
+            .into_par_iter()
+            .map_init(A::default, |a, x| hash_data(a, &x))
+            .collect();
+
+        Self::try_from_iter_parallel(hashes)
+    }
+
+    /// Builds the levels above the leafs row, hashing the nodes of each
+    /// level in parallel, since they only depend on the level below.
+    fn build_parallel(&mut self) {
+        let mut levels = self.levels();
+        let (mut base, mut width) = match levels.next() {
+            Some(level) => level,
+            None => return,
         };
 
-        mt.build();
-        mt
+        for (next, _) in levels {
+            // if there is odd num of elements, fill in to the even
+            if width & 1 == 1 {
+                let he = self.data[base + width - 1].clone();
+                self.data.push(he);
+            }
+
+            let nodes: Vec<T> = self.data[base..next]
+                .par_chunks(2)
+                .map_init(A::default, |a, pair| {
+                    a.reset();
+                    a.node(pair[0].clone(), pair[1].clone())
+                })
+                .collect();
+            for node in nodes {
+                self.data.push(node);
+            }
+
+            base = next;
+            width = self.data.len() - next;
+        }
+    }
+}
+
+/// Iterator over the index of the first node and the width of each tree
+/// level in memory layout.
+#[derive(Debug, Clone)]
+pub(crate) struct Levels {
+    base: usize,
+    width: usize,
+    remaining: usize,
+}
+
+impl Levels {
+    /// Returns an iterator over the levels of the tree over `leafs` leafs
+    /// of height `height`.
+    pub(crate) fn new(leafs: usize, height: usize) -> Levels {
+        Levels {
+            base: 0,
+            width: leafs,
+            remaining: height,
+        }
+    }
+}
+
+impl Iterator for Levels {
+    type Item = (usize, usize);
+
+    fn next(&mut self) -> Option<(usize, usize)> {
+        if self.remaining == 0 {
+            return None;
+        }
+        self.remaining -= 1;
+
+        let level = (self.base, self.width);
+        // odd levels are filled up with the duplicate of the last node
+        self.base += self.width;
+        if self.width > 1 {
+            self.base += self.width & 1;
+        }
+        self.width = (self.width + 1) >> 1;
+        Some(level)
+    }
+}
+
+/// Trees can be collected from any iterator over hashes, with or without
+/// the exact size hint. Algorithm is instantiated with `A::default()`.
+///
+/// ```
+/// extern crate merkle_light;
+///
+/// use merkle_light::hash::{Algorithm, Hashable};
+/// use merkle_light::merkle::MerkleTree;
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+///
+/// #[derive(Default)]
+/// struct Sip(DefaultHasher);
+///
+/// impl Hasher for Sip {
+///     fn write(&mut self, msg: &[u8]) {
+///         self.0.write(msg)
+///     }
+///
+///     fn finish(&self) -> u64 {
+///         self.0.finish()
+///     }
+/// }
+///
+/// impl Algorithm<[u8; 8]> for Sip {
+///     fn hash(&mut self) -> [u8; 8] {
+///         self.0.finish().to_le_bytes()
+///     }
+/// }
+///
+/// fn main() {
+///     let hash = |x: &&str| {
+///         let mut a = Sip::default();
+///         x.hash(&mut a);
+///         a.hash()
+///     };
+///     let items = ["a", "b", "c", "d", "e"];
+///
+///     let t: MerkleTree<[u8; 8], Sip> = items.iter().map(hash).collect();
+///     assert_eq!(t.root(), MerkleTree::<[u8; 8], Sip>::new(items.iter().map(hash)).root());
+///
+///     // no upper bound on the number of leafs here
+///     let t: MerkleTree<[u8; 8], Sip> = items.iter().filter(|x| **x != "c").map(hash).collect();
+///     assert_eq!(t.leafs(), 4);
+/// }
+/// ```
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T>> FromIterator<T>
+    for MerkleTree<T, A, S>
+{
+    /// Creates new merkle tree from an iterator over hashable objects.
+    fn from_iter<I: IntoIterator<Item = T>>(into: I) -> Self {
+        Self::try_from_iter(into).unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> From<Vec<T>> for MerkleTree<T, A> {
+    /// Creates new merkle tree from a vector of hashes, as in
+    /// [`MerkleTree::from_vec`].
+    fn from(data: Vec<T>) -> Self {
+        Self::from_vec(data)
+    }
+}
+
+/// Extending the tree appends all the new leafs first, and then rehashes
+/// only the nodes over the new leafs and the right spine of the old tree,
+/// as many times as tree height grows.
+///
+/// # Panics
+///
+/// Panics if the tree overflows capacity.
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T>> Extend<T> for MerkleTree<T, A, S> {
+    fn extend<I: IntoIterator<Item = T>>(&mut self, into: I) {
+        let leafs = self.leafs;
+        let upper = self.data.split_off(leafs);
+
+        for item in into {
+            self.alg.reset();
+            self.data.push(self.alg.leaf(item));
+        }
+
+        let layout = tree_layout(self.data.len()).unwrap_or_else(|e| panic!("{}", e));
+        self.rebuild(upper, leafs, layout);
     }
 }
 
-impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> ops::Deref for MerkleTree<T, A> {
+/// Tree dereferences to the slice of all its nodes in memory layout, so that
+/// `tree.iter()`, `tree.first()` or `&tree[..tree.leafs()]` work directly.
+/// Leafs are better exported with [`MerkleTree::leaves`] or
+/// [`MerkleTree::enumerate_leaves`], as the slice past `leafs()` goes on to
+/// the duplicate filling up the odd leafs row and the levels above.
+///
+/// Inherent [`MerkleTree::len`] and [`MerkleTree::is_empty`] take precedence
+/// over the slice ones, but they report exactly the same as the slice does:
+/// the number of nodes in the tree, not the number of leafs.
+impl<T, A, S> ops::Deref for MerkleTree<T, A, S>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T> + ops::Deref<Target = [T]>,
+{
     type Target = [T];
 
     fn deref(&self) -> &[T] {
@@ -210,26 +2907,292 @@ impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> ops::Deref for MerkleTree<T,
     }
 }
 
-/// `next_pow2` returns next highest power of two from a given number if
-/// it is not already a power of two.
-///
-/// [](http://locklessinc.com/articles/next_pow2/)
-/// [](https://stackoverflow.com/questions/466204/rounding-up-to-next-power-of-2/466242#466242)
-pub fn next_pow2(mut n: usize) -> usize {
-    n -= 1;
-    n |= n >> 1;
-    n |= n >> 2;
-    n |= n >> 4;
-    n |= n >> 8;
-    n |= n >> 16;
-    #[cfg(target_pointer_width = "64")]
+impl<'a, T, A, S> IntoIterator for &'a MerkleTree<T, A, S>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T> + ops::Deref<Target = [T]>,
+{
+    type Item = &'a T;
+    type IntoIter = slice::Iter<'a, T>;
+
+    /// Creates an iterator over the leafs hashes of the tree.
+    fn into_iter(self) -> slice::Iter<'a, T> {
+        self.leaves()
+    }
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> IntoIterator for MerkleTree<T, A> {
+    type Item = T;
+    type IntoIter = vec::IntoIter<T>;
+
+    /// Creates a consuming iterator over the leafs hashes of the tree.
+    fn into_iter(mut self) -> vec::IntoIter<T> {
+        self.data.truncate(self.leafs);
+        self.data.into_vec().into_iter()
+    }
+}
+
+/// Trees serialize as their nodes in memory layout, number of leafs,
+/// height, padding strategy and pair ordering.
+///
+/// Deserializing checks the layout as [`MerkleTree::from_parts`] does, but
+/// does not rehash the nodes. Missing padding strategy or pair ordering
+/// means the default. Algorithm is `A::default()`.
+#[cfg(feature = "serde")]
+impl<T, A> Serialize for MerkleTree<T, A>
+where
+    T: Ord + Clone + AsRef<[u8]> + Serialize,
+    A: Algorithm<T>,
+{
+    fn serialize<R: Serializer>(&self, serializer: R) -> Result<R::Ok, R::Error> {
+        // layout ahead of the nodes, which bounds them as they are read
+        let mut s = serializer.serialize_struct("MerkleTree", 5)?;
+        s.serialize_field("leafs", &self.leafs)?;
+        s.serialize_field("height", &self.height)?;
+        s.serialize_field("data", self.as_slice())?;
+        s.serialize_field("padding", &self.padding)?;
+        s.serialize_field("ordering", &self.ordering)?;
+        s.end()
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T, A> Deserialize<'de> for MerkleTree<T, A>
+where
+    T: Ord + Clone + AsRef<[u8]> + Deserialize<'de>,
+    A: Algorithm<T>,
+{
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        const FIELDS: &[&str] = &["leafs", "height", "data", "padding", "ordering"];
+        deserializer.deserialize_struct("MerkleTree", FIELDS, TreeVisitor(PhantomData))
+    }
+}
+
+/// Visitor of the serialized tree, which reads no more nodes than the tree
+/// over the leafs declared ahead of them lays out.
+#[cfg(feature = "serde")]
+struct TreeVisitor<T, A>(PhantomData<(T, A)>);
+
+#[cfg(feature = "serde")]
+impl<'de, T, A> de::Visitor<'de> for TreeVisitor<T, A>
+where
+    T: Ord + Clone + AsRef<[u8]> + Deserialize<'de>,
+    A: Algorithm<T>,
+{
+    type Value = MerkleTree<T, A>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.write_str("struct MerkleTree")
+    }
+
+    fn visit_seq<V: de::SeqAccess<'de>>(self, mut seq: V) -> Result<Self::Value, V::Error> {
+        let missing = |i| de::Error::invalid_length(i, &"struct MerkleTree with 5 elements");
+        let leafs: usize = seq.next_element()?.ok_or_else(|| missing(0))?;
+        let height: usize = seq.next_element()?.ok_or_else(|| missing(1))?;
+        let nodes = node_bound(leafs).map_err(de::Error::custom)?;
+        let data = seq.next_element_seed(Bounded::new(nodes))?;
+        let data = data.ok_or_else(|| missing(2))?;
+        let padding = seq.next_element()?.unwrap_or_default();
+        let ordering = seq.next_element()?.unwrap_or_default();
+        tree_of_parts(data, leafs, height, padding, ordering)
+    }
+
+    fn visit_map<V: de::MapAccess<'de>>(self, mut map: V) -> Result<Self::Value, V::Error> {
+        #[derive(Deserialize)]
+        #[serde(field_identifier, rename_all = "lowercase")]
+        enum Field {
+            Leafs,
+            Height,
+            Data,
+            Padding,
+            Ordering,
+            #[serde(other)]
+            Other,
+        }
+
+        let (mut leafs, mut height, mut data) = (None, None, None);
+        let (mut padding, mut ordering) = (None, None);
+        while let Some(field) = map.next_key()? {
+            match field {
+                Field::Leafs => leafs = Some(map.next_value()?),
+                Field::Height => height = Some(map.next_value()?),
+                // bounded only if the leafs are ahead, as they are written
+                Field::Data => {
+                    let nodes = match leafs {
+                        Some(leafs) => node_bound(leafs).map_err(de::Error::custom)?,
+                        None => usize::MAX,
+                    };
+                    data = Some(map.next_value_seed(Bounded::new(nodes))?);
+                }
+                Field::Padding => padding = Some(map.next_value()?),
+                Field::Ordering => ordering = Some(map.next_value()?),
+                Field::Other => {
+                    map.next_value::<de::IgnoredAny>()?;
+                }
+            }
+        }
+
+        let leafs = leafs.ok_or_else(|| de::Error::missing_field("leafs"))?;
+        let height = height.ok_or_else(|| de::Error::missing_field("height"))?;
+        let data = data.ok_or_else(|| de::Error::missing_field("data"))?;
+        // missing of the trees serialized before they were
+        let (padding, ordering) = (padding.unwrap_or_default(), ordering.unwrap_or_default());
+        tree_of_parts(data, leafs, height, padding, ordering)
+    }
+}
+
+/// Returns the number of the nodes of the tree over `leafs` leafs, which
+/// bounds the nodes deserialized.
+#[cfg(feature = "serde")]
+fn node_bound(leafs: usize) -> Result<usize, MerkleError> {
+    tree_layout(leafs).map(|(nodes, _)| nodes)
+}
+
+/// Returns the tree of the deserialized parts, of the layout validated as
+/// in [`MerkleTree::from_parts`].
+#[cfg(feature = "serde")]
+fn tree_of_parts<T, A, E>(
+    data: Vec<T>,
+    leafs: usize,
+    height: usize,
+    padding: PaddingStrategy,
+    ordering: PairOrdering,
+) -> Result<MerkleTree<T, A>, E>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    E: de::Error,
+{
+    let mt = MerkleTree::from_parts(data, leafs, height).map_err(E::custom)?;
+    Ok(mt.with_padding(padding).with_pair_ordering(ordering))
+}
+
+#[cfg(feature = "serde")]
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> MerkleTree<T, A> {
+    /// Deserializes the tree as [`Deserialize`] does, and rehashes all its
+    /// nodes, for the trees from untrusted sources. Fails with the error of
+    /// [`MerkleTree::validate`] if any node disagrees with its children.
+    ///
+    /// Fits `#[serde(deserialize_with = "MerkleTree::deserialize_verified")]`
+    /// of the fields of the tree type. Nodes are read after the number of
+    /// leafs, and no more of them than the tree over the leafs lays out, so
+    /// that the length prefix of the nodes cannot exhaust the memory.
+    pub fn deserialize_verified<'de, D>(deserializer: D) -> Result<Self, D::Error>
+    where
+        T: Deserialize<'de>,
+        A: Clone,
+        D: Deserializer<'de>,
     {
-        n |= n >> 32;
+        let mt = Self::deserialize(deserializer)?;
+        mt.validate().map_err(de::Error::custom)?;
+        Ok(mt)
+    }
+}
+
+impl<T, A, S, I> ops::Index<I> for MerkleTree<T, A, S>
+where
+    T: Ord + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T> + ops::Deref<Target = [T]>,
+    I: SliceIndex<[T]> + Clone + fmt::Debug,
+{
+    type Output = I::Output;
+
+    /// Returns node hash (or hashes for a range) at index `i` of the tree
+    /// in memory layout.
+    fn index(&self, i: I) -> &I::Output {
+        self.data.get(i.clone()).unwrap_or_else(|| {
+            panic!(
+                "node index {:?} is out of bounds of the tree with {} nodes",
+                i,
+                self.data.len()
+            )
+        })
+    }
+}
+
+/// `next_pow2` returns next highest power of two from a given number if
+/// it is not already a power of two, and 1 of 0, as
+/// [`usize::next_power_of_two`] does, whatever the width of `usize` is.
+///
+/// # Panics
+///
+/// Panics if the power of two overflows `usize`, of the numbers past
+/// `usize::MAX / 2 + 1`.
+pub fn next_pow2(n: usize) -> usize {
+    if n <= 1 {
+        return 1;
+    }
+
+    // power is the bit above the highest one of n - 1
+    let bits = usize::BITS - (n - 1).leading_zeros();
+    assert!(
+        bits < usize::BITS,
+        "next power of two of {} overflows usize",
+        n
+    );
+    1 << bits
+}
+
+/// `tree_layout` returns storage capacity and height of the tree over
+/// `leafs` leafs, or an error if the tree would not fit into memory.
+pub(crate) fn tree_layout(leafs: usize) -> Result<(usize, usize), MerkleError> {
+    if leafs == 0 {
+        return Ok((0, 0));
     }
-    n + 1
+
+    let size = leafs
+        .checked_next_power_of_two()
+        .and_then(|pow| pow.checked_mul(2))
+        .ok_or(MerkleError::CapacityOverflow { leafs })?
+        - 1;
+
+    Ok((size, log2_pow2(size + 1)))
 }
 
 /// find power of 2 of a number which is power of 2
 pub fn log2_pow2(n: usize) -> usize {
     n.trailing_zeros() as usize
 }
+
+/// Returns the root of the tree of no leafs hashed with `alg`, the hash of
+/// the empty data stream of the reset algorithm, such as of its key.
+fn empty_root<T: Clone + AsRef<[u8]>, A: Algorithm<T>>(alg: &mut A) -> T {
+    alg.reset();
+    alg.hash()
+}
+
+/// Calls `f` with every node of the sorted `nodes` and the proofs of the
+/// node, the run of `proofs` along the run of the node in `nodes`.
+fn each_run<F: FnMut(usize, &[usize])>(nodes: &[usize], proofs: &[usize], mut f: F) {
+    let mut start = 0;
+    while start < nodes.len() {
+        let j = nodes[start];
+        let end = start + nodes[start..].iter().take_while(|&&n| n == j).count();
+        f(j, &proofs[start..end]);
+        start = end;
+    }
+}
+
+/// Checks that `index` is of one of the `leafs` leafs of the tree, failing
+/// with [`MerkleError::PaddingIndex`] if it is of the padding up to
+/// [`padded_leafs`], and [`MerkleError::IndexOutOfBounds`] if it is past.
+pub(crate) fn leaf_index(index: usize, leafs: usize) -> Result<(), MerkleError> {
+    match index {
+        i if i < leafs => Ok(()),
+        i if i < padded_leafs(leafs) => Err(MerkleError::PaddingIndex { index, leafs }),
+        _ => Err(MerkleError::IndexOutOfBounds { index, leafs }),
+    }
+}
+
+/// Returns the number of the leafs of the complete tree over `leafs`
+/// leafs, the power of two they are padded up to: 0 of no leafs, and 1 of
+/// the tree of one leaf, which is the leaf.
+pub(crate) fn padded_leafs(leafs: usize) -> usize {
+    match leafs {
+        0 | 1 => leafs,
+        // more leafs than the address space holds nodes of do not lay out
+        _ => leafs.checked_next_power_of_two().unwrap_or(usize::MAX),
+    }
+}
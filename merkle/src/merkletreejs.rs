@@ -0,0 +1,245 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::hash::Hasher;
+use crate::hash::Algorithm;
+use crate::merkle::{MerkleError, MerkleTree, PaddingStrategy, PairOrdering};
+use crate::proof::Proof;
+
+/// [`Algorithm`] of the trees of merkletreejs over the hash function of the
+/// inner algorithm `A`, which concatenates the children of the nodes with
+/// no prefix, and takes the leafs as they are:
+///
+/// ```text
+/// leaf(x)       = x
+/// node(l, r)    = A(l || r)
+/// ```
+///
+/// `A(x)` is the hash of `A` of the bytes `x` written, such as SHA-256 of
+/// [`crate::sha256::Sha256Algorithm`] or Keccak-256 of
+/// [`crate::keccak::Keccak256Algorithm`], as the `hashFn` of merkletreejs.
+#[derive(Debug, Clone, Default)]
+pub struct JsCompatAlgorithm<A>(pub A);
+
+impl<A: Hasher> Hasher for JsCompatAlgorithm<A> {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl<T: Clone + AsRef<[u8]>, A: Algorithm<T>> Algorithm<T> for JsCompatAlgorithm<A> {
+    #[inline]
+    fn hash(&mut self) -> T {
+        self.0.hash()
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.0.reset()
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: T) -> T {
+        leaf
+    }
+
+    #[inline]
+    fn node(&mut self, left: T, right: T) -> T {
+        self.0.write(left.as_ref());
+        self.0.write(right.as_ref());
+        self.0.hash()
+    }
+
+    #[inline]
+    fn node_ref(&mut self, left: &T, right: &T) -> T {
+        self.0.write(left.as_ref());
+        self.0.write(right.as_ref());
+        self.0.hash()
+    }
+}
+
+/// Options of the `MerkleTree` of merkletreejs the trees are built to
+/// match, of the same names and defaults, all off:
+///
+/// - `hash_leaves` hashes the leafs before building the tree upon them,
+///   rather than taking them as they are;
+/// - `sort_pairs` sorts the children of every node before hashing them, as
+///   [`PairOrdering::Sorted`] does;
+/// - `duplicate_odd` hashes the last node of the odd levels with itself, as
+///   [`PaddingStrategy::HashWithSelf`] does, rather than promoting it as
+///   [`PaddingStrategy::PromoteOdd`] does.
+///
+/// The other options of merkletreejs, such as `sortLeaves` and
+/// `isBitcoinTree`, are not supported.
+///
+/// ```
+/// # #[cfg(feature = "sha256")] {
+/// use merkle_light::hash::Algorithm;
+/// use merkle_light::merkle::MerkleTree;
+/// use merkle_light::merkletreejs::{JsCompatAlgorithm, JsCompatOptions};
+/// use merkle_light::sha256::{Sha256Algorithm, Sha256Hash};
+/// use std::hash::Hasher;
+///
+/// // new MerkleTree(['a', 'b', 'c'].map(x => SHA256(x)), SHA256)
+/// let leaves = [b"a", b"b", b"c"].map(|x| {
+///     let mut a = Sha256Algorithm::new();
+///     a.write(x);
+///     a.hash()
+/// });
+/// let tree: MerkleTree<Sha256Hash, JsCompatAlgorithm<Sha256Algorithm>> =
+///     MerkleTree::from_js_leaves(leaves, JsCompatOptions::default());
+/// assert_eq!(
+///     tree.root().to_string(),
+///     "7075152d03a5cd92104887b476862778ec0c87be5c2fa1c0a90f87c49fad6eff"
+/// );
+/// # }
+/// ```
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash, Default)]
+pub struct JsCompatOptions {
+    /// Hashes the leafs, as `hashLeaves`.
+    pub hash_leaves: bool,
+    /// Sorts the children of the nodes, as `sortPairs`.
+    pub sort_pairs: bool,
+    /// Hashes the last node of the odd levels with itself, as
+    /// `duplicateOdd`.
+    pub duplicate_odd: bool,
+}
+
+impl JsCompatOptions {
+    /// Returns the padding strategy of the trees of the options.
+    pub fn padding(&self) -> PaddingStrategy {
+        match self.duplicate_odd {
+            true => PaddingStrategy::HashWithSelf,
+            false => PaddingStrategy::PromoteOdd,
+        }
+    }
+
+    /// Returns the pair ordering of the trees of the options.
+    pub fn pair_ordering(&self) -> PairOrdering {
+        match self.sort_pairs {
+            true => PairOrdering::Sorted,
+            false => PairOrdering::Ordered,
+        }
+    }
+
+    /// Returns the leaf of the tree of the options of the leaf `leaf` given
+    /// to the tree, which is the target node merkletreejs verifies the
+    /// proofs of: its hash with `A` if `hash_leaves`, and the leaf itself
+    /// otherwise.
+    pub fn leaf<T, A: Algorithm<T>>(&self, leaf: T) -> T
+    where
+        T: Clone + AsRef<[u8]>,
+    {
+        if !self.hash_leaves {
+            return leaf;
+        }
+        let mut a = A::default();
+        a.write(leaf.as_ref());
+        a.hash()
+    }
+
+    /// Verifies the proof against the trusted root `root` as
+    /// `MerkleTree.verify` of merkletreejs does, hashing up from the target
+    /// node `leaf`, as returned by [`JsCompatOptions::leaf`], with the
+    /// algorithm `A` of the tree.
+    pub fn verify<T, A>(&self, proof: &[JsProofNode<T>], leaf: &T, root: &T) -> bool
+    where
+        T: Eq + Clone + AsRef<[u8]>,
+        A: Algorithm<T>,
+    {
+        let mut a = JsCompatAlgorithm(A::default());
+        let h = proof.iter().fold(leaf.clone(), |h, node| {
+            let data = node.data.clone();
+            match (self.sort_pairs, node.position) {
+                (true, _) => PairOrdering::Sorted.node(&mut a, h, data),
+                (false, JsPosition::Left) => PairOrdering::Ordered.node(&mut a, data, h),
+                (false, JsPosition::Right) => PairOrdering::Ordered.node(&mut a, h, data),
+            }
+        });
+        h == *root
+    }
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> MerkleTree<T, JsCompatAlgorithm<A>> {
+    /// Creates new merkle tree of the leafs as merkletreejs builds it of
+    /// the hash function of `A` and of the options `options`.
+    ///
+    /// Root of the empty tree is the hash of no bytes, where merkletreejs
+    /// has the empty buffer.
+    pub fn from_js_leaves<I: IntoIterator<Item = T>>(
+        leaves: I,
+        options: JsCompatOptions,
+    ) -> MerkleTree<T, JsCompatAlgorithm<A>> {
+        Self::try_from_js_leaves(leaves, options).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new merkle tree of the leafs as merkletreejs builds it.
+    ///
+    /// Fallible version of [`MerkleTree::from_js_leaves`].
+    pub fn try_from_js_leaves<I: IntoIterator<Item = T>>(
+        leaves: I,
+        options: JsCompatOptions,
+    ) -> Result<MerkleTree<T, JsCompatAlgorithm<A>>, MerkleError> {
+        let leaves = leaves.into_iter().map(|l| options.leaf::<T, A>(l));
+        MerkleTree::try_build(leaves, options.padding(), options.pair_ordering())
+    }
+}
+
+/// Side of the sibling of a step of the proof of merkletreejs.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub enum JsPosition {
+    /// Sibling is the left child, `"left"`.
+    Left,
+    /// Sibling is the right child, `"right"`.
+    Right,
+}
+
+impl JsPosition {
+    /// Returns the position as merkletreejs names it.
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            JsPosition::Left => "left",
+            JsPosition::Right => "right",
+        }
+    }
+}
+
+/// Step of the proof as `getProof` of merkletreejs returns it, of the
+/// sibling and its side: `{ position: 'left' | 'right', data: Buffer }`.
+#[derive(Debug, Clone, Copy, Eq, PartialEq, Hash)]
+pub struct JsProofNode<T> {
+    /// Side of the sibling.
+    pub position: JsPosition,
+    /// Sibling.
+    pub data: T,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> Proof<T> {
+    /// Returns the steps of the proof from the leaf up, as `getProof` of
+    /// merkletreejs returns them, which `MerkleTree.verify` takes.
+    ///
+    /// Proof of the last leaf of the trees of `duplicate_odd` has the steps
+    /// of the levels where the node is hashed with itself, which `getProof`
+    /// leaves out, so that merkletreejs fails to verify its own proof, but
+    /// verifies this one.
+    pub fn to_js_proof(&self) -> Vec<JsProofNode<T>> {
+        self.lemma()
+            .iter()
+            .skip(1)
+            .zip(self.path())
+            .map(|(sibling, &left)| JsProofNode {
+                position: match left {
+                    true => JsPosition::Right,
+                    false => JsPosition::Left,
+                },
+                data: sibling.clone(),
+            })
+            .collect()
+    }
+}
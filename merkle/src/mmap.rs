@@ -0,0 +1,239 @@
+#![allow(unsafe_code)]
+
+use std::fs::{File, OpenOptions};
+use std::io;
+use std::marker::PhantomData;
+use std::ops;
+use std::path::Path;
+use memmap2::MmapMut;
+use crate::store::Store;
+
+/// Fixed width element of [`MmapStore`].
+pub trait Element: Sized {
+    /// Number of bytes the element takes in the store.
+    const BYTE_LEN: usize;
+
+    /// Reads the element from `BYTE_LEN` bytes.
+    fn from_slice(bytes: &[u8]) -> Self;
+
+    /// Writes the element to `BYTE_LEN` bytes.
+    fn copy_to_slice(&self, bytes: &mut [u8]);
+}
+
+impl<const N: usize> Element for [u8; N] {
+    const BYTE_LEN: usize = N;
+
+    fn from_slice(bytes: &[u8]) -> Self {
+        let mut x = [0; N];
+        x.copy_from_slice(bytes);
+        x
+    }
+
+    fn copy_to_slice(&self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(self);
+    }
+}
+
+/// Store of the nodes in a memory mapped file, for trees larger than RAM.
+///
+/// Nodes are laid out in the file one after another, `T::BYTE_LEN` bytes
+/// each, and the file grows as the nodes are pushed. When the store is
+/// dropped, the file is cut down to the nodes it holds, so that it can be
+/// reopened with [`MmapStore::open`] and put back into the tree with
+/// [`crate::merkle::MerkleTree::from_store`] without rebuilding it.
+///
+/// Store created with [`Store::new`] maps anonymous memory, which is not
+/// backed by any file.
+///
+/// ```no_run
+/// extern crate merkle_light;
+///
+/// use merkle_light::hash::Algorithm;
+/// use merkle_light::merkle::MerkleTree;
+/// use merkle_light::mmap::MmapStore;
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+///
+/// #[derive(Clone, Default)]
+/// struct Sip(DefaultHasher);
+///
+/// impl Hasher for Sip {
+///     fn write(&mut self, msg: &[u8]) {
+///         self.0.write(msg)
+///     }
+///
+///     fn finish(&self) -> u64 {
+///         self.0.finish()
+///     }
+/// }
+///
+/// impl Algorithm<[u8; 8]> for Sip {
+///     fn hash(&mut self) -> [u8; 8] {
+///         self.0.finish().to_le_bytes()
+///     }
+/// }
+///
+/// fn main() {
+///     let hashes = (0..1000u64).map(|x| x.to_le_bytes());
+///
+///     let store = MmapStore::create("tree.bin", 2 * 1024 - 1).unwrap();
+///     let t: MerkleTree<[u8; 8], Sip, MmapStore<[u8; 8]>> =
+///         MerkleTree::try_from_iter_in(store, hashes).unwrap();
+///     let (root, leafs, height) = (t.root(), t.leafs(), t.height());
+///     drop(t);
+///
+///     let store = MmapStore::open("tree.bin").unwrap();
+///     let t: MerkleTree<[u8; 8], Sip, MmapStore<[u8; 8]>> =
+///         MerkleTree::from_store(store, leafs, height).unwrap();
+///     assert!(t.verify());
+///     assert_eq!(t.root(), root);
+/// }
+/// ```
+#[derive(Debug)]
+pub struct MmapStore<T: Element> {
+    file: Option<File>,
+    map: MmapMut,
+    len: usize,
+    _t: PhantomData<T>,
+}
+
+impl<T: Element> MmapStore<T> {
+    /// Creates new empty store in the file at `path`, with the room for
+    /// `size` nodes. Existing file is truncated.
+    pub fn create<P: AsRef<Path>>(path: P, size: usize) -> io::Result<MmapStore<T>> {
+        let file = OpenOptions::new()
+            .read(true)
+            .write(true)
+            .create(true)
+            .truncate(true)
+            .open(path)?;
+        Self::map_file(file, 0, size)
+    }
+
+    /// Opens the store of all the nodes in the file at `path`, as left by
+    /// the dropped store.
+    pub fn open<P: AsRef<Path>>(path: P) -> io::Result<MmapStore<T>> {
+        let file = OpenOptions::new().read(true).write(true).open(path)?;
+        let len = file.metadata()?.len() as usize / T::BYTE_LEN;
+        Self::map_file(file, len, len)
+    }
+
+    /// Flushes the nodes to the file.
+    pub fn flush(&self) -> io::Result<()> {
+        self.map.flush()
+    }
+
+    /// Returns the number of nodes the store has the room for.
+    fn capacity(&self) -> usize {
+        self.map.len() / T::BYTE_LEN
+    }
+
+    fn map_file(file: File, len: usize, size: usize) -> io::Result<MmapStore<T>> {
+        // empty maps are not supported
+        let size = size.max(len).max(1);
+        file.set_len((size * T::BYTE_LEN) as u64)?;
+
+        // SAFETY: the file is opened for writing by the store alone, and it
+        // is only resized while the old map is not used any more.
+        let map = unsafe { MmapMut::map_mut(&file)? };
+        Ok(MmapStore {
+            file: Some(file),
+            map,
+            len,
+            _t: PhantomData,
+        })
+    }
+
+    /// Grows the store to the room for at least `size` nodes.
+    fn grow(&mut self, size: usize) {
+        let size = size.max(2 * self.capacity());
+        let map = match self.file {
+            Some(ref file) => {
+                self.map.flush().expect("flush memory map");
+                file.set_len((size * T::BYTE_LEN) as u64)
+                    .expect("grow file of memory map");
+                // SAFETY: as in `map_file`, the old map is replaced below.
+                unsafe { MmapMut::map_mut(file).expect("memory map file") }
+            }
+            None => {
+                let mut map = MmapMut::map_anon(size * T::BYTE_LEN).expect("memory map");
+                map[..self.map.len()].copy_from_slice(&self.map);
+                map
+            }
+        };
+        self.map = map;
+    }
+
+    /// Returns the range of bytes of node `i` in the map.
+    fn range(&self, i: usize) -> ops::Range<usize> {
+        assert!(
+            i < self.len,
+            "node index {} is out of bounds of the store with {} nodes",
+            i,
+            self.len
+        );
+        i * T::BYTE_LEN..(i + 1) * T::BYTE_LEN
+    }
+}
+
+impl<T: Element> Store<T> for MmapStore<T> {
+    fn new(size: usize) -> Self {
+        let map = MmapMut::map_anon(size.max(1) * T::BYTE_LEN).expect("memory map");
+        MmapStore {
+            file: None,
+            map,
+            len: 0,
+            _t: PhantomData,
+        }
+    }
+
+    fn read(&self, i: usize) -> T {
+        T::from_slice(&self.map[self.range(i)])
+    }
+
+    fn write(&mut self, i: usize, value: T) {
+        let range = self.range(i);
+        value.copy_to_slice(&mut self.map[range]);
+    }
+
+    fn push(&mut self, value: T) {
+        if self.len == self.capacity() {
+            self.grow(self.len + 1);
+        }
+        self.len += 1;
+        self.write(self.len - 1, value);
+    }
+
+    fn len(&self) -> usize {
+        self.len
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.len = self.len.min(len);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        if self.len + additional > self.capacity() {
+            self.grow(self.len + additional);
+        }
+    }
+
+    /// Nodes of the file are not on the heap, and the anonymous memory is
+    /// reported of the whole map.
+    fn heap_size(&self) -> usize {
+        match self.file {
+            Some(_) => 0,
+            None => self.map.len(),
+        }
+    }
+}
+
+impl<T: Element> Drop for MmapStore<T> {
+    /// Flushes the nodes and cuts the file down to them.
+    fn drop(&mut self) {
+        if let Some(ref file) = self.file {
+            let _ = self.map.flush();
+            let _ = file.set_len((self.len * T::BYTE_LEN) as u64);
+        }
+    }
+}
@@ -0,0 +1,396 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use crate::hash::Algorithm;
+use crate::merkle::MerkleError;
+use crate::store::{Store, VecStore};
+
+/// Merkle mountain range, the append-only accumulator of the perfect binary
+/// trees of distinct heights, its peaks, of which nodes never change once
+/// they are pushed, so that the positions of the leafs are stable and the
+/// appends only hash the nodes the new leaf completes.
+///
+/// Nodes are laid out in postorder, every parent right after its children,
+/// numbered from 0 as in grin: the range of 4 leafs `a b c d` is
+///
+/// ```text
+///       6
+///     /   \
+///    2     5
+///   / \   / \
+///  0   1 3   4
+/// ```
+///
+/// Leafs are hashed with [`Algorithm::leaf`] and the parents with
+/// [`Algorithm::node`], as of [`crate::merkle::MerkleTree`], so that the
+/// range of the power of two of leafs is the tree over them. Root bags the
+/// peaks from the right, `node(p0, node(p1, .. node(pk-1, pk)))`, and is
+/// `A::default().hash()` of the empty range.
+///
+/// ```
+/// # #[cfg(feature = "sha256")] {
+/// use merkle_light::mmr::Mmr;
+/// use merkle_light::sha256::{Sha256Algorithm, Sha256Hash};
+///
+/// let mut mmr: Mmr<Sha256Hash, Sha256Algorithm> = Mmr::new();
+/// let positions: Vec<usize> = (0..11u8).map(|x| mmr.push([x; 32].into())).collect();
+/// assert_eq!(mmr.len(), 19);
+/// assert_eq!(mmr.peaks(), vec![14, 17, 18]);
+///
+/// let root = mmr.root();
+/// let proof = mmr.gen_proof(positions[4]);
+/// mmr.push([11; 32].into());
+/// assert!(proof.validate_against::<Sha256Algorithm>(&root));
+/// assert!(mmr.gen_proof(positions[4]).validate_against::<Sha256Algorithm>(&mmr.root()));
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct Mmr<T: Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T> = VecStore<T>> {
+    data: S,
+    leafs: usize,
+    _t: PhantomData<(T, A)>,
+}
+
+impl<T, A, S> PartialEq for Mmr<T, A, S>
+where
+    T: Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T> + PartialEq,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.data == other.data
+    }
+}
+
+impl<T, A, S> Eq for Mmr<T, A, S>
+where
+    T: Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    S: Store<T> + Eq,
+{
+}
+
+impl<T: Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T>> Default for Mmr<T, A, S> {
+    fn default() -> Mmr<T, A, S> {
+        Mmr::new()
+    }
+}
+
+impl<T: Clone + AsRef<[u8]>, A: Algorithm<T>, S: Store<T>> Mmr<T, A, S> {
+    /// Creates new empty merkle mountain range.
+    pub fn new() -> Mmr<T, A, S> {
+        Self::new_in(S::new(0))
+    }
+
+    /// Creates new empty merkle mountain range in the store `data`, such as
+    /// the one backed by a file. Nodes the store holds are dropped first.
+    pub fn new_in(mut data: S) -> Mmr<T, A, S> {
+        data.truncate(0);
+        Mmr {
+            data,
+            leafs: 0,
+            _t: PhantomData,
+        }
+    }
+
+    /// Creates merkle mountain range of the nodes of the store `data`, as
+    /// returned by [`Mmr::into_store`], such as of the file the range was
+    /// pushed to before.
+    ///
+    /// Only the number of nodes is validated, nodes are not rehashed.
+    pub fn from_store(data: S) -> Result<Mmr<T, A, S>, MerkleError> {
+        let size = data.len();
+        let peaks = peak_positions(size).ok_or(MerkleError::InvalidMmrSize { size })?;
+        let leafs = peaks.iter().map(|&p| 1 << node_height(p)).sum();
+        Ok(Mmr {
+            data,
+            leafs,
+            _t: PhantomData,
+        })
+    }
+
+    /// Decomposes the range into the store of its nodes, to be put back
+    /// together with [`Mmr::from_store`].
+    pub fn into_store(self) -> S {
+        self.data
+    }
+
+    /// Appends the leaf hash of `leaf`, and the parents it completes, and
+    /// returns the position of the leaf.
+    pub fn push(&mut self, leaf: T) -> usize {
+        let mut a = A::default();
+        let leaf = a.leaf(leaf);
+
+        let position = self.data.len();
+        self.data.push(leaf);
+        self.leafs += 1;
+
+        // next position is the parent of the node just pushed, of its left
+        // sibling as far back as the size of the subtree of the node
+        let (mut pos, mut height) = (position, 0);
+        while node_height(pos + 1) > height {
+            let left = self.data.read(pos + 1 - (2 << height));
+            let right = self.data.read(pos);
+            a.reset();
+            self.data.push(a.node(left, right));
+            pos += 1;
+            height += 1;
+        }
+        position
+    }
+
+    /// Returns the merkle root, the peaks bagged from the right.
+    pub fn root(&self) -> T {
+        let peaks: Vec<T> = self
+            .peaks()
+            .into_iter()
+            .map(|p| self.data.read(p))
+            .collect();
+        bag::<T, A>(&peaks)
+    }
+
+    /// Returns the positions of the peaks, from the highest, left to right.
+    pub fn peaks(&self) -> Vec<usize> {
+        peak_positions(self.data.len()).expect("range is of the size of the peaks")
+    }
+
+    /// Returns the node at the position `position`, or `None` if it is out
+    /// of bounds.
+    pub fn node(&self, position: usize) -> Option<T> {
+        match position < self.data.len() {
+            true => Some(self.data.read(position)),
+            false => None,
+        }
+    }
+
+    /// Returns number of leafs.
+    pub fn leafs(&self) -> usize {
+        self.leafs
+    }
+
+    /// Returns number of nodes, the size of the range.
+    pub fn len(&self) -> usize {
+        self.data.len()
+    }
+
+    /// Returns `true` if the range has no leafs.
+    pub fn is_empty(&self) -> bool {
+        self.data.is_empty()
+    }
+
+    /// Generate inclusion proof of the node at the position `position`,
+    /// such as of the leaf returned by [`Mmr::push`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if `position` is out of bounds of the nodes.
+    pub fn gen_proof(&self, position: usize) -> MmrProof<T> {
+        self.try_gen_proof(position)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate inclusion proof of the node at the position `position`.
+    ///
+    /// Fallible version of [`Mmr::gen_proof`].
+    pub fn try_gen_proof(&self, position: usize) -> Result<MmrProof<T>, MerkleError> {
+        let size = self.data.len();
+        if position >= size {
+            return Err(MerkleError::PositionOutOfBounds { position, size });
+        }
+
+        // siblings up to the peak of the node, past which there is no parent
+        let mut siblings = Vec::new();
+        let (mut pos, mut height) = (position, node_height(position));
+        while let Some((sibling, parent)) = climb(pos, height, size) {
+            siblings.push(self.data.read(sibling));
+            pos = parent;
+            height += 1;
+        }
+
+        let peaks = self
+            .peaks()
+            .into_iter()
+            .filter(|&p| p != pos)
+            .map(|p| self.data.read(p))
+            .collect();
+        Ok(MmrProof {
+            item: self.data.read(position),
+            position,
+            size,
+            siblings,
+            peaks,
+        })
+    }
+}
+
+/// Inclusion proof of the node of [`Mmr`] in the range of `size` nodes:
+/// the siblings of the node up to its peak, and the other peaks, left to
+/// right, which the peak of the node hashed up is bagged among.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MmrProof<T: Clone + AsRef<[u8]>> {
+    item: T,
+    position: usize,
+    size: usize,
+    siblings: Vec<T>,
+    peaks: Vec<T>,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> MmrProof<T> {
+    /// Creates new proof of the node `item` at the position `position` in
+    /// the range of `size` nodes, of its siblings bottom-up and the other
+    /// peaks left to right.
+    pub fn new(item: T, position: usize, size: usize, siblings: Vec<T>, peaks: Vec<T>) -> Self {
+        MmrProof {
+            item,
+            position,
+            size,
+            siblings,
+            peaks,
+        }
+    }
+
+    /// Returns the node the proof is of, the leaf hash of the leafs.
+    pub fn item(&self) -> T {
+        self.item.clone()
+    }
+
+    /// Returns the position of the node.
+    pub fn position(&self) -> usize {
+        self.position
+    }
+
+    /// Returns the number of nodes of the range the proof is of.
+    pub fn size(&self) -> usize {
+        self.size
+    }
+
+    /// Returns the siblings of the node up to its peak, bottom-up.
+    pub fn siblings(&self) -> &[T] {
+        &self.siblings
+    }
+
+    /// Returns the peaks other than the one of the node, left to right.
+    pub fn peaks(&self) -> &[T] {
+        &self.peaks
+    }
+
+    /// Verifies the proof against the trusted root `root` of the range of
+    /// [`MmrProof::size`] nodes.
+    ///
+    /// Position and size of the proof lay out the way the node is hashed
+    /// up, so that the proof of the siblings or the peaks other in number
+    /// than the range of the size has does not validate, nor does the proof
+    /// of the range of more than `usize::MAX / 2` nodes, which no store
+    /// holds.
+    pub fn validate_against<A: Algorithm<T>>(&self, root: &T) -> bool {
+        if self.position >= self.size || self.size > usize::MAX / 2 {
+            return false;
+        }
+        let all = match peak_positions(self.size) {
+            Some(all) if all.len() == self.peaks.len() + 1 => all,
+            _ => return false,
+        };
+
+        let mut a = A::default();
+        let (mut pos, mut height) = (self.position, node_height(self.position));
+        let mut h = self.item.clone();
+        for sibling in &self.siblings {
+            let (_, parent) = match climb(pos, height, self.size) {
+                Some(step) => step,
+                None => return false,
+            };
+            a.reset();
+            h = match parent == pos + 1 {
+                // parent right after the node is of the left sibling
+                true => a.node(sibling.clone(), h),
+                false => a.node(h, sibling.clone()),
+            };
+            pos = parent;
+            height += 1;
+        }
+
+        let k = match all.iter().position(|&p| p == pos) {
+            Some(k) => k,
+            None => return false,
+        };
+        let mut peaks = self.peaks.clone();
+        peaks.insert(k, h);
+        bag::<T, A>(&peaks) == *root
+    }
+}
+
+/// Returns the position of the leaf `n`, counted from 0, in the range of
+/// more than `n` leafs, `2n - popcount(n)`.
+pub fn leaf_position(n: usize) -> usize {
+    2 * n - n.count_ones() as usize
+}
+
+/// Returns the height of the node at the position `position`, of the
+/// perfect tree it is the root of, which is 0 of the leafs.
+pub fn node_height(position: usize) -> usize {
+    // jumps left over the subtrees to the leftmost path of the positions,
+    // of which 1-based positions are all ones
+    let mut p = position + 1;
+    while (p + 1) & p != 0 {
+        let bits = usize::BITS - p.leading_zeros();
+        p -= (1 << (bits - 1)) - 1;
+    }
+    (usize::BITS - p.leading_zeros()) as usize - 1
+}
+
+/// Returns the positions of the peaks of the range of `size` nodes, from
+/// the highest, left to right, or `None` if no range is of that size.
+pub fn peak_positions(size: usize) -> Option<Vec<usize>> {
+    let mut peaks = Vec::new();
+    let (mut base, mut rest) = (0, size);
+    let mut last = None;
+    while rest > 0 {
+        // largest perfect tree, of 2^k - 1 nodes, which fits into the rest
+        let bits = usize::BITS - rest.leading_zeros();
+        let nodes = match rest.wrapping_add(1) & rest {
+            0 => rest,
+            _ => (1 << (bits - 1)) - 1,
+        };
+        // peaks are distinct in height, or the size is past the range of
+        // the leafs before the parent of the last two
+        if last.is_some_and(|last| nodes >= last) {
+            return None;
+        }
+        peaks.push(base + nodes - 1);
+        base += nodes;
+        rest -= nodes;
+        last = Some(nodes);
+    }
+    Some(peaks)
+}
+
+/// Returns the sibling and the parent of the node at the position `pos` of
+/// the height `height`, or `None` if the node is the peak of the range of
+/// `size` nodes, of no parent in it.
+fn climb(pos: usize, height: usize, size: usize) -> Option<(usize, usize)> {
+    let subtree = (2 << height) - 1;
+    let (sibling, parent) = match node_height(pos + 1) > height {
+        // right child is followed by its parent
+        true => (pos.checked_sub(subtree)?, pos + 1),
+        false => (pos + subtree, pos + subtree + 1),
+    };
+    match parent < size {
+        true => Some((sibling, parent)),
+        false => None,
+    }
+}
+
+/// Returns the root of the peaks, bagged from the right, which is
+/// `A::default().hash()` of no peaks.
+fn bag<T: Clone + AsRef<[u8]>, A: Algorithm<T>>(peaks: &[T]) -> T {
+    let mut a = A::default();
+    let (last, rest) = match peaks.split_last() {
+        Some(split) => split,
+        None => return a.hash(),
+    };
+    rest.iter().rev().fold(last.clone(), |h, peak| {
+        a.reset();
+        a.node(peak.clone(), h)
+    })
+}
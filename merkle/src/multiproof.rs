@@ -0,0 +1,443 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use crate::hash::Algorithm;
+use crate::merkle::{tree_layout, Levels};
+use crate::proof::{read_varint, varint_len, write_varint, ProofDecodeError};
+
+/// Merkle tree inclusion proof of several leafs at once, as generated by
+/// [`crate::merkle::MerkleTree::gen_multiproof`].
+///
+/// Proof holds the leafs, and the nodes which are needed to hash them up to
+/// the root and which cannot be hashed from the leafs themselves, each node
+/// once. Siblings which are both proven, or are the duplicate filling up an
+/// odd level, need no node at all.
+///
+/// Nodes are in the canonical order, which is the order of the depth first
+/// walk of the tree from the root, left child before the right one: every
+/// subtree without proven leafs is a node of the proof, and is not walked
+/// into; every subtree with proven leafs is walked into, down to the leafs.
+/// The duplicate filling up an odd level is not walked into. That is, nodes
+/// are ordered by the first leaf under them.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct MultiProof<T: Eq + Clone + AsRef<[u8]>> {
+    indices: Vec<usize>,
+    leaves: Vec<T>,
+    nodes: Vec<T>,
+    leafs: usize,
+    height: usize,
+}
+
+/// Encoding of the leaf indices of [`MultiProof::encode`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum IndexEncoding {
+    /// Number of the indices, then the gaps between them, the first index
+    /// and then every index less the one before it and one, as varints.
+    /// Fits the sparse indices.
+    List,
+    /// Bit of every leaf of the tree, set for the proven leafs, from the
+    /// lowest bit of the first byte. Fits the dense indices.
+    Bitmap,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> MultiProof<T> {
+    pub(crate) fn new(
+        indices: Vec<usize>,
+        leaves: Vec<T>,
+        nodes: Vec<T>,
+        leafs: usize,
+        height: usize,
+    ) -> MultiProof<T> {
+        MultiProof {
+            indices,
+            leaves,
+            nodes,
+            leafs,
+            height,
+        }
+    }
+
+    /// Returns indices of the proven leafs, sorted.
+    pub fn indices(&self) -> &[usize] {
+        &self.indices
+    }
+
+    /// Returns hashes of the proven leafs, in the order of their indices.
+    pub fn leaves(&self) -> &[T] {
+        &self.leaves
+    }
+
+    /// Returns the nodes shared by the proofs of the leafs, in the
+    /// canonical order.
+    pub fn nodes(&self) -> &[T] {
+        &self.nodes
+    }
+
+    /// Returns number of leafs of the tree.
+    pub fn leafs(&self) -> usize {
+        self.leafs
+    }
+
+    /// Returns height of the tree.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Verifies the proof against the trusted root `root`, hashing the
+    /// leafs up to the root with the nodes of the proof.
+    ///
+    /// Proof of no leafs proves nothing, and does not validate.
+    pub fn validate<A: Algorithm<T>>(&self, root: &T) -> bool {
+        self.validate_with(&mut A::default(), root)
+    }
+
+    /// Verifies the proof against the trusted root `root` with the algorithm
+    /// `a`, such as the keyed one the tree is built with, as
+    /// [`MultiProof::validate`].
+    pub fn validate_with<A: Algorithm<T>>(&self, a: &mut A, root: &T) -> bool {
+        if self.indices.len() != self.leaves.len() {
+            return false;
+        }
+
+        let leaves = self.leaves.iter().cloned();
+        let nodes = self.nodes.iter().cloned();
+        verify(
+            a,
+            root,
+            &self.indices,
+            leaves,
+            nodes,
+            self.leafs,
+            self.height,
+        )
+    }
+
+    /// Encodes the proof into bytes, the leaf indices in `encoding`:
+    ///
+    /// ```text
+    /// digest length u8 | leafs varint | encoding u8 | indices | leaves | nodes
+    /// ```
+    ///
+    /// Varints are LEB128. Encoding is `0` for [`IndexEncoding::List`] and
+    /// `1` for [`IndexEncoding::Bitmap`]. Leaf hashes follow in the order of
+    /// their indices, nodes in the canonical order, see [`MultiProof`].
+    ///
+    /// # Panics
+    ///
+    /// Panics if the hashes are longer than 255 bytes, or differ in length.
+    pub fn encode(&self, encoding: IndexEncoding) -> Vec<u8>
+    where
+        T: Default,
+    {
+        let digest = T::default().as_ref().len();
+        assert!(
+            digest <= u8::MAX as usize,
+            "hashes of {} bytes are too long",
+            digest
+        );
+
+        let mut bytes = Vec::with_capacity(self.serialized_size(encoding));
+        bytes.push(digest as u8);
+        write_varint(&mut bytes, self.leafs);
+
+        match encoding {
+            IndexEncoding::List => {
+                bytes.push(0);
+                write_varint(&mut bytes, self.indices.len());
+                let mut next = 0;
+                for &i in &self.indices {
+                    write_varint(&mut bytes, i - next);
+                    next = i + 1;
+                }
+            }
+            IndexEncoding::Bitmap => {
+                bytes.push(1);
+                let start = bytes.len();
+                bytes.resize(start + self.leafs.div_ceil(8), 0);
+                for &i in &self.indices {
+                    bytes[start + (i >> 3)] |= 1 << (i & 7);
+                }
+            }
+        }
+
+        for h in self.leaves.iter().chain(&self.nodes) {
+            assert_eq!(h.as_ref().len(), digest, "proof hashes differ in length");
+            bytes.extend_from_slice(h.as_ref());
+        }
+        bytes
+    }
+
+    /// Returns number of bytes of the proof encoded with
+    /// [`MultiProof::encode`] in `encoding`.
+    pub fn serialized_size(&self, encoding: IndexEncoding) -> usize
+    where
+        T: Default,
+    {
+        let indices = match encoding {
+            IndexEncoding::List => {
+                let mut next = 0;
+                let gaps = self.indices.iter().map(|&i| {
+                    let gap = i - next;
+                    next = i + 1;
+                    varint_len(gap)
+                });
+                varint_len(self.indices.len()) + gaps.sum::<usize>()
+            }
+            IndexEncoding::Bitmap => self.leafs.div_ceil(8),
+        };
+
+        let digest = T::default().as_ref().len();
+        let hashes = self.leaves.len() + self.nodes.len();
+        2 + varint_len(self.leafs) + indices + digest * hashes
+    }
+
+    /// Decodes the proof from the bytes encoded by [`MultiProof::encode`],
+    /// in either encoding of the indices.
+    ///
+    /// Bytes must hold exactly one proof of the hashes as long as `T`, with
+    /// exactly as many nodes as the proof of its leaf indices needs.
+    pub fn decode(bytes: &[u8]) -> Result<MultiProof<T>, ProofDecodeError>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        let (&digest, mut rest) = bytes.split_first().ok_or(ProofDecodeError::Truncated)?;
+        let expected = T::default().as_ref().len();
+        if digest as usize != expected {
+            return Err(ProofDecodeError::DigestLength {
+                expected,
+                found: digest as usize,
+            });
+        }
+
+        let leafs = read_varint(&mut rest)?;
+        let (_, height) = tree_layout(leafs).map_err(|_| ProofDecodeError::TreeSize)?;
+
+        let (&encoding, tail) = rest.split_first().ok_or(ProofDecodeError::Truncated)?;
+        rest = tail;
+        let mut indices = Vec::new();
+        match encoding {
+            0 => {
+                let len = read_varint(&mut rest)?;
+                let mut next = 0usize;
+                for _ in 0..len {
+                    let i = next
+                        .checked_add(read_varint(&mut rest)?)
+                        .filter(|&i| i < leafs)
+                        .ok_or(ProofDecodeError::Indices)?;
+                    indices.push(i);
+                    next = i + 1;
+                }
+            }
+            1 => {
+                let len = leafs.div_ceil(8);
+                if rest.len() < len {
+                    return Err(ProofDecodeError::Truncated);
+                }
+                let (bitmap, tail) = rest.split_at(len);
+                rest = tail;
+                if leafs & 7 != 0 && bitmap[len - 1] >> (leafs & 7) != 0 {
+                    return Err(ProofDecodeError::Indices);
+                }
+                indices.extend((0..leafs).filter(|&i| bitmap[i >> 3] >> (i & 7) & 1 == 1));
+            }
+            _ => return Err(ProofDecodeError::Indices),
+        }
+
+        let mut len = 0;
+        decommitments(leafs, height, &indices, &mut |_, _| len += 1);
+
+        let hashes = indices.len() + len;
+        let need = hashes
+            .checked_mul(expected)
+            .ok_or(ProofDecodeError::Truncated)?;
+        if rest.len() < need {
+            return Err(ProofDecodeError::Truncated);
+        }
+        if rest.len() > need {
+            return Err(ProofDecodeError::TrailingBytes(rest.len() - need));
+        }
+
+        let mut hashes: Vec<T> = (0..hashes)
+            .map(|i| {
+                let mut h = T::default();
+                h.as_mut()
+                    .copy_from_slice(&rest[i * expected..(i + 1) * expected]);
+                h
+            })
+            .collect();
+        let nodes = hashes.split_off(indices.len());
+
+        Ok(MultiProof::new(indices, hashes, nodes, leafs, height))
+    }
+}
+
+/// Verifies the multiproof of the leaf hashes `leaves` at their indices,
+/// with the nodes `decommitments` in the canonical order of [`MultiProof`],
+/// against the trusted root `root` of the tree of `tree_size` leafs, with
+/// the algorithm `a`, such as the keyed one the tree is built with.
+///
+/// Root is hashed up of the proof alone, with no [`MultiProof`] nor the
+/// tree, such as of the light client holding the root and receiving the
+/// parts of the proof. Leafs must be sorted by their indices, with no
+/// duplicates, all less than `tree_size`. Proof of no leafs, or of the
+/// nodes either missing or left over past the root, does not validate.
+/// Odd level hashes the same as of its last node duplicated, so that the
+/// proof of the tree of the odd number of leafs is as valid of the tree of
+/// one leaf more, of the leaf the duplicate of the last one.
+///
+/// ```
+/// # use merkle_light::hash::Algorithm;
+/// # use merkle_light::merkle::MerkleTree;
+/// # use std::collections::hash_map::DefaultHasher;
+/// # use std::hash::Hasher;
+/// # #[derive(Default)]
+/// # struct Sip(DefaultHasher);
+/// # impl Hasher for Sip {
+/// #     fn write(&mut self, msg: &[u8]) { self.0.write(msg) }
+/// #     fn finish(&self) -> u64 { self.0.finish() }
+/// # }
+/// # impl Algorithm<[u8; 8]> for Sip {
+/// #     fn hash(&mut self) -> [u8; 8] { self.finish().to_le_bytes() }
+/// #     fn reset(&mut self) { *self = Sip::default() }
+/// # }
+/// use merkle_light::multiproof::verify_multiproof;
+///
+/// let mt: MerkleTree<[u8; 8], Sip> = MerkleTree::new((0..7u64).map(u64::to_le_bytes));
+/// let mp = mt.gen_multiproof(&[1, 4]);
+///
+/// let root = mt.root();
+/// let leaves = [(1, mt[1]), (4, mt[4])];
+/// assert!(verify_multiproof(&root, &leaves, mp.nodes(), 7, &mut Sip::default()));
+/// assert!(!verify_multiproof(&root, &leaves, &mp.nodes()[1..], 7, &mut Sip::default()));
+/// assert!(!verify_multiproof(&root, &leaves, mp.nodes(), 9, &mut Sip::default()));
+/// ```
+pub fn verify_multiproof<T, A>(
+    root: &T,
+    leaves: &[(usize, T)],
+    decommitments: &[T],
+    tree_size: usize,
+    a: &mut A,
+) -> bool
+where
+    T: Eq + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+{
+    let height = match tree_layout(tree_size) {
+        Ok((_, height)) => height,
+        Err(_) => return false,
+    };
+    let indices: Vec<usize> = leaves.iter().map(|&(i, _)| i).collect();
+    let hashes = leaves.iter().map(|(_, h)| h.clone());
+    let nodes = decommitments.iter().cloned();
+    verify(a, root, &indices, hashes, nodes, tree_size, height)
+}
+
+/// Verifies the proof of the leaf hashes `leaves` at the sorted `indices`,
+/// and of the `nodes`, against the root `root` of the tree of `leafs` leafs
+/// and `height`.
+fn verify<T, A, L, N>(
+    a: &mut A,
+    root: &T,
+    indices: &[usize],
+    mut leaves: L,
+    mut nodes: N,
+    leafs: usize,
+    height: usize,
+) -> bool
+where
+    T: Eq + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    L: Iterator<Item = T>,
+    N: Iterator<Item = T>,
+{
+    let sorted = indices.windows(2).all(|w| w[0] < w[1]);
+    if indices.is_empty() || !sorted || indices[indices.len() - 1] >= leafs || height == 0 {
+        return false;
+    }
+
+    let widths: Vec<usize> = Levels::new(leafs, height).map(|(_, width)| width).collect();
+    let h = climb(&widths, height - 1, 0, indices, &mut leaves, &mut nodes, a);
+    h.is_some_and(|h| nodes.next().is_none() && h == *root)
+}
+
+/// Calls `visit` with the level and the index in the level of every node the
+/// proof of the sorted leaf `indices` needs, in the canonical order.
+pub(crate) fn decommitments<F: FnMut(usize, usize)>(
+    leafs: usize,
+    height: usize,
+    indices: &[usize],
+    visit: &mut F,
+) {
+    if height == 0 {
+        return;
+    }
+
+    let widths: Vec<usize> = Levels::new(leafs, height).map(|(_, width)| width).collect();
+    descend(&widths, height - 1, 0, indices, visit);
+}
+
+/// Walks the subtree of the node `index` of `level`, with the proven leafs
+/// `indices` under it.
+fn descend<F: FnMut(usize, usize)>(
+    widths: &[usize],
+    level: usize,
+    index: usize,
+    indices: &[usize],
+    visit: &mut F,
+) {
+    if indices.is_empty() {
+        visit(level, index);
+        return;
+    }
+    if level == 0 {
+        return;
+    }
+
+    let (left, right) = split(level, index, indices);
+    descend(widths, level - 1, index << 1, left, visit);
+    // duplicate filling up an odd level is hashed from the left child
+    if (index << 1) + 1 < widths[level - 1] {
+        descend(widths, level - 1, (index << 1) + 1, right, visit);
+    }
+}
+
+/// Hashes the node `index` of `level` up from the proven leafs `indices`
+/// under it, in the walk of [`descend`].
+fn climb<T, A, L, N>(
+    widths: &[usize],
+    level: usize,
+    index: usize,
+    indices: &[usize],
+    leaves: &mut L,
+    nodes: &mut N,
+    a: &mut A,
+) -> Option<T>
+where
+    T: Eq + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    L: Iterator<Item = T>,
+    N: Iterator<Item = T>,
+{
+    if indices.is_empty() {
+        return nodes.next();
+    }
+    if level == 0 {
+        return leaves.next();
+    }
+
+    let (l, r) = split(level, index, indices);
+    let left = climb(widths, level - 1, index << 1, l, leaves, nodes, a)?;
+    let right = match (index << 1) + 1 < widths[level - 1] {
+        true => climb(widths, level - 1, (index << 1) + 1, r, leaves, nodes, a)?,
+        false => left.clone(),
+    };
+    a.reset();
+    Some(a.node(left, right))
+}
+
+/// Splits the leaf `indices` under the node `index` of `level` into the
+/// ones under its left and right children.
+fn split(level: usize, index: usize, indices: &[usize]) -> (&[usize], &[usize]) {
+    let mid = ((index << 1) + 1) << (level - 1);
+    indices.split_at(indices.partition_point(|&i| i < mid))
+}
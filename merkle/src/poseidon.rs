@@ -0,0 +1,300 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::Hasher;
+use core::mem;
+use core::str::FromStr;
+use ark_bn254::Fr;
+use ark_ff::{BigInteger, PrimeField};
+use light_poseidon::{Poseidon, PoseidonHasher};
+use crate::hash::{Algorithm, Hashable};
+use crate::hex::HexDisplay;
+
+/// Element of the scalar field of BN254, of the circuits of circom and
+/// snarkjs, the hash of [`PoseidonAlgorithm`].
+///
+/// Element is held as the 32 big endian bytes of its canonical value, below
+/// the modulus of the field, which it orders, compares and hashes as. Every
+/// constructor reduces the value, or rejects the one not reduced, so that
+/// the bytes are of exactly one element.
+///
+/// Displays as the decimal of the value, as circom and snarkjs print the
+/// signals, and parses of it.
+///
+/// ```
+/// # #[cfg(feature = "poseidon")] {
+/// use merkle_light::poseidon::FieldElement;
+///
+/// let x: FieldElement = "1234".parse().unwrap();
+/// assert_eq!(x, FieldElement::from_u64(1234));
+/// assert_eq!(x.to_string(), "1234");
+/// assert_eq!(x.as_bytes()[30..], [0x04, 0xd2]);
+/// # }
+/// ```
+#[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash, Default)]
+pub struct FieldElement([u8; 32]);
+
+/// Error of the element of [`FieldElement::from_be_bytes`] and of its
+/// parsing.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum FieldElementError {
+    /// Value is not below the modulus of the field.
+    Modulus,
+    /// Decimal is of no digits, or of the characters other than digits.
+    Decimal,
+}
+
+impl fmt::Display for FieldElementError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            FieldElementError::Modulus => write!(f, "value is not below the field modulus"),
+            FieldElementError::Decimal => write!(f, "field element is not a decimal"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for FieldElementError {}
+
+impl FieldElement {
+    /// Returns the element of the value `x`.
+    pub fn from_u64(x: u64) -> FieldElement {
+        FieldElement::from(Fr::from(x))
+    }
+
+    /// Returns the element of the big endian bytes of its canonical value,
+    /// or fails with [`FieldElementError::Modulus`] if the value is not
+    /// below the modulus.
+    pub fn from_be_bytes(bytes: [u8; 32]) -> Result<FieldElement, FieldElementError> {
+        let x = FieldElement::from_be_bytes_mod_order(&bytes);
+        match x.0 == bytes {
+            true => Ok(x),
+            false => Err(FieldElementError::Modulus),
+        }
+    }
+
+    /// Returns the element of the big endian bytes of any length, of their
+    /// value reduced modulo the modulus.
+    pub fn from_be_bytes_mod_order(bytes: &[u8]) -> FieldElement {
+        FieldElement::from(Fr::from_be_bytes_mod_order(bytes))
+    }
+
+    /// Returns the big endian bytes of the canonical value.
+    pub fn as_bytes(&self) -> &[u8; 32] {
+        &self.0
+    }
+
+    /// Returns the element of the field of `ark-bn254`.
+    pub fn to_fr(&self) -> Fr {
+        Fr::from_be_bytes_mod_order(&self.0)
+    }
+}
+
+impl From<Fr> for FieldElement {
+    fn from(x: Fr) -> FieldElement {
+        let mut bytes = [0; 32];
+        bytes.copy_from_slice(&x.into_bigint().to_bytes_be());
+        FieldElement(bytes)
+    }
+}
+
+impl From<FieldElement> for Fr {
+    fn from(x: FieldElement) -> Fr {
+        x.to_fr()
+    }
+}
+
+impl AsRef<[u8]> for FieldElement {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Displays as the decimal of the value.
+impl fmt::Display for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "{}", self.to_fr().into_bigint())
+    }
+}
+
+impl fmt::Debug for FieldElement {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "FieldElement({:#})", HexDisplay::new(&self.0))
+    }
+}
+
+/// Parses the decimal of the value below the modulus.
+impl FromStr for FieldElement {
+    type Err = FieldElementError;
+
+    fn from_str(s: &str) -> Result<FieldElement, FieldElementError> {
+        if s.is_empty() || !s.bytes().all(|b| b.is_ascii_digit()) {
+            return Err(FieldElementError::Decimal);
+        }
+        // value of 256 bits in little endian limbs, of the decimals which
+        // overflow it above the modulus
+        let mut limbs = [0u64; 4];
+        for b in s.bytes() {
+            let mut carry = u64::from(b - b'0');
+            for limb in limbs.iter_mut() {
+                let x = u128::from(*limb) * 10 + u128::from(carry);
+                *limb = x as u64;
+                carry = (x >> 64) as u64;
+            }
+            if carry != 0 {
+                return Err(FieldElementError::Modulus);
+            }
+        }
+        let mut bytes = [0; 32];
+        for (chunk, limb) in bytes.chunks_mut(8).zip(limbs.iter().rev()) {
+            chunk.copy_from_slice(&limb.to_be_bytes());
+        }
+        FieldElement::from_be_bytes(bytes)
+    }
+}
+
+impl<H: Hasher> Hashable<H> for FieldElement {
+    fn hash(&self, state: &mut H) {
+        state.write(&self.0)
+    }
+}
+
+/// Poseidon [`Algorithm`] of the trees over [`FieldElement`], of the
+/// parameters of circomlib over BN254 (`x^5`, 8 full rounds, 57 partial
+/// rounds of the width of 3), so that the roots computed natively are the
+/// ones the circuits of circomlib `Poseidon(2)` compute.
+///
+/// Semantics are frozen, so that the roots never change:
+///
+/// ```text
+/// leaf(x)       = x, or Poseidon(x) if HASH_LEAFS
+/// node(l, r)    = Poseidon(l, r)
+/// hash()        = h(n) of the n chunks c(i) of 31 bytes, big endian, of
+///                 everything written since reset, the last one shorter:
+///                 h(0) = len, h(i + 1) = Poseidon(h(i), c(i))
+/// finish()      = first 8 bytes of hash() as big endian u64
+/// ```
+///
+/// Default algorithm takes the leafs as they are, of the field elements
+/// reduced by the caller, see [`PoseidonLeafAlgorithm`] for the one which
+/// hashes them with `Poseidon(1)`. Nodes have no prefix, as there is none in
+/// the circuits, so that the verifier fixes the depth of the proofs. Hash of
+/// nothing written is the element 0, the leaf of no value of the circuits.
+///
+/// ```
+/// # #[cfg(feature = "poseidon")] {
+/// use merkle_light::merkle::MerkleTree;
+/// use merkle_light::poseidon::{FieldElement, PoseidonAlgorithm};
+///
+/// let leafs = (1..=4).map(FieldElement::from_u64);
+/// let mt: MerkleTree<FieldElement, PoseidonAlgorithm> = MerkleTree::new(leafs);
+/// assert!(mt.gen_proof(2).validate::<PoseidonAlgorithm>());
+///
+/// // Poseidon([1, 2]) of circomlib
+/// assert_eq!(
+///     mt[4].to_string(),
+///     "7853200120776062878684798364095072458815029376092732009249414926327459813530"
+/// );
+/// # }
+/// ```
+pub struct PoseidonAlgorithm<const HASH_LEAFS: bool = false> {
+    bytes: Vec<u8>,
+    pair: Poseidon<Fr>,
+    // Poseidon(1) of the leafs, of HASH_LEAFS only
+    single: Option<Poseidon<Fr>>,
+}
+
+/// Poseidon algorithm which hashes the leafs, `leaf(x) = Poseidon(x)`.
+pub type PoseidonLeafAlgorithm = PoseidonAlgorithm<true>;
+
+impl<const HASH_LEAFS: bool> PoseidonAlgorithm<HASH_LEAFS> {
+    /// Creates new algorithm with nothing written.
+    pub fn new() -> PoseidonAlgorithm<HASH_LEAFS> {
+        let circom = |inputs| Poseidon::<Fr>::new_circom(inputs).expect("circom width");
+        PoseidonAlgorithm {
+            bytes: Vec::new(),
+            pair: circom(2),
+            single: match HASH_LEAFS {
+                true => Some(circom(1)),
+                false => None,
+            },
+        }
+    }
+
+    /// Returns `Poseidon(l, r)`.
+    fn pair(&mut self, l: Fr, r: Fr) -> Fr {
+        self.pair.hash(&[l, r]).expect("two inputs")
+    }
+}
+
+impl<const HASH_LEAFS: bool> Default for PoseidonAlgorithm<HASH_LEAFS> {
+    fn default() -> PoseidonAlgorithm<HASH_LEAFS> {
+        PoseidonAlgorithm::new()
+    }
+}
+
+/// Clones the bytes written, with the parameters created anew.
+impl<const HASH_LEAFS: bool> Clone for PoseidonAlgorithm<HASH_LEAFS> {
+    fn clone(&self) -> PoseidonAlgorithm<HASH_LEAFS> {
+        let mut a = PoseidonAlgorithm::new();
+        a.bytes.clone_from(&self.bytes);
+        a
+    }
+}
+
+impl<const HASH_LEAFS: bool> fmt::Debug for PoseidonAlgorithm<HASH_LEAFS> {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        f.debug_struct("PoseidonAlgorithm")
+            .field("bytes", &self.bytes)
+            .finish_non_exhaustive()
+    }
+}
+
+impl<const HASH_LEAFS: bool> Hasher for PoseidonAlgorithm<HASH_LEAFS> {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.bytes.extend_from_slice(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        let h = self.clone().hash();
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&h.0[..8]);
+        u64::from_be_bytes(bytes)
+    }
+}
+
+impl<const HASH_LEAFS: bool> Algorithm<FieldElement> for PoseidonAlgorithm<HASH_LEAFS> {
+    #[inline]
+    fn hash(&mut self) -> FieldElement {
+        let bytes = mem::take(&mut self.bytes);
+        let len = Fr::from(bytes.len() as u64);
+        let h = bytes.chunks(31).fold(len, |h, chunk| {
+            self.pair(h, Fr::from_be_bytes_mod_order(chunk))
+        });
+        self.bytes = bytes;
+        FieldElement::from(h)
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.bytes.clear();
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: FieldElement) -> FieldElement {
+        match self.single {
+            Some(ref mut single) => {
+                FieldElement::from(single.hash(&[leaf.to_fr()]).expect("one input"))
+            }
+            None => leaf,
+        }
+    }
+
+    #[inline]
+    fn node(&mut self, left: FieldElement, right: FieldElement) -> FieldElement {
+        FieldElement::from(self.pair(left.to_fr(), right.to_fr()))
+    }
+}
@@ -1,8 +1,62 @@
+#[cfg(feature = "alloc")]
 extern crate alloc;
 
+#[cfg(feature = "alloc")]
+use alloc::string::String;
+#[cfg(feature = "alloc")]
 use alloc::vec::Vec;
 use crate::hash::Algorithm;
+#[cfg(feature = "alloc")]
+use crate::hash::{commit_leafs, ct_eq, hash_data, Hashable};
+#[cfg(feature = "alloc")]
+use crate::hex::{from_hex, to_hex};
+#[cfg(feature = "alloc")]
+use crate::merkle::{tree_layout, MerkleError, PaddingStrategy, PairOrdering};
+use core::fmt;
+#[cfg(feature = "serde")]
+use core::marker::PhantomData;
+#[cfg(feature = "serde")]
+use serde::de::{self, DeserializeSeed, Deserializer, SeqAccess, Visitor};
+#[cfg(feature = "serde")]
 use serde::{Serialize, Deserialize};
+
+/// Longest path of a proof of the tree over as many leafs as `usize` holds,
+/// which bounds the proofs deserialized.
+pub const MAX_PATH: usize = usize::BITS as usize;
+
+/// Flag of the proof bytes of [`Proof::to_bytes`] followed by the leaf
+/// index and the tree depth.
+#[cfg(feature = "alloc")]
+const POSITION: u8 = 0x01;
+
+/// Flag of the proof bytes of [`Proof::to_bytes`] of the sorted pairs.
+#[cfg(feature = "alloc")]
+const SORTED: u8 = 0x02;
+
+/// Longest path the proofs hold without allocating with the `smallvec`
+/// feature, of the trees of up to `2^32` leafs.
+pub const INLINE_PATH: usize = 32;
+
+/// Lemma of the proofs, held inline up to [`INLINE_PATH`] steps with the
+/// `smallvec` feature, and in a vector otherwise.
+#[cfg(all(feature = "alloc", feature = "smallvec"))]
+pub type Lemma<T> = smallvec::SmallVec<[T; INLINE_PATH + 2]>;
+
+/// Lemma of the proofs, held inline up to [`INLINE_PATH`] steps with the
+/// `smallvec` feature, and in a vector otherwise.
+#[cfg(all(feature = "alloc", not(feature = "smallvec")))]
+pub type Lemma<T> = Vec<T>;
+
+/// Path of the proofs, held inline up to [`INLINE_PATH`] steps with the
+/// `smallvec` feature, and in a vector otherwise.
+#[cfg(all(feature = "alloc", feature = "smallvec"))]
+pub type Path = smallvec::SmallVec<[bool; INLINE_PATH]>;
+
+/// Path of the proofs, held inline up to [`INLINE_PATH`] steps with the
+/// `smallvec` feature, and in a vector otherwise.
+#[cfg(all(feature = "alloc", not(feature = "smallvec")))]
+pub type Path = Vec<bool>;
+
 /// Merkle tree inclusion proof for data element, for which item = Leaf(Hash(Data Item)).
 ///
 /// Lemma layout:
@@ -12,19 +66,140 @@ use serde::{Serialize, Deserialize};
 /// ```
 ///
 /// Proof validation is positioned hash against lemma path to match root hash.
-
-#[derive(Serialize, Deserialize, Debug, Clone, Eq, PartialEq, Default)]
+/// Item is the inner node for the proofs of the subtrees instead, see
+/// [`crate::merkle::MerkleTree::gen_node_proof`], which validate the same.
+///
+/// Proofs deserialize only of the lemma one hash longer than the path plus
+/// the root, and of the path no longer than [`MAX_PATH`], which is checked
+/// as the proof is read, so that the hostile proof cannot exhaust memory.
+///
+/// Proof holds the index of its leaf and the depth of the tree, which the
+/// path alone does not tell for the trees which promote the last node of
+/// the odd levels, see [`crate::merkle::PaddingStrategy`]: their proofs
+/// skip the levels of the node promoted.
+///
+/// Proof validates in the pair ordering of the tree it is generated by, see
+/// [`PairOrdering`], which is encoded and serialized with it.
+#[cfg(feature = "alloc")]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[derive(Debug, Clone, Eq, PartialEq, Default)]
 pub struct Proof<T: Eq + Clone + AsRef<[u8]>> {
-    lemma: Vec<T>,
-    path: Vec<bool>,
+    lemma: Lemma<T>,
+    path: Path,
+    index: usize,
+    depth: usize,
+    ordering: PairOrdering,
 }
 
+#[cfg(feature = "alloc")]
 impl<T: Eq + Clone + AsRef<[u8]>> Proof<T> {
-    /// Creates new MT inclusion proof
-    pub fn new(hash: Vec<T>, path: Vec<bool>) -> Proof<T> {
-        assert!(hash.len() > 2);
-        assert_eq!(hash.len() - 2, path.len());
-        Proof { lemma: hash, path }
+    /// Creates new MT inclusion proof of the lemma `hash` and the path
+    /// `path`, such as of vectors, or of [`Lemma`] and [`Path`] which
+    /// are taken as they are.
+    ///
+    /// Lemma is one hash longer than the path plus the root, which is only
+    /// asserted in debug builds, and the proof of any other lengths does not
+    /// validate. Use [`Proof::try_new`] for the lemma and the path of the
+    /// lengths not known to fit.
+    pub fn new<L: Into<Lemma<T>>, P: Into<Path>>(hash: L, path: P) -> Proof<T> {
+        let proof = Proof::from_parts(hash.into(), path.into());
+        debug_assert!(
+            proof.is_well_formed(),
+            "lemma of {} hashes does not fit the path of {} steps",
+            proof.lemma.len(),
+            proof.path.len()
+        );
+        proof
+    }
+
+    /// Creates new MT inclusion proof of the lemma `hash` and the path
+    /// `path`.
+    ///
+    /// Fallible version of [`Proof::new`], which fails with
+    /// [`MerkleError::ProofShape`] unless the lemma is one hash longer than
+    /// the path plus the root.
+    pub fn try_new<L: Into<Lemma<T>>, P: Into<Path>>(
+        hash: L,
+        path: P,
+    ) -> Result<Proof<T>, MerkleError> {
+        let proof = Proof::from_parts(hash.into(), path.into());
+        match proof.is_well_formed() {
+            true => Ok(proof),
+            false => Err(MerkleError::ProofShape {
+                lemma: proof.lemma.len(),
+                path: proof.path.len(),
+            }),
+        }
+    }
+
+    /// Creates the proof of the lemma and the path as they are, of whatever
+    /// lengths, such as the malformed proofs of the tests.
+    pub(crate) fn from_parts(lemma: Lemma<T>, path: Path) -> Proof<T> {
+        let index = path_index(&path);
+        let depth = path.len();
+        Proof {
+            lemma,
+            path,
+            index,
+            depth,
+            ordering: PairOrdering::default(),
+        }
+    }
+
+    /// Returns whether the lemma is one hash longer than the path plus the
+    /// root, which the proofs of the other lengths fail to validate for.
+    fn is_well_formed(&self) -> bool {
+        self.lemma.len() >= 2 && self.lemma.len() - 2 == self.path.len()
+    }
+
+    /// Replaces the pair ordering the proof validates in with `ordering`,
+    /// such as [`PairOrdering::Sorted`] for the proof of the tree of sorted
+    /// pairs decoded from the bytes.
+    pub fn with_pair_ordering(mut self, ordering: PairOrdering) -> Proof<T> {
+        self.ordering = ordering;
+        self
+    }
+
+    /// Returns the pair ordering the proof validates in.
+    pub fn pair_ordering(&self) -> PairOrdering {
+        self.ordering
+    }
+
+    /// Replaces the leaf index and the tree depth of the proof with `index`
+    /// and `depth`, such as for the proof of a leaf whose node is promoted
+    /// on some levels.
+    ///
+    /// Proofs start out at the index and depth their path tells. Index and
+    /// depth are not hashed when the proof validates, but
+    /// [`Proof::validate_committed`] checks them against the path.
+    pub fn with_position(mut self, index: usize, depth: usize) -> Proof<T> {
+        self.index = index;
+        self.depth = depth;
+        self
+    }
+
+    /// Returns whether the index and the depth are as the path tells, so
+    /// that the encodings leave them out.
+    fn is_path_position(&self) -> bool {
+        self.depth == self.path.len() && self.index == path_index(&self.path)
+    }
+
+    /// Replaces the leaf index and the tree depth of the decoded proof, as
+    /// [`Proof::with_position`] does.
+    ///
+    /// Fails with [`ProofDecodeError::Position`] unless the tree is at least
+    /// as deep as the path is long, and no deeper than [`MAX_PATH`], and the
+    /// index fits the depth.
+    pub(crate) fn try_with_position(
+        self,
+        index: usize,
+        depth: usize,
+    ) -> Result<Proof<T>, ProofDecodeError> {
+        let fits = index.checked_shr(depth as u32).unwrap_or(0) == 0;
+        match depth >= self.path.len() && depth <= MAX_PATH && fits {
+            true => Ok(self.with_position(index, depth)),
+            false => Err(ProofDecodeError::Position),
+        }
     }
 
     /// Return proof target leaf
@@ -38,25 +213,247 @@ impl<T: Eq + Clone + AsRef<[u8]>> Proof<T> {
     }
 
     /// Verifies MT inclusion proof
+    ///
+    /// Checks the lemma against the root it carries, which is as untrusted
+    /// as the rest of the proof. Use [`Proof::validate_against`] to check
+    /// the proof against the trusted root.
     pub fn validate<A: Algorithm<T>>(&self) -> bool {
+        self.is_well_formed() && self.validate_against::<A>(&self.root())
+    }
+
+    /// Verifies MT inclusion proof against the trusted root `root`, rather
+    /// than the root embedded into the proof.
+    pub fn validate_against<A: Algorithm<T>>(&self, root: &T) -> bool {
+        self.validate_against_with(&mut A::default(), root)
+    }
+
+    /// Verifies MT inclusion proof with the algorithm `a`, such as the keyed
+    /// one the tree is built with, rather than `A::default()`.
+    ///
+    /// Checks the lemma against the root it carries, as [`Proof::validate`].
+    pub fn validate_with<A: Algorithm<T>>(&self, a: &mut A) -> bool {
+        self.is_well_formed() && self.validate_against_with(a, &self.root())
+    }
+
+    /// Verifies MT inclusion proof against the trusted root `root` with the
+    /// algorithm `a`.
+    ///
+    /// Proof of sorted pairs ignores the path, and sorts every pair as it is
+    /// hashed instead. Proof of the lemma and path of the lengths which do
+    /// not fit does not validate.
+    pub fn validate_against_with<A: Algorithm<T>>(&self, a: &mut A, root: &T) -> bool {
+        self.validate_by(a, root, |h, root| h == root)
+    }
+
+    /// Verifies MT inclusion proof against the root it carries, comparing
+    /// the hashes in constant time, as [`Proof::validate_against_ct`].
+    pub fn validate_ct<A: Algorithm<T>>(&self) -> bool {
+        self.is_well_formed() && self.validate_against_ct::<A>(&self.root())
+    }
+
+    /// Verifies MT inclusion proof against the trusted root `root`, as
+    /// [`Proof::validate_against`], comparing the root the lemma hashes up
+    /// to with `root` in constant time with [`ct_eq`], so that the time of
+    /// the validation does not tell where they differ.
+    pub fn validate_against_ct<A: Algorithm<T>>(&self, root: &T) -> bool {
+        self.validate_against_with_ct(&mut A::default(), root)
+    }
+
+    /// Verifies MT inclusion proof against the trusted root `root` with the
+    /// algorithm `a`, comparing the roots in constant time, as
+    /// [`Proof::validate_against_ct`].
+    pub fn validate_against_with_ct<A: Algorithm<T>>(&self, a: &mut A, root: &T) -> bool {
+        self.validate_by(a, root, |h, root| ct_eq(h.as_ref(), root.as_ref()))
+    }
+
+    /// Hashes the lemma up with the algorithm `a`, and compares the root it
+    /// hashes up to with `root` with `eq`.
+    fn validate_by<A, F>(&self, a: &mut A, root: &T, eq: F) -> bool
+    where
+        A: Algorithm<T>,
+        F: FnOnce(&T, &T) -> bool,
+    {
+        if !self.is_well_formed() {
+            return false;
+        }
+
         let size = self.lemma.len();
-        if size < 2 {
+        let siblings = &self.lemma[1..size - 1];
+        match self.ordering {
+            PairOrdering::Ordered => verify_by(root, &self.lemma[0], siblings, &self.path, a, eq),
+            PairOrdering::Sorted => {
+                let h = siblings.iter().cloned().fold(self.item(), |h, sibling| {
+                    PairOrdering::Sorted.node(a, h, sibling)
+                });
+                eq(&h, root)
+            }
+        }
+    }
+
+    /// Verifies MT inclusion proof against the trusted root `committed`,
+    /// committed to the number of the tree leafs `leafs`, as returned by
+    /// [`crate::merkle::MerkleTree::committed_root`].
+    ///
+    /// Checks that the root of the proof and `leafs` hash into `committed`,
+    /// and that the proof is of one of the `leafs` leafs, as deep as the
+    /// leafs of the tree, so that the proof of the duplicate filling up the
+    /// tree over more leafs does not pass for the proof of this tree.
+    ///
+    /// Path is checked to be the one of the leaf index, its steps left out
+    /// where the odd levels promote the last node, so that the proofs of
+    /// the trees of any [`PaddingStrategy`] validate, and the index the
+    /// proof claims is not trusted.
+    pub fn validate_committed<A: Algorithm<T>>(&self, committed: &T, leafs: usize) -> bool {
+        self.validate_committed_with(&mut A::default(), committed, leafs)
+    }
+
+    /// Verifies MT inclusion proof against the trusted root `committed` with
+    /// the algorithm `a`, such as the keyed one the tree is built with, as
+    /// [`Proof::validate_committed`].
+    pub fn validate_committed_with<A: Algorithm<T>>(
+        &self,
+        a: &mut A,
+        committed: &T,
+        leafs: usize,
+    ) -> bool {
+        let height = match tree_layout(leafs) {
+            Ok((_, height)) if self.is_well_formed() => height,
+            _ => return false,
+        };
+
+        // padding with the pad node hashes the path of the leaf the same
+        // with or without the hash of the empty data
+        let promotions = [
+            PaddingStrategy::HashWithSelf,
+            PaddingStrategy::PromoteOdd,
+            PaddingStrategy::DuplicateLast,
+        ];
+        let root = self.root();
+        self.index() < leafs
+            && self.depth() + 1 == height
+            && promotions.iter().any(|&p| self.is_leaf_path(leafs, p))
+            && commit_leafs(a, &root, leafs) == *committed
+            && self.validate_against_with(a, &root)
+    }
+
+    /// Returns `true` if the path is the one of the leaf of the proof index
+    /// in the tree over `leafs` leafs, of the padding strategy `padding`.
+    fn is_leaf_path(&self, leafs: usize, padding: PaddingStrategy) -> bool {
+        let mut steps = self.path.iter();
+        let mut width = leafs;
+        for level in 0..self.depth {
+            let j = self.index >> level;
+            let promoted = j ^ 1 == width && padding.promotes(level);
+            if !promoted && steps.next() != Some(&(j & 1 == 0)) {
+                return false;
+            }
+            width = width.div_ceil(2);
+        }
+        steps.next().is_none()
+    }
+
+    /// Verifies MT inclusion proof of the trusted leaf hash `leaf` against
+    /// the trusted root `root`.
+    pub fn validate_leaf_against<A: Algorithm<T>>(&self, leaf: &T, root: &T) -> bool {
+        self.is_well_formed() && self.item() == *leaf && self.validate_against::<A>(root)
+    }
+
+    /// Verifies MT inclusion proof of the trusted leaf hash `leaf` against
+    /// the trusted root `root`, comparing both the leaf and the root in
+    /// constant time, as [`Proof::validate_against_ct`].
+    pub fn validate_leaf_against_ct<A: Algorithm<T>>(&self, leaf: &T, root: &T) -> bool {
+        // shape of the proof is not secret, but both of the hashes are
+        // compared, whichever differs
+        if !self.is_well_formed() {
             return false;
         }
+        ct_eq(self.item().as_ref(), leaf.as_ref()) & self.validate_against_ct::<A>(root)
+    }
 
-        let mut h = self.item();
-        let mut a = A::default();
+    /// Verifies MT inclusion proof of the object `item`, hashing it into
+    /// the leaf just as [`crate::merkle::MerkleTree::from_data`] does.
+    ///
+    /// Checks the lemma against the root it carries, as [`Proof::validate`].
+    pub fn validate_data<O: Hashable<A>, A: Algorithm<T>>(&self, item: &O) -> bool {
+        self.is_well_formed() && self.validate_data_against::<O, A>(item, &self.root())
+    }
+
+    /// Verifies MT inclusion proof of the object `item` against the trusted
+    /// root `root`.
+    pub fn validate_data_against<O: Hashable<A>, A: Algorithm<T>>(
+        &self,
+        item: &O,
+        root: &T,
+    ) -> bool {
+        self.validate_data_against_with(&mut A::default(), item, root)
+    }
 
-        for i in 1..size - 1 {
-            a.reset();
-            h = if self.path[i - 1] {
-                a.node(h, self.lemma[i].clone())
-            } else {
-                a.node(self.lemma[i].clone(), h)
-            };
+    /// Verifies MT inclusion proof of the object `item` against the trusted
+    /// root `root` with the algorithm `a`, hashing the object into the leaf
+    /// as [`crate::merkle::MerkleTree::from_data_with`] does.
+    pub fn validate_data_against_with<O: Hashable<A>, A: Algorithm<T>>(
+        &self,
+        a: &mut A,
+        item: &O,
+        root: &T,
+    ) -> bool {
+        let h = hash_data(a, item);
+        a.reset();
+        let leaf = a.leaf(h);
+        self.is_well_formed() && self.item() == leaf && self.validate_against_with(a, root)
+    }
+
+    /// Composes this proof of the leaf of a subtree with the proof `outer`
+    /// of the subtree root in the larger tree, as generated by
+    /// [`crate::merkle::MerkleTree::gen_node_proof`], into the proof of the
+    /// leaf in the larger tree.
+    ///
+    /// Subtree may as well be a tree of its own, such as a shard, which
+    /// root is the leaf `outer` proves in the tree over the roots of the
+    /// shards as they are, built with
+    /// [`crate::merkle::MerkleTree::from_leaf_hashes`]. Shards may be of
+    /// any number of leafs each, so that the proofs composed are of as many
+    /// steps as their shard and the outer tree are high.
+    ///
+    /// Fails if the proofs are malformed, or the root of this proof is not
+    /// of the width of the node `outer` proves, or is not that node, or the
+    /// proofs differ in pair ordering.
+    pub fn compose(self, outer: Proof<T>) -> Result<Proof<T>, ComposeError> {
+        if !self.is_well_formed() || !outer.is_well_formed() {
+            return Err(ComposeError::Malformed);
+        }
+        let (root, joint) = (self.root(), outer.item());
+        let (inner_len, outer_len) = (root.as_ref().len(), joint.as_ref().len());
+        if inner_len != outer_len {
+            return Err(ComposeError::DigestLength {
+                inner: inner_len,
+                outer: outer_len,
+            });
+        }
+        if root != joint {
+            return Err(ComposeError::Joint);
+        }
+        if self.ordering != outer.ordering {
+            return Err(ComposeError::Ordering {
+                inner: self.ordering,
+                outer: outer.ordering,
+            });
         }
 
-        h == self.root()
+        let index = outer.index.checked_shl(self.depth as u32).unwrap_or(0) | self.index;
+        let depth = self.depth + outer.depth;
+        let Proof {
+            mut lemma,
+            mut path,
+            ordering,
+            ..
+        } = self;
+        lemma.pop();
+        lemma.extend(outer.lemma.into_iter().skip(1));
+        path.extend(outer.path);
+        Ok(Proof::new(lemma, path)
+            .with_pair_ordering(ordering)
+            .with_position(index, depth))
     }
 
     /// Returns the path of this proof.
@@ -68,4 +465,741 @@ impl<T: Eq + Clone + AsRef<[u8]>> Proof<T> {
     pub fn lemma(&self) -> &[T] {
         &self.lemma
     }
+
+    /// Returns the index of the proven leaf.
+    ///
+    /// Index is as the path tells it unless set with
+    /// [`Proof::with_position`]: every step where the node is right of its
+    /// sibling sets the bit of its level.
+    pub fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Returns the height of the tree less one, which is the number of the
+    /// steps of the path unless some node of the path is promoted.
+    pub fn depth(&self) -> usize {
+        self.depth
+    }
+
+    /// Encodes the proof into the compact bytes, to be decoded with
+    /// [`Proof::from_bytes`]:
+    ///
+    /// ```text
+    /// digest length u8 | flags u8 | path length varint
+    ///     [| index varint | depth varint] | path bits | lemma hashes
+    /// ```
+    ///
+    /// Numbers are LEB128 varints. Flag `0x01` is set when the index and
+    /// depth differ from what the path tells, and only then are they
+    /// written. Flag `0x02` is set for [`PairOrdering::Sorted`]. Path is packed 8 steps a byte, least significant bit first,
+    /// and the unused high bits of the last byte are zero. Lemma hashes
+    /// follow one after another.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the hashes are longer than 255 bytes, or differ in length.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        let digest = self.item().as_ref().len();
+        assert!(
+            digest <= u8::MAX as usize,
+            "hashes of {} bytes are too long",
+            digest
+        );
+
+        let size = 32 + self.path.len().div_ceil(8) + digest * self.lemma.len();
+        let mut bytes = Vec::with_capacity(size);
+        bytes.push(digest as u8);
+        let position = !self.is_path_position();
+        let sorted = self.ordering == PairOrdering::Sorted;
+        bytes.push(if position { POSITION } else { 0 } | if sorted { SORTED } else { 0 });
+
+        write_varint(&mut bytes, self.path.len());
+        if position {
+            write_varint(&mut bytes, self.index);
+            write_varint(&mut bytes, self.depth);
+        }
+
+        for steps in self.path.chunks(8) {
+            let byte = steps
+                .iter()
+                .rev()
+                .fold(0, |byte, &left| byte << 1 | left as u8);
+            bytes.push(byte);
+        }
+
+        for h in &self.lemma {
+            assert_eq!(h.as_ref().len(), digest, "lemma hashes differ in length");
+            bytes.extend_from_slice(h.as_ref());
+        }
+        bytes
+    }
+
+    /// Decodes the proof from the bytes encoded by [`Proof::to_bytes`].
+    ///
+    /// Bytes must hold exactly one proof of the hashes as long as `T`, of
+    /// the path no longer than [`MAX_PATH`].
+    pub fn from_bytes(bytes: &[u8]) -> Result<Proof<T>, ProofDecodeError>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        let (&digest, mut rest) = bytes.split_first().ok_or(ProofDecodeError::Truncated)?;
+        let expected = T::default().as_ref().len();
+        if digest as usize != expected {
+            return Err(ProofDecodeError::DigestLength {
+                expected,
+                found: digest as usize,
+            });
+        }
+
+        let (&flags, tail) = rest.split_first().ok_or(ProofDecodeError::Truncated)?;
+        rest = tail;
+        if flags & !(POSITION | SORTED) != 0 {
+            return Err(ProofDecodeError::Flags(flags));
+        }
+
+        let len = match read_varint(&mut rest) {
+            Err(ProofDecodeError::Number) => return Err(ProofDecodeError::PathLength),
+            len => len?,
+        };
+        if len > MAX_PATH {
+            return Err(ProofDecodeError::PathLength);
+        }
+        let position = match flags & POSITION {
+            0 => None,
+            _ => Some((read_varint(&mut rest)?, read_varint(&mut rest)?)),
+        };
+
+        let path_bytes = len.div_ceil(8);
+        if rest.len() < path_bytes {
+            return Err(ProofDecodeError::Truncated);
+        }
+        let (packed, rest) = rest.split_at(path_bytes);
+        if len & 7 != 0 && packed[path_bytes - 1] >> (len & 7) != 0 {
+            return Err(ProofDecodeError::PathPadding);
+        }
+        let path: Path = (0..len)
+            .map(|i| packed[i >> 3] >> (i & 7) & 1 == 1)
+            .collect();
+
+        let lemma_bytes = (len + 2) * expected;
+        if rest.len() < lemma_bytes {
+            return Err(ProofDecodeError::Truncated);
+        }
+        if rest.len() > lemma_bytes {
+            return Err(ProofDecodeError::TrailingBytes(rest.len() - lemma_bytes));
+        }
+        let lemma: Lemma<T> = (0..len + 2)
+            .map(|i| {
+                let mut h = T::default();
+                let chunk = &rest[i * expected..(i + 1) * expected];
+                h.as_mut().copy_from_slice(chunk);
+                h
+            })
+            .collect();
+
+        let ordering = match flags & SORTED {
+            0 => PairOrdering::Ordered,
+            _ => PairOrdering::Sorted,
+        };
+        let proof = Proof::new(lemma, path).with_pair_ordering(ordering);
+        match position {
+            None => Ok(proof),
+            Some((index, depth)) => proof.try_with_position(index, depth),
+        }
+    }
+
+    /// Encodes the proof into the `0x` prefixed lowercase hex string of
+    /// the bytes of [`Proof::to_bytes`].
+    pub fn to_hex(&self) -> String {
+        to_hex(&self.to_bytes())
+    }
+
+    /// Decodes the proof from the hex string encoded by [`Proof::to_hex`],
+    /// with or without the prefix, in any case.
+    pub fn from_hex(s: &str) -> Result<Proof<T>, ProofDecodeError>
+    where
+        T: Default + AsMut<[u8]>,
+    {
+        let bytes = from_hex(s).map_err(|_| ProofDecodeError::Hex)?;
+        Self::from_bytes(&bytes)
+    }
+
+    /// Returns the lemma hashes as `0x` prefixed lowercase hex strings,
+    /// from the leaf to the root.
+    pub fn to_hex_lemma(&self) -> Vec<String> {
+        self.lemma.iter().map(|h| to_hex(h.as_ref())).collect()
+    }
+}
+
+/// Merkle tree inclusion proof of the references to the nodes of the tree,
+/// as returned by [`crate::merkle::MerkleTree::gen_proof_ref`], which are
+/// not cloned out of the tree, so that the proof validates and serializes
+/// with no clones of the hashes.
+///
+/// Proof borrows the tree, and does not outlive it. Proof of the hashes
+/// cloned, which does, is returned by [`ProofRef::to_owned`]:
+///
+/// ```
+/// use merkle_light::hash::Algorithm;
+/// use merkle_light::merkle::MerkleTree;
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+///
+/// #[derive(Clone, Default)]
+/// struct Sip(DefaultHasher);
+///
+/// impl Hasher for Sip {
+///     fn write(&mut self, msg: &[u8]) {
+///         self.0.write(msg)
+///     }
+///
+///     fn finish(&self) -> u64 {
+///         self.0.finish()
+///     }
+/// }
+///
+/// impl Algorithm<[u8; 8]> for Sip {
+///     fn hash(&mut self) -> [u8; 8] {
+///         self.0.finish().to_le_bytes()
+///     }
+/// }
+///
+/// let proof = {
+///     let t: MerkleTree<[u8; 8], Sip> = MerkleTree::new((0..5u64).map(|x| x.to_le_bytes()));
+///     let proof = t.gen_proof_ref(3);
+///     assert!(proof.validate::<Sip>());
+///     assert_eq!(proof.to_bytes(), t.gen_proof(3).to_bytes());
+///     proof.to_owned()
+/// };
+/// assert!(proof.validate::<Sip>());
+/// ```
+///
+/// Proof of the references does not outlive the tree:
+///
+/// ```compile_fail,E0597
+/// # use merkle_light::hash::Algorithm;
+/// # use merkle_light::merkle::MerkleTree;
+/// # use std::collections::hash_map::DefaultHasher;
+/// # use std::hash::Hasher;
+/// #
+/// # #[derive(Clone, Default)]
+/// # struct Sip(DefaultHasher);
+/// #
+/// # impl Hasher for Sip {
+/// #     fn write(&mut self, msg: &[u8]) {
+/// #         self.0.write(msg)
+/// #     }
+/// #
+/// #     fn finish(&self) -> u64 {
+/// #         self.0.finish()
+/// #     }
+/// # }
+/// #
+/// # impl Algorithm<[u8; 8]> for Sip {
+/// #     fn hash(&mut self) -> [u8; 8] {
+/// #         self.0.finish().to_le_bytes()
+/// #     }
+/// # }
+/// #
+/// let proof = {
+///     let t: MerkleTree<[u8; 8], Sip> = MerkleTree::new((0..5u64).map(|x| x.to_le_bytes()));
+///     t.gen_proof_ref(3)
+/// };
+/// assert!(proof.validate::<Sip>());
+/// ```
+#[cfg(feature = "alloc")]
+#[cfg_attr(feature = "serde", derive(Serialize))]
+#[cfg_attr(feature = "serde", serde(transparent))]
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct ProofRef<'a, T: Eq + Clone + AsRef<[u8]>> {
+    proof: Proof<&'a T>,
+}
+
+#[cfg(feature = "alloc")]
+impl<'a, T: Eq + Clone + AsRef<[u8]>> ProofRef<'a, T> {
+    /// Creates new MT inclusion proof of the references of the proof
+    /// `proof`.
+    pub(crate) fn new(proof: Proof<&'a T>) -> ProofRef<'a, T> {
+        ProofRef { proof }
+    }
+
+    /// Returns the pair ordering the proof validates in.
+    pub fn pair_ordering(&self) -> PairOrdering {
+        self.proof.pair_ordering()
+    }
+
+    /// Return proof target leaf
+    pub fn item(&self) -> &'a T {
+        self.proof.item()
+    }
+
+    /// Return tree root
+    pub fn root(&self) -> &'a T {
+        self.proof.root()
+    }
+
+    /// Returns the lemma of this proof.
+    pub fn lemma(&self) -> &[&'a T] {
+        self.proof.lemma()
+    }
+
+    /// Returns the path of this proof.
+    pub fn path(&self) -> &[bool] {
+        self.proof.path()
+    }
+
+    /// Returns the index of the proven leaf, as [`Proof::index`].
+    pub fn index(&self) -> usize {
+        self.proof.index()
+    }
+
+    /// Verifies MT inclusion proof against the root it carries, as
+    /// [`Proof::validate`].
+    pub fn validate<A: Algorithm<T>>(&self) -> bool {
+        self.validate_against::<A>(self.root())
+    }
+
+    /// Verifies MT inclusion proof against the trusted root `root`, as
+    /// [`Proof::validate_against`].
+    pub fn validate_against<A: Algorithm<T>>(&self, root: &T) -> bool {
+        self.validate_against_with(&mut A::default(), root)
+    }
+
+    /// Verifies MT inclusion proof against the trusted root `root` with the
+    /// algorithm `a`, as [`Proof::validate_against_with`], hashing the
+    /// nodes of the lemma as they are borrowed with
+    /// [`Algorithm::node_ref`].
+    pub fn validate_against_with<A: Algorithm<T>>(&self, a: &mut A, root: &T) -> bool {
+        self.validate_by(a, root, |h, root| h == root)
+    }
+
+    /// Verifies MT inclusion proof against the trusted root `root`,
+    /// comparing the roots in constant time, as
+    /// [`Proof::validate_against_ct`].
+    pub fn validate_against_ct<A: Algorithm<T>>(&self, root: &T) -> bool {
+        let eq = |h: &T, root: &T| ct_eq(h.as_ref(), root.as_ref());
+        self.validate_by(&mut A::default(), root, eq)
+    }
+
+    /// Hashes the borrowed lemma up with the algorithm `a`, and compares
+    /// the root it hashes up to with `root` with `eq`.
+    fn validate_by<A, F>(&self, a: &mut A, root: &T, eq: F) -> bool
+    where
+        A: Algorithm<T>,
+        F: FnOnce(&T, &T) -> bool,
+    {
+        let (lemma, ordering) = (self.lemma(), self.pair_ordering());
+        let siblings = &lemma[1..lemma.len() - 1];
+        let mut h: Option<T> = None;
+        for (&sibling, &left) in siblings.iter().zip(self.path()) {
+            let node = h.as_ref().unwrap_or(lemma[0]);
+            h = Some(match left {
+                true => ordering.node_ref(a, node, sibling),
+                false => ordering.node_ref(a, sibling, node),
+            });
+        }
+        eq(h.as_ref().unwrap_or(lemma[0]), root)
+    }
+
+    /// Encodes the proof into the bytes of [`Proof::to_bytes`], the same
+    /// as of the proof of the hashes cloned.
+    pub fn to_bytes(&self) -> Vec<u8> {
+        self.proof.to_bytes()
+    }
+
+    /// Encodes the proof into the hex of [`Proof::to_hex`].
+    pub fn to_hex(&self) -> String {
+        self.proof.to_hex()
+    }
+
+    /// Returns the proof of the hashes cloned out of the tree, which
+    /// outlives it.
+    pub fn to_owned(&self) -> Proof<T> {
+        let lemma: Lemma<T> = self.lemma().iter().map(|&h| h.clone()).collect();
+        Proof::new(lemma, Path::from(self.path()))
+            .with_pair_ordering(self.pair_ordering())
+            .with_position(self.proof.index, self.proof.depth)
+    }
+}
+
+/// Verifies the inclusion of the leaf hash `leaf`, hashed with
+/// [`Algorithm::leaf`] already, under the trusted root `root` with the
+/// algorithm `alg`, of the siblings of its path from the leaf up and the
+/// path, `true` of every step the node is left of its sibling, as of
+/// [`Proof::lemma`] and [`Proof::path`], with no tree nor proof at hand.
+///
+/// Siblings other in number than the steps of the path do not verify.
+/// Every proof of the ordered pairs, such as of [`Proof::validate`],
+/// verifies with it, without allocating.
+///
+/// ```
+/// # #[cfg(feature = "alloc")] {
+/// use merkle_light::hash::Algorithm;
+/// use merkle_light::merkle::MerkleTree;
+/// use merkle_light::verify;
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+///
+/// #[derive(Clone, Default)]
+/// struct Sip(DefaultHasher);
+///
+/// impl Hasher for Sip {
+///     fn write(&mut self, msg: &[u8]) {
+///         self.0.write(msg)
+///     }
+///
+///     fn finish(&self) -> u64 {
+///         self.0.finish()
+///     }
+/// }
+///
+/// impl Algorithm<[u8; 8]> for Sip {
+///     fn hash(&mut self) -> [u8; 8] {
+///         self.finish().to_le_bytes()
+///     }
+///
+///     fn reset(&mut self) {
+///         *self = Sip::default();
+///     }
+/// }
+///
+/// let t: MerkleTree<[u8; 8], Sip> = MerkleTree::new((0..5u64).map(u64::to_le_bytes));
+/// let p = t.gen_proof(3);
+/// let (lemma, path) = (p.lemma(), p.path());
+/// let siblings = &lemma[1..lemma.len() - 1];
+/// assert!(verify(&t.root(), &lemma[0], siblings, path, &mut Sip::default()));
+/// assert!(!verify(&t.root(), &lemma[0], siblings, &[true; 3], &mut Sip::default()));
+/// # }
+/// ```
+pub fn verify<T, A>(root: &T, leaf: &T, siblings: &[T], path: &[bool], alg: &mut A) -> bool
+where
+    T: Eq + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+{
+    verify_by(root, leaf, siblings, path, alg, |h, root| h == root)
+}
+
+/// Verifies the inclusion of the leaf `leaf` as it is, hashed into the
+/// leaf hash with [`Algorithm::leaf`] of `alg` first, as the trees hash the
+/// hashes they are built upon, and verified as [`verify`] does.
+pub fn verify_raw_leaf<T, A>(root: &T, leaf: &T, siblings: &[T], path: &[bool], alg: &mut A) -> bool
+where
+    T: Eq + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+{
+    alg.reset();
+    let leaf = alg.leaf(leaf.clone());
+    verify(root, &leaf, siblings, path, alg)
+}
+
+/// Hashes the path of [`verify`] up with the algorithm `a`, and compares
+/// the root it hashes up to with `root` with `eq`.
+fn verify_by<T, A, F>(root: &T, leaf: &T, siblings: &[T], path: &[bool], a: &mut A, eq: F) -> bool
+where
+    T: Eq + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    F: FnOnce(&T, &T) -> bool,
+{
+    if siblings.len() != path.len() {
+        return false;
+    }
+    let steps = siblings.iter().cloned().zip(path.iter().cloned());
+    eq(&path_root(a, leaf.clone(), steps), root)
+}
+
+/// Verifies the path from the leaf hash `leaf` up to the trusted root
+/// `root`, folding the steps as they come, without allocating.
+///
+/// Every step is the sibling of the node and `true` if the node is left of
+/// it, as in [`Proof::path`], from the leaf up.
+pub fn verify_path<T, A, I>(leaf: T, steps: I, root: &T) -> bool
+where
+    T: Eq + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    I: IntoIterator<Item = (T, bool)>,
+{
+    verify_path_with(&mut A::default(), leaf, steps, root)
+}
+
+/// Verifies the path from the leaf hash `leaf` up to the trusted root
+/// `root` with the algorithm `a`, as [`verify_path`] does.
+pub fn verify_path_with<T, A, I>(a: &mut A, leaf: T, steps: I, root: &T) -> bool
+where
+    T: Eq + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    I: IntoIterator<Item = (T, bool)>,
+{
+    path_root(a, leaf, steps) == *root
+}
+
+/// Returns the root the path from the leaf hash `leaf` hashes up to with
+/// the algorithm `a`.
+fn path_root<T, A, I>(a: &mut A, leaf: T, steps: I) -> T
+where
+    T: Eq + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+    I: IntoIterator<Item = (T, bool)>,
+{
+    steps.into_iter().fold(leaf, |h, (sibling, left)| {
+        a.reset();
+        match left {
+            true => a.node(h, sibling),
+            false => a.node(sibling, h),
+        }
+    })
+}
+
+/// Error of decoding the proof with [`Proof::from_bytes`], or
+/// [`crate::multiproof::MultiProof::decode`].
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ProofDecodeError {
+    /// Bytes end before the proof does.
+    Truncated,
+    /// Bytes go on past the end of the proof, by that many.
+    TrailingBytes(usize),
+    /// Hashes of the proof are not as long as the hash type.
+    DigestLength {
+        /// Length of the hash type.
+        expected: usize,
+        /// Length of the hashes of the proof.
+        found: usize,
+    },
+    /// Path is longer than [`MAX_PATH`], or its length is not encoded in
+    /// the fewest bytes.
+    PathLength,
+    /// Unused bits of the last path byte are not zero.
+    PathPadding,
+    /// Number is not encoded in the fewest bytes, or overflows `usize`.
+    Number,
+    /// Leaf indices are out of order or out of bounds of the tree, or are
+    /// encoded in an unknown way.
+    Indices,
+    /// Tree is too large to fit into the address space.
+    TreeSize,
+    /// Hex string is of odd length, or of other characters than hex digits.
+    Hex,
+    /// Flags byte has bits set that no flag uses.
+    Flags(u8),
+    /// Leaf index does not fit the tree depth, or the depth is shorter than
+    /// the path or longer than [`MAX_PATH`].
+    Position,
+}
+
+impl fmt::Display for ProofDecodeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ProofDecodeError::Truncated => write!(f, "proof bytes end before the proof does"),
+            ProofDecodeError::TrailingBytes(n) => {
+                write!(f, "{} bytes go on past the end of the proof", n)
+            }
+            ProofDecodeError::DigestLength { expected, found } => write!(
+                f,
+                "proof hashes of {} bytes do not fit the hash type of {} bytes",
+                found, expected
+            ),
+            ProofDecodeError::PathLength => write!(f, "proof path length is invalid"),
+            ProofDecodeError::PathPadding => write!(f, "proof path padding bits are not zero"),
+            ProofDecodeError::Number => write!(f, "proof number is encoded invalidly"),
+            ProofDecodeError::Indices => write!(f, "proof leaf indices are invalid"),
+            ProofDecodeError::TreeSize => write!(f, "proof tree is too large"),
+            ProofDecodeError::Hex => write!(f, "proof is not a hex string"),
+            ProofDecodeError::Flags(flags) => write!(f, "proof flags {:#04x} are unknown", flags),
+            ProofDecodeError::Position => write!(f, "proof leaf index or depth is invalid"),
+        }
+    }
+}
+
+#[cfg(feature = "std")]
+impl std::error::Error for ProofDecodeError {}
+
+/// Error of composing the proofs with [`Proof::compose`].
+#[cfg(feature = "alloc")]
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum ComposeError {
+    /// Either proof is malformed, of the lemma not fitting the path.
+    Malformed,
+    /// Root of the inner proof is not the node the outer proof proves.
+    Joint,
+    /// Root of the inner proof is not as long as the node the outer proof
+    /// proves.
+    DigestLength {
+        /// Length of the root of the inner proof.
+        inner: usize,
+        /// Length of the node of the outer proof.
+        outer: usize,
+    },
+    /// Proofs differ in pair ordering.
+    Ordering {
+        /// Pair ordering of the inner proof.
+        inner: PairOrdering,
+        /// Pair ordering of the outer proof.
+        outer: PairOrdering,
+    },
+}
+
+#[cfg(feature = "alloc")]
+impl fmt::Display for ComposeError {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        match *self {
+            ComposeError::Malformed => write!(f, "composed proof is malformed"),
+            ComposeError::Joint => {
+                write!(f, "inner proof root is not the node of outer proof")
+            }
+            ComposeError::DigestLength { inner, outer } => write!(
+                f,
+                "inner proof root of {} bytes does not fit outer proof node of {} bytes",
+                inner, outer
+            ),
+            ComposeError::Ordering { inner, outer } => write!(
+                f,
+                "inner proof pair ordering {:?} differs from outer proof pair ordering {:?}",
+                inner, outer
+            ),
+        }
+    }
+}
+
+#[cfg(all(feature = "alloc", feature = "std"))]
+impl std::error::Error for ComposeError {}
+
+/// Returns the leaf index the path tells: every step where the node is
+/// right of its sibling sets the bit of its level.
+#[cfg(feature = "alloc")]
+fn path_index(path: &[bool]) -> usize {
+    path.iter()
+        .rev()
+        .fold(0, |index, &left| index << 1 | !left as usize)
+}
+
+/// Appends `n` to `bytes` as LEB128 varint, 7 bits per byte from the
+/// lowest, with the high bit set on all bytes but the last.
+#[cfg(feature = "alloc")]
+pub(crate) fn write_varint(bytes: &mut Vec<u8>, mut n: usize) {
+    while n >= 0x80 {
+        bytes.push(n as u8 | 0x80);
+        n >>= 7;
+    }
+    bytes.push(n as u8);
+}
+
+/// Returns number of bytes of `n` as LEB128 varint.
+#[cfg(feature = "alloc")]
+pub(crate) fn varint_len(n: usize) -> usize {
+    let bits = usize::BITS - n.leading_zeros();
+    bits.div_ceil(7).max(1) as usize
+}
+
+/// Reads LEB128 varint off the front of `bytes`, which is encoded in the
+/// fewest bytes and fits `usize`.
+#[cfg(feature = "alloc")]
+pub(crate) fn read_varint(bytes: &mut &[u8]) -> Result<usize, ProofDecodeError> {
+    let mut n = 0;
+    let mut shift = 0;
+    loop {
+        let (&byte, tail) = bytes.split_first().ok_or(ProofDecodeError::Truncated)?;
+        *bytes = tail;
+        let bits = (byte & 0x7f) as usize;
+        if shift > 0 && bits >> (usize::BITS - shift) != 0 {
+            return Err(ProofDecodeError::Number);
+        }
+        n |= bits << shift;
+        if byte & 0x80 == 0 {
+            // zero last byte is encoded in the fewer bytes
+            if byte == 0 && shift > 0 {
+                return Err(ProofDecodeError::Number);
+            }
+            return Ok(n);
+        }
+
+        shift += 7;
+        if shift >= usize::BITS {
+            return Err(ProofDecodeError::Number);
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Eq + Clone + AsRef<[u8]> + Deserialize<'de>> Deserialize<'de> for Proof<T> {
+    fn deserialize<D: Deserializer<'de>>(deserializer: D) -> Result<Self, D::Error> {
+        #[derive(Deserialize)]
+        #[serde(rename = "Proof", bound = "T: Deserialize<'de>")]
+        struct Parts<T> {
+            #[serde(deserialize_with = "bounded::<_, T, { MAX_PATH + 2 }>")]
+            lemma: Vec<T>,
+            #[serde(deserialize_with = "bounded::<_, bool, MAX_PATH>")]
+            path: Vec<bool>,
+            index: usize,
+            depth: usize,
+            #[serde(default)]
+            ordering: PairOrdering,
+        }
+
+        let parts = Parts::<T>::deserialize(deserializer)?;
+        let proof = Proof::try_new(parts.lemma, parts.path).map_err(de::Error::custom)?;
+        proof
+            .with_pair_ordering(parts.ordering)
+            .try_with_position(parts.index, parts.depth)
+            .map_err(de::Error::custom)
+    }
+}
+
+/// Deserializes the sequence of no more than `N` elements, failing as soon
+/// as it grows longer.
+#[cfg(feature = "serde")]
+fn bounded<'de, D, T, const N: usize>(deserializer: D) -> Result<Vec<T>, D::Error>
+where
+    D: Deserializer<'de>,
+    T: Deserialize<'de>,
+{
+    deserializer.deserialize_seq(Bounded::<T>::new(N))
+}
+
+/// Seed of the sequence of no more than `max` elements, which fails as soon
+/// as it grows longer. Elements are allocated ahead up to `max`, and up to
+/// 1 MiB of them, whatever length the format declares.
+#[cfg(feature = "serde")]
+pub(crate) struct Bounded<T> {
+    max: usize,
+    _t: PhantomData<T>,
+}
+
+#[cfg(feature = "serde")]
+impl<T> Bounded<T> {
+    pub(crate) fn new(max: usize) -> Bounded<T> {
+        Bounded {
+            max,
+            _t: PhantomData,
+        }
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> Visitor<'de> for Bounded<T> {
+    type Value = Vec<T>;
+
+    fn expecting(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "sequence of at most {} elements", self.max)
+    }
+
+    fn visit_seq<S: SeqAccess<'de>>(self, mut seq: S) -> Result<Vec<T>, S::Error> {
+        let cautious = (1 << 20) / size_of::<T>().max(1);
+        let hint = seq.size_hint().unwrap_or(0).min(self.max);
+        let mut v = Vec::with_capacity(hint.min(cautious));
+        while let Some(x) = seq.next_element()? {
+            if v.len() == self.max {
+                return Err(de::Error::invalid_length(self.max + 1, &self));
+            }
+            v.push(x);
+        }
+        Ok(v)
+    }
+}
+
+#[cfg(feature = "serde")]
+impl<'de, T: Deserialize<'de>> DeserializeSeed<'de> for Bounded<T> {
+    type Value = Vec<T>;
+
+    fn deserialize<D: Deserializer<'de>>(self, deserializer: D) -> Result<Vec<T>, D::Error> {
+        deserializer.deserialize_seq(self)
+    }
 }
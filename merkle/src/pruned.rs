@@ -0,0 +1,109 @@
+extern crate alloc;
+
+use alloc::collections::{BTreeMap, BTreeSet};
+use core::marker::PhantomData;
+use crate::hash::Algorithm;
+use crate::merkle::{Levels, MerkleError};
+use crate::proof::{Lemma, Path, Proof};
+
+/// Merkle tree pruned down to the inclusion proofs of the chosen leafs, as
+/// returned by [`crate::merkle::MerkleTree::prune`].
+///
+/// Only the chosen leafs, their siblings along the paths to the root and
+/// the root are kept, nodes shared by the paths are kept once, so that the
+/// tree takes `O(k log n)` nodes for `k` leafs out of `n`.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct PrunedTree<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> {
+    retained: BTreeSet<usize>,
+    /// Kept nodes by their index in memory layout of the full tree.
+    nodes: BTreeMap<usize, T>,
+    leafs: usize,
+    height: usize,
+    root: T,
+    _a: PhantomData<A>,
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> PrunedTree<T, A> {
+    pub(crate) fn new(
+        retained: BTreeSet<usize>,
+        nodes: BTreeMap<usize, T>,
+        leafs: usize,
+        height: usize,
+        root: T,
+    ) -> PrunedTree<T, A> {
+        PrunedTree {
+            retained,
+            nodes,
+            leafs,
+            height,
+            root,
+            _a: PhantomData,
+        }
+    }
+
+    /// Returns merkle root of the full tree.
+    pub fn root(&self) -> T {
+        self.root.clone()
+    }
+
+    /// Returns number of leafs of the full tree.
+    pub fn leafs(&self) -> usize {
+        self.leafs
+    }
+
+    /// Returns height of the full tree.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Returns number of the nodes kept, not counting the root.
+    pub fn len(&self) -> usize {
+        self.nodes.len()
+    }
+
+    /// Returns `true` if no leafs are kept.
+    pub fn is_empty(&self) -> bool {
+        self.nodes.is_empty()
+    }
+
+    /// Returns an iterator over the indices of the kept leafs, in order.
+    pub fn retained(&self) -> impl Iterator<Item = usize> + '_ {
+        self.retained.iter().cloned()
+    }
+
+    /// Generate merkle tree inclusion proof for the kept leaf `i`
+    pub fn gen_proof(&self, i: usize) -> Proof<T> {
+        self.try_gen_proof(i).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate merkle tree inclusion proof for the kept leaf `i`
+    ///
+    /// Fallible version of [`PrunedTree::gen_proof`].
+    pub fn try_gen_proof(&self, i: usize) -> Result<Proof<T>, MerkleError> {
+        // i in [0 .. self.leafs)
+        if i >= self.leafs {
+            return Err(MerkleError::IndexOutOfBounds {
+                index: i,
+                leafs: self.leafs,
+            });
+        }
+        if !self.retained.contains(&i) {
+            return Err(MerkleError::Pruned { index: i });
+        }
+
+        let mut lemma = Lemma::with_capacity(self.height + 1); // path + root
+        let mut path = Path::with_capacity(self.height - 1); // path - 1
+
+        let mut j = i;
+        lemma.push(self.nodes[&j].clone());
+        for (base, _) in Levels::new(self.leafs, self.height).take(self.height - 1) {
+            lemma.push(self.nodes[&(base + (j ^ 1))].clone());
+            path.push(j & 1 == 0);
+            j >>= 1;
+        }
+
+        // root is final
+        lemma.push(self.root());
+        Ok(Proof::new(lemma, path))
+    }
+}
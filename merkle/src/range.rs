@@ -0,0 +1,111 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use core::ops::Range;
+use crate::hash::Algorithm;
+use crate::merkle::Levels;
+
+/// Merkle tree inclusion proof of a run of leafs, as generated by
+/// [`crate::merkle::MerkleTree::gen_range_proof`].
+///
+/// Proof holds only the nodes bounding the range at every level, the left
+/// sibling of its first node and the right sibling of its last one where
+/// they lie outside of the range, bottom-up. Proof of the range over all
+/// the leafs holds no nodes at all. Leafs themselves are supplied to
+/// [`RangeProof::validate`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct RangeProof<T: Eq + Clone + AsRef<[u8]>> {
+    range: Range<usize>,
+    nodes: Vec<T>,
+    leafs: usize,
+    height: usize,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> RangeProof<T> {
+    pub(crate) fn new(
+        range: Range<usize>,
+        nodes: Vec<T>,
+        leafs: usize,
+        height: usize,
+    ) -> RangeProof<T> {
+        RangeProof {
+            range,
+            nodes,
+            leafs,
+            height,
+        }
+    }
+
+    /// Returns the range of the proven leafs.
+    pub fn range(&self) -> Range<usize> {
+        self.range.clone()
+    }
+
+    /// Returns the nodes bounding the range, bottom-up.
+    pub fn nodes(&self) -> &[T] {
+        &self.nodes
+    }
+
+    /// Returns number of leafs of the tree.
+    pub fn leafs(&self) -> usize {
+        self.leafs
+    }
+
+    /// Returns height of the tree.
+    pub fn height(&self) -> usize {
+        self.height
+    }
+
+    /// Verifies that `leaves` are the leaf hashes of the range under the
+    /// trusted root `root`, hashing them up to the root together with the
+    /// nodes of the proof.
+    pub fn validate<A: Algorithm<T>>(&self, leaves: &[T], root: &T) -> bool {
+        self.validate_with(&mut A::default(), leaves, root)
+    }
+
+    /// Verifies the range of `leaves` under the trusted root `root` with the
+    /// algorithm `a`, such as the keyed one the tree is built with, as
+    /// [`RangeProof::validate`].
+    pub fn validate_with<A: Algorithm<T>>(&self, a: &mut A, leaves: &[T], root: &T) -> bool {
+        let (mut lo, mut hi) = (self.range.start, self.range.end);
+        if lo >= hi || hi > self.leafs || leaves.len() != hi - lo {
+            return false;
+        }
+
+        let mut run = leaves.to_vec();
+        let mut nodes = self.nodes.iter().cloned();
+
+        let levels = Levels::new(self.leafs, self.height);
+        for (_, width) in levels.take(self.height - 1) {
+            if lo & 1 == 1 {
+                match nodes.next() {
+                    Some(left) => run.insert(0, left),
+                    None => return false,
+                }
+            }
+            if hi & 1 == 1 {
+                // last node of an odd level is paired with its duplicate
+                let right = match hi == width {
+                    true => run.last().cloned(),
+                    false => nodes.next(),
+                };
+                match right {
+                    Some(right) => run.push(right),
+                    None => return false,
+                }
+            }
+
+            run = run
+                .chunks(2)
+                .map(|pair| {
+                    a.reset();
+                    a.node(pair[0].clone(), pair[1].clone())
+                })
+                .collect();
+            lo >>= 1;
+            hi = (hi + 1) >> 1;
+        }
+
+        nodes.next().is_none() && run.len() == 1 && run[0] == *root
+    }
+}
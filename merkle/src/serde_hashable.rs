@@ -0,0 +1,428 @@
+extern crate alloc;
+
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use core::fmt;
+use core::hash::Hasher;
+use serde::ser::{self, Serialize};
+use crate::hash::Hashable;
+
+/// [`Hashable`] of any [`Serialize`] value, which feeds the value into the
+/// hasher in a canonical encoding, as the [`Hashable`] of the common types
+/// would:
+///
+/// ```text
+/// bool, integers, char        as their Hashable
+/// f32, f64                    bits as u32, u64 little endian
+/// str, bytes                  u64 length, then the bytes
+/// None, Some(x)               u8 0, or u8 1 then x
+/// unit, unit struct           nothing
+/// newtype struct              the value
+/// seq                         u64 length in items, then each item
+/// tuple, tuple struct, struct each field in order, no names
+/// map                         u64 length in entries, then each key and
+///                             value, in the order of the encoded keys
+/// enum variant                u32 index of the variant, then its content
+/// ```
+///
+/// Encoding is deterministic for the deterministic [`Serialize`] impls.
+/// Maps are sorted by the encoding of their keys, so that `HashMap` and
+/// `BTreeMap` of the same entries hash the same regardless of the order of
+/// the iteration, at the cost of buffering the entries. Sequences of unknown
+/// length are buffered as well. Field names are not hashed, so that the
+/// structs with the same fields in the same order hash the same, and the
+/// fields skipped by `#[serde(skip_serializing_if)]` are left out of the
+/// hash, just as with `#[serde(skip)]`. Floats hash by their bits, so that
+/// `0.0` and `-0.0` hash apart, as do the NaNs of different payloads.
+///
+/// # Panics
+///
+/// [`Hashable::hash`] panics if the [`Serialize`] impl of the value fails.
+///
+/// ```
+/// extern crate merkle_light;
+/// extern crate serde;
+///
+/// use merkle_light::hash::Hashable;
+/// use merkle_light::serde_hashable::SerdeHashable;
+/// use serde::Serialize;
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+///
+/// #[derive(Serialize)]
+/// struct Person {
+///     id: u32,
+///     name: String,
+///     phone: u64,
+/// }
+///
+/// let foo = Person {
+///     id: 1,
+///     name: String::from("blah"),
+///     phone: 2,
+/// };
+///
+/// let mut hr = DefaultHasher::new();
+/// SerdeHashable(&foo).hash(&mut hr);
+///
+/// let mut manual = DefaultHasher::new();
+/// (1u32, "blah", 2u64).hash(&mut manual);
+/// assert_eq!(hr.finish(), manual.finish());
+/// ```
+#[derive(Debug, Clone, Copy, Default, Eq, PartialEq)]
+pub struct SerdeHashable<T>(pub T);
+
+impl<H: Hasher, T: Serialize> Hashable<H> for SerdeHashable<T> {
+    fn hash(&self, state: &mut H) {
+        self.0
+            .serialize(Encoder { state })
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+}
+
+/// Error of the [`Serialize`] impl of the value.
+#[derive(Debug)]
+struct Error(String);
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
+        write!(f, "failed to serialize hashable value: {}", self.0)
+    }
+}
+
+impl core::error::Error for Error {}
+
+impl ser::Error for Error {
+    fn custom<M: fmt::Display>(msg: M) -> Error {
+        Error(msg.to_string())
+    }
+}
+
+/// Hasher which records the bytes fed into it, to buffer the items of the
+/// maps and the sequences of unknown length.
+#[derive(Debug, Default)]
+struct Buffer(Vec<u8>);
+
+impl Hasher for Buffer {
+    fn write(&mut self, msg: &[u8]) {
+        self.0.extend_from_slice(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        unreachable!("buffer is never finished")
+    }
+}
+
+/// Serializer feeding the canonical encoding of the value into the hasher.
+#[derive(Debug)]
+struct Encoder<'a, H> {
+    state: &'a mut H,
+}
+
+impl<H: Hasher> Encoder<'_, H> {
+    fn reborrow(&mut self) -> Encoder<'_, H> {
+        Encoder { state: self.state }
+    }
+
+    fn tag(&mut self, variant_index: u32) {
+        variant_index.hash(self.state)
+    }
+}
+
+macro_rules! serialize_hashable {
+    ($(($meth:ident, $ty:ty),)*) => {$(
+        fn $meth(self, v: $ty) -> Result<(), Error> {
+            v.hash(self.state);
+            Ok(())
+        }
+    )*}
+}
+
+impl<'a, H: Hasher> ser::Serializer for Encoder<'a, H> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = Seq<'a, H>;
+    type SerializeTuple = Self;
+    type SerializeTupleStruct = Self;
+    type SerializeTupleVariant = Self;
+    type SerializeMap = Map<'a, H>;
+    type SerializeStruct = Self;
+    type SerializeStructVariant = Self;
+
+    serialize_hashable! {
+        (serialize_bool, bool),
+        (serialize_i8, i8),
+        (serialize_i16, i16),
+        (serialize_i32, i32),
+        (serialize_i64, i64),
+        (serialize_i128, i128),
+        (serialize_u8, u8),
+        (serialize_u16, u16),
+        (serialize_u32, u32),
+        (serialize_u64, u64),
+        (serialize_u128, u128),
+        (serialize_char, char),
+        (serialize_str, &str),
+        (serialize_bytes, &[u8]),
+    }
+
+    fn serialize_f32(self, v: f32) -> Result<(), Error> {
+        self.serialize_u32(v.to_bits())
+    }
+
+    fn serialize_f64(self, v: f64) -> Result<(), Error> {
+        self.serialize_u64(v.to_bits())
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        self.serialize_u8(0)
+    }
+
+    fn serialize_some<T: ?Sized + Serialize>(mut self, value: &T) -> Result<(), Error> {
+        1u8.hash(self.state);
+        value.serialize(self.reborrow())
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_struct(self, _name: &'static str) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_unit_variant(
+        mut self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+    ) -> Result<(), Error> {
+        self.tag(variant_index);
+        Ok(())
+    }
+
+    fn serialize_newtype_struct<T: ?Sized + Serialize>(
+        self,
+        _name: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: ?Sized + Serialize>(
+        mut self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        self.tag(variant_index);
+        value.serialize(self)
+    }
+
+    fn serialize_seq(self, len: Option<usize>) -> Result<Seq<'a, H>, Error> {
+        let buffer = match len {
+            Some(len) => {
+                len.hash(self.state);
+                None
+            }
+            None => Some((0, Buffer::default())),
+        };
+        Ok(Seq {
+            state: self.state,
+            buffer,
+        })
+    }
+
+    fn serialize_tuple(self, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_tuple_variant(
+        mut self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.tag(variant_index);
+        Ok(self)
+    }
+
+    fn serialize_map(self, len: Option<usize>) -> Result<Map<'a, H>, Error> {
+        Ok(Map {
+            state: self.state,
+            entries: Vec::with_capacity(len.unwrap_or(0)),
+            key: None,
+        })
+    }
+
+    fn serialize_struct(self, _name: &'static str, _len: usize) -> Result<Self, Error> {
+        Ok(self)
+    }
+
+    fn serialize_struct_variant(
+        mut self,
+        _name: &'static str,
+        variant_index: u32,
+        _variant: &'static str,
+        _len: usize,
+    ) -> Result<Self, Error> {
+        self.tag(variant_index);
+        Ok(self)
+    }
+
+    fn is_human_readable(&self) -> bool {
+        false
+    }
+}
+
+impl<H: Hasher> ser::SerializeTuple for Encoder<'_, H> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(self.reborrow())
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<H: Hasher> ser::SerializeTupleStruct for Encoder<'_, H> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(self.reborrow())
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<H: Hasher> ser::SerializeTupleVariant for Encoder<'_, H> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(self.reborrow())
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<H: Hasher> ser::SerializeStruct for Encoder<'_, H> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self.reborrow())
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+impl<H: Hasher> ser::SerializeStructVariant for Encoder<'_, H> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: ?Sized + Serialize>(
+        &mut self,
+        _key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self.reborrow())
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+/// Sequence fed into the hasher as it goes if its length is known, or
+/// buffered with the count of its items until the end otherwise.
+#[derive(Debug)]
+struct Seq<'a, H> {
+    state: &'a mut H,
+    buffer: Option<(usize, Buffer)>,
+}
+
+impl<H: Hasher> ser::SerializeSeq for Seq<'_, H> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        match self.buffer {
+            Some((ref mut len, ref mut buffer)) => {
+                *len += 1;
+                value.serialize(Encoder { state: buffer })
+            }
+            None => value.serialize(Encoder { state: self.state }),
+        }
+    }
+
+    fn end(self) -> Result<(), Error> {
+        if let Some((len, buffer)) = self.buffer {
+            len.hash(self.state);
+            self.state.write(&buffer.0);
+        }
+        Ok(())
+    }
+}
+
+/// Map buffering its entries until the end, to feed them into the hasher in
+/// the order of the encoded keys.
+#[derive(Debug)]
+struct Map<'a, H> {
+    state: &'a mut H,
+    entries: Vec<(Vec<u8>, Vec<u8>)>,
+    key: Option<Vec<u8>>,
+}
+
+impl<H: Hasher> ser::SerializeMap for Map<'_, H> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_key<T: ?Sized + Serialize>(&mut self, key: &T) -> Result<(), Error> {
+        let mut buffer = Buffer::default();
+        key.serialize(Encoder { state: &mut buffer })?;
+        self.key = Some(buffer.0);
+        Ok(())
+    }
+
+    fn serialize_value<T: ?Sized + Serialize>(&mut self, value: &T) -> Result<(), Error> {
+        let key = self
+            .key
+            .take()
+            .ok_or_else(|| Error(String::from("map value without a key")))?;
+        let mut buffer = Buffer::default();
+        value.serialize(Encoder { state: &mut buffer })?;
+        self.entries.push((key, buffer.0));
+        Ok(())
+    }
+
+    fn end(mut self) -> Result<(), Error> {
+        self.entries.sort_unstable();
+        self.entries.len().hash(self.state);
+        for (key, value) in &self.entries {
+            self.state.write(key);
+            self.state.write(value);
+        }
+        Ok(())
+    }
+}
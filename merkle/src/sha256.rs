@@ -0,0 +1,146 @@
+use core::hash::Hasher;
+use sha2::{Digest, Sha256};
+use crate::fixed_hash::{FixedHash, Hash32};
+use crate::hash::Algorithm;
+
+/// SHA-256 hash of the trees.
+pub type Sha256Hash = Hash32;
+
+/// Prefix of the leaf hashes.
+const LEAF: u8 = 0x00;
+
+/// Prefix of the interior node hashes.
+const INTERIOR: u8 = 0x01;
+
+/// SHA-256 [`Algorithm`] of the trees over [`Sha256Hash`], as in RFC 6962.
+///
+/// Semantics are frozen, so that the roots never change:
+///
+/// ```text
+/// hash()        = SHA-256(everything written since reset)
+/// leaf(x)       = SHA-256(0x00 || x)
+/// node(l, r)    = SHA-256(0x01 || l || r)
+/// finish()      = first 8 bytes of hash() as big endian u64
+/// ```
+///
+/// Root of the empty tree is `SHA-256("")`. Objects are hashed into the
+/// leafs with [`crate::hash::Hashable`] first, which feeds them into
+/// `write`, so that the leaf of the object `x` of the tree built with
+/// [`crate::merkle::MerkleTree::from_data`] is `leaf(hash(x))`.
+#[derive(Debug, Clone, Default)]
+pub struct Sha256Algorithm(Sha256);
+
+impl Sha256Algorithm {
+    /// Creates new algorithm with nothing written.
+    pub fn new() -> Sha256Algorithm {
+        Sha256Algorithm(Sha256::new())
+    }
+}
+
+impl Hasher for Sha256Algorithm {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.update(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        let h = self.0.clone().finalize();
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&h[..8]);
+        u64::from_be_bytes(bytes)
+    }
+}
+
+impl Algorithm<Sha256Hash> for Sha256Algorithm {
+    #[inline]
+    fn hash(&mut self) -> Sha256Hash {
+        FixedHash(self.0.clone().finalize().into())
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.0 = Sha256::new();
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: Sha256Hash) -> Sha256Hash {
+        self.0.update([LEAF]);
+        self.0.update(leaf);
+        self.hash()
+    }
+
+    #[inline]
+    fn node(&mut self, left: Sha256Hash, right: Sha256Hash) -> Sha256Hash {
+        self.0.update([INTERIOR]);
+        self.0.update(left);
+        self.0.update(right);
+        self.hash()
+    }
+}
+
+/// Bitcoin [`Algorithm`] of the trees over [`Sha256Hash`], which hashes the
+/// nodes with double SHA-256 as the merkle roots of the blocks are.
+///
+/// Semantics are frozen, so that the roots never change:
+///
+/// ```text
+/// hash()        = SHA-256(SHA-256(everything written since reset))
+/// leaf(x)       = x
+/// node(l, r)    = SHA-256(SHA-256(l || r))
+/// finish()      = first 8 bytes of hash() as big endian u64
+/// ```
+///
+/// Leafs are the txids of the block as they are, in the internal byte
+/// order, which is the reverse of the hex the txids and the roots are shown
+/// in. Trees fill up odd levels with the duplicate of the last node as the
+/// blocks do, so that the root of the tree over the txids of a block in
+/// order is the merkle root of the block.
+#[derive(Debug, Clone, Default)]
+pub struct BitcoinAlgorithm(Sha256);
+
+impl BitcoinAlgorithm {
+    /// Creates new algorithm with nothing written.
+    pub fn new() -> BitcoinAlgorithm {
+        BitcoinAlgorithm(Sha256::new())
+    }
+}
+
+impl Hasher for BitcoinAlgorithm {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.update(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        let h = self.clone().hash();
+        let mut bytes = [0; 8];
+        bytes.copy_from_slice(&h.0[..8]);
+        u64::from_be_bytes(bytes)
+    }
+}
+
+impl Algorithm<Sha256Hash> for BitcoinAlgorithm {
+    #[inline]
+    fn hash(&mut self) -> Sha256Hash {
+        FixedHash(Sha256::digest(self.0.clone().finalize()).into())
+    }
+
+    #[inline]
+    fn reset(&mut self) {
+        self.0 = Sha256::new();
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: Sha256Hash) -> Sha256Hash {
+        leaf
+    }
+
+    #[inline]
+    fn node(&mut self, left: Sha256Hash, right: Sha256Hash) -> Sha256Hash {
+        self.0.update(left);
+        self.0.update(right);
+        self.hash()
+    }
+}
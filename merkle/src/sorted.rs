@@ -0,0 +1,211 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use crate::hash::Algorithm;
+use crate::merkle::{MerkleError, MerkleTree};
+use crate::proof::Proof;
+
+/// Merkle tree over the sorted unique items, which commits to the set of
+/// the items, so that the absence of an item from the set can be proven
+/// along with the presence of the ones in it.
+///
+/// Items are the keys of the set, and are hashed into the leafs as in
+/// [`MerkleTree::new`]. Absent key falls between two adjacent leafs, or
+/// before the first leaf, or after the last one, see [`AbsenceProof`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SortedMerkleTree<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> {
+    keys: Vec<T>,
+    tree: MerkleTree<T, A>,
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>, A: Algorithm<T>> SortedMerkleTree<T, A> {
+    /// Creates new sorted merkle tree of the items, sorting them and
+    /// dropping the duplicates.
+    pub fn new<I: IntoIterator<Item = T>>(items: I) -> SortedMerkleTree<T, A> {
+        Self::try_new(items).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new sorted merkle tree of the items.
+    ///
+    /// Fallible version of [`SortedMerkleTree::new`].
+    pub fn try_new<I: IntoIterator<Item = T>>(
+        items: I,
+    ) -> Result<SortedMerkleTree<T, A>, MerkleError> {
+        let mut keys: Vec<T> = items.into_iter().collect();
+        keys.sort_unstable();
+        keys.dedup();
+        Self::try_from_sorted(keys)
+    }
+
+    /// Creates new sorted merkle tree of the items, which must be sorted
+    /// and unique.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the items are not sorted, or are not unique.
+    pub fn from_sorted(keys: Vec<T>) -> SortedMerkleTree<T, A> {
+        Self::try_from_sorted(keys).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Creates new sorted merkle tree of the sorted unique items.
+    ///
+    /// Fallible version of [`SortedMerkleTree::from_sorted`].
+    pub fn try_from_sorted(keys: Vec<T>) -> Result<SortedMerkleTree<T, A>, MerkleError> {
+        if let Some(i) = keys.windows(2).position(|w| w[0] >= w[1]) {
+            return Err(MerkleError::Unsorted { index: i + 1 });
+        }
+
+        let tree = MerkleTree::try_new(keys.iter().cloned())?;
+        Ok(SortedMerkleTree { keys, tree })
+    }
+
+    /// Returns the tree upon the items.
+    pub fn tree(&self) -> &MerkleTree<T, A> {
+        &self.tree
+    }
+
+    /// Returns the items, sorted.
+    pub fn keys(&self) -> &[T] {
+        &self.keys
+    }
+
+    /// Returns merkle root.
+    pub fn root(&self) -> T {
+        self.tree.root()
+    }
+
+    /// Returns number of leafs.
+    pub fn leafs(&self) -> usize {
+        self.keys.len()
+    }
+
+    /// Returns the leaf index of the item `key`, or `None` if it is absent.
+    pub fn position(&self, key: &T) -> Option<usize> {
+        self.keys.binary_search(key).ok()
+    }
+
+    /// Generate merkle tree inclusion proof for the item `key`.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the item is absent.
+    pub fn gen_proof(&self, key: &T) -> Proof<T> {
+        let i = self.position(key).expect("item is in the tree");
+        self.tree.gen_proof(i)
+    }
+
+    /// Generate proof of the absence of the item `key` from the tree.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the item is in the tree.
+    pub fn gen_absence_proof(&self, key: &T) -> AbsenceProof<T> {
+        self.try_gen_absence_proof(key)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate proof of the absence of the item `key` from the tree.
+    ///
+    /// Fallible version of [`SortedMerkleTree::gen_absence_proof`].
+    pub fn try_gen_absence_proof(&self, key: &T) -> Result<AbsenceProof<T>, MerkleError> {
+        let succ = match self.keys.binary_search(key) {
+            Ok(index) => return Err(MerkleError::Present { index }),
+            Err(succ) => succ,
+        };
+
+        let bound = |i: usize| (self.keys[i].clone(), self.tree.gen_proof(i));
+        Ok(AbsenceProof {
+            pred: succ.checked_sub(1).map(bound),
+            succ: Some(succ).filter(|&i| i < self.keys.len()).map(bound),
+        })
+    }
+}
+
+/// Proof of the absence of an item from [`SortedMerkleTree`], as generated
+/// by [`SortedMerkleTree::gen_absence_proof`].
+///
+/// Proof holds the items adjacent to the absent one, the predecessor and
+/// the successor, with their inclusion proofs. Item before the first leaf
+/// has no predecessor, item after the last leaf has no successor, and the
+/// items all are absent from the empty tree, which proof holds neither.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct AbsenceProof<T: Eq + Clone + AsRef<[u8]>> {
+    pred: Option<(T, Proof<T>)>,
+    succ: Option<(T, Proof<T>)>,
+}
+
+impl<T: Ord + Clone + AsRef<[u8]>> AbsenceProof<T> {
+    /// Returns the largest item less than the absent one, and its proof.
+    pub fn predecessor(&self) -> Option<(&T, &Proof<T>)> {
+        self.pred.as_ref().map(|(key, proof)| (key, proof))
+    }
+
+    /// Returns the smallest item greater than the absent one, and its proof.
+    pub fn successor(&self) -> Option<(&T, &Proof<T>)> {
+        self.succ.as_ref().map(|(key, proof)| (key, proof))
+    }
+
+    /// Verifies that the item `key` is absent from the sorted tree of the
+    /// trusted root `root`.
+    ///
+    /// Checks the inclusion proofs of the predecessor and the successor
+    /// against the root, that they are ordered around `key`, and that their
+    /// leafs are adjacent, or that the successor is the first leaf, or that
+    /// the predecessor is the last one.
+    pub fn validate<A: Algorithm<T>>(&self, root: &T, key: &T) -> bool {
+        self.validate_with(&mut A::default(), root, key)
+    }
+
+    /// Verifies that the item `key` is absent from the sorted tree of the
+    /// trusted root `root` with the algorithm `a`, such as the keyed one the
+    /// tree is built with, as [`AbsenceProof::validate`].
+    pub fn validate_with<A: Algorithm<T>>(&self, a: &mut A, root: &T, key: &T) -> bool {
+        let mut included = |(item, proof): &(T, Proof<T>)| {
+            a.reset();
+            let leaf = a.leaf(item.clone());
+            proof.validate_against_with(a, root) && proof.item() == leaf
+        };
+
+        match (&self.pred, &self.succ) {
+            (None, None) => {
+                a.reset();
+                *root == a.hash()
+            }
+            (Some(pred), None) => pred.0 < *key && included(pred) && is_last(a, &pred.1),
+            (None, Some(succ)) => *key < succ.0 && included(succ) && succ.1.index() == 0,
+            (Some(pred), Some(succ)) => {
+                pred.0 < *key
+                    && *key < succ.0
+                    && included(pred)
+                    && included(succ)
+                    && pred.1.depth() == succ.1.depth()
+                    && pred.1.index() + 1 == succ.1.index()
+            }
+        }
+    }
+}
+
+/// Returns `true` if the proof is of the last leaf of the tree: each time
+/// the path goes on the left, the sibling is the duplicate of the node,
+/// hashed with `a`, filling up the odd level.
+///
+/// Leafs of the sorted tree are unique, so that no other sibling is equal
+/// to the node.
+fn is_last<T: Eq + Clone + AsRef<[u8]>, A: Algorithm<T>>(a: &mut A, proof: &Proof<T>) -> bool {
+    let lemma = proof.lemma();
+    let mut h = proof.item();
+
+    for (i, &left) in proof.path().iter().enumerate() {
+        let sibling = lemma[i + 1].clone();
+        if left && sibling != h {
+            return false;
+        }
+
+        a.reset();
+        h = match left {
+            true => a.node(h, sibling),
+            false => a.node(sibling, h),
+        };
+    }
+    true
+}
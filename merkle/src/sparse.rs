@@ -0,0 +1,342 @@
+use std::collections::HashMap;
+use std::hash::Hash;
+use std::marker::PhantomData;
+use crate::hash::Algorithm;
+use crate::merkle::MerkleError;
+
+/// Sparse merkle tree mapping keys to values. Keys are hashes of `A`, and
+/// the tree has one leaf per key: `2^depth` leafs, where `depth` is the
+/// number of bits in a hash (256 for SHA-256).
+///
+/// Path of the key from the root goes by its bits, the most significant
+/// first, left of 0 and right of 1. Leaf of the value `v` is
+/// [`Algorithm::leaf`] of it, the leaf of no value is `A::default().hash()`
+/// and the parents are hashed with [`Algorithm::node`], so that the empty
+/// subtrees of every height hash the same, computed once as the tree is
+/// created. Only the nodes other than the empty ones are held, so that the
+/// memory is of the depth per key inserted.
+///
+/// ```
+/// # #[cfg(feature = "sha256")] {
+/// use merkle_light::sparse::SparseMerkleTree;
+/// use merkle_light::sha256::{Sha256Algorithm, Sha256Hash};
+///
+/// let mut smt: SparseMerkleTree<Sha256Hash, Sha256Algorithm> = SparseMerkleTree::new();
+/// let empty = smt.root();
+/// smt.insert([1; 32].into(), [2; 32].into());
+///
+/// let root = smt.root();
+/// let present = smt.gen_proof(&[1; 32].into());
+/// assert_eq!(present.value(), Some(&[2; 32].into()));
+/// assert!(present.validate_against::<Sha256Algorithm>(&root));
+///
+/// let absent = smt.gen_proof(&[3; 32].into());
+/// assert_eq!(absent.value(), None);
+/// assert!(absent.validate_against::<Sha256Algorithm>(&root));
+///
+/// smt.remove(&[1; 32].into());
+/// assert_eq!(smt.root(), empty);
+/// # }
+/// ```
+#[derive(Debug, Clone)]
+pub struct SparseMerkleTree<T: Eq + Hash + Clone + AsRef<[u8]>, A: Algorithm<T>> {
+    values: HashMap<T, T>,
+    // nodes other than the empty ones, by their depth and the bits of the
+    // path to them, of the bytes of the key up to the depth
+    nodes: HashMap<(usize, Vec<u8>), T>,
+    // roots of the empty subtrees, by height
+    empty: Vec<T>,
+    _a: PhantomData<A>,
+}
+
+impl<T, A> PartialEq for SparseMerkleTree<T, A>
+where
+    T: Eq + Hash + Clone + AsRef<[u8]>,
+    A: Algorithm<T>,
+{
+    fn eq(&self, other: &Self) -> bool {
+        self.values == other.values
+    }
+}
+
+impl<T: Eq + Hash + Clone + AsRef<[u8]>, A: Algorithm<T>> Eq for SparseMerkleTree<T, A> {}
+
+impl<T: Eq + Hash + Clone + AsRef<[u8]>, A: Algorithm<T>> Default for SparseMerkleTree<T, A> {
+    fn default() -> SparseMerkleTree<T, A> {
+        SparseMerkleTree::new()
+    }
+}
+
+impl<T: Eq + Hash + Clone + AsRef<[u8]>, A: Algorithm<T>> SparseMerkleTree<T, A> {
+    /// Creates new empty sparse merkle tree.
+    pub fn new() -> SparseMerkleTree<T, A> {
+        SparseMerkleTree {
+            values: HashMap::new(),
+            nodes: HashMap::new(),
+            empty: empty_roots::<T, A>(),
+            _a: PhantomData,
+        }
+    }
+
+    /// Returns the depth of the tree, the number of bits of the keys.
+    pub fn depth(&self) -> usize {
+        self.empty.len() - 1
+    }
+
+    /// Returns the merkle root, `empty_root` of the empty tree.
+    pub fn root(&self) -> T {
+        self.node(0, &[])
+    }
+
+    /// Returns the root of the empty tree of the depth of the tree.
+    pub fn empty_root(&self) -> T {
+        self.empty[self.depth()].clone()
+    }
+
+    /// Returns number of the keys of values.
+    pub fn len(&self) -> usize {
+        self.values.len()
+    }
+
+    /// Returns `true` if there are no values.
+    pub fn is_empty(&self) -> bool {
+        self.values.is_empty()
+    }
+
+    /// Returns the value at the key `key`.
+    pub fn get(&self, key: &T) -> Option<&T> {
+        self.values.get(key)
+    }
+
+    /// Sets the value at the key `key` to `value`, and returns the value it
+    /// replaces.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not of the bytes of the hashes of the tree.
+    pub fn insert(&mut self, key: T, value: T) -> Option<T> {
+        self.try_insert(key, value)
+            .unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Sets the value at the key `key` to `value`.
+    ///
+    /// Fallible version of [`SparseMerkleTree::insert`].
+    pub fn try_insert(&mut self, key: T, value: T) -> Result<Option<T>, MerkleError> {
+        self.key_len(&key)?;
+        let leaf = A::default().leaf(value.clone());
+        self.update(key.as_ref(), leaf);
+        Ok(self.values.insert(key, value))
+    }
+
+    /// Removes the value at the key `key`, and returns it, so that the tree
+    /// is of the values other than it.
+    pub fn remove(&mut self, key: &T) -> Option<T> {
+        let value = self.values.remove(key)?;
+        self.update(key.as_ref(), self.empty[0].clone());
+        Some(value)
+    }
+
+    /// Generate the proof of the value at the key `key`, of the inclusion of
+    /// it, or of no value at the key.
+    ///
+    /// # Panics
+    ///
+    /// Panics if the key is not of the bytes of the hashes of the tree.
+    pub fn gen_proof(&self, key: &T) -> SparseProof<T> {
+        self.try_gen_proof(key).unwrap_or_else(|e| panic!("{}", e))
+    }
+
+    /// Generate the proof of the value at the key `key`.
+    ///
+    /// Fallible version of [`SparseMerkleTree::gen_proof`].
+    pub fn try_gen_proof(&self, key: &T) -> Result<SparseProof<T>, MerkleError> {
+        self.key_len(key)?;
+        let depth = self.depth();
+        let mut bitmap = vec![0; key.as_ref().len()];
+        let mut siblings = Vec::new();
+        let mut path = key.as_ref().to_vec();
+        for d in (1..=depth).rev() {
+            let height = depth - d;
+            flip(&mut path, d - 1);
+            let sibling = self.node(d, &path);
+            flip(&mut path, d - 1);
+            if sibling != self.empty[height] {
+                bitmap[height / 8] |= 1 << (height % 8);
+                siblings.push(sibling);
+            }
+            up(&mut path, d);
+        }
+        Ok(SparseProof {
+            key: key.clone(),
+            value: self.values.get(key).cloned(),
+            bitmap,
+            siblings,
+        })
+    }
+
+    fn key_len(&self, key: &T) -> Result<(), MerkleError> {
+        let (len, expected) = (key.as_ref().len(), self.depth() / 8);
+        match len == expected {
+            true => Ok(()),
+            false => Err(MerkleError::KeyLength { len, expected }),
+        }
+    }
+
+    /// Returns the node at the depth `d` of the path `path`.
+    fn node(&self, d: usize, path: &[u8]) -> T {
+        match self.nodes.get(&(d, path.to_vec())) {
+            Some(node) => node.clone(),
+            None => self.empty[self.depth() - d].clone(),
+        }
+    }
+
+    /// Sets the leaf at the key `key` to `leaf`, and rehashes the nodes up
+    /// its path, dropping the ones which turn empty.
+    fn update(&mut self, key: &[u8], leaf: T) {
+        let depth = self.depth();
+        let mut a = A::default();
+        let mut h = leaf;
+        let mut path = key.to_vec();
+        for d in (1..=depth).rev() {
+            self.set(d, &path, h.clone());
+            flip(&mut path, d - 1);
+            let sibling = self.node(d, &path);
+            flip(&mut path, d - 1);
+            a.reset();
+            h = match bit(key, d - 1) {
+                true => a.node(sibling, h),
+                false => a.node(h, sibling),
+            };
+            up(&mut path, d);
+        }
+        self.set(0, &path, h);
+    }
+
+    fn set(&mut self, d: usize, path: &[u8], node: T) {
+        match node == self.empty[self.depth() - d] {
+            true => self.nodes.remove(&(d, path.to_vec())),
+            false => self.nodes.insert((d, path.to_vec()), node),
+        };
+    }
+}
+
+/// Proof of the value at the key of [`SparseMerkleTree`], or of no value at
+/// it, compressed of the siblings of the path of the key which are not the
+/// roots of the empty subtrees.
+///
+/// Bit `i` of the bitmap, of the byte `i / 8` from its least significant
+/// bit, is set if the sibling of the height `i` is in the siblings, which
+/// are bottom-up.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SparseProof<T: Eq + Clone + AsRef<[u8]>> {
+    key: T,
+    value: Option<T>,
+    bitmap: Vec<u8>,
+    siblings: Vec<T>,
+}
+
+impl<T: Eq + Clone + AsRef<[u8]>> SparseProof<T> {
+    /// Creates new proof of the value `value` at the key `key`, or of no
+    /// value if `None`, of the bitmap and the siblings it sets.
+    pub fn new(key: T, value: Option<T>, bitmap: Vec<u8>, siblings: Vec<T>) -> Self {
+        SparseProof {
+            key,
+            value,
+            bitmap,
+            siblings,
+        }
+    }
+
+    /// Returns the key the proof is of.
+    pub fn key(&self) -> &T {
+        &self.key
+    }
+
+    /// Returns the value at the key, or `None` if the proof is of no value.
+    pub fn value(&self) -> Option<&T> {
+        self.value.as_ref()
+    }
+
+    /// Returns the bitmap of the siblings which are not empty.
+    pub fn bitmap(&self) -> &[u8] {
+        &self.bitmap
+    }
+
+    /// Returns the siblings which are not empty, bottom-up.
+    pub fn siblings(&self) -> &[T] {
+        &self.siblings
+    }
+
+    /// Verifies the proof against the trusted root `root` of the tree of
+    /// the algorithm `A`, of the value, or of no value, at the key.
+    ///
+    /// Proof of the key or the bitmap not of the bytes of the hashes of
+    /// `A`, or of the siblings other in number than the bits the bitmap
+    /// sets, does not validate.
+    pub fn validate_against<A: Algorithm<T>>(&self, root: &T) -> bool {
+        let empty = empty_roots::<T, A>();
+        let depth = empty.len() - 1;
+        let bytes = depth / 8;
+        let set: u32 = self.bitmap.iter().map(|b| b.count_ones()).sum();
+        if self.key.as_ref().len() != bytes
+            || self.bitmap.len() != bytes
+            || set as usize != self.siblings.len()
+        {
+            return false;
+        }
+
+        let mut a = A::default();
+        let mut h = match &self.value {
+            Some(value) => a.leaf(value.clone()),
+            None => empty[0].clone(),
+        };
+        let mut siblings = self.siblings.iter();
+        for (height, empty) in empty.iter().enumerate().take(depth) {
+            let sibling = match self.bitmap[height / 8] >> (height % 8) & 1 {
+                1 => siblings.next().cloned().expect("bits set are of siblings"),
+                _ => empty.clone(),
+            };
+            a.reset();
+            h = match bit(self.key.as_ref(), depth - 1 - height) {
+                true => a.node(sibling, h),
+                false => a.node(h, sibling),
+            };
+        }
+        h == *root
+    }
+}
+
+/// Returns the roots of the empty subtrees of `A` by height, from the leaf
+/// of no value up to the root of the empty tree of the depth of the bits of
+/// the hashes.
+fn empty_roots<T: Clone + AsRef<[u8]>, A: Algorithm<T>>() -> Vec<T> {
+    let mut a = A::default();
+    let mut h = a.hash();
+    let depth = h.as_ref().len() * 8;
+    let mut empty = Vec::with_capacity(depth + 1);
+    empty.push(h.clone());
+    for _ in 0..depth {
+        a.reset();
+        h = a.node(h.clone(), h);
+        empty.push(h.clone());
+    }
+    empty
+}
+
+/// Returns the bit `i` of the key, counted from the most significant.
+fn bit(key: &[u8], i: usize) -> bool {
+    key[i / 8] >> (7 - i % 8) & 1 == 1
+}
+
+/// Flips the bit `i` of the path.
+fn flip(path: &mut [u8], i: usize) {
+    path[i / 8] ^= 1 << (7 - i % 8);
+}
+
+/// Turns the path of the node at the depth `d` into the path of its parent,
+/// clearing the last bit of it.
+fn up(path: &mut Vec<u8>, d: usize) {
+    path[(d - 1) / 8] &= !(1 << (7 - (d - 1) % 8));
+    path.truncate((d - 1).div_ceil(8));
+}
@@ -0,0 +1,456 @@
+extern crate alloc;
+
+use alloc::boxed::Box;
+use alloc::vec::Vec;
+use core::marker::PhantomData;
+use core::mem;
+use core::ops;
+
+/// Storage of the merkle tree nodes in memory layout.
+///
+/// Nodes are addressed by their index in memory layout, and the tree only
+/// ever grows the store at its end or cuts it down. Every method but `new`
+/// is object safe, so that the store can live behind a pointer, and the
+/// default methods can be overridden where the store does better, as
+/// [`VecStore`] does.
+pub trait Store<T> {
+    /// Creates new empty store with the room for `size` nodes.
+    fn new(size: usize) -> Self
+    where
+        Self: Sized;
+
+    /// Returns node at index `i`.
+    ///
+    /// Panics if `i >= len()`.
+    fn read(&self, i: usize) -> T;
+
+    /// Returns node at index `i` borrowed, or `None` if the store does not
+    /// hold the nodes as they are, as the trees read the children of the
+    /// nodes they hash without cloning them where the store lends them.
+    ///
+    /// Panics if `i >= len()` where the store lends its nodes.
+    fn read_ref(&self, i: usize) -> Option<&T> {
+        let _ = i;
+        None
+    }
+
+    /// Reads node at index `i` into `node`, reusing the room of `node`
+    /// where the store can, as the trees read the children of the nodes
+    /// they hash where the store does not lend them.
+    ///
+    /// Panics if `i >= len()`.
+    fn read_into(&self, i: usize, node: &mut T) {
+        *node = self.read(i);
+    }
+
+    /// Replaces node at index `i` with `value`.
+    ///
+    /// Panics if `i >= len()`.
+    fn write(&mut self, i: usize, value: T);
+
+    /// Appends node to the end of the store.
+    fn push(&mut self, value: T);
+
+    /// Returns the number of nodes in the store.
+    fn len(&self) -> usize;
+
+    /// Returns `true` if the store holds no nodes.
+    fn is_empty(&self) -> bool {
+        self.len() == 0
+    }
+
+    /// Shortens the store to the first `len` nodes. Has no effect if the
+    /// store is not longer than that.
+    fn truncate(&mut self, len: usize);
+
+    /// Reserves the room for at least `additional` more nodes.
+    fn reserve(&mut self, additional: usize) {
+        let _ = additional;
+    }
+
+    /// Drops the room reserved past the nodes the store holds, where the
+    /// store can.
+    fn shrink_to_fit(&mut self) {}
+
+    /// Returns the estimate of the bytes of the heap the store holds, of
+    /// its nodes and the room reserved past them, not counting the heap
+    /// the nodes own themselves. Stores which do not estimate it report the
+    /// bytes of the nodes as they are.
+    fn heap_size(&self) -> usize {
+        self.len() * size_of::<T>()
+    }
+
+    /// Returns nodes in the range `r` of indices.
+    fn read_range(&self, r: ops::Range<usize>) -> Vec<T> {
+        r.map(|i| self.read(i)).collect()
+    }
+
+    /// Splits the store at index `at`, returning the nodes past it.
+    fn split_off(&mut self, at: usize) -> Vec<T> {
+        let tail = self.read_range(at..self.len());
+        self.truncate(at);
+        tail
+    }
+}
+
+/// In memory store of the nodes in a vector, the default tree store.
+///
+/// Store dereferences to the slice of the nodes.
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct VecStore<T>(Vec<T>);
+
+impl<T> VecStore<T> {
+    /// Returns the vector of the nodes, without cloning them.
+    pub fn into_vec(self) -> Vec<T> {
+        self.0
+    }
+}
+
+impl<T> Default for VecStore<T> {
+    fn default() -> Self {
+        VecStore(Vec::new())
+    }
+}
+
+impl<T> From<Vec<T>> for VecStore<T> {
+    fn from(data: Vec<T>) -> Self {
+        VecStore(data)
+    }
+}
+
+impl<T> ops::Deref for VecStore<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T: Clone> Store<T> for VecStore<T> {
+    fn new(size: usize) -> Self {
+        VecStore(Vec::with_capacity(size))
+    }
+
+    fn read(&self, i: usize) -> T {
+        self.0[i].clone()
+    }
+
+    fn read_ref(&self, i: usize) -> Option<&T> {
+        Some(&self.0[i])
+    }
+
+    fn write(&mut self, i: usize, value: T) {
+        self.0[i] = value;
+    }
+
+    fn push(&mut self, value: T) {
+        self.0.push(value);
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        self.0.reserve_exact(additional);
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.0.shrink_to_fit();
+    }
+
+    fn heap_size(&self) -> usize {
+        self.0.capacity() * size_of::<T>()
+    }
+
+    fn read_range(&self, r: ops::Range<usize>) -> Vec<T> {
+        self.0[r].to_vec()
+    }
+
+    fn split_off(&mut self, at: usize) -> Vec<T> {
+        self.0.split_off(at)
+    }
+}
+
+/// In memory store of the nodes in a boxed slice of exactly the nodes, with
+/// no room reserved past them, the store of
+/// [`crate::frozen::FrozenTree`].
+///
+/// Store dereferences to the slice of the nodes. Every node pushed
+/// reallocates the slice, so that the trees which grow are better off with
+/// [`VecStore`].
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct BoxStore<T>(Box<[T]>);
+
+impl<T> BoxStore<T> {
+    /// Returns the vector of the nodes, without cloning them.
+    pub fn into_vec(self) -> Vec<T> {
+        self.0.into_vec()
+    }
+}
+
+impl<T> Default for BoxStore<T> {
+    fn default() -> Self {
+        BoxStore(Box::new([]))
+    }
+}
+
+impl<T> From<Vec<T>> for BoxStore<T> {
+    fn from(data: Vec<T>) -> Self {
+        BoxStore(data.into_boxed_slice())
+    }
+}
+
+impl<T> From<VecStore<T>> for BoxStore<T> {
+    fn from(data: VecStore<T>) -> Self {
+        BoxStore::from(data.into_vec())
+    }
+}
+
+impl<T> ops::Deref for BoxStore<T> {
+    type Target = [T];
+
+    fn deref(&self) -> &[T] {
+        &self.0
+    }
+}
+
+impl<T: Clone> Store<T> for BoxStore<T> {
+    fn new(_size: usize) -> Self {
+        BoxStore::default()
+    }
+
+    fn read(&self, i: usize) -> T {
+        self.0[i].clone()
+    }
+
+    fn read_ref(&self, i: usize) -> Option<&T> {
+        Some(&self.0[i])
+    }
+
+    fn write(&mut self, i: usize, value: T) {
+        self.0[i] = value;
+    }
+
+    fn push(&mut self, value: T) {
+        let mut data = mem::take(&mut self.0).into_vec();
+        data.push(value);
+        self.0 = data.into_boxed_slice();
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if len < self.0.len() {
+            let mut data = mem::take(&mut self.0).into_vec();
+            data.truncate(len);
+            self.0 = data.into_boxed_slice();
+        }
+    }
+
+    fn read_range(&self, r: ops::Range<usize>) -> Vec<T> {
+        self.0[r].to_vec()
+    }
+}
+
+/// In memory store of the nodes of the same width in one buffer of bytes,
+/// for the hashes of bytes such as `Vec<u8>`, of which [`VecStore`] holds
+/// every node in an allocation of its own.
+///
+/// Width of the nodes is given with [`FlatStore::with_width`], or taken of
+/// the first node pushed into the store of [`Store::new`]. Nodes of other
+/// widths are not stored, and panic.
+///
+/// ```
+/// use merkle_light::hash::Algorithm;
+/// use merkle_light::merkle::MerkleTree;
+/// use merkle_light::store::FlatStore;
+/// use std::collections::hash_map::DefaultHasher;
+/// use std::hash::Hasher;
+/// use std::iter::FromIterator;
+///
+/// #[derive(Clone, Default)]
+/// struct Sip(DefaultHasher);
+///
+/// impl Hasher for Sip {
+///     fn write(&mut self, msg: &[u8]) {
+///         self.0.write(msg)
+///     }
+///
+///     fn finish(&self) -> u64 {
+///         self.0.finish()
+///     }
+/// }
+///
+/// impl Algorithm<Vec<u8>> for Sip {
+///     fn hash(&mut self) -> Vec<u8> {
+///         self.0.finish().to_le_bytes().to_vec()
+///     }
+/// }
+///
+/// let hashes = (0..5u64).map(|x| x.to_le_bytes().to_vec());
+/// let t: MerkleTree<Vec<u8>, Sip, FlatStore<Vec<u8>>> = MerkleTree::from_iter(hashes.clone());
+/// let v: MerkleTree<Vec<u8>, Sip> = MerkleTree::from_iter(hashes);
+/// assert_eq!(t.root(), v.root());
+/// assert_eq!(t.gen_proof(3), v.gen_proof(3));
+///
+/// // bytes of the nodes of the proof are borrowed out of the store
+/// let p = t.gen_proof_bytes(3);
+/// assert_eq!(p.to_bytes(), v.gen_proof(3).to_bytes());
+/// ```
+#[derive(Debug, Clone)]
+pub struct FlatStore<T> {
+    bytes: Vec<u8>,
+    width: usize,
+    // nodes to reserve the room for once the width is known
+    room: usize,
+    _t: PhantomData<T>,
+}
+
+impl<T> FlatStore<T> {
+    /// Creates new empty store of the nodes of `width` bytes, with the room
+    /// for `size` nodes.
+    ///
+    /// Panics if `width` is 0, or the bytes of `size` nodes overflow
+    /// `usize`.
+    pub fn with_width(width: usize, size: usize) -> FlatStore<T> {
+        assert!(width > 0, "nodes of the store are of no bytes");
+        FlatStore {
+            bytes: Vec::with_capacity(bytes_of(size, width)),
+            width,
+            room: 0,
+            _t: PhantomData,
+        }
+    }
+
+    /// Returns the width of the nodes in bytes, or 0 if the store is not
+    /// given it and no node has been pushed yet.
+    pub fn width(&self) -> usize {
+        self.width
+    }
+
+    /// Returns the bytes of node at index `i`, borrowed out of the buffer.
+    ///
+    /// Panics if `i >= len()`.
+    pub fn get(&self, i: usize) -> &[u8] {
+        let len = self.bytes.len() / self.width.max(1);
+        assert!(
+            i < len,
+            "node index {} is out of bounds of the store with {} nodes",
+            i,
+            len
+        );
+        &self.bytes[i * self.width..(i + 1) * self.width]
+    }
+
+    /// Returns the buffer of the bytes of all the nodes, one after another.
+    pub fn as_bytes(&self) -> &[u8] {
+        &self.bytes
+    }
+
+    /// Checks that `node` is of the width of the store, taking its width if
+    /// the store has none yet.
+    fn fit(&mut self, node: &[u8]) {
+        if self.width == 0 {
+            assert!(!node.is_empty(), "nodes of the store are of no bytes");
+            self.width = node.len();
+            self.bytes.reserve_exact(bytes_of(self.room, self.width));
+        }
+        assert_eq!(
+            node.len(),
+            self.width,
+            "node of {} bytes in the store of the nodes of {} bytes",
+            node.len(),
+            self.width
+        );
+    }
+}
+
+/// Returns the number of bytes of `nodes` nodes of `width` bytes.
+///
+/// Panics if it overflows `usize`, rather than reserving the room wrapped.
+fn bytes_of(nodes: usize, width: usize) -> usize {
+    nodes.checked_mul(width).unwrap_or_else(|| {
+        panic!(
+            "{} nodes of {} bytes overflow capacity of {}-bit target",
+            nodes,
+            width,
+            usize::BITS
+        )
+    })
+}
+
+impl<T> PartialEq for FlatStore<T> {
+    fn eq(&self, other: &FlatStore<T>) -> bool {
+        self.bytes == other.bytes && (self.bytes.is_empty() || self.width == other.width)
+    }
+}
+
+impl<T> Eq for FlatStore<T> {}
+
+impl<T> Store<T> for FlatStore<T>
+where
+    T: AsRef<[u8]> + AsMut<[u8]> + for<'a> From<&'a [u8]>,
+{
+    fn new(size: usize) -> Self {
+        FlatStore {
+            bytes: Vec::new(),
+            width: 0,
+            room: size,
+            _t: PhantomData,
+        }
+    }
+
+    fn read(&self, i: usize) -> T {
+        T::from(self.get(i))
+    }
+
+    fn read_into(&self, i: usize, node: &mut T) {
+        let bytes = self.get(i);
+        match node.as_mut() {
+            room if room.len() == bytes.len() => room.copy_from_slice(bytes),
+            _ => *node = T::from(bytes),
+        }
+    }
+
+    fn write(&mut self, i: usize, value: T) {
+        let width = self.get(i).len();
+        self.fit(value.as_ref());
+        self.bytes[i * width..(i + 1) * width].copy_from_slice(value.as_ref());
+    }
+
+    fn push(&mut self, value: T) {
+        self.fit(value.as_ref());
+        self.bytes.extend_from_slice(value.as_ref());
+    }
+
+    fn len(&self) -> usize {
+        self.bytes.len() / self.width.max(1)
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.bytes.truncate(len.saturating_mul(self.width));
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        match self.width {
+            0 => self.room = self.room.saturating_add(additional),
+            width => self.bytes.reserve_exact(bytes_of(additional, width)),
+        }
+    }
+
+    fn shrink_to_fit(&mut self) {
+        self.room = 0;
+        self.bytes.shrink_to_fit();
+    }
+
+    fn heap_size(&self) -> usize {
+        self.bytes.capacity()
+    }
+}
@@ -0,0 +1,148 @@
+use std::fmt::Debug;
+use std::ops::Range;
+use proptest::collection::vec;
+use proptest::prelude::*;
+use crate::hash::Algorithm;
+use crate::merkle::MerkleTree;
+use crate::proof::Proof;
+
+/// Returns the strategy of the hashes of random bytes, as many as `T` has,
+/// such as of the leafs received by the trees.
+pub fn hash<T>() -> impl Strategy<Value = T>
+where
+    T: Default + AsMut<[u8]> + Clone + Debug,
+{
+    let len = T::default().as_mut().len();
+    vec(any::<u8>(), len).prop_map(|bytes| {
+        let mut h = T::default();
+        h.as_mut().copy_from_slice(&bytes);
+        h
+    })
+}
+
+/// Returns the strategy of the trees of the algorithm `A` over the random
+/// hashes of [`hash`], of the number of leafs in the range `leafs`, as built
+/// with [`MerkleTree::new`].
+///
+/// ```
+/// # #[cfg(feature = "sha256")] {
+/// use merkle_light::merkle::MerkleTree;
+/// use merkle_light::sha256::{Sha256Algorithm, Sha256Hash};
+/// use merkle_light::strategy;
+/// use proptest::prelude::*;
+///
+/// proptest!(|(mt in strategy::tree::<Sha256Hash, Sha256Algorithm>(2..40))| {
+///     prop_assert!(mt.verify());
+///     prop_assert!(mt.leafs() >= 2);
+/// });
+/// # }
+/// ```
+pub fn tree<T, A>(leafs: Range<usize>) -> impl Strategy<Value = MerkleTree<T, A>>
+where
+    T: Ord + Default + AsRef<[u8]> + AsMut<[u8]> + Clone + Debug,
+    A: Algorithm<T> + Debug,
+{
+    vec(hash::<T>(), leafs).prop_map(MerkleTree::new)
+}
+
+/// Returns the strategy of the trees of [`tree`] of at least one leaf, of
+/// the random leaf, and the proof of it of [`MerkleTree::gen_proof`], which
+/// validates against the root of the tree.
+pub fn proven<T, A>(
+    leafs: Range<usize>,
+) -> impl Strategy<Value = (MerkleTree<T, A>, usize, Proof<T>)>
+where
+    T: Ord + Default + AsRef<[u8]> + AsMut<[u8]> + Clone + Debug,
+    A: Algorithm<T> + Debug,
+{
+    let leafs = leafs.start.max(1)..leafs.end.max(2);
+    vec(hash::<T>(), leafs)
+        .prop_flat_map(|hashes| {
+            let leafs = hashes.len();
+            (Just(hashes), 0..leafs)
+        })
+        .prop_map(|(hashes, i)| {
+            let mt = MerkleTree::new(hashes);
+            let proof = mt.gen_proof(i);
+            (mt, i, proof)
+        })
+}
+
+/// Corruption of the proof of [`Corruption::apply`], of which the proof of
+/// [`proven`] neither validates against the root of its tree nor validates
+/// against the root it carries.
+#[derive(Debug, Clone, Copy, Eq, PartialEq)]
+pub enum Corruption {
+    /// Flips the bit `bit` of the hash `hash` of the lemma, of the leaf or
+    /// the siblings, both modulo their number.
+    Bit {
+        /// Index of the hash in the lemma.
+        hash: usize,
+        /// Index of the bit in the hash.
+        bit: usize,
+    },
+    /// Drops the `steps` last steps of the path, and their siblings, of at
+    /// least one, so that the lemma and the path still fit each other.
+    Truncate {
+        /// Number of the steps dropped, modulo the number of the steps.
+        steps: usize,
+    },
+}
+
+impl Corruption {
+    /// Returns the proof corrupted, of the pair ordering of `proof`.
+    ///
+    /// Proof of no steps, of the tree of one leaf, is not truncated, and
+    /// its leaf is flipped instead.
+    pub fn apply<T>(&self, proof: &Proof<T>) -> Proof<T>
+    where
+        T: Eq + Clone + AsRef<[u8]> + AsMut<[u8]>,
+    {
+        let mut lemma = proof.lemma().to_vec();
+        let mut path = proof.path().to_vec();
+        match *self {
+            Corruption::Truncate { steps } if !path.is_empty() => {
+                let steps = 1 + steps % path.len();
+                let root = lemma.pop().expect("lemma has the root");
+                lemma.truncate(lemma.len() - steps);
+                lemma.push(root);
+                path.truncate(path.len() - steps);
+            }
+            Corruption::Truncate { .. } => flip(&mut lemma[0], 0),
+            Corruption::Bit { hash, bit } => {
+                // the root is not flipped, the proof of which still
+                // validates against the root of the tree
+                let hash = hash % (lemma.len() - 1);
+                flip(&mut lemma[hash], bit)
+            }
+        }
+        Proof::new(lemma, path).with_pair_ordering(proof.pair_ordering())
+    }
+}
+
+/// Flips the bit `bit` of the hash, modulo its bits.
+fn flip<T: AsMut<[u8]>>(h: &mut T, bit: usize) {
+    let bytes = h.as_mut();
+    let bit = bit % (8 * bytes.len());
+    bytes[bit / 8] ^= 1 << (bit % 8);
+}
+
+/// Returns the strategy of the corruptions of [`Corruption::apply`].
+pub fn corruption() -> impl Strategy<Value = Corruption> {
+    prop_oneof![
+        (any::<usize>(), any::<usize>()).prop_map(|(hash, bit)| Corruption::Bit { hash, bit }),
+        any::<usize>().prop_map(|steps| Corruption::Truncate { steps }),
+    ]
+}
+
+/// Returns the strategy of the trees of [`proven`], of the random leaf and
+/// the proof of it corrupted with [`corruption`], which does not validate.
+pub fn corrupted<T, A>(
+    leafs: Range<usize>,
+) -> impl Strategy<Value = (MerkleTree<T, A>, usize, Proof<T>)>
+where
+    T: Ord + Default + AsRef<[u8]> + AsMut<[u8]> + Clone + Debug,
+    A: Algorithm<T> + Debug,
+{
+    (proven::<T, A>(leafs), corruption()).prop_map(|((mt, i, proof), c)| (mt, i, c.apply(&proof)))
+}
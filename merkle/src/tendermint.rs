@@ -0,0 +1,119 @@
+extern crate alloc;
+
+use alloc::vec::Vec;
+use crate::ct::{split, CtAlgorithm, CtTree};
+use crate::hash::Algorithm;
+use crate::merkle::MerkleError;
+use crate::sha256::Sha256Hash;
+
+/// Returns the simple merkle root of Tendermint and CometBFT of the items,
+/// as `merkle.HashFromByteSlices` of CometBFT and
+/// `simple_hash_from_byte_vectors` of tendermint-rs.
+///
+/// Simple merkle tree is the tree of RFC 6962, see [`crate::ct`]: leafs are
+/// `SHA-256(0x00 || item)`, nodes are `SHA-256(0x01 || left || right)`,
+/// and the items split at the largest power of two less than their number.
+/// Root of no items is `SHA-256("")`, and of one item is its leaf hash.
+pub fn simple_hash_from_byte_vectors<E: AsRef<[u8]>>(items: &[E]) -> Sha256Hash {
+    CtTree::from_entries(items).root()
+}
+
+/// Returns the simple merkle root of the items and the proofs of all of
+/// them in order, as `merkle.ProofsFromByteSlices` of CometBFT.
+pub fn proofs_from_byte_vectors<E: AsRef<[u8]>>(items: &[E]) -> (Sha256Hash, Vec<SimpleProof>) {
+    let log = CtTree::from_entries(items);
+    let proofs = (0..items.len())
+        .map(|i| SimpleProof::of(&log, i).expect("index is of the log"))
+        .collect();
+    (log.root(), proofs)
+}
+
+/// Inclusion proof of the simple merkle tree of Tendermint and CometBFT,
+/// of the fields of `merkle.Proof` of CometBFT and `merkle::Proof` of
+/// tendermint-rs.
+///
+/// Aunts are the audit path of RFC 6962 of the item, bottom-up.
+///
+/// ```
+/// use merkle_light::tendermint::{proofs_from_byte_vectors, simple_hash_from_byte_vectors};
+///
+/// let items = [&b"a"[..], b"b", b"c"];
+/// let (root, proofs) = proofs_from_byte_vectors(&items);
+/// assert_eq!(root, simple_hash_from_byte_vectors(&items));
+/// assert!(proofs[2].verify(&root, b"c"));
+/// assert!(!proofs[2].verify(&root, b"a"));
+/// ```
+#[derive(Debug, Clone, Eq, PartialEq)]
+pub struct SimpleProof {
+    /// Number of the items of the tree.
+    pub total: u64,
+    /// Index of the proven item.
+    pub index: u64,
+    /// Leaf hash of the proven item.
+    pub leaf_hash: Sha256Hash,
+    /// Hashes from the sibling of the leaf up to the child of the root.
+    pub aunts: Vec<Sha256Hash>,
+}
+
+impl SimpleProof {
+    /// Generates the proof of the item `index` of the log.
+    ///
+    /// Fails with [`MerkleError::IndexOutOfBounds`] unless `index` is less
+    /// than the size of the log.
+    pub fn of(log: &CtTree, index: usize) -> Result<SimpleProof, MerkleError> {
+        let aunts = log.audit_path(index)?;
+        Ok(SimpleProof {
+            total: log.size() as u64,
+            index: index as u64,
+            leaf_hash: *log.tree().leaf(index).expect("index is of the log"),
+            aunts,
+        })
+    }
+
+    /// Verifies the proof of the item `leaf` against the trusted root
+    /// `root`, as `Proof.Verify` of CometBFT.
+    pub fn verify(&self, root: &Sha256Hash, leaf: &[u8]) -> bool {
+        CtAlgorithm::leaf_hash(leaf) == self.leaf_hash
+            && self.compute_root_hash().as_ref() == Some(root)
+    }
+
+    /// Returns the root the proof hashes up to, as `Proof.ComputeRootHash`
+    /// of CometBFT, or `None` if the proof does not fit the tree of `total`
+    /// items, such as of an index out of bounds, or of more or less aunts
+    /// than the path of the item has.
+    pub fn compute_root_hash(&self) -> Option<Sha256Hash> {
+        let index = usize::try_from(self.index).ok()?;
+        let total = usize::try_from(self.total).ok()?;
+        hash_from_aunts(index, total, self.leaf_hash, &self.aunts)
+    }
+}
+
+/// Returns the root of the tree of `total` items hashed up from the leaf
+/// `leaf` of the item `index` with the `aunts`, top one last.
+fn hash_from_aunts(
+    index: usize,
+    total: usize,
+    leaf: Sha256Hash,
+    aunts: &[Sha256Hash],
+) -> Option<Sha256Hash> {
+    if index >= total {
+        return None;
+    }
+    if total == 1 {
+        return match aunts.is_empty() {
+            true => Some(leaf),
+            false => None,
+        };
+    }
+
+    let (aunt, aunts) = aunts.split_last()?;
+    let left = split(total);
+    let mut a = CtAlgorithm::new();
+    match index < left {
+        true => Some(a.node(hash_from_aunts(index, left, leaf, aunts)?, *aunt)),
+        false => {
+            let right = hash_from_aunts(index - left, total - left, leaf, aunts)?;
+            Some(a.node(*aunt, right))
+        }
+    }
+}
@@ -0,0 +1,78 @@
+#![cfg(test)]
+
+use crate::fixed_hash::FixedHash;
+use crate::hash::Algorithm;
+use crate::merkle::MerkleTree;
+use crate::blake3::{Blake3Algorithm, Blake3Hash};
+use std::hash::Hasher;
+
+/// Tree over the items `[i; 32]` for `i` in `0..n`.
+fn tree(n: u8) -> MerkleTree<Blake3Hash, Blake3Algorithm> {
+    MerkleTree::new((0..n).map(|i| FixedHash([i; 32])))
+}
+
+#[test]
+fn test_blake3_algorithm() {
+    let mut a = Blake3Algorithm::new();
+    assert_eq!(
+        a.hash().to_string(),
+        "af1349b9f5f9a1a6a0404dea36dcc9499bcb25c9adc112b7cc9a93cae41f3262"
+    );
+
+    a.write(b"abc");
+    assert_eq!(a.finish(), 0x6437b3ac38465133);
+    assert_eq!(
+        a.hash().to_string(),
+        "6437b3ac38465133ffb63b75273a8db548c558465d79db03fd359c6cd5bd9d85"
+    );
+
+    // hash does not consume the data written
+    assert_eq!(a.hash(), a.hash());
+    a.reset();
+    assert_eq!(a.hash(), Blake3Algorithm::default().hash());
+}
+
+#[test]
+fn test_blake3_roots() {
+    // pinned from this implementation, roots must never change
+    assert_eq!(tree(0).root(), Blake3Algorithm::new().hash());
+    assert_eq!(
+        tree(4).root().to_string(),
+        "0429a1b33a4380638861ac320355688368261d8ed7ff2acb4b9701a8480b9e4b"
+    );
+    assert_eq!(
+        tree(5).root().to_string(),
+        "a615e7942efa9cbf9568d0a0f8cf9bf2921ff5e13ca61cf8ddece7a249a6e3ae"
+    );
+}
+
+#[test]
+fn test_blake3_domain_separation() {
+    let mut a = Blake3Algorithm::new();
+    let (l, r) = (FixedHash([1; 32]), FixedHash([2; 32]));
+
+    let leaf = a.leaf(l);
+    a.reset();
+    a.write(&[0x00]);
+    a.write(l.as_ref());
+    assert_eq!(a.hash(), leaf);
+
+    a.reset();
+    let node = a.node(l, r);
+    a.reset();
+    a.write(&[0x01]);
+    a.write(l.as_ref());
+    a.write(r.as_ref());
+    assert_eq!(a.hash(), node);
+    assert_ne!(node, leaf);
+}
+
+#[test]
+fn test_blake3_proof() {
+    let mt = tree(5);
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert!(p.validate::<Blake3Algorithm>());
+        assert_eq!(p.root(), mt.root());
+    }
+}
@@ -0,0 +1,34 @@
+#![cfg(test)]
+
+use crate::builder::MerkleTreeBuilder;
+use crate::merkle::MerkleTree;
+use crate::test_item::{leafs, Item};
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_builder_chunks() {
+    let hashes = leafs(40);
+
+    for items in 0..hashes.len() {
+        let expected: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::new(hashes[..items].iter().cloned());
+
+        for chunk in 1..items + 2 {
+            let mut b: MerkleTreeBuilder<Item, DefaultHasher> = MerkleTreeBuilder::new();
+            for c in hashes[..items].chunks(chunk) {
+                b.push_chunk(c);
+            }
+            assert_eq!(b.leafs(), items);
+
+            let mt = b.finish();
+            assert_eq!(mt.as_slice(), expected.as_slice());
+            assert_eq!(mt.root(), expected.root());
+        }
+
+        let mut b: MerkleTreeBuilder<Item, DefaultHasher> = MerkleTreeBuilder::default();
+        for h in &hashes[..items] {
+            b.push(*h);
+        }
+        assert_eq!(b.finish().as_slice(), expected.as_slice());
+    }
+}
@@ -1,6 +1,6 @@
 #![cfg(test)]
 
-use crate::hash::{Hashable, Algorithm};
+use crate::hash::{Hashable, Algorithm, DomainSeparated};
 use crate::merkle::MerkleTree;
 use crate::test_item::Item;
 use std::collections::hash_map::DefaultHasher;
@@ -8,6 +8,7 @@ use std::hash::Hasher;
 use std::iter::FromIterator;
 
 /// Custom merkle hash util test
+#[allow(clippy::upper_case_acronyms)]
 #[derive(Debug, Clone, Default)]
 struct CMH(DefaultHasher);
 
@@ -74,3 +75,308 @@ fn test_custom_merkle_hasher() {
     );
     assert_eq!(mt.as_slice().iter().filter(|&&x| x.0 > 65535).count(), 0);
 }
+
+/// Merkle hasher which takes the leafs as they are, and does not separate
+/// the interior nodes
+#[derive(Debug, Clone, Default)]
+struct Plain(DefaultHasher);
+
+impl Hasher for Plain {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Item> for Plain {
+    #[inline]
+    fn hash(&mut self) -> Item {
+        Item(self.finish())
+    }
+
+    #[inline]
+    fn leaf(&mut self, leaf: Item) -> Item {
+        leaf
+    }
+
+    #[inline]
+    fn node(&mut self, left: Item, right: Item) -> Item {
+        self.write(left.as_ref());
+        self.write(right.as_ref());
+        self.hash()
+    }
+}
+
+/// Presents the interior node of the tree as its leaf, by the proof of the
+/// node, and checks it is included.
+fn node_as_leaf<A: Algorithm<Item>>() -> bool {
+    let mt: MerkleTree<Item, A> = MerkleTree::from_iter((1..=4).map(Item));
+    let node = mt[mt.leafs()];
+    let proof = mt.gen_node_proof(1, 0);
+    assert_eq!(proof.item(), node);
+    assert!(proof.validate::<A>());
+
+    let leaf = A::default().leaf(node);
+    proof.validate_leaf_against::<A>(&leaf, &mt.root())
+}
+
+#[test]
+fn test_domain_separated() {
+    assert!(node_as_leaf::<Plain>());
+    assert!(!node_as_leaf::<DomainSeparated<Plain>>());
+
+    let mut a = DomainSeparated(Plain::default());
+    let leaf = a.leaf(Item(1));
+    a.reset();
+    let node = a.node(Item(1), Item(2));
+
+    let mut b = Plain::default();
+    b.write(&[0x00]);
+    b.write(Item(1).as_ref());
+    assert_eq!(leaf, b.hash());
+    b.reset();
+    b.write(&[0x01]);
+    b.write(Item(1).as_ref());
+    b.write(Item(2).as_ref());
+    assert_eq!(node, b.hash());
+
+    let mt: MerkleTree<Item, DomainSeparated<Plain>> = MerkleTree::from_iter((1..=5).map(Item));
+    for i in 0..mt.leafs() {
+        assert!(mt.gen_proof(i).validate::<DomainSeparated<Plain>>());
+    }
+}
+
+/// Hasher which records the bytes fed into it.
+#[derive(Debug, Default)]
+struct Bytes(Vec<u8>);
+
+impl Hasher for Bytes {
+    fn write(&mut self, msg: &[u8]) {
+        self.0.extend_from_slice(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        unimplemented!()
+    }
+}
+
+fn encode<T: Hashable<Bytes> + ?Sized>(x: &T) -> Vec<u8> {
+    let mut b = Bytes::default();
+    x.hash(&mut b);
+    b.0
+}
+
+#[test]
+fn test_hashable_encodings() {
+    assert_eq!(encode(&0x01u8), [0x01]);
+    assert_eq!(encode(&0x0102u16), [0x02, 0x01]);
+    assert_eq!(encode(&0x01020304u32), [0x04, 0x03, 0x02, 0x01]);
+    assert_eq!(encode(&1u64), [1, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(encode(&1u128), [&[1][..], &[0; 15]].concat());
+    assert_eq!(encode(&1usize), [1, 0, 0, 0, 0, 0, 0, 0]);
+    assert_eq!(encode(&-1i8), [0xff]);
+    assert_eq!(encode(&-2i16), [0xfe, 0xff]);
+    assert_eq!(encode(&-2i32), [0xfe, 0xff, 0xff, 0xff]);
+    assert_eq!(
+        encode(&-2i64),
+        [0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+    );
+    assert_eq!(encode(&-1i128), [0xff; 16]);
+    assert_eq!(
+        encode(&-2isize),
+        [0xfe, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff, 0xff]
+    );
+    assert_eq!(encode(&true), [1]);
+    assert_eq!(encode(&false), [0]);
+    assert_eq!(encode(&'a'), [0x61, 0, 0, 0]);
+
+    assert_eq!(encode("ab"), [2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b']);
+    assert_eq!(encode(&String::from("ab")), encode("ab"));
+    assert_eq!(encode(""), [0; 8]);
+    assert_eq!(encode(&b"ab"[..]), [2, 0, 0, 0, 0, 0, 0, 0, b'a', b'b']);
+    assert_eq!(encode(&b"ab".to_vec()), encode(&b"ab"[..]));
+    assert_eq!(encode(b"ab"), [b'a', b'b']);
+    assert_eq!(encode(&[1u16, 2][..]), [2, 0, 0, 0, 0, 0, 0, 0, 1, 0, 2, 0]);
+
+    assert_eq!(encode(&None::<u8>), [0]);
+    assert_eq!(encode(&Some(7u8)), [1, 7]);
+    assert_eq!(encode(&Some("")), [&[1][..], &[0; 8]].concat());
+
+    assert_eq!(encode(&(1u8,)), [1]);
+    assert_eq!(encode(&(1u8, true)), [1, 1]);
+    assert_eq!(
+        encode(&(1u8, 2u16, "a")),
+        [&[1, 2, 0][..], &[1, 0, 0, 0, 0, 0, 0, 0, b'a']].concat()
+    );
+    assert_eq!(encode(&(1u8, 2u8, 3u8, None::<u8>)), [1, 2, 3, 0]);
+    assert_ne!(encode(&("ab", "c")), encode(&("a", "bc")));
+    assert_ne!(
+        encode(&(Some(0u8), None::<u8>)),
+        encode(&(None::<u8>, Some(0u8)))
+    );
+}
+
+#[test]
+fn test_fixed_hash() {
+    use crate::fixed_hash::{FixedHash, FixedHashError, Hash32, Hash64};
+
+    let mut bytes = [0; 32];
+    for (i, b) in bytes.iter_mut().enumerate() {
+        *b = i as u8 * 8;
+    }
+    let h = Hash32::from(bytes);
+    let hex = "0008101820283038404850586068707880889098a0a8b0b8c0c8d0d8e0e8f0f8";
+
+    assert_eq!(Hash32::default(), FixedHash([0; 32]));
+    assert!(Hash32::default() < h);
+    assert_eq!(h.as_ref(), &bytes[..]);
+    assert_eq!(<[u8; 32]>::from(h), bytes);
+    assert_eq!(Vec::from(h), bytes.to_vec());
+    assert_eq!(encode(&h), bytes.to_vec());
+
+    assert_eq!(h.to_string(), hex);
+    assert_eq!(format!("{:#}", h), format!("0x{}", hex));
+    assert_eq!(format!("{:?}", h), format!("FixedHash(0x{})", hex));
+    assert_eq!(hex.parse::<Hash32>(), Ok(h));
+    assert_eq!(format!("0x{}", hex.to_uppercase()).parse::<Hash32>(), Ok(h));
+    assert_eq!(
+        hex.parse::<Hash64>(),
+        Err(FixedHashError::Length {
+            expected: 64,
+            found: 32
+        })
+    );
+    assert_eq!(
+        hex.replace("0f8", "0g8").parse::<Hash32>(),
+        Err(FixedHashError::Hex { index: 62 })
+    );
+
+    assert_eq!(Hash32::try_from(&bytes[..]), Ok(h));
+    assert_eq!(Hash32::try_from(bytes.to_vec()), Ok(h));
+    assert_eq!(
+        Hash32::try_from(&bytes[1..]),
+        Err(FixedHashError::Length {
+            expected: 32,
+            found: 31
+        })
+    );
+
+    #[cfg(feature = "serde")]
+    {
+        // hex in the human readable formats, bytes in the binary ones
+        let json = serde_json::to_string(&h).unwrap();
+        assert_eq!(json, format!("\"0x{}\"", hex));
+        assert_eq!(serde_json::from_str::<Hash32>(&json).unwrap(), h);
+        assert!(serde_json::from_str::<Hash64>(&json).is_err());
+
+        let bin = bincode::serialize(&h).unwrap();
+        assert_eq!(bin, [&[32, 0, 0, 0, 0, 0, 0, 0][..], &bytes].concat());
+        assert_eq!(bincode::deserialize::<Hash32>(&bin).unwrap(), h);
+        assert!(bincode::deserialize::<Hash64>(&bin).is_err());
+
+        let wide = Hash64::from([0xab; 64]);
+        let bin = bincode::serialize(&wide).unwrap();
+        assert_eq!(bincode::deserialize::<Hash64>(&bin).unwrap(), wide);
+    }
+}
+
+thread_local! {
+    static CLONES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Hash of the bytes in a vector, which counts its clones
+#[derive(Debug, PartialEq, Eq, PartialOrd, Ord, Default)]
+struct Counted(Vec<u8>);
+
+impl Clone for Counted {
+    fn clone(&self) -> Counted {
+        CLONES.with(|c| c.set(c.get() + 1));
+        Counted(self.0.clone())
+    }
+}
+
+impl AsRef<[u8]> for Counted {
+    fn as_ref(&self) -> &[u8] {
+        &self.0
+    }
+}
+
+/// Merkle hasher of the hashes in vectors, which hashes the children
+/// borrowed if `BORROWS`, and clones them into `node` otherwise
+#[derive(Debug, Clone, Default)]
+struct VecCMH<const BORROWS: bool>(DefaultHasher);
+
+impl<const BORROWS: bool> Hasher for VecCMH<BORROWS> {
+    #[inline]
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    #[inline]
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl<const BORROWS: bool> Algorithm<Counted> for VecCMH<BORROWS> {
+    #[inline]
+    fn hash(&mut self) -> Counted {
+        Counted(self.finish().to_le_bytes().repeat(4))
+    }
+
+    #[inline]
+    fn node(&mut self, left: Counted, right: Counted) -> Counted {
+        self.write(&[1u8]);
+        self.write(left.as_ref());
+        self.write(right.as_ref());
+        self.hash()
+    }
+
+    #[inline]
+    fn node_ref(&mut self, left: &Counted, right: &Counted) -> Counted {
+        if !BORROWS {
+            return self.node(left.clone(), right.clone());
+        }
+        self.write(&[1u8]);
+        self.write(left.as_ref());
+        self.write(right.as_ref());
+        self.hash()
+    }
+}
+
+/// Returns the nodes of the tree of `n` leafs and the clones of the hashes
+/// the tree is built with.
+fn build_counted<const BORROWS: bool>(n: u64) -> (Vec<Counted>, usize) {
+    let leafs: Vec<Counted> = (0..n).map(|i| Counted(i.to_le_bytes().to_vec())).collect();
+    CLONES.with(|c| c.set(0));
+    let mt: MerkleTree<Counted, VecCMH<BORROWS>> = MerkleTree::from_iter(leafs);
+    let clones = CLONES.with(|c| c.get());
+    (mt.as_slice().to_vec(), clones)
+}
+
+#[test]
+fn test_build_borrows_children() {
+    for n in [1, 2, 5, 8, 1000] {
+        let (nodes, clones) = build_counted::<true>(n);
+        let (cloned, clones_of_node) = build_counted::<false>(n);
+        assert_eq!(nodes, cloned, "tree of {} leafs", n);
+
+        // only the last nodes of the odd levels are cloned, into their pads
+        let odd_levels: usize = (0..64)
+            .scan(n as usize, |w, _| {
+                let odd = *w > 1 && *w & 1 == 1;
+                *w = w.div_ceil(2);
+                Some(odd as usize)
+            })
+            .sum();
+        assert_eq!(clones, odd_levels, "tree of {} leafs", n);
+        // where the children of every node are cloned otherwise
+        let parents = nodes.len() - n as usize - odd_levels;
+        assert_eq!(clones_of_node, clones + 2 * parents);
+    }
+}
@@ -0,0 +1,205 @@
+#![cfg(test)]
+
+use crate::merkle::MerkleTree;
+use crate::test_xor128::{Item, SIZE, XOR128};
+
+#[test]
+fn test_codec_golden() {
+    use crate::codec::DecodeError;
+    use crate::merkle::{PaddingStrategy, PairOrdering};
+
+    // encoding is frozen, any change to these bytes breaks the stored trees
+    let golden: &[(u64, &[u8])] = &[
+        (5, include_bytes!("../testdata/xor128_5_v1.bin")),
+        (0, include_bytes!("../testdata/xor128_empty_v1.bin")),
+    ];
+    for &(items, bytes) in golden {
+        let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..items);
+        let mut encoded = Vec::new();
+        mt.encode(&mut encoded).unwrap();
+        assert_eq!(encoded, bytes);
+
+        let decoded: MerkleTree<Item, XOR128> = MerkleTree::decode(bytes, XOR128::new()).unwrap();
+        assert_eq!(decoded.as_slice(), mt.as_slice());
+    }
+
+    // leafs of the non-default hashing decode rehashed with it
+    let bytes = include_bytes!("../testdata/xor128_5_promote_sorted_v1.bin");
+    let plain: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..5u64);
+    let leafs: Vec<Item> = plain.leaves().copied().collect();
+    let (padding, ordering) = (PaddingStrategy::PromoteOdd, PairOrdering::Sorted);
+    let mt: MerkleTree<Item, XOR128> =
+        MerkleTree::from_leafs_with(leafs.into(), XOR128::new(), padding, ordering).unwrap();
+    let mut encoded = Vec::new();
+    mt.encode(&mut encoded).unwrap();
+    assert_eq!(encoded, bytes);
+    let decoded: MerkleTree<Item, XOR128> = MerkleTree::decode(&bytes[..], XOR128::new()).unwrap();
+    assert_eq!(decoded, mt);
+    assert_ne!(decoded.root(), plain.root());
+
+    let bytes = golden[0].1;
+    let decode =
+        |bytes: &[u8]| MerkleTree::<Item, XOR128>::decode(bytes, XOR128::new()).unwrap_err();
+
+    let mut magic = bytes.to_vec();
+    magic[0] = b'X';
+    assert!(matches!(decode(&magic), DecodeError::Magic(m) if m == *b"XKLT"));
+
+    let mut version = bytes.to_vec();
+    version[4] = 2;
+    assert!(matches!(decode(&version), DecodeError::Version(2)));
+
+    let mut padding = bytes.to_vec();
+    padding[5] = 4;
+    assert!(matches!(decode(&padding), DecodeError::Padding(4)));
+    let mut ordering = bytes.to_vec();
+    ordering[6] = 2;
+    assert!(matches!(decode(&ordering), DecodeError::Ordering(2)));
+
+    let mut digest = bytes.to_vec();
+    digest[7] = 32;
+    assert!(matches!(
+        decode(&digest),
+        DecodeError::DigestLength {
+            expected: 16,
+            found: 32
+        }
+    ));
+
+    match decode(&bytes[..bytes.len() - 1]) {
+        DecodeError::Io(e) => assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof),
+        e => panic!("unexpected error {}", e),
+    }
+}
+
+#[test]
+fn test_codec_keyed() {
+    use crate::hash::KeyedAlgorithm;
+
+    type Keyed = KeyedAlgorithm<XOR128>;
+    let mt: MerkleTree<Item, Keyed> = MerkleTree::from_data_with(0..7u64, Keyed::new(b"secret"));
+    let mut bytes = Vec::new();
+    mt.encode(&mut bytes).unwrap();
+
+    // nodes are rehashed with the key given
+    let decoded = MerkleTree::<Item, Keyed>::decode(&bytes[..], Keyed::new(b"secret")).unwrap();
+    assert_eq!(decoded.as_slice(), mt.as_slice());
+    let other = MerkleTree::<Item, Keyed>::decode(&bytes[..], Keyed::new(b"other")).unwrap();
+    assert!(other.leaves().eq(mt.leaves()));
+    assert_ne!(other.root(), mt.root());
+}
+
+#[test]
+fn test_codec_stream() {
+    use crate::codec::{DecodeError, CHUNK};
+    use std::io::{self, Cursor, Read, Write};
+
+    // writer and reader of at most `max` bytes a call, the writer of the
+    // longest write
+    struct Trickle<T> {
+        inner: T,
+        max: usize,
+        longest: usize,
+    }
+
+    impl<T: Write> Write for Trickle<T> {
+        fn write(&mut self, buf: &[u8]) -> io::Result<usize> {
+            self.longest = self.longest.max(buf.len());
+            let n = buf.len().min(self.max);
+            self.inner.write(&buf[..n])
+        }
+
+        fn flush(&mut self) -> io::Result<()> {
+            self.inner.flush()
+        }
+    }
+
+    impl<T: Read> Read for Trickle<T> {
+        fn read(&mut self, buf: &mut [u8]) -> io::Result<usize> {
+            let n = buf.len().min(self.max);
+            self.inner.read(&mut buf[..n])
+        }
+    }
+
+    let stream = |mt: &MerkleTree<Item, XOR128>| {
+        let mut bytes = Vec::new();
+        mt.write_to(&mut bytes).unwrap();
+        bytes
+    };
+    let read = |bytes: &[u8]| MerkleTree::<Item, XOR128>::read_from(&mut &bytes[..], XOR128::new());
+
+    // more leafs than a chunk holds
+    for items in [0, 1, 5, 17, 3 * CHUNK as u64 / SIZE as u64 + 5] {
+        let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..items);
+        let bytes = stream(&mt);
+        assert_eq!(bytes.len(), 19 + SIZE * (mt.leafs() + 1));
+
+        let mut cursor = Cursor::new(&bytes);
+        let decoded = MerkleTree::<Item, XOR128>::read_from(&mut cursor, XOR128::new());
+        assert_eq!(decoded.unwrap().as_slice(), mt.as_slice());
+
+        let mut w = Trickle {
+            inner: Vec::new(),
+            max: 7,
+            longest: 0,
+        };
+        mt.write_to(&mut w).unwrap();
+        assert_eq!(w.inner, bytes);
+        assert!(w.longest <= CHUNK);
+
+        let mut r = Trickle {
+            inner: &bytes[..],
+            max: 5,
+            longest: 0,
+        };
+        let decoded = MerkleTree::<Item, XOR128>::read_from(&mut r, XOR128::new());
+        assert_eq!(decoded.unwrap().as_slice(), mt.as_slice());
+    }
+
+    // through the pipe, the buffer of which is shorter than the stream
+    let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..20_000u64);
+    let (mut r, mut w) = io::pipe().unwrap();
+    let decoded = std::thread::scope(|scope| {
+        let mt = &mt;
+        scope.spawn(move || mt.write_to(&mut w).unwrap());
+        MerkleTree::<Item, XOR128>::read_from(&mut r, XOR128::new()).unwrap()
+    });
+    assert_eq!(decoded.as_slice(), mt.as_slice());
+
+    let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..5u64);
+    let bytes = stream(&mt);
+    for len in 0..bytes.len() {
+        match read(&bytes[..len]) {
+            Err(DecodeError::Io(e)) => assert_eq!(e.kind(), io::ErrorKind::UnexpectedEof),
+            _ => panic!("truncated stream of {} bytes decoded", len),
+        }
+    }
+
+    // bytes past the root are not read
+    let mut trailing = Cursor::new([&bytes[..], &[1, 2, 3]].concat());
+    let decoded = MerkleTree::<Item, XOR128>::read_from(&mut trailing, XOR128::new());
+    assert_eq!(decoded.unwrap().as_slice(), mt.as_slice());
+    assert_eq!(trailing.position() as usize, bytes.len());
+
+    let mut leaf = bytes.clone();
+    leaf[19] ^= 1;
+    assert!(matches!(read(&leaf), Err(DecodeError::Root)));
+
+    // fewer leafs end at the last leaf read as the root, more past the root
+    let mut fewer = bytes.clone();
+    fewer[11] = 4;
+    assert!(matches!(read(&fewer), Err(DecodeError::Root)));
+    let mut more = bytes.clone();
+    more[11] = 6;
+    assert!(matches!(read(&more), Err(DecodeError::Io(_))));
+
+    let mut encoded = Vec::new();
+    mt.encode(&mut encoded).unwrap();
+    assert!(matches!(read(&encoded), Err(DecodeError::Version(1))));
+    let mut padding = bytes.clone();
+    padding[5] = 4;
+    assert!(matches!(read(&padding), Err(DecodeError::Padding(4))));
+    let mut ordering = bytes.clone();
+    ordering[6] = 2;
+    assert!(matches!(read(&ordering), Err(DecodeError::Ordering(2))));
+}
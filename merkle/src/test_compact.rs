@@ -0,0 +1,47 @@
+#![cfg(test)]
+
+use crate::merkle::{MerkleError, MerkleTree};
+use crate::test_item::Item;
+use std::cmp;
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_compact() {
+    for items in 0..40u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        let leafs = mt.leafs();
+        let leaves: Vec<Item> = mt.leaves().cloned().collect();
+        let full = mt.clone();
+
+        let ct = mt.compact();
+        assert_eq!(ct.root(), full.root());
+        assert_eq!((ct.leafs(), ct.height()), (leafs, full.height()));
+        if leafs > 1 {
+            assert_eq!(ct.len(), full.len() - leafs - (leafs & 1));
+        } else {
+            assert!(ct.is_empty());
+        }
+
+        for i in 0..leafs {
+            // last leaf of an odd level is paired with itself
+            let sibling = leaves[cmp::min(i ^ 1, leafs - 1)];
+            let p = ct.gen_proof_with_leaf(i, leaves[i], sibling);
+            assert_eq!(p, full.gen_proof(i));
+            assert!(p.validate::<DefaultHasher>());
+
+            if leafs > 1 {
+                assert_eq!(
+                    ct.try_gen_proof_with_leaf(i, Item(items), sibling),
+                    Err(MerkleError::LeafMismatch { index: i })
+                );
+            }
+        }
+        assert_eq!(
+            ct.try_gen_proof_with_leaf(leafs, Item(0), Item(0)),
+            Err(MerkleError::IndexOutOfBounds {
+                index: leafs,
+                leafs
+            })
+        );
+    }
+}
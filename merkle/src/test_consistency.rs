@@ -0,0 +1,64 @@
+#![cfg(test)]
+
+use crate::merkle::{MerkleError, MerkleTree};
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_consistency_proof() {
+    for new_size in 0..40u64 {
+        let new: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..new_size);
+        let new_size = new_size as usize;
+
+        for old_size in 0..=new_size {
+            let old: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..old_size as u64);
+            let cp = new.gen_consistency_proof(old_size);
+            assert!(cp.nodes().len() <= new.height());
+            let valid = |old_root, old_size, new_root| {
+                cp.validate::<DefaultHasher>(&old_root, old_size, &new_root, new_size)
+            };
+            assert!(valid(old.root(), old_size, new.root()));
+
+            if old_size > 0 && old_size < new_size {
+                assert!(!valid(Item(old.root().0 ^ 1), old_size, new.root()));
+                assert!(!valid(old.root(), old_size, Item(new.root().0 ^ 1)));
+                assert!(!valid(old.root(), old_size + 1, new.root()));
+            }
+        }
+
+        assert_eq!(
+            new.try_gen_consistency_proof(new_size + 1).unwrap_err(),
+            MerkleError::InvalidRange {
+                start: 0,
+                end: new_size + 1,
+                leafs: new_size
+            }
+        );
+    }
+}
+
+#[test]
+fn test_consistency_proof_nodes() {
+    let new: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    let old: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..3u64);
+
+    // leaf 2, leaf 3 of the new tree, node 01, node 4567
+    let cp = new.gen_consistency_proof(3);
+    assert_eq!(cp.nodes(), &[new[2], new[3], new[8], new[13]]);
+    assert!(cp.validate::<DefaultHasher>(&old.root(), 3, &new.root(), 7));
+
+    // old root is node 0123 of the new tree, then node 4567
+    let old: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..4u64);
+    let cp = new.gen_consistency_proof(4);
+    assert_eq!(old.root(), new[12]);
+    assert_eq!(cp.nodes(), &[new[13]]);
+    assert!(cp.validate::<DefaultHasher>(&old.root(), 4, &new.root(), 7));
+
+    assert!(new.gen_consistency_proof(0).nodes().is_empty());
+    assert!(new.gen_consistency_proof(7).nodes().is_empty());
+    let empty = MerkleTree::<Item, DefaultHasher>::empty().root();
+    let cp = new.gen_consistency_proof(0);
+    assert!(cp.validate::<DefaultHasher>(&empty, 0, &new.root(), 7));
+    assert!(!cp.validate::<DefaultHasher>(&new.root(), 0, &new.root(), 7));
+    assert!(!cp.validate::<DefaultHasher>(&new.root(), 8, &new.root(), 7));
+}
@@ -0,0 +1,251 @@
+#![cfg(test)]
+
+use crate::ct::{mth, verify_audit_path, verify_consistency, CtAlgorithm, CtTree};
+use crate::hash::Algorithm;
+use crate::merkle::MerkleError;
+use crate::sha256::Sha256Hash;
+
+fn unhex(s: &str) -> Sha256Hash {
+    s.parse().unwrap()
+}
+
+fn unhex_all(hs: &[&str]) -> Vec<Sha256Hash> {
+    hs.iter().map(|h| unhex(h)).collect()
+}
+
+/// Entries of the test vectors of the reference implementation of
+/// Certificate Transparency.
+const ENTRIES: [&[u8]; 8] = [
+    b"",
+    b"\x00",
+    b"\x10",
+    b"\x20\x21",
+    b"\x30\x31",
+    b"\x40\x41\x42\x43",
+    b"\x50\x51\x52\x53\x54\x55\x56\x57",
+    b"\x60\x61\x62\x63\x64\x65\x66\x67\x68\x69\x6a\x6b\x6c\x6d\x6e\x6f",
+];
+
+/// Roots of the logs of the first `n` entries for `n` in `1..=8`.
+const ROOTS: [&str; 8] = [
+    "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+    "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+    "aeb6bcfe274b70a14fb067a5e5578264db0fa9b51af5e0ba159158f329e06e77",
+    "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+    "4e3bbb1f7b478dcfe71fb631631519a3bca12c9aefca1612bfce4c13a86264d4",
+    "76e67dadbcdf1e10e1b74ddc608abd2f98dfb16fbce75277b5232a127f2087ef",
+    "ddb89be403809e325750d3d263cd78929c2942b7942a34b77e122c9594a74c8c",
+    "5dc9da79a70659a9ad559cb701ded9a2ab9d823aad2f4960cfe370eff4604328",
+];
+
+#[test]
+fn test_ct_mth() {
+    let empty: [&[u8]; 0] = [];
+    assert_eq!(
+        mth(empty).to_string(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(mth(empty), CtAlgorithm::new().hash());
+
+    let mut log = CtTree::new();
+    for (n, root) in ROOTS.iter().enumerate() {
+        assert_eq!(
+            mth(&ENTRIES[..n + 1]).to_string(),
+            *root,
+            "{} entries",
+            n + 1
+        );
+        log.push(ENTRIES[n]);
+        assert_eq!(log.root().to_string(), *root);
+        assert_eq!(log.size(), n + 1);
+    }
+    assert_eq!(log, CtTree::from_entries(ENTRIES));
+    assert_eq!(log.tree().leaf(0), Some(&CtAlgorithm::leaf_hash(b"")));
+}
+
+#[test]
+fn test_ct_proof_index() {
+    for n in 1..=ENTRIES.len() {
+        let ct = CtTree::from_entries(&ENTRIES[..n]);
+        let depth = ct.tree().height() - 1;
+        for m in 0..n {
+            let p = ct.gen_proof(m).unwrap();
+            assert_eq!((p.index(), p.depth()), (m, depth));
+        }
+    }
+}
+
+#[test]
+fn test_ct_audit_path() {
+    // (m, n, PATH(m, D[n]))
+    let vectors: [(usize, usize, &[&str]); 5] = [
+        (0, 1, &[]),
+        (
+            0,
+            8,
+            &[
+                "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+                "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+            ],
+        ),
+        (
+            5,
+            8,
+            &[
+                "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+                "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+                "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+            ],
+        ),
+        (
+            2,
+            3,
+            &["fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125"],
+        ),
+        (
+            1,
+            5,
+            &[
+                "6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d",
+                "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+            ],
+        ),
+    ];
+    for (m, n, path) in vectors {
+        let log = CtTree::from_entries(&ENTRIES[..n]);
+        let expected = unhex_all(path);
+        assert_eq!(
+            log.audit_path(m),
+            Ok(expected.clone()),
+            "path of {} in {}",
+            m,
+            n
+        );
+
+        let leaf = CtAlgorithm::leaf_hash(ENTRIES[m]);
+        let root = unhex(ROOTS[n - 1]);
+        assert!(verify_audit_path(&leaf, m, n, &expected, &root));
+        assert!(!verify_audit_path(&leaf, n, n, &expected, &root));
+        if !expected.is_empty() {
+            let mut tampered = expected.clone();
+            tampered[0].0[0] ^= 1;
+            assert!(!verify_audit_path(&leaf, m, n, &tampered, &root));
+            assert!(!verify_audit_path(&leaf, m ^ 1, n, &expected, &root));
+            assert!(!verify_audit_path(&leaf, m, n, &expected[1..], &root));
+        }
+    }
+
+    // every path of every log verifies, as do the proofs of the tree
+    for n in 1..=ENTRIES.len() {
+        let log = CtTree::from_entries(&ENTRIES[..n]);
+        for (m, entry) in ENTRIES[..n].iter().enumerate() {
+            let path = log.audit_path(m).unwrap();
+            let leaf = CtAlgorithm::leaf_hash(entry);
+            assert!(verify_audit_path(&leaf, m, n, &path, &log.root()));
+            assert!(log.gen_proof(m).unwrap().validate::<CtAlgorithm>());
+        }
+        assert_eq!(
+            log.audit_path(n),
+            Err(MerkleError::IndexOutOfBounds { index: n, leafs: n })
+        );
+    }
+}
+
+#[test]
+fn test_ct_consistency_proof() {
+    // (m, n, PROOF(m, D[n]))
+    let vectors: [(usize, usize, &[&str]); 6] = [
+        (1, 1, &[]),
+        (
+            1,
+            8,
+            &[
+                "96a296d224f285c67bee93c30f8a309157f0daa35dc5b87e410b78630a09cfc7",
+                "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                "6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4",
+            ],
+        ),
+        (
+            6,
+            8,
+            &[
+                "0ebc5d3437fbe2db158b9f126a1d118e308181031d0a949f8dededebc558ef6a",
+                "ca854ea128ed050b41b35ffc1b87b8eb2bde461e9e3b5596ece6b9d5975a0ae0",
+                "d37ee418976dd95753c1c73862b9398fa2a2cf9b4ff0fdfe8b30cd95209614b7",
+            ],
+        ),
+        (
+            2,
+            5,
+            &[
+                "5f083f0a1a33ca076a95279832580db3e0ef4584bdff1f54c8a360f50de3031e",
+                "bc1a0643b12e4d2d7c77918f44e0f4f79a838b6cf9ec5b5c283e1f4d88599e6b",
+            ],
+        ),
+        (
+            3,
+            7,
+            &[
+                "0298d122906dcfc10892cb53a73992fc5b9f493ea4c9badb27b791b4127a7fe7",
+                "07506a85fd9dd2f120eb694f86011e5bb4662e5c415a62917033d4a9624487e7",
+                "fac54203e7cc696cf0dfcb42c92a1d9dbaf70ad9e621f4bd8d98662f00e3c125",
+                "837dbb152e9b079010717e84e865da4ebc0fa198a806d59d31bf15accef22d0e",
+            ],
+        ),
+        (
+            4,
+            8,
+            &["6b47aaf29ee3c2af9af889bc1fb9254dabd31177f16232dd6aab035ca39bf6e4"],
+        ),
+    ];
+    for (m, n, proof) in vectors {
+        let log = CtTree::from_entries(&ENTRIES[..n]);
+        let expected = unhex_all(proof);
+        assert_eq!(
+            log.consistency_proof(m),
+            Ok(expected.clone()),
+            "proof of {} in {}",
+            m,
+            n
+        );
+
+        let (old, new) = (unhex(ROOTS[m - 1]), unhex(ROOTS[n - 1]));
+        assert!(verify_consistency(m, n, &old, &new, &expected));
+        if !expected.is_empty() {
+            let mut tampered = expected.clone();
+            tampered[0].0[0] ^= 1;
+            assert!(!verify_consistency(m, n, &old, &new, &tampered));
+            assert!(!verify_consistency(m, n, &new, &old, &expected));
+            assert!(!verify_consistency(m - 1, n, &old, &new, &expected));
+            assert!(!verify_consistency(m, n, &old, &new, &expected[1..]));
+        }
+    }
+
+    // every proof of every pair of logs verifies
+    let empty = CtTree::new().root();
+    for n in 0..=ENTRIES.len() {
+        let log = CtTree::from_entries(&ENTRIES[..n]);
+        for m in 0..=n {
+            let proof = log.consistency_proof(m).unwrap();
+            let old = CtTree::from_entries(&ENTRIES[..m]).root();
+            assert!(
+                verify_consistency(m, n, &old, &log.root(), &proof),
+                "{} of {}",
+                m,
+                n
+            );
+            assert!(!verify_consistency(n + 1, n, &old, &log.root(), &proof));
+        }
+        assert!(verify_consistency(0, n, &empty, &log.root(), &[]));
+        assert_eq!(
+            log.consistency_proof(n + 1),
+            Err(MerkleError::InvalidRange {
+                start: 0,
+                end: n + 1,
+                leafs: n,
+            })
+        );
+    }
+}
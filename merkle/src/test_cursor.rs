@@ -0,0 +1,75 @@
+#![cfg(test)]
+
+use crate::merkle::MerkleTree;
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_cursor() {
+    use crate::merkle::PaddingStrategy::*;
+
+    let empty: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert!(empty.cursor().is_none());
+
+    for padding in [HashWithSelf, PromoteOdd, EmptyHashPerLevel, DuplicateLast] {
+        for items in 1..34u64 {
+            let mt: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::new_padded((0..items).map(Item), padding);
+            let root = mt.cursor().unwrap();
+            assert_eq!(root.hash(), &mt.root());
+            assert_eq!(root.level(), mt.height() - 1);
+            assert_eq!((root.parent(), root.sibling()), (None, None));
+            assert!(root.path_from_root().is_empty());
+
+            for i in 0..mt.leafs() {
+                // down by the bits of the index, from the top
+                let mut c = root;
+                for level in (1..mt.height()).rev() {
+                    assert!(!c.is_leaf());
+                    c = match (i >> (level - 1)) & 1 {
+                        0 => c.left(),
+                        _ => c.right(),
+                    }
+                    .unwrap();
+                    assert_eq!(c.level(), level - 1);
+                }
+                assert!(c.is_leaf());
+                assert_eq!((c.leaf_index(), c.index()), (Some(i), i));
+                assert_eq!((c.left(), c.right()), (None, None));
+
+                // up by the siblings, which are the lemma of the proof
+                let p = mt.gen_proof(i);
+                assert_eq!(c.hash(), &p.item());
+                let mut path = c.path_from_root();
+                path.reverse();
+                assert_eq!(path, p.path(), "{:?} leaf {} of {}", padding, i, items);
+
+                let mut lemma = vec![*c.hash()];
+                while let Some(parent) = c.parent() {
+                    if let Some(sibling) = c.sibling() {
+                        assert_eq!(sibling.sibling(), Some(c));
+                        assert_eq!(sibling.parent(), Some(parent));
+                        lemma.push(*sibling.hash());
+                    }
+                    c = parent;
+                }
+                assert_eq!(c, root);
+                lemma.push(mt.root());
+                assert_eq!(lemma, p.lemma());
+            }
+        }
+    }
+
+    // right child of the last node of the odd level is its duplicate
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+    let last = mt.cursor().and_then(|c| c.right()).unwrap();
+    assert_eq!(last.right(), None);
+    let leaf = last.left().and_then(|c| c.left()).unwrap();
+    assert_eq!(leaf.leaf_index(), Some(4));
+    assert_eq!(leaf.right(), None);
+    let padding = leaf.sibling().unwrap();
+    assert!(padding.is_padding() && padding.is_leaf());
+    assert_eq!(padding.leaf_index(), None);
+    assert_eq!(padding.hash(), leaf.hash());
+    assert_eq!(padding.left(), None);
+}
@@ -0,0 +1,88 @@
+#![cfg(test)]
+
+use crate::digest::DigestAlgorithm;
+use crate::fixed_hash::{FixedHash, Hash32};
+use crate::hash::Algorithm;
+use crate::merkle::MerkleTree;
+use sha2::{Digest, Sha224, Sha512};
+use sha3::Sha3_256;
+use std::hash::Hasher;
+
+/// Tree over the items `i` for `i` in `0..n`.
+fn tree<D: Digest + Clone, const N: usize>(
+    n: u64,
+    a: DigestAlgorithm<D, N>,
+) -> MerkleTree<FixedHash<N>, DigestAlgorithm<D, N>> {
+    MerkleTree::from_data_with(0..n, a)
+}
+
+/// Checks the proofs of all the leafs of the trees of up to 9 leafs.
+fn proofs<D: Digest + Clone, const N: usize>() {
+    for n in 1..10 {
+        let t = tree(n, DigestAlgorithm::<D, N>::new());
+        for i in 0..t.leafs() {
+            let p = t.gen_proof(i);
+            assert!(p.validate::<DigestAlgorithm<D, N>>());
+            assert_eq!(p.root(), t.root());
+        }
+    }
+}
+
+#[test]
+fn test_digest_algorithm() {
+    let mut a = DigestAlgorithm::<Sha3_256, 32>::new();
+    a.write(b"abc");
+    assert_eq!(a.hash().as_ref(), Sha3_256::digest(b"abc").as_slice());
+    assert_eq!(a.finish(), 0x3a985da74fe225b2);
+
+    let mut l = DigestAlgorithm::<Sha512, 64>::new();
+    let leaf = l.leaf(FixedHash([7; 64]));
+    assert_eq!(
+        leaf.as_ref(),
+        Sha512::new()
+            .chain_update([0x00])
+            .chain_update([7; 64])
+            .finalize()
+            .as_slice()
+    );
+
+    // hash does not consume the data written
+    assert_eq!(a.hash(), a.hash());
+    a.reset();
+    assert_eq!(a.hash(), DigestAlgorithm::<Sha3_256, 32>::default().hash());
+}
+
+#[test]
+fn test_digest_proofs() {
+    proofs::<Sha3_256, 32>();
+    proofs::<Sha512, 64>();
+    proofs::<Sha224, 28>();
+}
+
+#[test]
+fn test_digest_prefixes() {
+    let custom = || DigestAlgorithm::<Sha3_256, 32>::with_prefixes(b"leaf:", b"node:");
+    assert_eq!(custom().leaf_prefix(), b"leaf:");
+    assert_eq!(custom().node_prefix(), b"node:");
+
+    let t = tree(5, custom());
+    assert_ne!(
+        t.root(),
+        tree(5, DigestAlgorithm::<Sha3_256, 32>::new()).root()
+    );
+
+    // prefixes are kept over reset, the proofs validate with them only
+    let p = t.gen_proof(3);
+    assert!(p.validate_with(&mut custom()));
+    assert!(!p.validate::<DigestAlgorithm<Sha3_256, 32>>());
+}
+
+#[test]
+#[cfg(feature = "sha256")]
+fn test_digest_sha256() {
+    use crate::sha256::Sha256Algorithm;
+
+    let t: MerkleTree<Hash32, Sha256Algorithm> = MerkleTree::from_data(0..7u64);
+    let d = tree(7, DigestAlgorithm::<sha2::Sha256, 32>::new());
+    assert_eq!(t.root(), d.root());
+}
@@ -0,0 +1,55 @@
+#![cfg(test)]
+
+use crate::merkle::MerkleTree;
+use crate::test_item::{Item, VecSip};
+use std::collections::hash_map::DefaultHasher;
+use std::iter::FromIterator;
+
+#[test]
+fn test_shrink_freeze() {
+    use crate::store::FlatStore;
+
+    let exact = |nodes: usize| nodes * size_of::<Item>();
+    for items in [0, 1, 2, 5, 8, 100, 1025u64] {
+        // leafs of no size hint are buffered into the room grown past them
+        let hashes = (0..2 * items).filter(|x| x & 1 == 0).map(Item);
+        let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::try_from_iter(hashes).unwrap();
+        assert_eq!(mt.heap_size_estimate(), exact(mt.node_count()));
+
+        for x in 0..3 {
+            mt.push(Item(x));
+        }
+        assert!(mt.heap_size_estimate() >= exact(mt.node_count()));
+        mt.build_index();
+        let indexed = mt.heap_size_estimate();
+        assert!(indexed >= exact(mt.node_count()) + mt.leafs() * size_of::<(Item, usize)>());
+        mt.shrink_to_fit();
+        let index = mt.leafs() * size_of::<(Item, usize)>();
+        assert_eq!(mt.heap_size_estimate(), exact(mt.node_count()) + index);
+
+        let frozen = mt.clone().freeze();
+        assert_eq!(frozen.heap_size_estimate(), mt.heap_size_estimate());
+        assert_eq!(frozen.root(), mt.root());
+        assert_eq!(&frozen[..], &mt[..]);
+        assert!(frozen.verify());
+        for i in 0..mt.leafs() {
+            assert_eq!(frozen.gen_proof(i), mt.gen_proof(i));
+            assert_eq!(frozen.find_leaf(&mt[i]), mt.find_leaf(&mt[i]));
+        }
+
+        // thawed tree grows again as the tree it was frozen of
+        let mut thawed = frozen.thaw();
+        assert_eq!(thawed, mt);
+        thawed.push(Item(3));
+        mt.push(Item(3));
+        assert_eq!(thawed.root(), mt.root());
+    }
+
+    // flat store holds the nodes of its width in one buffer
+    let hashes = (0..5u64).map(|x| x.to_le_bytes().repeat(2));
+    let mut mt: MerkleTree<Vec<u8>, VecSip, FlatStore<Vec<u8>>> = MerkleTree::from_iter(hashes);
+    assert_eq!(mt.heap_size_estimate(), 16 * mt.node_count());
+    mt.push(vec![7; 16]);
+    mt.shrink_to_fit();
+    assert_eq!(mt.heap_size_estimate(), 16 * mt.node_count());
+}
@@ -0,0 +1,63 @@
+#![cfg(test)]
+
+use crate::merkle::MerkleTree;
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_hex() {
+    use crate::hex::{from_hex, parse_hash, parse_hashes, to_hex, HexDisplay, HexError};
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+    let root = mt.root();
+    assert_eq!(mt.root_hex(), format!("{:#}", HexDisplay::new(&root)));
+    let lemma = mt.gen_proof(0).to_hex_lemma();
+    assert_eq!(mt.root_hex(), *lemma.last().unwrap());
+    assert_eq!(format!("{}", HexDisplay::new(&root)), mt.root_hex()[2..]);
+    assert_eq!(from_hex(&mt.root_hex()), Ok(root.as_ref().to_vec()));
+    let empty: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(empty.root_hex(), to_hex(empty.root().as_ref()));
+
+    assert_eq!(to_hex(&[]), "0x");
+    assert_eq!(from_hex("0x"), Ok(vec![]));
+    assert_eq!(from_hex("00Ff7a"), Ok(vec![0, 0xff, 0x7a]));
+    for (s, err) in [
+        ("0x0", HexError::OddLength { digits: 1 }),
+        ("abc", HexError::OddLength { digits: 3 }),
+        ("0xzz", HexError::Digit { index: 0 }),
+        ("a-", HexError::Digit { index: 1 }),
+        (" 00", HexError::OddLength { digits: 3 }),
+        // non ascii characters are not digits
+        ("é", HexError::Digit { index: 0 }),
+    ] {
+        assert_eq!(from_hex(s), Err(err), "{:?}", s);
+        assert_eq!(parse_hash::<[u8; 1]>(s).unwrap_err(), err);
+    }
+    assert_eq!(from_hex("0x0x00"), Err(HexError::Digit { index: 1 }));
+    assert_eq!(
+        parse_hash::<[u8; 8]>("0x00").unwrap_err().to_string(),
+        "hash of 8 bytes is parsed of 1 bytes"
+    );
+
+    // hashes exported one per line are imported into the same tree
+    let items: Vec<Item> = (0..5).map(|x| Item(x * 3)).collect();
+    let line = |h| format!("{}\n", HexDisplay::new(h));
+    let lines: String = items.iter().map(line).collect();
+    let hashes: Vec<[u8; 8]> = parse_hashes(&lines).unwrap();
+    let hashes: Vec<Item> = hashes
+        .iter()
+        .map(|&b| u64::from_ne_bytes(b).into())
+        .collect();
+    assert_eq!(hashes, items);
+    let imported: MerkleTree<Item, DefaultHasher> = MerkleTree::new(hashes);
+    assert_eq!(imported, MerkleTree::new(items));
+
+    let err = parse_hashes::<[u8; 8]>(&format!("{}0011\n", lines)).unwrap_err();
+    assert_eq!(err.line, 6);
+    let expected = HexError::Length {
+        expected: 8,
+        found: 2,
+    };
+    assert_eq!(err.error, expected);
+    assert_eq!(parse_hashes::<[u8; 8]>("\n \n"), Ok(vec![]));
+}
@@ -1,16 +1,25 @@
 #![cfg(test)]
 #![allow(unsafe_code)]
 
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+use std::ptr;
 use std::slice;
-use std::mem;
 use crate::hash::{Hashable, Algorithm};
 
-#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Default, Debug)]
+#[derive(Copy, Clone, PartialEq, Eq, PartialOrd, Ord, Hash, Default, Debug)]
+#[cfg_attr(feature = "serde", derive(serde::Serialize, serde::Deserialize))]
 pub struct Item(pub u64);
 
 impl AsRef<[u8]> for Item {
     fn as_ref(&self) -> &[u8] {
-        unsafe { slice::from_raw_parts(mem::transmute(&self.0), 8) }
+        unsafe { slice::from_raw_parts(ptr::from_ref(&self.0).cast::<u8>(), 8) }
+    }
+}
+
+impl AsMut<[u8]> for Item {
+    fn as_mut(&mut self) -> &mut [u8] {
+        unsafe { slice::from_raw_parts_mut(ptr::from_mut(&mut self.0).cast::<u8>(), 8) }
     }
 }
 
@@ -26,9 +35,9 @@ impl From<u64> for Item {
     }
 }
 
-impl Into<u64> for Item {
-    fn into(self) -> u64 {
-        self.0
+impl From<Item> for u64 {
+    fn from(x: Item) -> Self {
+        x.0
     }
 }
 
@@ -37,3 +46,35 @@ impl<A: Algorithm<Item>> Hashable<A> for Item {
         state.write_u64(self.0)
     }
 }
+
+/// Returns the leaf of `x`, its hash with [`DefaultHasher`].
+pub fn leaf(x: u64) -> Item {
+    let mut a = DefaultHasher::new();
+    x.hash(&mut a);
+    a.hash()
+}
+
+/// Returns the `n` leafs of `0..n`, as of [`leaf`].
+pub fn leafs(n: u64) -> Vec<Item> {
+    (0..n).map(leaf).collect()
+}
+
+/// SIP over the hashes in byte vectors.
+#[derive(Clone, Default)]
+pub struct VecSip(DefaultHasher);
+
+impl Hasher for VecSip {
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Vec<u8>> for VecSip {
+    fn hash(&mut self) -> Vec<u8> {
+        self.finish().to_le_bytes().repeat(2)
+    }
+}
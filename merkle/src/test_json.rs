@@ -0,0 +1,79 @@
+#![cfg(test)]
+
+use crate::merkle::MerkleTree;
+use crate::test_xor128::{Item, SIZE, XOR128};
+
+#[test]
+fn test_json() {
+    use crate::proof::Proof;
+
+    for items in 0..18u64 {
+        let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..items);
+        let json = mt.to_json();
+        let t: MerkleTree<Item, XOR128> = MerkleTree::from_json(&json).unwrap();
+        assert_eq!(t.as_slice(), mt.as_slice());
+        assert_eq!((t.leafs(), t.height()), (mt.leafs(), mt.height()));
+
+        for i in 0..mt.leafs() {
+            let p = mt.gen_proof(i);
+            assert_eq!(Proof::<Item>::from_json(&p.to_json()).unwrap(), p);
+        }
+    }
+}
+
+#[test]
+fn test_json_vector() {
+    use crate::proof::Proof;
+
+    let (a, b, root) = (
+        "11111111111111111111111111111111",
+        "22222222222222222222222222222222",
+        "32333333333333333333333333333333",
+    );
+    let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(vec![[0x11; SIZE], [0x22; SIZE]]);
+    assert_eq!(
+        mt.to_json(),
+        r#"{"leafs":2,"height":2,"padding":"HashWithSelf","ordering":"Ordered","root":"0x32333333333333333333333333333333","nodes":["0x11111111111111111111111111111111","0x22222222222222222222222222222222","0x32333333333333333333333333333333"]}"#
+    );
+    assert_eq!(
+        mt.gen_proof(1).to_json(),
+        r#"{"lemma":["0x22222222222222222222222222222222","0x11111111111111111111111111111111","0x32333333333333333333333333333333"],"path":[false],"index":1,"depth":1,"ordering":"Ordered"}"#
+    );
+
+    // hand written, with and without the prefix
+    let tree = |a: &str, b: &str, root: &str| {
+        let nodes = format!(r#"["{}","{}","{}"]"#, a, b, root);
+        let layout = r#""leafs":2,"height":2"#;
+        format!(r#"{{{},"root":"{}","nodes":{}}}"#, layout, root, nodes)
+    };
+    let parse = |s: &str| MerkleTree::<Item, XOR128>::from_json(s).map(|t| t.root());
+    let error = |s: &str| parse(s).unwrap_err().to_string();
+    let prefixed = |h: &str| format!("0x{}", h);
+    assert_eq!(parse(&tree(a, b, &prefixed(root))).unwrap(), mt.root());
+    assert_eq!(parse(&tree(&prefixed(a), b, root)).unwrap(), mt.root());
+
+    assert!(error(&tree(&a[1..], b, root)).starts_with("odd number of hex digits"));
+    let short = "invalid length 15, expected hex string of 16 bytes";
+    assert!(error(&tree(&a[2..], b, root)).starts_with(short));
+    assert!(error(&tree(&a.replace('1', "x"), b, root)).starts_with("invalid hex digit"));
+    let mismatch = tree(a, b, root).replacen(root, a, 1);
+    assert!(error(&mismatch).starts_with("root does not match"));
+
+    let proof = |lemma: &str, path: &str| {
+        let s = format!(r#"{{"lemma":{},"path":{}}}"#, lemma, path);
+        Proof::<Item>::from_json(&s)
+    };
+    assert!(proof(&format!(r#"["{}"]"#, a), "[]").is_err());
+    assert!(proof(&format!(r#"["{}","{}"]"#, a, b), "[true]").is_err());
+    assert!(proof(&format!(r#"["{}","{}"]"#, a, b), "[]").is_ok());
+
+    let lemma = format!(r#"["{}","{}","{}"]"#, a, b, root);
+    let positioned = |position: &str| {
+        let s = format!(r#"{{"lemma":{},"path":[false],{}}}"#, lemma, position);
+        Proof::<Item>::from_json(&s)
+    };
+    assert_eq!(positioned(r#""index":4,"depth":3"#).unwrap().index(), 4);
+    assert_eq!(positioned(r#""depth":3"#).unwrap().depth(), 3);
+    assert!(positioned(r#""index":8,"depth":3"#).is_err());
+    assert!(positioned(r#""index":0,"depth":0"#).is_err());
+}
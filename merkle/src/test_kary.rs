@@ -0,0 +1,87 @@
+#![cfg(test)]
+
+use crate::hash::Algorithm;
+use crate::merkle::{MerkleError, MerkleTree};
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+use std::iter::FromIterator;
+
+#[test]
+fn test_kary_tree() {
+    use crate::kary::KaryMerkleTree;
+
+    // arity 2 is the binary tree
+    for n in 0..20u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_iter((0..n).map(Item));
+        let kt: KaryMerkleTree<Item, DefaultHasher> = KaryMerkleTree::new((0..n).map(Item), 2);
+        assert_eq!(kt.root(), mt.root());
+        assert_eq!(kt.height(), mt.height());
+
+        for i in 0..n as usize {
+            let (p, q) = (mt.gen_proof(i), kt.gen_proof(i));
+            assert_eq!(q.lemma(), p.lemma());
+            let path: Vec<usize> = p.path().iter().map(|&left| (!left) as usize).collect();
+            assert_eq!(q.path(), &path[..]);
+            assert!(q.validate::<DefaultHasher>());
+        }
+    }
+
+    // 4 leafs of 16 under the root, 9 leafs fill up their levels
+    let mut a = DefaultHasher::default();
+    let leafs: Vec<Item> = (0..9u64)
+        .map(|x| {
+            a.reset();
+            a.leaf(Item(x))
+        })
+        .collect();
+    let node = |a: &mut DefaultHasher, children: &[Item]| {
+        a.reset();
+        a.multi_node(children)
+    };
+    let n0 = node(&mut a, &leafs[0..4]);
+    let n1 = node(&mut a, &leafs[4..8]);
+    let n2 = node(&mut a, &[leafs[8]; 4]);
+    let root = node(&mut a, &[n0, n1, n2, n2]);
+
+    let kt: KaryMerkleTree<Item, DefaultHasher> = KaryMerkleTree::new((0..9).map(Item), 4);
+    assert_eq!(kt.root(), root);
+    assert_eq!(kt.height(), 3);
+
+    let p = kt.gen_proof(5);
+    assert_eq!(
+        p.lemma(),
+        [leafs[5], leafs[4], leafs[6], leafs[7], n0, n2, n2, root]
+    );
+    assert_eq!(p.path(), [1, 1]);
+    for i in 0..kt.leafs() {
+        assert!(kt.gen_proof(i).validate_against::<DefaultHasher>(&root));
+    }
+
+    // positions must match the order of the children
+    let swapped = crate::kary::KaryProof::new(p.lemma().to_vec(), vec![2, 1], 4);
+    assert!(!swapped.validate::<DefaultHasher>());
+    let short = crate::kary::KaryProof::new(p.lemma()[1..].to_vec(), vec![1, 1], 4);
+    assert!(!short.validate::<DefaultHasher>());
+
+    // other arities over the leaf counts not a power of them
+    for arity in 3..9 {
+        for n in 0..30u64 {
+            let kt: KaryMerkleTree<Item, DefaultHasher> =
+                KaryMerkleTree::new((0..n).map(Item), arity);
+            for i in 0..kt.leafs() {
+                let p = kt.gen_proof(i);
+                assert_eq!(p.lemma().len(), (kt.height() - 1) * (arity - 1) + 2);
+                assert!(p.validate_against::<DefaultHasher>(&kt.root()));
+            }
+        }
+    }
+
+    let kt: Result<KaryMerkleTree<Item, DefaultHasher>, _> = KaryMerkleTree::try_new(None, 1);
+    assert_eq!(kt.err(), Some(MerkleError::InvalidArity { arity: 1 }));
+    let kt: KaryMerkleTree<Item, DefaultHasher> = KaryMerkleTree::new(None, 4);
+    assert_eq!(kt.root(), DefaultHasher::default().hash());
+    assert_eq!(
+        kt.try_gen_proof(0),
+        Err(MerkleError::IndexOutOfBounds { index: 0, leafs: 0 })
+    );
+}
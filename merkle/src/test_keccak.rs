@@ -0,0 +1,152 @@
+#![cfg(test)]
+
+use crate::fixed_hash::FixedHash;
+use crate::hash::Algorithm;
+use crate::keccak::{Keccak256Algorithm, Keccak256Hash, Keccak256LeafAlgorithm};
+use crate::merkle::MerkleTree;
+use crate::proof::Proof;
+use std::hash::Hasher;
+
+fn unhex(s: &str) -> Keccak256Hash {
+    s.parse().unwrap()
+}
+
+/// Leafs `keccak256(abi.encode(uint256(i)))` for `i` in `0..5`.
+const LEAFS: [&str; 5] = [
+    "290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563",
+    "b10e2d527612073b26eecdfd717e6a320cf44b4afac2b0732d9fcbe2b7fa0cf6",
+    "405787fa12a823e0f2b7631cc41b3ba8828b3321ca811111fa75cd3aa3bb5ace",
+    "c2575a0e9e593c00f959f8c92f12db2869c3395a3b0502d05e2516446f71f85b",
+    "8a35acfbc15ff81a39ae7d344fd709f28e8600b4aa8c65c6b64bfe7fe36bd19b",
+];
+
+#[test]
+fn test_keccak_algorithm() {
+    let mut a = Keccak256Algorithm::<false>::new();
+    assert_eq!(
+        a.hash().to_string(),
+        "c5d2460186f7233c927e7db2dcc703c0e500b653ca82273b7bfad8045d85a470"
+    );
+
+    a.write(b"abc");
+    assert_eq!(a.finish(), 0x4e03657aea45a94f);
+    assert_eq!(
+        a.hash().to_string(),
+        "4e03657aea45a94fc7d47ba826c8d667c0d1e6e33a64a036ec44f58fa12d6c45"
+    );
+
+    // leafs as abi.encode(uint256(i)) hashed on chain
+    for (i, leaf) in LEAFS.iter().enumerate() {
+        let mut x = FixedHash::default();
+        x.0[31] = i as u8;
+        a.reset();
+        assert_eq!(Keccak256LeafAlgorithm::new().leaf(x), unhex(leaf));
+        assert_eq!(a.leaf(unhex(leaf)), unhex(leaf));
+    }
+}
+
+#[test]
+fn test_keccak_roots() {
+    // keccak256(abi.encodePacked(left, right)) up the tree, duplicating the
+    // last node of the odd levels, computed with an independent Keccak
+    let roots = [
+        "290decd9548b62a8d60345a988386fc84ba6bc95484008f6362f93160ef3e563",
+        "891370df4fadf33f50e41f7c8a791e680c0655695ea3404385a909c8f5e13fb4",
+        "dd418117b0a88f05caebb5b120373d5b38ff50557976adc7f787b53db6b4e74c",
+        "2c24f92f65cdd0fde0264c1f41fadf17cb35cdffeaca769e5673e72b072be707",
+        "1ec4076537f679fbe330b26b04494e566094dfee6b2654e6dcaa4bc1cf01fa64",
+    ];
+    for (n, root) in roots.iter().enumerate() {
+        let mt: MerkleTree<Keccak256Hash, Keccak256Algorithm> =
+            MerkleTree::new(LEAFS[..n + 1].iter().map(|l| unhex(l)));
+        assert_eq!(mt.root().to_string(), *root, "tree of {} leafs", n + 1);
+    }
+
+    // same tree hashing the leafs itself
+    let mt: MerkleTree<Keccak256Hash, Keccak256LeafAlgorithm> = MerkleTree::new((0..4).map(|i| {
+        let mut x = FixedHash::default();
+        x.0[31] = i;
+        x
+    }));
+    assert_eq!(mt.root().to_string(), roots[3]);
+}
+
+#[test]
+fn test_keccak_proof() {
+    let mt: MerkleTree<Keccak256Hash, Keccak256Algorithm> =
+        MerkleTree::new(LEAFS[..4].iter().map(|l| unhex(l)));
+
+    // proof of the leaf 2 as bytes32[] verified on chain, left to right
+    let p = mt.gen_proof(2);
+    assert_eq!(
+        p.lemma().iter().map(|h| h.to_string()).collect::<Vec<_>>(),
+        [
+            LEAFS[2],
+            LEAFS[3],
+            "891370df4fadf33f50e41f7c8a791e680c0655695ea3404385a909c8f5e13fb4",
+            "2c24f92f65cdd0fde0264c1f41fadf17cb35cdffeaca769e5673e72b072be707",
+        ]
+    );
+    assert_eq!(p.path(), [true, false]);
+    assert!(p.validate::<Keccak256Algorithm>());
+}
+
+#[test]
+fn test_keccak_sorted_pairs() {
+    use crate::merkle::PaddingStrategy::PromoteOdd;
+    use crate::merkle::PairOrdering::Sorted;
+
+    // root and proof of the leaf 3 of the tree of merkletreejs
+    // `new MerkleTree(LEAFS, keccak256, { sortPairs: true })`, which
+    // OpenZeppelin `MerkleProof.verify` takes, computed with an independent
+    // Keccak
+    let root = "c96cb43f0149b257acfbc3ebf2822b945c02a7874dccbd55312bd44589068e0a";
+    let proof = [
+        LEAFS[2],
+        "891370df4fadf33f50e41f7c8a791e680c0655695ea3404385a909c8f5e13fb4",
+        LEAFS[4],
+    ];
+
+    // merkletreejs promotes the last node of the odd levels
+    let mut mt: MerkleTree<Keccak256Hash, Keccak256Algorithm> = MerkleTree::empty()
+        .with_padding(PromoteOdd)
+        .with_pair_ordering(Sorted);
+    mt.extend(LEAFS.iter().map(|l| unhex(l)));
+    assert_eq!(mt.root().to_string(), root);
+
+    let p = mt.gen_proof(3);
+    let lemma = p.lemma();
+    assert_eq!(lemma[0], unhex(LEAFS[3]));
+    assert_eq!(
+        lemma[1..lemma.len() - 1]
+            .iter()
+            .map(|h| h.to_string())
+            .collect::<Vec<_>>(),
+        proof
+    );
+    assert!(p.validate_against::<Keccak256Algorithm>(&unhex(root)));
+
+    // tree decoded is of the same root and proofs, as the contract checks
+    #[cfg(feature = "std")]
+    {
+        let mut bytes = Vec::new();
+        mt.encode(&mut bytes).unwrap();
+        let decoded: MerkleTree<Keccak256Hash, Keccak256Algorithm> =
+            MerkleTree::decode(&bytes[..], Keccak256Algorithm::default()).unwrap();
+        assert_eq!(decoded.root().to_string(), root);
+        assert_eq!(decoded.gen_proof(3), p);
+    }
+
+    // ordering is encoded into the proofs
+    let decoded: Proof<Keccak256Hash> = Proof::from_bytes(&p.to_bytes()).unwrap();
+    assert_eq!(decoded, p);
+
+    // bytes32[] proof as the contract takes it, of no directions
+    let mut lemma = vec![unhex(LEAFS[3])];
+    lemma.extend(proof.iter().map(|h| unhex(h)));
+    lemma.push(unhex(root));
+    let p = Proof::new(lemma, vec![false; 3]);
+    assert!(!p.validate::<Keccak256Algorithm>());
+    let p = p.with_pair_ordering(Sorted);
+    assert!(p.validate::<Keccak256Algorithm>());
+}
@@ -0,0 +1,34 @@
+#![cfg(test)]
+
+use crate::lazy::LazyMerkleTree;
+use crate::merkle::MerkleTree;
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_lazy_tree() {
+    for items in 0..18u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        let lt: LazyMerkleTree<Item, DefaultHasher> = LazyMerkleTree::from_data(0..items);
+        assert_eq!(lt.leafs(), mt.leafs());
+        assert_eq!(lt.height(), mt.height());
+
+        // proofs first, so that nodes are computed in a random order
+        for i in (0..mt.leafs()).rev() {
+            assert_eq!(lt.leaf(i), mt.leaf(i));
+            assert_eq!(lt.gen_proof(i), mt.gen_proof(i));
+        }
+        assert_eq!(lt.try_gen_proof(mt.leafs()), mt.try_gen_proof(mt.leafs()));
+        assert_eq!(lt.root(), mt.root());
+
+        for level in 0..mt.height() + 1 {
+            for index in 0..mt.leafs() + 1 {
+                assert_eq!(lt.node(level, index).as_ref(), mt.node(level, index));
+            }
+        }
+
+        let lt: LazyMerkleTree<Item, DefaultHasher> = LazyMerkleTree::from_data(0..items);
+        assert_eq!(lt.root(), mt.root());
+        assert_eq!(lt.into_tree().as_slice(), mt.as_slice());
+    }
+}
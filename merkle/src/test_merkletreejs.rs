@@ -0,0 +1,222 @@
+#![cfg(test)]
+
+use crate::hash::Algorithm;
+use crate::merkle::MerkleTree;
+use crate::merkletreejs::{JsCompatAlgorithm, JsCompatOptions, JsPosition, JsProofNode};
+use crate::sha256::{Sha256Algorithm, Sha256Hash};
+use std::hash::Hasher;
+
+type JsTree = MerkleTree<Sha256Hash, JsCompatAlgorithm<Sha256Algorithm>>;
+
+/// Leaves `SHA256(x)` for `x` in `a b c d e`.
+fn leaves() -> Vec<Sha256Hash> {
+    [b"a", b"b", b"c", b"d", b"e"]
+        .iter()
+        .map(|x| {
+            let mut a = Sha256Algorithm::new();
+            a.write(&x[..]);
+            a.hash()
+        })
+        .collect()
+}
+
+fn options(hash_leaves: bool, sort_pairs: bool, duplicate_odd: bool) -> JsCompatOptions {
+    JsCompatOptions {
+        hash_leaves,
+        sort_pairs,
+        duplicate_odd,
+    }
+}
+
+fn js_proof(steps: &[(&str, &str)]) -> Vec<JsProofNode<Sha256Hash>> {
+    steps
+        .iter()
+        .map(|&(position, data)| JsProofNode {
+            position: match position {
+                "left" => JsPosition::Left,
+                _ => JsPosition::Right,
+            },
+            data: data.parse().unwrap(),
+        })
+        .collect()
+}
+
+/// Fixture of `new MerkleTree(leaves, SHA256, options)` of merkletreejs:
+/// the root, and `getProof` of the leaves 1 and 4, computed with a port of
+/// `createHashes`, `getProof` and `verify` of merkletreejs.
+struct Fixture {
+    options: JsCompatOptions,
+    root: &'static str,
+    proof_1: [(&'static str, &'static str); 3],
+    proof_4: &'static [(&'static str, &'static str)],
+}
+
+const A: &str = "ca978112ca1bbdcafac231b39a23dc4da786eff8147c4e72b9807785afee48bb";
+const E: &str = "3f79bb7b435b05321651daefd374cdc681dc06faa65e374e38337b88ca046dea";
+const AB: &str = "bffe0b34dba16bc6fac17c08bac55d676cded5a4ade41fe2c9924a5dde8f3e5b";
+const ABCD: &str = "14ede5e8e97ad9372327728f5099b95604a39593cac3bd38a343ad76205213e7";
+
+fn fixtures() -> [Fixture; 5] {
+    [
+        Fixture {
+            options: options(false, false, false),
+            root: "d71f8983ad4ee170f8129f1ebcdd7440be7798d8e1c80420bf11f1eced610dba",
+            proof_1: [("left", A), ("right", AB), ("right", E)],
+            proof_4: &[("left", ABCD)],
+        },
+        Fixture {
+            options: options(true, false, false),
+            root: "1a15a60e1ff5155bb41ffefba1fab07cc1aa19a555138024617c792cf454d88f",
+            proof_1: [
+                (
+                    "left",
+                    "bf5d3affb73efd2ec6c36ad3112dd933efed63c4e1cbffcfa88e2759c144f2d8",
+                ),
+                (
+                    "right",
+                    "ea0e26f7cde803d8090cf15c25d7842a1adc46a390405bcd7bb158258774e270",
+                ),
+                (
+                    "right",
+                    "88f78c1046639b63b4ed955562e77f7f629cbaf4b1d4be2e2ced0b018d1e975a",
+                ),
+            ],
+            proof_4: &[(
+                "left",
+                "b5de7828c4a3f5dc8934dd99a63930bd87cc3e70a32d0242c505b19758695240",
+            )],
+        },
+        Fixture {
+            options: options(false, true, false),
+            root: "930747c3ad2cac9fdc0cc025207d282e4f5f055169d11aaa320ddb0d133e2ef8",
+            proof_1: [
+                ("left", A),
+                (
+                    "right",
+                    "800e03ddb2432933692401d1631850c0af91953fd9c8f3874488c0541dfcf413",
+                ),
+                ("right", E),
+            ],
+            proof_4: &[(
+                "left",
+                "4c6aae040ffada3d02598207b8485fcbe161c03f4cb3f660e4d341e7496ff3b2",
+            )],
+        },
+        // getProof of merkletreejs leaves out the steps of the levels where
+        // the last node is hashed with itself, which verify needs, these
+        // proofs of the last leaf have them
+        Fixture {
+            options: options(false, false, true),
+            root: "dd14d0ba516bb654a3052b76f051db026f4e322d0be081468fab99440f9e7305",
+            proof_1: [
+                ("left", A),
+                ("right", AB),
+                (
+                    "right",
+                    "de913ac41aae6129f7358dadea47a987a81509a6fb267b01f0508280f8dd5b46",
+                ),
+            ],
+            proof_4: &[
+                ("right", E),
+                (
+                    "right",
+                    "75de222d8adebd767f99a5fe35a5f3f58dbfa3d51ec28b54e9da4225ec8f170d",
+                ),
+                ("left", ABCD),
+            ],
+        },
+        Fixture {
+            options: options(true, true, true),
+            root: "fe6d1a83ed5b116f4e61ac59d42668258e169e5998e3986e189a0fc72cc40487",
+            proof_1: [
+                (
+                    "left",
+                    "bf5d3affb73efd2ec6c36ad3112dd933efed63c4e1cbffcfa88e2759c144f2d8",
+                ),
+                (
+                    "right",
+                    "ea0e26f7cde803d8090cf15c25d7842a1adc46a390405bcd7bb158258774e270",
+                ),
+                (
+                    "right",
+                    "1ba635111fe5a76a99789e29cfc38fc2c30e4412867d55b5d0df934437fc9da9",
+                ),
+            ],
+            proof_4: &[
+                (
+                    "right",
+                    "88f78c1046639b63b4ed955562e77f7f629cbaf4b1d4be2e2ced0b018d1e975a",
+                ),
+                (
+                    "right",
+                    "1e4bb31c01395efb7f977899e86f09b5165ad660591774df5de263f8c6713b81",
+                ),
+                (
+                    "left",
+                    "5f934c91e9d5e70bccd99cbfcdc5c1c252f4e717e6bda7b599c0d86e4ce1e293",
+                ),
+            ],
+        },
+    ]
+}
+
+#[test]
+fn test_merkletreejs_fixtures() {
+    for f in fixtures() {
+        let mt = JsTree::from_js_leaves(leaves(), f.options);
+        assert_eq!(mt.root().to_string(), f.root, "{:?}", f.options);
+
+        let root: Sha256Hash = f.root.parse().unwrap();
+        for (i, expected) in [(1, &f.proof_1[..]), (4, f.proof_4)] {
+            let p = mt.gen_proof(i);
+            assert!(p.validate_against::<JsCompatAlgorithm<Sha256Algorithm>>(&root));
+
+            let proof = p.to_js_proof();
+            assert_eq!(proof, js_proof(expected), "{:?} leaf {}", f.options, i);
+
+            let verify = |proof: &[_], leaf: &Sha256Hash| {
+                f.options.verify::<_, Sha256Algorithm>(proof, leaf, &root)
+            };
+            let leaf = f.options.leaf::<_, Sha256Algorithm>(leaves()[i]);
+            assert_eq!(p.item(), leaf);
+            assert!(verify(&proof, &leaf));
+            assert!(!verify(&proof, &leaves()[0]));
+            assert!(!verify(&proof[1..], &leaf));
+        }
+    }
+}
+
+#[test]
+fn test_merkletreejs_options() {
+    assert_eq!(JsCompatOptions::default(), options(false, false, false));
+
+    // proof of the sorted tree does not verify as of the positions
+    let sorted = options(false, true, false);
+    let mt = JsTree::from_js_leaves(leaves(), sorted);
+    let proof = mt.gen_proof(1).to_js_proof();
+    let leaf = leaves()[1];
+    assert!(sorted.verify::<_, Sha256Algorithm>(&proof, &leaf, &mt.root()));
+    assert!(!JsCompatOptions::default().verify::<_, Sha256Algorithm>(&proof, &leaf, &mt.root()));
+
+    // tree of one leaf is the leaf
+    let mt = JsTree::from_js_leaves(leaves().into_iter().take(1), JsCompatOptions::default());
+    assert_eq!(mt.root(), leaves()[0]);
+    assert!(mt.gen_proof(0).to_js_proof().is_empty());
+}
+
+#[cfg(feature = "json")]
+#[test]
+fn test_merkletreejs_json() {
+    let mt = JsTree::from_js_leaves(leaves(), JsCompatOptions::default());
+    assert_eq!(
+        mt.gen_proof(4).to_js_json(),
+        format!(r#"[{{"position":"left","data":"0x{}"}}]"#, ABCD)
+    );
+    assert_eq!(
+        mt.gen_proof(1).to_js_json(),
+        format!(
+            r#"[{{"position":"left","data":"0x{}"}},{{"position":"right","data":"0x{}"}},{{"position":"right","data":"0x{}"}}]"#,
+            A, AB, E
+        )
+    );
+}
@@ -0,0 +1,63 @@
+#![cfg(test)]
+
+use crate::merkle::MerkleTree;
+use crate::test_item::{leafs, Item};
+use std::collections::hash_map::DefaultHasher;
+
+impl crate::mmap::Element for Item {
+    const BYTE_LEN: usize = 8;
+
+    fn from_slice(bytes: &[u8]) -> Self {
+        let mut x = [0; 8];
+        x.copy_from_slice(bytes);
+        Item(u64::from_le_bytes(x))
+    }
+
+    fn copy_to_slice(&self, bytes: &mut [u8]) {
+        bytes.copy_from_slice(&self.0.to_le_bytes());
+    }
+}
+
+#[test]
+fn test_mmap_store() {
+    use crate::mmap::MmapStore;
+
+    let path = std::env::temp_dir().join(format!("merkle_light_mmap_{}.bin", std::process::id()));
+
+    for items in [0, 1, 2, 5, 8, 100, 1025u64] {
+        let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        let hashes: Vec<Item> = expected.leaves().cloned().collect();
+
+        // file is created too small to hold the tree so that it grows
+        let store = MmapStore::create(&path, 2).unwrap();
+        let mt: MerkleTree<Item, DefaultHasher, MmapStore<Item>> =
+            MerkleTree::try_from_iter_in(store, leafs(items)).unwrap();
+        let (root, leafs, height) = (mt.root(), mt.leafs(), mt.height());
+        assert_eq!(root, expected.root());
+        drop(mt);
+
+        assert_eq!(
+            std::fs::metadata(&path).unwrap().len(),
+            8 * expected.len() as u64
+        );
+
+        let store = MmapStore::open(&path).unwrap();
+        let mt: MerkleTree<Item, DefaultHasher, MmapStore<Item>> =
+            MerkleTree::from_store(store, leafs, height).unwrap();
+        assert!(mt.verify());
+        assert_eq!(mt.root(), root);
+        for (i, h) in hashes.iter().enumerate() {
+            let p = mt.gen_proof(i);
+            assert!(p.validate::<DefaultHasher>());
+            assert_eq!(p.root(), root);
+            assert_eq!(p.item(), *h);
+        }
+    }
+
+    std::fs::remove_file(&path).unwrap();
+
+    // anonymous store
+    let mt: MerkleTree<Item, DefaultHasher, MmapStore<Item>> = MerkleTree::from_data(0..100u64);
+    let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..100u64);
+    assert_eq!(mt.root(), expected.root());
+}
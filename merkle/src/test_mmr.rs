@@ -0,0 +1,117 @@
+#![cfg(test)]
+
+use crate::hash::Algorithm;
+use crate::merkle::{MerkleError, MerkleTree};
+use crate::store::Store;
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_mmr_layout() {
+    use crate::mmr::{leaf_position, node_height, peak_positions};
+
+    // postorder positions of grin, from 0
+    let heights = [0, 0, 1, 0, 0, 1, 2, 0, 0, 1, 0, 0, 1, 2, 3, 0, 0, 1, 0];
+    for (pos, &height) in heights.iter().enumerate() {
+        assert_eq!(node_height(pos), height, "position {}", pos);
+    }
+    let leafs: Vec<usize> = (0..9).map(leaf_position).collect();
+    assert_eq!(leafs, [0, 1, 3, 4, 7, 8, 10, 11, 15]);
+
+    assert_eq!(peak_positions(0), Some(vec![]));
+    assert_eq!(peak_positions(1), Some(vec![0]));
+    assert_eq!(peak_positions(4), Some(vec![2, 3]));
+    assert_eq!(peak_positions(10), Some(vec![6, 9]));
+    assert_eq!(peak_positions(11), Some(vec![6, 9, 10]));
+    assert_eq!(peak_positions(19), Some(vec![14, 17, 18]));
+    assert_eq!(peak_positions(22), Some(vec![14, 21]));
+    for size in [2, 5, 6, 9, 12, 13, 14, 20, 21] {
+        assert_eq!(peak_positions(size), None, "size {}", size);
+    }
+    assert_eq!(peak_positions(usize::MAX).map(|p| p.len()), Some(1));
+}
+
+#[test]
+fn test_mmr() {
+    use crate::mmr::{leaf_position, Mmr, MmrProof};
+
+    let node = |l: Item, r: Item| DefaultHasher::new().node(l, r);
+    let leaf = |x: u64| DefaultHasher::new().leaf(Item(x));
+
+    let mut mmr: Mmr<Item, DefaultHasher> = Mmr::new();
+    assert!(mmr.is_empty());
+    assert_eq!(mmr.root(), DefaultHasher::new().hash());
+    let out = MerkleError::PositionOutOfBounds {
+        position: 0,
+        size: 0,
+    };
+    assert_eq!(mmr.try_gen_proof(0), Err(out));
+
+    // every leaf pushed stays provable as the range grows, at its position
+    let mut pushed: Vec<(usize, Item, Item, MmrProof<Item>)> = Vec::new();
+    for n in 0..70u64 {
+        let position = mmr.push(Item(n));
+        assert_eq!(position, leaf_position(n as usize));
+        assert_eq!(mmr.leafs(), n as usize + 1);
+        let leafs = mmr.leafs();
+        assert_eq!(mmr.len(), 2 * leafs - leafs.count_ones() as usize);
+        assert_eq!(mmr.node(position), Some(leaf(n)));
+
+        let root = mmr.root();
+        pushed.push((position, leaf(n), root, mmr.gen_proof(position)));
+        for (i, (pos, item, old_root, old_proof)) in pushed.iter().enumerate() {
+            let p = mmr.gen_proof(*pos);
+            assert_eq!(p.item(), *item);
+            assert!(p.validate_against::<DefaultHasher>(&root), "leaf {}", i);
+            assert!(old_proof.validate_against::<DefaultHasher>(old_root));
+            if n > i as u64 {
+                assert!(!p.validate_against::<DefaultHasher>(old_root));
+                assert!(!old_proof.validate_against::<DefaultHasher>(&root));
+            }
+        }
+    }
+
+    // power of two leafs is the tree over them, and the rest are bagged
+    let mut mmr: Mmr<Item, DefaultHasher> = Mmr::new();
+    for n in 0..11 {
+        mmr.push(Item(n));
+    }
+    let tree = |r: std::ops::Range<u64>| MerkleTree::<Item, DefaultHasher>::new(r.map(Item)).root();
+    assert_eq!(mmr.node(14), Some(tree(0..8)));
+    assert_eq!(mmr.root(), node(tree(0..8), node(tree(8..10), leaf(10))));
+    assert_eq!(mmr.peaks(), [14, 17, 18]);
+
+    // proofs of the interior nodes, and of the nodes moved or forged fail
+    let root = mmr.root();
+    for pos in 0..mmr.len() {
+        let p = mmr.gen_proof(pos);
+        assert!(p.validate_against::<DefaultHasher>(&root), "node {}", pos);
+        let (siblings, peaks) = (p.siblings(), p.peaks());
+        let with = |item, position, size| {
+            MmrProof::new(item, position, size, siblings.to_vec(), peaks.to_vec())
+        };
+        let valid = |p: MmrProof<Item>| p.validate_against::<DefaultHasher>(&root);
+        assert!(!valid(with(p.item(), pos ^ 1, p.size())));
+        assert!(!valid(with(Item(0), pos, p.size())));
+        assert!(!valid(with(p.item(), pos, 22)));
+    }
+    for size in [0, 20, usize::MAX] {
+        let p = MmrProof::new(leaf(0), 0, size, vec![leaf(1)], vec![root]);
+        assert!(!p.validate_against::<DefaultHasher>(&root));
+    }
+    assert_eq!(
+        mmr.try_gen_proof(19).unwrap_err().to_string(),
+        "position 19 is out of bounds of the merkle mountain range of 19 nodes"
+    );
+
+    // store of the nodes is put back together
+    let reopened = Mmr::<Item, DefaultHasher>::from_store(mmr.clone().into_store()).unwrap();
+    assert_eq!(reopened, mmr);
+    assert_eq!(reopened.leafs(), 11);
+    let mut store = mmr.into_store();
+    store.push(Item(0));
+    assert_eq!(
+        Mmr::<Item, DefaultHasher>::from_store(store),
+        Err(MerkleError::InvalidMmrSize { size: 20 })
+    );
+}
@@ -0,0 +1,213 @@
+#![cfg(test)]
+
+use crate::merkle::{MerkleError, MerkleTree};
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_multiproof() {
+    for items in 1..40u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        let leafs = mt.leafs();
+        let root = mt.root();
+
+        for step in 1..5 {
+            let indices: Vec<usize> = (0..leafs).step_by(step).collect();
+            let mp = mt.gen_multiproof(&indices);
+            assert_eq!(mp.indices(), &indices[..]);
+            assert!(mp.validate::<DefaultHasher>(&root));
+
+            // each node once, and fewer than in the separate proofs
+            let separate: usize = indices
+                .iter()
+                .map(|&i| mt.gen_proof(i).lemma().len() - 2)
+                .sum();
+            assert!(mp.nodes().len() <= separate);
+            if indices.len() > 1 {
+                assert!(mp.nodes().len() < separate);
+            }
+
+            let mut forged = mp.leaves().to_vec();
+            forged[0] = Item(forged[0].0 ^ 1);
+            let forged = crate::multiproof::MultiProof::new(
+                indices.clone(),
+                forged,
+                mp.nodes().to_vec(),
+                leafs,
+                mt.height(),
+            );
+            assert!(!forged.validate::<DefaultHasher>(&root));
+        }
+
+        assert_eq!(
+            mt.try_gen_multiproof(&[0, leafs]).unwrap_err(),
+            MerkleError::IndexOutOfBounds {
+                index: leafs,
+                leafs
+            }
+        );
+    }
+}
+
+#[test]
+fn test_multiproof_siblings() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..8u64);
+    let root = mt.root();
+
+    // adjacent siblings need no node at the leaf level
+    let mp = mt.gen_multiproof(&[3, 2, 3]);
+    assert_eq!(mp.indices(), &[2, 3]);
+    assert_eq!(mp.nodes().len(), 2);
+    assert!(mp.validate::<DefaultHasher>(&root));
+
+    let mp = mt.gen_multiproof(&[0, 1, 2, 3]);
+    assert_eq!(mp.nodes().len(), 1);
+    assert!(mp.validate::<DefaultHasher>(&root));
+
+    let mp = mt.gen_multiproof(&(0..8).collect::<Vec<_>>());
+    assert!(mp.nodes().is_empty());
+    assert!(mp.validate::<DefaultHasher>(&root));
+
+    // last leaf of an odd level is paired with its duplicate
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+    let mp = mt.gen_multiproof(&[4]);
+    assert_eq!(mp.nodes().len(), 1);
+    assert!(mp.validate::<DefaultHasher>(&mt.root()));
+
+    assert!(!mt.gen_multiproof(&[]).validate::<DefaultHasher>(&mt.root()));
+}
+
+#[test]
+fn test_verify_multiproof() {
+    use crate::multiproof::verify_multiproof;
+
+    for items in 1..40u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        let leafs = mt.leafs();
+        let root = mt.root();
+        let mut a = DefaultHasher::default();
+
+        for step in 1..5 {
+            let indices: Vec<usize> = (0..leafs).step_by(step).collect();
+            let mp = mt.gen_multiproof(&indices);
+            let leaves: Vec<(usize, Item)> = indices.iter().map(|&i| (i, mt[i])).collect();
+            let nodes = mp.nodes();
+            assert!(verify_multiproof(&root, &leaves, nodes, leafs, &mut a));
+
+            // nodes left over, or missing
+            let mut more = nodes.to_vec();
+            more.push(root);
+            assert!(!verify_multiproof(&root, &leaves, &more, leafs, &mut a));
+            if !nodes.is_empty() {
+                let fewer = &nodes[..nodes.len() - 1];
+                assert!(!verify_multiproof(&root, &leaves, fewer, leafs, &mut a));
+
+                let mut forged = nodes.to_vec();
+                forged[0] = Item(forged[0].0 ^ 1);
+                assert!(!verify_multiproof(&root, &leaves, &forged, leafs, &mut a));
+            }
+
+            // leafs out of bounds, unsorted, duplicate or forged
+            let mut out = leaves.clone();
+            out[0].0 = leafs;
+            assert!(!verify_multiproof(&root, &out, nodes, leafs, &mut a));
+            let mut unsorted = leaves.clone();
+            unsorted.reverse();
+            if leaves.len() > 1 {
+                assert!(!verify_multiproof(&root, &unsorted, nodes, leafs, &mut a));
+            }
+            let mut duplicate = leaves.clone();
+            duplicate.insert(0, leaves[0]);
+            assert!(!verify_multiproof(&root, &duplicate, nodes, leafs, &mut a));
+            let mut forged = leaves.clone();
+            forged[0].1 = Item(forged[0].1 .0 ^ 1);
+            assert!(!verify_multiproof(&root, &forged, nodes, leafs, &mut a));
+
+            // tree of other height; of the same, the odd tree is the one
+            // of its last leaf duplicated
+            let other = 2 * leafs + 1;
+            assert!(!verify_multiproof(&root, &leaves, nodes, other, &mut a));
+        }
+
+        assert!(!verify_multiproof(&root, &[], &[], leafs, &mut a));
+        assert!(!verify_multiproof(&root, &[(0, mt[0])], &[], 0, &mut a));
+    }
+}
+
+#[test]
+fn test_multiproof_golden() {
+    use crate::multiproof::{IndexEncoding, MultiProof};
+    use crate::proof::ProofDecodeError;
+    use crate::test_xor128::{Item, XOR128};
+
+    let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..5u64);
+    let mp = mt.gen_multiproof(&[4, 1]);
+    assert_eq!(mp.nodes(), [mt[0], mt[7]]);
+    let hex = |bytes: Vec<u8>| -> String { bytes.iter().map(|b| format!("{:02x}", b)).collect() };
+
+    // leafs 1 and 4, then leaf 0 and node 34 in the depth first order
+    let hashes = [
+        "00010000000000000000000000000000",
+        "00040000000000000000000000000000",
+        "00000000000000000000000000000000",
+        "01000100000000000000000000000000",
+    ];
+
+    // digest length 16, leafs 5, list of 2 indices with gaps 1 and 2
+    let list = ["10", "05", "00", "02", "01", "02"].concat() + &hashes.concat();
+    assert_eq!(hex(mp.encode(IndexEncoding::List)), list);
+    assert_eq!(mp.serialized_size(IndexEncoding::List), list.len() / 2);
+
+    // digest length 16, leafs 5, bitmap 0b10010
+    let bitmap = ["10", "05", "01", "12"].concat() + &hashes.concat();
+    assert_eq!(hex(mp.encode(IndexEncoding::Bitmap)), bitmap);
+    assert_eq!(mp.serialized_size(IndexEncoding::Bitmap), bitmap.len() / 2);
+
+    for encoding in [IndexEncoding::List, IndexEncoding::Bitmap] {
+        let decoded = MultiProof::<Item>::decode(&mp.encode(encoding)).unwrap();
+        assert_eq!(decoded, mp);
+        assert!(decoded.validate::<XOR128>(&mt.root()));
+    }
+
+    let bytes = mp.encode(IndexEncoding::List);
+    let decode = |f: &dyn Fn(&mut Vec<u8>)| {
+        let mut bytes = bytes.clone();
+        f(&mut bytes);
+        MultiProof::<Item>::decode(&bytes).unwrap_err()
+    };
+    // leafs 0 and 3 need the nodes of leafs 1, 2 and of leafs 4 to 7
+    assert_eq!(decode(&|b| b[4] = 0), ProofDecodeError::Truncated);
+    let trailing = decode(&|b| b.extend_from_slice(&[0; 16]));
+    assert_eq!(trailing, ProofDecodeError::TrailingBytes(16));
+    assert_eq!(decode(&|b| b[5] = 3), ProofDecodeError::Indices);
+    assert_eq!(decode(&|b| b[2] = 2), ProofDecodeError::Indices);
+    let truncated = decode(&|b| b.truncate(bytes.len() - 1));
+    assert_eq!(truncated, ProofDecodeError::Truncated);
+    let overlong = decode(&|b| b.splice(1..2, [0x85, 0x00]).for_each(drop));
+    assert_eq!(overlong, ProofDecodeError::Number);
+
+    let bytes = mp.encode(IndexEncoding::Bitmap);
+    let mut padded = bytes.clone();
+    padded[3] |= 0x20;
+    let padded = MultiProof::<Item>::decode(&padded).unwrap_err();
+    assert_eq!(padded, ProofDecodeError::Indices);
+}
+
+#[test]
+fn test_multiproof_encoding() {
+    use crate::multiproof::{IndexEncoding, MultiProof};
+    use crate::test_xor128::{Item, XOR128};
+
+    for items in 1..40u64 {
+        let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..items);
+        for step in 1..5 {
+            let indices: Vec<usize> = (0..mt.leafs()).step_by(step).collect();
+            let mp = mt.gen_multiproof(&indices);
+            for encoding in [IndexEncoding::List, IndexEncoding::Bitmap] {
+                let bytes = mp.encode(encoding);
+                assert_eq!(bytes.len(), mp.serialized_size(encoding));
+                assert_eq!(MultiProof::<Item>::decode(&bytes).unwrap(), mp);
+            }
+        }
+    }
+}
@@ -0,0 +1,140 @@
+#![cfg(test)]
+
+use crate::hash::Algorithm;
+use crate::merkle::MerkleTree;
+use crate::poseidon::{FieldElement, FieldElementError, PoseidonAlgorithm, PoseidonLeafAlgorithm};
+use std::hash::Hasher;
+
+fn fe(s: &str) -> FieldElement {
+    s.parse().unwrap()
+}
+
+/// Modulus of the scalar field of BN254.
+const MODULUS: &str =
+    "21888242871839275222246405745257275088548364400416034343698204186575808495617";
+
+/// `poseidon([1])` of circomlibjs.
+const POSEIDON_1: &str =
+    "18586133768512220936620570745912940619677854269274689475585506675881198879027";
+
+/// `poseidon([1, 2])` of circomlibjs.
+const POSEIDON_1_2: &str =
+    "7853200120776062878684798364095072458815029376092732009249414926327459813530";
+
+/// Roots of the trees of the zero leafs of the heights 1, 2 and 3, up from
+/// `poseidon([0, 0])`, as the zero values of the circomlib trees of the
+/// Tornado Cash and Semaphore circuits.
+const ZEROS: [&str; 3] = [
+    "0",
+    "14744269619966411208579211824598458697587494354926760081771325075741142829156",
+    "7423237065226347324353380772367382631490014989348495481811164164159255474657",
+];
+
+#[test]
+fn test_poseidon_algorithm() {
+    let mut a = PoseidonAlgorithm::<false>::new();
+    let (one, two) = (FieldElement::from_u64(1), FieldElement::from_u64(2));
+    assert_eq!(a.node(one, two), fe(POSEIDON_1_2));
+    assert_eq!(
+        a.node(one, one),
+        fe("217234377348884654691879377518794323857294947151490278790710809376325639809")
+    );
+    assert_eq!(a.leaf(one), one);
+    assert_eq!(PoseidonLeafAlgorithm::new().leaf(one), fe(POSEIDON_1));
+
+    // nothing written hashes to the leaf of no value
+    assert_eq!(a.hash(), FieldElement::default());
+    assert_eq!(a.finish(), 0);
+
+    // bytes chained in chunks of 31 bytes, behind their length
+    let mut b = PoseidonAlgorithm::<false>::new();
+    let mut chain = |len: u64, chunks: &[&[u8]]| {
+        let mut h = FieldElement::from_u64(len);
+        for chunk in chunks {
+            h = b.node(h, FieldElement::from_be_bytes_mod_order(chunk));
+        }
+        h
+    };
+    a.write(&[1]);
+    let h = chain(1, &[&[1]]);
+    assert_eq!(a.hash(), h);
+    let head = u64::from_be_bytes(h.as_bytes()[..8].try_into().unwrap());
+    assert_eq!(a.finish(), head);
+    a.reset();
+    a.write(&[2; 40]);
+    assert_eq!(a.hash(), chain(40, &[&[2; 31], &[2; 9]]));
+    a.reset();
+    assert_eq!(a.hash(), FieldElement::default());
+}
+
+#[test]
+fn test_poseidon_zeros() {
+    for (height, zero) in ZEROS.iter().enumerate() {
+        let leafs = vec![FieldElement::default(); 1 << height];
+        let mt: MerkleTree<FieldElement, PoseidonAlgorithm> = MerkleTree::new(leafs);
+        assert_eq!(mt.root(), fe(zero), "tree of height {}", height + 1);
+    }
+}
+
+#[test]
+fn test_poseidon_tree() {
+    let leafs: Vec<FieldElement> = (1..=5).map(FieldElement::from_u64).collect();
+    let mt: MerkleTree<FieldElement, PoseidonAlgorithm> = MerkleTree::new(leafs.clone());
+    let mut a = PoseidonAlgorithm::<false>::new();
+
+    // odd levels are filled up with the duplicate of their last node
+    let n01 = a.node(leafs[0], leafs[1]);
+    let n23 = a.node(leafs[2], leafs[3]);
+    let n44 = a.node(leafs[4], leafs[4]);
+    assert_eq!(n01, fe(POSEIDON_1_2));
+    let (n03, n47) = (a.node(n01, n23), a.node(n44, n44));
+    assert_eq!(mt.root(), a.node(n03, n47));
+
+    for (i, leaf) in leafs.iter().enumerate() {
+        let p = mt.gen_proof(i);
+        assert_eq!(p.item(), *leaf);
+        assert!(p.validate::<PoseidonAlgorithm>());
+        assert!(p.validate_against::<PoseidonAlgorithm>(&mt.root()));
+    }
+
+    // leafs hashed with Poseidon(1) are the leafs of the default algorithm
+    let hashed: MerkleTree<FieldElement, PoseidonLeafAlgorithm> = MerkleTree::new(leafs.clone());
+    let mut single = PoseidonLeafAlgorithm::new();
+    let prehashed = leafs.iter().map(|&x| single.leaf(x));
+    let expected: MerkleTree<FieldElement, PoseidonAlgorithm> = MerkleTree::new(prehashed);
+    assert_eq!(hashed.root(), expected.root());
+    assert_eq!(hashed[0], fe(POSEIDON_1));
+}
+
+#[test]
+fn test_field_element() {
+    let x = fe("1234");
+    assert_eq!(x, FieldElement::from_u64(1234));
+    assert_eq!(x.to_string(), "1234");
+    let debug = format!("FieldElement(0x{}04d2)", "0".repeat(60));
+    assert_eq!(format!("{:?}", x), debug);
+    assert_eq!(fe("0001234"), x);
+    assert_eq!(fe("0").to_string(), "0");
+    assert!(FieldElement::from_u64(2) < FieldElement::from_u64(256));
+
+    // values of the modulus and above are rejected, below are kept
+    let last = format!("{}6", &MODULUS[..MODULUS.len() - 1]);
+    assert_eq!(fe(&last).to_string(), last);
+    let above = format!("{}0", MODULUS);
+    let invalid = [
+        (MODULUS, FieldElementError::Modulus),
+        (&above, FieldElementError::Modulus),
+        ("", FieldElementError::Decimal),
+        ("12a", FieldElementError::Decimal),
+        ("-1", FieldElementError::Decimal),
+    ];
+    for (s, err) in invalid {
+        assert_eq!(s.parse::<FieldElement>(), Err(err), "{}", s);
+    }
+
+    let bytes = FieldElement::from_be_bytes;
+    assert_eq!(bytes(*fe(&last).as_bytes()), Ok(fe(&last)));
+    assert_eq!(bytes([0xff; 32]), Err(FieldElementError::Modulus));
+    assert_eq!(bytes(*x.as_bytes()), Ok(x));
+    assert_eq!(FieldElement::from(x.to_fr()), x);
+}
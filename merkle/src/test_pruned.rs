@@ -0,0 +1,48 @@
+#![cfg(test)]
+
+use crate::merkle::{MerkleError, MerkleTree};
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_prune() {
+    for items in 1..40u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        let leafs = mt.leafs();
+
+        let indices: Vec<usize> = (0..leafs)
+            .filter(|i| i % 3 == 0 || i + 1 == leafs)
+            .collect();
+        let pt = mt.prune(&indices);
+        assert_eq!(pt.root(), mt.root());
+        assert_eq!((pt.leafs(), pt.height()), (leafs, mt.height()));
+        assert_eq!(pt.retained().collect::<Vec<_>>(), indices);
+        assert!(pt.len() <= indices.len() * mt.height());
+
+        for i in 0..leafs {
+            if indices.contains(&i) {
+                assert_eq!(pt.gen_proof(i), mt.gen_proof(i));
+            } else {
+                assert_eq!(pt.try_gen_proof(i), Err(MerkleError::Pruned { index: i }));
+            }
+        }
+        assert_eq!(
+            pt.try_gen_proof(leafs),
+            Err(MerkleError::IndexOutOfBounds {
+                index: leafs,
+                leafs
+            })
+        );
+        assert_eq!(
+            mt.try_prune(&[0, leafs]).unwrap_err(),
+            MerkleError::IndexOutOfBounds {
+                index: leafs,
+                leafs
+            }
+        );
+
+        let pt = mt.prune(&[]);
+        assert!(pt.is_empty());
+        assert_eq!(pt.root(), mt.root());
+    }
+}
@@ -0,0 +1,59 @@
+#![cfg(test)]
+
+use crate::merkle::{MerkleError, MerkleTree};
+use crate::test_item::Item;
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_range_proof() {
+    for items in 1..40u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        let leafs = mt.leafs();
+        let root = mt.root();
+        let leaves: Vec<Item> = mt.as_slice()[..leafs].to_vec();
+
+        for start in 0..leafs {
+            for end in start + 1..=leafs {
+                let rp = mt.gen_range_proof(start..end);
+                assert_eq!(rp.range(), start..end);
+                assert!(rp.nodes().len() <= 2 * mt.height());
+                assert!(rp.validate::<DefaultHasher>(&leaves[start..end], &root));
+
+                let mut forged = leaves[start..end].to_vec();
+                forged[0] = Item(forged[0].0 ^ 1);
+                assert!(!rp.validate::<DefaultHasher>(&forged, &root));
+                assert!(!rp.validate::<DefaultHasher>(&leaves[start..end - 1], &root));
+            }
+        }
+
+        // full tree needs no siblings
+        assert!(mt.gen_range_proof(0..leafs).nodes().is_empty());
+
+        for range in [0..0, 1..1, 0..leafs + 1, leafs..leafs + 1] {
+            assert_eq!(
+                mt.try_gen_range_proof(range.clone()).unwrap_err(),
+                MerkleError::InvalidRange {
+                    start: range.start,
+                    end: range.end,
+                    leafs
+                }
+            );
+        }
+    }
+}
+
+#[test]
+fn test_range_proof_aligned() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..16u64);
+    let leaves: Vec<Item> = mt.as_slice()[..16].to_vec();
+
+    // run of a whole subtree needs only the siblings above it
+    let rp = mt.gen_range_proof(4..8);
+    assert_eq!(rp.nodes(), &[mt[24], mt[29]]);
+    assert!(rp.validate::<DefaultHasher>(&leaves[4..8], &mt.root()));
+
+    // unaligned run needs the siblings on both of its sides
+    let rp = mt.gen_range_proof(3..9);
+    assert_eq!(rp.nodes().len(), 5);
+    assert!(rp.validate::<DefaultHasher>(&leaves[3..9], &mt.root()));
+}
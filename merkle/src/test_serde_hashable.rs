@@ -0,0 +1,82 @@
+#![cfg(test)]
+
+use crate::hash::{Hashable, Algorithm};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::Hasher;
+
+#[test]
+fn test_serde_hashable() {
+    use crate::serde_hashable::SerdeHashable;
+    use serde::Serialize;
+    use std::collections::{BTreeMap, HashMap};
+
+    #[derive(Serialize, Clone)]
+    enum Kind {
+        User,
+        Admin { level: u8 },
+    }
+
+    #[derive(Serialize, Clone)]
+    struct Account {
+        id: u64,
+        name: String,
+        tags: Vec<String>,
+        limit: Option<u32>,
+        kind: Kind,
+        scores: HashMap<String, i64>,
+    }
+
+    let hash = |x: &Account| {
+        let mut a = DefaultHasher::default();
+        SerdeHashable(x).hash(&mut a);
+        let h = a.hash();
+        a.leaf(h)
+    };
+
+    let base = Account {
+        id: 1,
+        name: String::from("alice"),
+        tags: vec![String::from("a"), String::from("b")],
+        limit: Some(10),
+        kind: Kind::Admin { level: 2 },
+        scores: (0..32).map(|i| (i.to_string(), i)).collect(),
+    };
+
+    // structurally equal values hash the same, whatever the map order
+    let mut equal = base.clone();
+    equal.scores = (0..32).rev().map(|i| (i.to_string(), i)).collect();
+    assert_eq!(hash(&base), hash(&equal));
+
+    let sorted: BTreeMap<_, _> = base.scores.clone().into_iter().collect();
+    let mut a = DefaultHasher::default();
+    SerdeHashable(&base.scores).hash(&mut a);
+    let mut b = DefaultHasher::default();
+    SerdeHashable(&sorted).hash(&mut b);
+    assert_eq!(a.finish(), b.finish());
+
+    let changes: Vec<fn(&mut Account)> = vec![
+        |x| x.id = 2,
+        |x| x.name.push('!'),
+        |x| x.tags.swap(0, 1),
+        |x| x.tags.truncate(1),
+        |x| x.limit = None,
+        |x| x.kind = Kind::User,
+        |x| x.kind = Kind::Admin { level: 3 },
+        |x| *x.scores.get_mut("7").unwrap() += 1,
+        |x| {
+            x.scores.remove("7");
+        },
+    ];
+    for change in changes {
+        let mut changed = base.clone();
+        change(&mut changed);
+        assert_ne!(hash(&base), hash(&changed));
+    }
+
+    // same encoding as the Hashable of the common types
+    let mut a = DefaultHasher::default();
+    SerdeHashable((1u8, "ab", vec![2u16], Some(true))).hash(&mut a);
+    let mut b = DefaultHasher::default();
+    (1u8, "ab", vec![2u16], Some(true)).hash(&mut b);
+    assert_eq!(a.finish(), b.finish());
+}
@@ -0,0 +1,158 @@
+#![cfg(test)]
+
+use crate::fixed_hash::FixedHash;
+use crate::hash::Algorithm;
+use crate::merkle::MerkleTree;
+use crate::sha256::{BitcoinAlgorithm, Sha256Algorithm, Sha256Hash};
+use std::hash::Hasher;
+
+/// Tree over the items `[i; 32]` for `i` in `0..n`.
+fn tree(n: u8) -> MerkleTree<Sha256Hash, Sha256Algorithm> {
+    MerkleTree::new((0..n).map(|i| FixedHash([i; 32])))
+}
+
+#[test]
+fn test_sha256_algorithm() {
+    let mut a = Sha256Algorithm::new();
+    assert_eq!(
+        a.hash().to_string(),
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+
+    a.write(b"abc");
+    assert_eq!(a.finish(), 0xba7816bf8f01cfea);
+    assert_eq!(
+        a.hash().to_string(),
+        "ba7816bf8f01cfea414140de5dae2223b00361a396177a9cb410ff61f20015ad"
+    );
+
+    // hash does not consume the data written
+    assert_eq!(a.hash(), a.hash());
+    a.reset();
+    assert_eq!(a.hash(), Sha256Algorithm::default().hash());
+}
+
+#[test]
+fn test_sha256_roots() {
+    // computed independently with the layout of the tree
+    let sizes = [0, 1, 2, 3, 4, 5, 7, 8];
+    let roots = [
+        "e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855",
+        "7f9c9e31ac8256ca2f258583df262dbc7d6f68f2a03043d5c99a4ae5a7396ce9",
+        "28fb81e496897e0ce886f08602392e9239b65c659041e5202163e58ad898f444",
+        "9aa08d413285ecda667944ba9446d77c1f1712ecf946bfcf74453c731a38e7b7",
+        "fdea52008cdae79fa8bf806261959e23f5e11681646a2fa2bc9b5e56b32030a2",
+        "2d331714d5160948ab3e13bed57544502653b85d90b7d71f98580eeb835b9c06",
+        "2e111e6d4a477ccd5884806ac8c9995089bda351c4e66e88bd64e38b17cab33a",
+        "f907f23f76aa01b755a614d31ef9832909f44638b4590073301e61e6d01f9a1d",
+    ];
+    for (n, root) in sizes.into_iter().zip(roots) {
+        assert_eq!(tree(n).root().to_string(), root, "tree of {} leafs", n);
+    }
+}
+
+#[test]
+fn test_sha256_proof() {
+    let mt = tree(5);
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert!(p.validate::<Sha256Algorithm>());
+        assert_eq!(p.root(), mt.root());
+    }
+}
+
+/// Parses the hash shown in hex, reversed, into the internal byte order.
+fn txid(s: &str) -> Sha256Hash {
+    let mut h = FixedHash::default();
+    for (i, b) in h.0.iter_mut().rev().enumerate() {
+        *b = u8::from_str_radix(&s[2 * i..2 * i + 2], 16).unwrap();
+    }
+    h
+}
+
+fn block(txids: &[&str]) -> MerkleTree<Sha256Hash, BitcoinAlgorithm> {
+    MerkleTree::new(txids.iter().map(|t| txid(t)))
+}
+
+#[test]
+fn test_bitcoin_algorithm() {
+    let mut a = BitcoinAlgorithm::new();
+    a.write(b"hello");
+    assert_eq!(
+        a.hash(),
+        txid("503d8319a48348cdc610a582f7bf754b5833df65038606eb48510790dfc99595")
+    );
+    assert_eq!(a.leaf(FixedHash([7; 32])), FixedHash([7; 32]));
+}
+
+#[test]
+fn test_bitcoin_blocks() {
+    // block 0, the single coinbase is the root
+    let genesis = "4a5e1e4baab89f3a32518a88c31bc87f618f76673e2cc77ab2127b7afdeda33b";
+    assert_eq!(block(&[genesis]).root(), txid(genesis));
+
+    // block 170
+    let mt = block(&[
+        "b1fea52486ce0c62bb442b530a3f0132b826c74e473d1f2c220bfa78111c5082",
+        "f4184fc596403b9d638783cf57adfe4c75c605f6356fbc91338530e9831e9e16",
+    ]);
+    assert_eq!(
+        mt.root(),
+        txid("7dac2c5666815c17a3b36427de37bb9d2e2c5ccec3f8633eb91a4205cb4c10ff")
+    );
+
+    // block 100000
+    let mt = block(&[
+        "8c14f0db3df150123e6f3dbbf30f8b955a8249b62ac1d1ff16284aefa3d06d87",
+        "fff2525b8931402dd09222c50775608f75787bd2b87e56995a7bdd30f79702c4",
+        "6359f0868171b1d194cbee1af2f16ea598ae8fad666d9b012c8ed2b79a236ec4",
+        "e9a66845e05d5abc0ad04ec80f774a7e585c6e8db975962d069a522137b80c1d",
+    ]);
+    assert_eq!(
+        mt.root(),
+        txid("f3e94742aca4b5ef85488dc37c06c3282295ffec960994b2c0d5ac2a25a95766")
+    );
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert!(p.validate::<BitcoinAlgorithm>());
+        assert_eq!(p.root(), mt.root());
+    }
+}
+
+/// [](http://chimera.labs.oreilly.com/books/1234000001802/ch07.html#merkle_trees)
+#[test]
+fn test_bitcoin_odd() {
+    let mut h = [FixedHash([0; 32]); 3];
+    h[0].0[0] = 0x00;
+    h[1].0[0] = 0x11;
+    h[2].0[0] = 0x22;
+
+    let mt: MerkleTree<Sha256Hash, BitcoinAlgorithm> = MerkleTree::new(h);
+    assert_eq!(
+        mt.root(),
+        txid("d47780c084bad3830bcdaf6eace035e4c6cbf646d103795d22104fb105014ba3")
+    );
+    for i in 0..mt.leafs() {
+        assert!(mt.gen_proof(i).validate::<BitcoinAlgorithm>());
+    }
+}
+
+#[cfg(feature = "proptest")]
+proptest::proptest! {
+    #[test]
+    fn test_strategy_proven(
+        (mt, i, p) in crate::strategy::proven::<Sha256Hash, Sha256Algorithm>(1..70)
+    ) {
+        proptest::prop_assert_eq!(p.index(), i);
+        proptest::prop_assert!(p.validate::<Sha256Algorithm>());
+        proptest::prop_assert!(p.validate_against::<Sha256Algorithm>(&mt.root()));
+    }
+
+    #[test]
+    fn test_strategy_corrupted(
+        (mt, _, p) in crate::strategy::corrupted::<Sha256Hash, Sha256Algorithm>(1..70)
+    ) {
+        proptest::prop_assert!(!p.validate::<Sha256Algorithm>());
+        proptest::prop_assert!(!p.validate_against::<Sha256Algorithm>(&mt.root()));
+    }
+}
@@ -1,13 +1,17 @@
 #![cfg(test)]
 
 use crate::hash::{Hashable, Algorithm};
-use crate::merkle::MerkleTree;
+use crate::lazy::LazyMerkleTree;
+use crate::merkle::{tree_layout, MerkleError, MerkleTree};
 use crate::merkle::next_pow2;
 use crate::merkle::log2_pow2;
-use crate::test_item::Item;
+use crate::proof::{ComposeError, Proof};
+use crate::store::Store;
+use crate::test_item::{leaf, leafs, Item, VecSip};
+use std::cmp;
 use std::collections::hash_map::DefaultHasher;
 use std::hash::Hasher;
-use std::iter::FromIterator;
+use std::iter::{self, FromIterator};
 
 impl Algorithm<Item> for DefaultHasher {
     #[inline]
@@ -118,3 +122,2081 @@ fn test_simple_tree() {
         }
     }
 }
+
+/// Iterator with a deliberately vague size hint.
+struct Hinted<I> {
+    iter: I,
+    lower: usize,
+}
+
+impl<I: Iterator> Iterator for Hinted<I> {
+    type Item = I::Item;
+
+    fn next(&mut self) -> Option<I::Item> {
+        self.iter.next()
+    }
+
+    fn size_hint(&self) -> (usize, Option<usize>) {
+        (self.lower, None)
+    }
+}
+
+#[test]
+fn test_from_iter_size_hint() {
+    let leafs = leafs(13);
+
+    for items in 2..leafs.len() {
+        let expected: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::from_iter(leafs[..items].iter().cloned());
+
+        // (0, None)
+        let mut it = leafs[..items].iter().cloned();
+        let mt: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::from_iter(iter::from_fn(|| it.next()));
+        assert_eq!(mt.as_slice(), expected.as_slice());
+        assert_eq!(mt.leafs(), expected.leafs());
+
+        // lower bound is below the actual number of items
+        for lower in [0, 1, items / 2, items] {
+            let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_iter(Hinted {
+                iter: leafs[..items].iter().cloned(),
+                lower,
+            });
+            assert_eq!(mt.as_slice(), expected.as_slice());
+            assert_eq!(mt.height(), expected.height());
+        }
+
+        // size-erased by filter
+        let mt: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::from_iter(leafs.iter().cloned().filter(|x| leafs[..items].contains(x)));
+        assert_eq!(mt.as_slice(), expected.as_slice());
+    }
+}
+
+#[test]
+fn test_empty_tree_root() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(mt.root(), Item(DefaultHasher::new().finish()));
+    assert!(!MerkleTree::<Item, DefaultHasher>::from_iter(vec![Item(1)]).is_empty());
+}
+
+#[test]
+fn test_try_constructors() {
+    for items in 0..9u64 {
+        let data: Vec<u64> = (0..items).collect();
+        let hashes = leafs(items);
+
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(&data);
+        let td: MerkleTree<Item, DefaultHasher> = MerkleTree::try_from_data(&data).unwrap();
+        let ti: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::try_from_iter(hashes.iter().cloned()).unwrap();
+        let tn: MerkleTree<Item, DefaultHasher> = MerkleTree::try_new(hashes.clone()).unwrap();
+
+        for t in [&td, &ti, &tn] {
+            assert_eq!(t.as_slice(), mt.as_slice());
+            assert_eq!(t.leafs(), mt.leafs());
+            assert_eq!(t.height(), mt.height());
+        }
+
+        for i in 0..mt.leafs() {
+            assert_eq!(mt.try_gen_proof(i), Ok(mt.gen_proof(i)));
+        }
+        assert_eq!(mt.try_gen_proof(mt.leafs()), Err(past_leafs(mt.leafs())));
+    }
+}
+
+/// Error of the proof of the leaf just past the `leafs` leafs, of the
+/// padding of the leafs unless there are a power of two of them.
+pub(crate) fn past_leafs(leafs: usize) -> MerkleError {
+    match leafs {
+        l if l.is_power_of_two() || l == 0 => MerkleError::IndexOutOfBounds { index: l, leafs: l },
+        l => MerkleError::PaddingIndex { index: l, leafs: l },
+    }
+}
+
+#[test]
+fn test_gen_proof_padding() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+    let lt: LazyMerkleTree<Item, DefaultHasher> = LazyMerkleTree::from_data(0..5u64);
+    assert!(mt.try_gen_proof(4).unwrap().validate::<DefaultHasher>());
+    for e in [
+        mt.try_gen_proof(5).unwrap_err(),
+        mt.try_gen_proofs(&[0, 5, 6]).unwrap_err(),
+        mt.try_gen_proof_ref(5).unwrap_err(),
+        lt.try_gen_proof(5).unwrap_err(),
+    ] {
+        assert_eq!(e, MerkleError::PaddingIndex { index: 5, leafs: 5 });
+    }
+    // padding is up to the power of two, past the duplicate of the last leaf
+    for index in [6, 7] {
+        assert_eq!(
+            mt.try_gen_proof(index),
+            Err(MerkleError::PaddingIndex { index, leafs: 5 })
+        );
+    }
+    assert_eq!(
+        mt.try_gen_proof(8),
+        Err(MerkleError::IndexOutOfBounds { index: 8, leafs: 5 })
+    );
+    assert_eq!(
+        MerkleError::PaddingIndex { index: 5, leafs: 5 }.to_string(),
+        "leaf index 5 is of the padding of the tree with 5 leafs, not of a leaf"
+    );
+
+    // set of the node padding the leafs is still out of bounds
+    let mut mt = mt;
+    assert_eq!(
+        mt.set_leaf(5, Item(0)),
+        Err(MerkleError::IndexOutOfBounds { index: 5, leafs: 5 })
+    );
+}
+
+#[test]
+fn test_malformed_proofs() {
+    use crate::merkle::PairOrdering;
+    use crate::proof::{Lemma, Path};
+
+    // xorshift, for the reproducible random lengths and hashes
+    let mut seed: u64 = 0x9e37_79b9_7f4a_7c15;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..13u64);
+    let good = mt.gen_proof(6);
+    assert!(good.validate::<DefaultHasher>());
+    for _ in 0..2000 {
+        let lemma: Lemma<Item> = (0..next() % 8).map(|_| Item(next() % 4)).collect();
+        let path: Path = (0..next() % 8).map(|_| next() & 1 == 1).collect();
+        let ordering = match next() & 1 {
+            0 => PairOrdering::Ordered,
+            _ => PairOrdering::Sorted,
+        };
+        let (lemma_len, path_len) = (lemma.len(), path.len());
+        let fits = lemma_len >= 2 && lemma_len - 2 == path_len;
+        let p = Proof::from_parts(lemma.clone(), path.clone()).with_pair_ordering(ordering);
+
+        assert_eq!(
+            Proof::try_new(lemma, path).map(|p| p.with_pair_ordering(ordering)),
+            match fits {
+                true => Ok(p.clone()),
+                false => Err(MerkleError::ProofShape {
+                    lemma: lemma_len,
+                    path: path_len,
+                }),
+            }
+        );
+
+        // malformed proofs of any lengths fail, and never panic
+        let leaf = Item(next() % 4);
+        let valid = [
+            p.validate::<DefaultHasher>(),
+            p.validate_against::<DefaultHasher>(&leaf),
+            p.validate_with(&mut DefaultHasher::new()),
+            p.validate_committed::<DefaultHasher>(&leaf, next() as usize % 16),
+            p.validate_leaf_against::<DefaultHasher>(&leaf, &mt.root()),
+            p.validate_data::<u64, DefaultHasher>(&(next() % 4)),
+            p.validate_ct::<DefaultHasher>(),
+            p.validate_against_ct::<DefaultHasher>(&leaf),
+            p.validate_leaf_against_ct::<DefaultHasher>(&leaf, &mt.root()),
+        ];
+        assert_eq!(valid[6], valid[0]);
+        assert_eq!(valid[7], valid[1]);
+        assert_eq!(valid[8], valid[4]);
+        if !fits {
+            assert_eq!(valid, [false; 9], "lemma {} path {}", lemma_len, path_len);
+            let malformed = Err(ComposeError::Malformed);
+            assert_eq!(p.clone().compose(good.clone()), malformed);
+            assert_eq!(good.clone().compose(p.clone()), malformed);
+        }
+    }
+
+    let empty: Proof<Item> = Proof::default();
+    assert!(!empty.validate::<DefaultHasher>());
+    assert!(!empty.validate_against::<DefaultHasher>(&mt.root()));
+    assert_eq!(
+        Proof::<Item>::try_new(vec![Item(0); 3], vec![true; 2]),
+        Err(MerkleError::ProofShape { lemma: 3, path: 2 })
+    );
+    assert_eq!(
+        MerkleError::ProofShape { lemma: 3, path: 2 }.to_string(),
+        "lemma of 3 hashes does not fit the path of 2 steps"
+    );
+    assert_eq!(
+        Proof::try_new(good.lemma().to_vec(), good.path().to_vec()),
+        Ok(good)
+    );
+}
+
+#[test]
+fn test_validate_ct() {
+    use crate::hash::ct_eq;
+    use crate::merkle::PaddingStrategy::*;
+    use crate::merkle::PairOrdering;
+
+    let bytes = [0x5a; 33];
+    for len in 0..bytes.len() {
+        let a = &bytes[..len];
+        assert!(ct_eq(a, a));
+        assert!(!ct_eq(a, &bytes[..len + 1]));
+        for i in 0..len {
+            let mut b = a.to_vec();
+            b[i] ^= 0x80;
+            assert!(!ct_eq(a, &b));
+        }
+    }
+
+    // constant time validation agrees with the early exiting one, of the
+    // roots differing in any one bit
+    for padding in [HashWithSelf, PromoteOdd, EmptyHashPerLevel, DuplicateLast] {
+        for ordering in [PairOrdering::Ordered, PairOrdering::Sorted] {
+            for items in [1, 2, 5, 8, 13u64] {
+                let mt: MerkleTree<Item, DefaultHasher> =
+                    MerkleTree::try_build((0..items).map(Item), padding, ordering).unwrap();
+                let root = mt.root();
+                for i in 0..mt.leafs() {
+                    let p = mt.gen_proof(i);
+                    let r = mt.gen_proof_ref(i);
+                    assert!(p.validate_ct::<DefaultHasher>());
+                    assert!(p.validate_against_ct::<DefaultHasher>(&root));
+                    assert!(p.validate_against_with_ct(&mut DefaultHasher::new(), &root));
+                    assert!(p.validate_leaf_against_ct::<DefaultHasher>(&p.item(), &root));
+                    assert!(r.validate_against_ct::<DefaultHasher>(&root));
+
+                    for bit in 0..64 {
+                        let other = Item(root.0 ^ 1 << bit);
+                        assert!(!p.validate_against_ct::<DefaultHasher>(&other));
+                        assert!(!r.validate_against_ct::<DefaultHasher>(&other));
+                        assert!(!p.validate_leaf_against_ct::<DefaultHasher>(&other, &root));
+                    }
+
+                    // forged proof of the other leaf
+                    let mut lemma = p.lemma().to_vec();
+                    lemma[0] = Item(lemma[0].0 ^ 1);
+                    let forged = Proof::new(lemma, p.path().to_vec()).with_pair_ordering(ordering);
+                    assert_eq!(
+                        forged.validate_ct::<DefaultHasher>(),
+                        forged.validate::<DefaultHasher>()
+                    );
+                    assert!(!forged.validate_against_ct::<DefaultHasher>(&root));
+                }
+            }
+        }
+    }
+}
+
+#[cfg(debug_assertions)]
+#[test]
+#[should_panic(expected = "lemma of 2 hashes does not fit the path of 1 steps")]
+fn test_malformed_proof_new() {
+    Proof::new(vec![Item(0); 2], vec![true]);
+}
+
+#[test]
+#[should_panic(expected = "leaf index 3 is of the padding")]
+fn test_gen_proof_padding_panics() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..3u64);
+    mt.gen_proof(3);
+}
+
+#[test]
+#[should_panic(expected = "not supported by the merkle trees of PromoteOdd padding")]
+fn test_gen_multiproof_padding_panics() {
+    use crate::merkle::PaddingStrategy::PromoteOdd;
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::new_padded((0..3).map(Item), PromoteOdd);
+    mt.gen_multiproof(&[0]);
+}
+
+#[test]
+#[should_panic(expected = "not supported by the merkle trees of Sorted pair ordering")]
+fn test_prune_ordering_panics() {
+    use crate::merkle::PairOrdering::Sorted;
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::new_ordered((0..3).map(Item), Sorted);
+    mt.prune(&[0]);
+}
+
+#[test]
+fn test_tree_layout_overflow() {
+    assert_eq!(tree_layout(0), Ok((0, 0)));
+    assert_eq!(tree_layout(1), Ok((1, 1)));
+    assert_eq!(tree_layout(5), Ok((15, 4)));
+    assert_eq!(
+        tree_layout(usize::MAX),
+        Err(MerkleError::CapacityOverflow { leafs: usize::MAX })
+    );
+    assert_eq!(
+        tree_layout(usize::MAX / 2 + 2),
+        Err(MerkleError::CapacityOverflow {
+            leafs: usize::MAX / 2 + 2
+        })
+    );
+    assert_eq!(
+        MerkleError::CapacityOverflow { leafs: 7 }.to_string(),
+        format!(
+            "merkle tree over 7 leafs overflows capacity of {}-bit target",
+            usize::BITS
+        )
+    );
+}
+
+#[test]
+fn test_next_pow2() {
+    assert_eq!(next_pow2(0), 1);
+    assert_eq!(next_pow2(1), 1);
+    assert_eq!(next_pow2(3), 4);
+    for k in 1..usize::BITS {
+        let pow = 1usize << k;
+        assert_eq!(next_pow2(pow), pow);
+        assert_eq!(next_pow2(pow / 2 + 1), pow);
+    }
+    assert_eq!(next_pow2(usize::MAX / 2), usize::MAX / 2 + 1);
+    assert_eq!(next_pow2(usize::MAX / 2 + 1), usize::MAX / 2 + 1);
+
+    // largest tree fitting in usize is over a quarter of it
+    let pow = usize::MAX / 4 + 1;
+    assert_eq!(
+        tree_layout(pow),
+        Ok((usize::MAX / 2, usize::BITS as usize - 1))
+    );
+    assert_eq!(tree_layout(pow - 1), tree_layout(pow));
+    assert_eq!(
+        tree_layout(pow + 1),
+        Err(MerkleError::CapacityOverflow { leafs: pow + 1 })
+    );
+}
+
+#[test]
+#[should_panic(expected = "overflows usize")]
+fn test_next_pow2_overflow() {
+    next_pow2(usize::MAX / 2 + 2);
+}
+
+#[cfg(target_pointer_width = "32")]
+#[test]
+fn test_next_pow2_32() {
+    assert_eq!(next_pow2(0x4000_0001), 0x8000_0000);
+    assert_eq!(next_pow2(0x8000_0000), 0x8000_0000);
+    assert_eq!(tree_layout(0x4000_0000), Ok((0x7fff_ffff, 31)));
+    assert_eq!(
+        tree_layout(0x4000_0001),
+        Err(MerkleError::CapacityOverflow { leafs: 0x4000_0001 })
+    );
+}
+
+#[test]
+fn test_from_iter_overflow() {
+    // the tree over that many leafs overflows before any of them is taken
+    assert_eq!(
+        MerkleTree::<Item, DefaultHasher>::try_from_iter(iter::repeat(Item(0))).err(),
+        Some(MerkleError::CapacityOverflow { leafs: usize::MAX })
+    );
+    let leafs = usize::MAX / 4 + 2;
+    assert_eq!(
+        MerkleTree::<Item, DefaultHasher>::try_from_iter(iter::repeat_n(Item(0), leafs)).err(),
+        Some(MerkleError::CapacityOverflow { leafs })
+    );
+}
+
+/// Store of no room, which fails the test if it is asked for the room of
+/// more nodes than fit into memory, rather than allocating it.
+#[derive(Debug, Clone)]
+struct RoomStore(Vec<Item>);
+
+impl Store<Item> for RoomStore {
+    fn new(size: usize) -> Self {
+        assert!(tree_layout(size).is_ok(), "room of {} nodes", size);
+        RoomStore(Vec::new())
+    }
+
+    fn read(&self, i: usize) -> Item {
+        self.0[i]
+    }
+
+    fn write(&mut self, i: usize, value: Item) {
+        self.0[i] = value;
+    }
+
+    fn push(&mut self, value: Item) {
+        self.0.push(value);
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+
+    fn reserve(&mut self, additional: usize) {
+        assert!(tree_layout(additional).is_ok(), "room of {}", additional);
+    }
+}
+
+#[test]
+fn test_constructors_overflow() {
+    use crate::merkle::{PaddingStrategy, PairOrdering};
+
+    type RoomTree = MerkleTree<Item, DefaultHasher, RoomStore>;
+    let leafs = usize::MAX / 4 + 2;
+    let overflow = Some(MerkleError::CapacityOverflow { leafs });
+    let items = || iter::repeat_n(Item(0), leafs);
+
+    // the layout is checked before the room of the leafs is reserved
+    assert_eq!(RoomTree::try_new(items()).err(), overflow);
+    assert_eq!(RoomTree::try_from_iter(items()).err(), overflow);
+    let alg = DefaultHasher::default();
+    assert_eq!(RoomTree::try_new_with(items(), alg).err(), overflow);
+    let padded = RoomTree::try_new_padded(items(), PaddingStrategy::PromoteOdd);
+    assert_eq!(padded.err(), overflow);
+    let ordered = RoomTree::try_new_ordered(items(), PairOrdering::Sorted);
+    assert_eq!(ordered.err(), overflow);
+    let data = iter::repeat_n(0u64, leafs);
+    assert_eq!(RoomTree::try_from_data(data).err(), overflow);
+
+    // the tree of the leafs which fit is built in the store as any other
+    let mt = RoomTree::try_new(iter::repeat_n(Item(0), 5)).unwrap();
+    assert_eq!(mt.leafs(), 5);
+}
+
+#[test]
+#[should_panic(expected = "leafs overflows capacity of")]
+fn test_new_overflow_panics() {
+    MerkleTree::<Item, DefaultHasher, RoomStore>::new(iter::repeat_n(Item(0), usize::MAX));
+}
+
+#[test]
+fn test_push() {
+    let hashes = leafs(33);
+
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    for (i, h) in hashes.iter().enumerate() {
+        mt.push(*h);
+
+        // capacity grows at 2, 3, 5, 9, 17 and 33 leafs
+        let expected: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::new(hashes[..=i].iter().cloned());
+        assert_eq!(mt.as_slice(), expected.as_slice());
+        assert_eq!(mt.leafs(), i + 1);
+        assert_eq!(mt.height(), expected.height());
+        assert_eq!(mt.root(), expected.root());
+    }
+}
+
+thread_local! {
+    static NODES: std::cell::Cell<usize> = const { std::cell::Cell::new(0) };
+}
+
+/// Algorithm of [`DefaultHasher`] which counts the nodes it hashes.
+#[derive(Default)]
+struct NodeCount(DefaultHasher);
+
+impl Hasher for NodeCount {
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Item> for NodeCount {
+    fn hash(&mut self) -> Item {
+        self.0.hash()
+    }
+
+    fn reset(&mut self) {
+        self.0.reset()
+    }
+
+    fn node(&mut self, left: Item, right: Item) -> Item {
+        NODES.with(|n| n.set(n.get() + 1));
+        self.0.node(left, right)
+    }
+}
+
+#[test]
+fn test_push_rehashes_path() {
+    let mut mt: MerkleTree<Item, NodeCount> = MerkleTree::empty();
+    for x in 0..65u64 {
+        let height = mt.height();
+        NODES.with(|n| n.set(0));
+        mt.push(Item(x));
+
+        // one node per level above the leafs, unless the height grows
+        let rehashed = NODES.with(|n| n.get());
+        if mt.height() == height {
+            assert_eq!(rehashed, height - 1, "push of leaf {}", x);
+        }
+        let expected: MerkleTree<Item, NodeCount> = MerkleTree::new((0..=x).map(Item));
+        assert_eq!(mt.as_slice(), expected.as_slice());
+    }
+}
+
+#[test]
+fn test_set_leaf() {
+    // xorshift, to mutate leafs in a reproducible random order
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    for items in 1..18 {
+        let mut hashes = leafs(items);
+        let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::new(hashes.iter().cloned());
+
+        for _ in 0..2 * items {
+            let i = (next() % items) as usize;
+            let h = leaf(next());
+            mt.set_leaf(i, h).unwrap();
+            hashes[i] = h;
+
+            let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::new(hashes.iter().cloned());
+            assert_eq!(mt.as_slice(), expected.as_slice());
+            assert_eq!(mt.root(), expected.root());
+        }
+
+        let root = mt.root();
+        assert_eq!(
+            mt.set_leaf(items as usize, leaf(0)),
+            Err(MerkleError::IndexOutOfBounds {
+                index: items as usize,
+                leafs: items as usize,
+            })
+        );
+        assert_eq!(mt.root(), root);
+    }
+}
+
+#[test]
+fn test_update_batch() {
+    use crate::merkle::PaddingStrategy::*;
+    use crate::merkle::PairOrdering;
+
+    // xorshift, for the reproducible random batches
+    let mut seed: u64 = 0x2545_f491_4f6c_dd1d;
+    let mut next = move || {
+        seed ^= seed << 13;
+        seed ^= seed >> 7;
+        seed ^= seed << 17;
+        seed
+    };
+
+    for padding in [HashWithSelf, PromoteOdd, EmptyHashPerLevel, DuplicateLast] {
+        for ordering in [PairOrdering::Ordered, PairOrdering::Sorted] {
+            for items in 1..34u64 {
+                let mut hashes: Vec<Item> = (0..items).map(Item).collect();
+                let mut mt: MerkleTree<Item, DefaultHasher> =
+                    MerkleTree::try_build(hashes.iter().cloned(), padding, ordering).unwrap();
+
+                for len in [0, 1, 2, 5, 2 * items] {
+                    // with duplicates, the last of which is the leaf
+                    let updates: Vec<(usize, Item)> = (0..len)
+                        .map(|_| ((next() % items) as usize, Item(next())))
+                        .collect();
+                    for &(i, h) in &updates {
+                        hashes[i] = h;
+                    }
+                    mt.update_batch(&updates).unwrap();
+
+                    let expected: MerkleTree<Item, DefaultHasher> =
+                        MerkleTree::try_build(hashes.iter().cloned(), padding, ordering).unwrap();
+                    assert_eq!(mt.as_slice(), expected.as_slice(), "{:?}", padding);
+                }
+
+                let root = mt.root();
+                let updates = [(0, Item(1)), (items as usize, Item(2))];
+                assert_eq!(
+                    mt.update_batch(&updates),
+                    Err(MerkleError::IndexOutOfBounds {
+                        index: items as usize,
+                        leafs: items as usize,
+                    })
+                );
+                assert_eq!(mt.root(), root);
+            }
+        }
+    }
+}
+
+#[test]
+fn test_truncate() {
+    let hashes = leafs(20);
+
+    for items in 0..hashes.len() {
+        let orig: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::new(hashes[..items].iter().cloned());
+
+        // append-then-truncate rolls back to the original tree
+        let mut mt = orig.clone();
+        for h in &hashes[items..] {
+            mt.push(*h);
+        }
+        mt.truncate(items);
+        assert_eq!(mt.as_slice(), orig.as_slice());
+        assert_eq!(mt.leafs(), orig.leafs());
+        assert_eq!(mt.height(), orig.height());
+        assert_eq!(mt.root(), orig.root());
+
+        // truncating beyond the number of leafs has no effect
+        mt.truncate(items + 1);
+        assert_eq!(mt.as_slice(), orig.as_slice());
+    }
+
+    let mut mt: MerkleTree<Item, DefaultHasher> = MerkleTree::new(hashes.iter().cloned());
+    mt.truncate(0);
+    assert!(mt.is_empty());
+    assert_eq!(mt.leafs(), 0);
+    assert_eq!(mt.height(), 0);
+    assert_eq!(mt.root(), MerkleTree::<Item, DefaultHasher>::empty().root());
+}
+
+#[test]
+fn test_extend() {
+    let hashes = leafs(48);
+
+    for items in 0..13 {
+        // the last batch quadruples the number of leafs
+        for batch in [1, 3, 3 * items] {
+            let mut mt: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::new(hashes[..items].iter().cloned());
+            mt.extend(hashes[items..items + batch].iter().cloned());
+
+            let expected: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::new(hashes[..items + batch].iter().cloned());
+            assert_eq!(mt.as_slice(), expected.as_slice());
+            assert_eq!(mt.leafs(), expected.leafs());
+            assert_eq!(mt.height(), expected.height());
+            assert_eq!(mt.root(), expected.root());
+        }
+    }
+}
+
+#[test]
+fn test_merge() {
+    let hashes = leafs(32);
+
+    for items in [1, 2, 4, 8, 16] {
+        let left: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::new(hashes[..items].iter().cloned());
+        let right: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::new(hashes[items..2 * items].iter().cloned());
+        let (left_root, right_root) = (left.root(), right.root());
+
+        let mt = MerkleTree::merge(left, right).unwrap();
+        let expected: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::new(hashes[..2 * items].iter().cloned());
+        assert_eq!(mt.as_slice(), expected.as_slice());
+        assert_eq!(mt.leafs(), 2 * items);
+        assert_eq!(mt.height(), expected.height());
+
+        let top = mt.height() - 2;
+        assert_eq!(mt.node(top, 0), Some(&left_root));
+        assert_eq!(mt.node(top, 1), Some(&right_root));
+
+        // proofs across the seam verify against the merged root
+        for i in 0..2 * items {
+            let p = mt.gen_proof(i);
+            assert!(p.validate::<DefaultHasher>());
+            assert_eq!(p.root(), mt.root());
+        }
+    }
+
+    for (l, r) in [(2, 4), (3, 3), (0, 0)] {
+        let left: MerkleTree<Item, DefaultHasher> = MerkleTree::new(hashes[..l].iter().cloned());
+        let right: MerkleTree<Item, DefaultHasher> = MerkleTree::new(hashes[..r].iter().cloned());
+        assert_eq!(
+            MerkleTree::merge(left, right).unwrap_err(),
+            MerkleError::MergeMismatch { left: l, right: r }
+        );
+    }
+}
+
+#[test]
+fn test_subtree() {
+    let hashes = leafs(17);
+
+    for items in 1..hashes.len() + 1 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::new(hashes[..items].iter().cloned());
+
+        for level in 0..mt.height() {
+            for index in 0..mt.row(level).len() {
+                let first = index << level;
+                let leafs = cmp::min(items - first, 1 << level);
+                let st = match mt.subtree(level, index) {
+                    Some(st) => st,
+                    None => {
+                        // right child is the duplicate of the left one
+                        assert!(level > 0 && leafs <= 1 << (level - 1));
+                        continue;
+                    }
+                };
+
+                assert_eq!(Some(&st.root()), mt.node(level, index));
+                assert_eq!(st.leaves().as_slice(), &mt[first..first + leafs]);
+                assert_eq!(st.height(), level + 1);
+
+                // subtree proof is the lower part of the tree proof
+                for i in 0..leafs {
+                    let p = st.gen_proof(i);
+                    let q = mt.gen_proof(first + i);
+                    assert!(p.validate::<DefaultHasher>());
+                    assert_eq!(p.lemma()[..level + 1], q.lemma()[..level + 1]);
+                    assert_eq!(p.path(), &q.path()[..level]);
+                    assert_eq!(Some(&p.root()), mt.node(level, index));
+                }
+            }
+            assert!(mt.subtree(level, mt.row(level).len()).is_none());
+        }
+        assert!(mt.subtree(mt.height(), 0).is_none());
+        assert_eq!(
+            mt.subtree(mt.height() - 1, 0).unwrap().as_slice(),
+            mt.as_slice()
+        );
+    }
+}
+
+#[test]
+fn test_from_vec() {
+    for items in 0..18u64 {
+        let hashes = leafs(items);
+
+        let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::new(hashes.iter().cloned());
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_vec(hashes.clone());
+        assert_eq!(mt.as_slice(), expected.as_slice());
+        assert_eq!(mt.leafs(), expected.leafs());
+        assert_eq!(mt.height(), expected.height());
+        assert_eq!(mt.root(), expected.root());
+
+        let mt: MerkleTree<Item, DefaultHasher> = hashes.into();
+        assert_eq!(mt.as_slice(), expected.as_slice());
+    }
+}
+
+#[test]
+fn test_into_parts() {
+    for items in 0..18u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        let (data, leafs, height) = mt.clone().into_parts();
+        assert_eq!(data, mt.as_slice());
+        assert_eq!((leafs, height), (mt.leafs(), mt.height()));
+        assert_eq!(mt.clone().into_vec(), data);
+
+        let rt: MerkleTree<Item, DefaultHasher> =
+            MerkleTree::from_parts(data.clone(), leafs, height).unwrap();
+        assert_eq!(rt.as_slice(), mt.as_slice());
+        assert_eq!(rt.root(), mt.root());
+        for i in 0..leafs {
+            assert_eq!(rt.gen_proof(i), mt.gen_proof(i));
+        }
+
+        let invalid = [
+            (data.len() + 1, leafs, height),
+            (data.len(), 2 * leafs + 1, height),
+            (data.len(), leafs, height + 1),
+        ];
+        for &(len, leafs, height) in &invalid {
+            let mut data = data.clone();
+            data.resize(len, Item(0));
+            assert_eq!(
+                MerkleTree::<Item, DefaultHasher>::from_parts(data, leafs, height).unwrap_err(),
+                MerkleError::InvalidLayout { len, leafs, height }
+            );
+        }
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_from_iter_parallel() {
+    for items in [0, 1, 2, 3, 5, 7, 8, 16, 31, 64, 100, 1000, 1024, 1025] {
+        let hashes = leafs(items);
+
+        let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::new(hashes.iter().cloned());
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_iter_parallel(hashes);
+        assert_eq!(mt.as_slice(), expected.as_slice());
+        assert_eq!(mt.leafs(), expected.leafs());
+        assert_eq!(mt.height(), expected.height());
+        assert_eq!(mt.root(), expected.root());
+    }
+}
+
+#[cfg(feature = "rayon")]
+#[test]
+fn test_from_data_parallel() {
+    let data: Vec<Vec<u8>> = (0..3000u32)
+        .map(|i| {
+            (0..4096u32)
+                .map(|j| (i.wrapping_mul(31) ^ j) as u8)
+                .collect()
+        })
+        .collect();
+
+    for items in [0, 1, 3, 1024, 3000] {
+        let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(&data[..items]);
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data_parallel(&data[..items]);
+        assert_eq!(mt.as_slice(), expected.as_slice());
+        assert_eq!(mt.root(), expected.root());
+    }
+}
+
+#[test]
+fn test_verify() {
+    for items in 0..18u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        assert!(mt.verify());
+
+        // every node, including the duplicates filling up odd levels
+        let (data, leafs, height) = mt.into_parts();
+        for i in 0..data.len() {
+            let mut tampered = data.clone();
+            tampered[i] = Item(tampered[i].0 ^ 1);
+            let mt: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::from_parts(tampered, leafs, height).unwrap();
+            assert!(!mt.verify() || i + 1 == data.len() && leafs == 1);
+        }
+    }
+}
+
+#[test]
+fn test_validate() {
+    for items in 2..18u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        assert_eq!(mt.validate(), Ok(()));
+
+        let (data, leafs, height) = mt.clone().into_parts();
+        for i in 0..data.len() {
+            let mut tampered = data.clone();
+            tampered[i] = Item(tampered[i].0 ^ 1 << 7);
+            let t: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::from_parts(tampered, leafs, height).unwrap();
+
+            // leafs are caught by the duplicate or by the parent
+            let expected = match mt.level_of(i).unwrap() {
+                0 if i + 1 >= leafs && leafs & 1 == 1 => (leafs, 0),
+                0 => (mt.parent_index(i).unwrap(), 1),
+                level => (i, level),
+            };
+            assert_eq!(
+                t.validate(),
+                Err(MerkleError::InvalidNode {
+                    index: expected.0,
+                    level: expected.1
+                })
+            );
+        }
+    }
+}
+
+#[test]
+fn test_rehash() {
+    for items in 0..20u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+
+        let same = mt.rehash(|h| *h, DefaultHasher::new());
+        assert_eq!(same.as_slice(), mt.as_slice());
+
+        let flipped = mt.rehash(|h| Item(!h.0), DefaultHasher::new());
+        assert_eq!(flipped.leafs(), mt.leafs());
+        assert_eq!(flipped.height(), mt.height());
+        assert!(flipped.leaves().zip(mt.leaves()).all(|(a, b)| a.0 == !b.0));
+        assert!(flipped.verify());
+        if items > 0 {
+            assert_ne!(flipped.root(), mt.root());
+        }
+    }
+}
+
+#[test]
+fn test_rehash_instance() {
+    use crate::hash::KeyedAlgorithm;
+    use crate::merkle::{PaddingStrategy, PairOrdering};
+
+    // same algorithm type of the other key rehashes the nodes, not the leafs
+    type Keyed = KeyedAlgorithm<DefaultHasher>;
+    let old: MerkleTree<Item, Keyed> = MerkleTree::from_data_with(0..11u64, Keyed::new(b"old"));
+    let mut mt = old.rehash(|h| *h, Keyed::new(b"new"));
+    assert!(mt.leaves().eq(old.leaves()));
+    assert_ne!(mt.root(), old.root());
+    assert!(mt.verify());
+
+    let rebuilt = |leafs: Vec<Item>| -> MerkleTree<Item, Keyed> {
+        let (padding, ordering) = (PaddingStrategy::default(), PairOrdering::default());
+        MerkleTree::from_leafs_with(leafs.into(), Keyed::new(b"new"), padding, ordering).unwrap()
+    };
+    let mut leafs: Vec<Item> = old.leaves().copied().collect();
+    assert_eq!(mt, rebuilt(leafs.clone()));
+    let p = mt.gen_proof(4);
+    assert!(p.validate_with(&mut Keyed::new(b"new")));
+    assert!(!p.validate_with(&mut Keyed::new(b"old")));
+
+    // tree keeps the algorithm for the nodes it hashes later
+    mt.push(Item(7));
+    leafs.push(Keyed::new(b"new").leaf(Item(7)));
+    assert_eq!(mt.root(), rebuilt(leafs).root());
+}
+
+/// Store counting the nodes read from it.
+#[derive(Debug, Clone)]
+struct CountingStore(Vec<Item>, std::cell::Cell<usize>);
+
+impl Store<Item> for CountingStore {
+    fn new(size: usize) -> Self {
+        CountingStore(Vec::with_capacity(size), Default::default())
+    }
+
+    fn read(&self, i: usize) -> Item {
+        self.1.set(self.1.get() + 1);
+        self.0[i]
+    }
+
+    fn write(&mut self, i: usize, value: Item) {
+        self.0[i] = value;
+    }
+
+    fn push(&mut self, value: Item) {
+        self.0.push(value);
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        self.0.truncate(len);
+    }
+}
+
+#[test]
+fn test_diff() {
+    for a in 0..20u64 {
+        for b in 0..20u64 {
+            let ta: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..a);
+            let mut tb: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..b);
+            let changed: Vec<usize> = (0..b as usize).filter(|i| i % 7 == 3).collect();
+            for &i in &changed {
+                tb.set_leaf(i, Item(100 + i as u64)).unwrap();
+            }
+
+            let expected: Vec<usize> = (0..cmp::max(a, b) as usize)
+                .filter(|&i| i >= cmp::min(a, b) as usize || changed.contains(&i))
+                .collect();
+            assert_eq!(ta.diff(&tb), expected);
+            assert_eq!(tb.diff(&ta), expected);
+            assert!(ta.diff(&ta).is_empty());
+        }
+    }
+}
+
+#[test]
+fn test_diff_reads() {
+    let items = 1 << 20;
+    let ta: MerkleTree<Item, DefaultHasher, CountingStore> = MerkleTree::from_data(0..items);
+    let mut tb = ta.clone();
+    for &i in &[17, 1 << 19, items as usize - 1] {
+        tb.set_leaf(i, Item(0)).unwrap();
+    }
+
+    let (store, leafs, height) = ta.into_store();
+    store.1.set(0);
+    let ta = MerkleTree::<Item, DefaultHasher, CountingStore>::from_store(store, leafs, height);
+    let ta = ta.unwrap();
+    assert_eq!(ta.diff(&tb), [17, 1 << 19, items as usize - 1]);
+
+    // tree is read along the paths of the differing leafs alone
+    let (store, _, _) = ta.into_store();
+    assert!(store.1.get() <= 3 * 2 * height, "{} reads", store.1.get());
+}
+
+#[test]
+fn test_to_dot() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+    let mut dot = Vec::new();
+    mt.to_dot(&mut dot, Some(4)).unwrap();
+    let dot = String::from_utf8(dot).unwrap();
+    assert_eq!(
+        dot,
+        r#"digraph merkle {
+    node [shape=box, fontname=monospace];
+    n0 [label="8f1f8d76"];
+    n1 [label="17ebb5ce"];
+    n2 [label="60d53b46"];
+    n3 [label="99a16d89"];
+    n4 [label="83b321a3", color=red];
+    n5 [label="83b321a3", style=dashed, color=blue];
+    n6 [label="83bb3e35"];
+    n7 [label="1ff7eab6"];
+    n8 [label="8c71eeed", color=red];
+    n9 [label="8c71eeed", style=dashed, color=blue];
+    n10 [label="7bdb6b17", color=blue];
+    n11 [label="90b76afb", color=red];
+    n12 [label="e57cce7d", style=bold, color=red];
+    n6 -> n0;
+    n6 -> n1;
+    n7 -> n2;
+    n7 -> n3;
+    n8 -> n4;
+    n8 -> n5;
+    n10 -> n6;
+    n10 -> n7;
+    n11 -> n8;
+    n11 -> n9;
+    n12 -> n10;
+    n12 -> n11;
+}
+"#
+    );
+
+    let mut plain = Vec::new();
+    mt.to_dot(&mut plain, None).unwrap();
+    let plain = String::from_utf8(plain).unwrap();
+    let uncolored = dot.replace(", color=red", "").replace(", color=blue", "");
+    assert_eq!(plain, uncolored);
+}
+
+#[test]
+fn test_pretty() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..3u64);
+    assert_eq!(
+        mt.pretty(),
+        "        e7f0\n   83bb      319e\n8f1f 17eb 60d5 ....\n"
+    );
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..6u64);
+    let expected = [
+        "                  ef9c",
+        "        7bdb                54cb",
+        "   83bb      1ff7      c366      ....",
+        "8f1f 17eb 60d5 99a1 83b3 752f",
+    ];
+    assert_eq!(mt.pretty().lines().collect::<Vec<_>>(), expected);
+
+    // too wide for the triangle
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..17u64);
+    let pretty = mt.pretty();
+    let lines: Vec<&str> = pretty.lines().collect();
+    assert_eq!(lines.len(), mt.height());
+    assert_eq!(lines[0], "5: 424d");
+    assert_eq!(lines[3], "2: 7bdb 7c85 70e4 eb56 263c ....");
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..0u64);
+    assert_eq!(mt.pretty(), "");
+}
+
+#[test]
+fn test_validate_against() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..7u64);
+    let forged: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(100..107u64);
+    let root = mt.root();
+
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        assert!(p.validate_against::<DefaultHasher>(&root));
+        assert!(p.validate_leaf_against::<DefaultHasher>(&mt[i], &root));
+        assert!(!p.validate_leaf_against::<DefaultHasher>(&forged[i], &root));
+
+        // forged proof is consistent with the root it carries alone
+        let f = forged.gen_proof(i);
+        assert!(f.validate::<DefaultHasher>());
+        assert!(!f.validate_against::<DefaultHasher>(&root));
+        assert!(!f.validate_leaf_against::<DefaultHasher>(&f.item(), &root));
+
+        // trusted root swapped into the forged proof
+        let mut lemma = f.lemma().to_vec();
+        *lemma.last_mut().unwrap() = root;
+        let f = Proof::new(lemma, f.path().to_vec());
+        assert!(!f.validate::<DefaultHasher>());
+        assert!(!f.validate_against::<DefaultHasher>(&root));
+    }
+}
+
+#[test]
+fn test_proof_index() {
+    use crate::merkle::PaddingStrategy::*;
+
+    for padding in [HashWithSelf, PromoteOdd, EmptyHashPerLevel, DuplicateLast] {
+        for items in 1..130u64 {
+            let mt: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::new_padded((0..items).map(Item), padding);
+            let depth = mt.height() - 1;
+            for i in 0..mt.leafs() {
+                let p = mt.gen_proof(i);
+                let at = format!("{} of {} leafs {:?}", i, items, padding);
+                assert_eq!((p.index(), p.depth()), (i, depth), "{}", at);
+                assert!(p.validate::<DefaultHasher>(), "{}", at);
+            }
+        }
+    }
+
+    // last of 5 leafs is promoted twice
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::new_padded((0..5).map(Item), PromoteOdd);
+    let p = mt.gen_proof(4);
+    assert_eq!((p.path().len(), p.index(), p.depth()), (1, 4, 3));
+    let p = mt.gen_node_proof(1, 2);
+    assert_eq!((p.path().len(), p.index(), p.depth()), (1, 2, 2));
+    let p = mt.gen_proof_ref(4).to_owned();
+    assert_eq!((p.index(), p.depth()), (4, 3));
+}
+
+#[test]
+fn test_gen_proofs() {
+    use crate::merkle::PaddingStrategy::*;
+
+    for padding in [HashWithSelf, PromoteOdd, EmptyHashPerLevel, DuplicateLast] {
+        for items in 0..40u64 {
+            let mt: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::new_padded((0..items).map(Item), padding);
+            let leafs = mt.leafs();
+
+            // any order, with duplicates
+            let mut indices: Vec<usize> = (0..leafs).rev().step_by(2).collect();
+            indices.extend(0..leafs);
+            let proofs = mt.gen_proofs(&indices);
+            assert_eq!(proofs.len(), indices.len());
+            for (&i, p) in indices.iter().zip(&proofs) {
+                assert_eq!(*p, mt.gen_proof(i), "{:?} of {} leafs", padding, items);
+            }
+
+            assert!(mt.gen_proofs(&[]).is_empty());
+            assert_eq!(
+                mt.try_gen_proofs(&[2 * leafs + 1, 0, leafs]).unwrap_err(),
+                MerkleError::IndexOutOfBounds {
+                    index: 2 * leafs + 1,
+                    leafs
+                }
+            );
+        }
+    }
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..11u64);
+    let mt = mt.with_pair_ordering(crate::merkle::PairOrdering::Sorted);
+    assert_eq!(mt.gen_proofs(&[3]), vec![mt.gen_proof(3)]);
+}
+
+#[test]
+fn test_gen_all_proofs() {
+    use crate::merkle::PaddingStrategy::*;
+
+    for padding in [HashWithSelf, PromoteOdd, EmptyHashPerLevel, DuplicateLast] {
+        for items in 0..40u64 {
+            let mt: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::new_padded((0..items).map(Item), padding);
+            let naive: Vec<_> = (0..mt.leafs()).map(|i| mt.gen_proof(i)).collect();
+            let all = mt.gen_all_proofs();
+            assert_eq!(all, naive, "{:?} of {} leafs", padding, items);
+
+            let mut next = 0;
+            mt.for_each_proof(|i, p| {
+                assert_eq!(i, next);
+                assert_eq!(p, naive[i]);
+                next += 1;
+            });
+            assert_eq!(next, mt.leafs());
+        }
+    }
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..11u64);
+    let mt = mt.with_pair_ordering(crate::merkle::PairOrdering::Sorted);
+    assert_eq!(mt.gen_all_proofs()[3], mt.gen_proof(3));
+}
+
+#[test]
+fn test_default_leafs() {
+    use crate::merkle::PaddingStrategy::*;
+    use crate::merkle::PairOrdering;
+
+    // default of the hash is a leaf as any other, not the empty slot of the
+    // odd levels, which are of the number of the leafs alone
+    for padding in [HashWithSelf, PromoteOdd, EmptyHashPerLevel, DuplicateLast] {
+        for ordering in [PairOrdering::Ordered, PairOrdering::Sorted] {
+            for items in [1usize, 2, 3, 5, 7, 8, 9] {
+                for zero in [0, items / 2, items - 1] {
+                    let leafs = |x: u64| {
+                        (0..items).map(move |i| Item(if i == zero { x } else { i as u64 + 1 }))
+                    };
+                    let mt: MerkleTree<Item, DefaultHasher> =
+                        MerkleTree::try_build(leafs(0), padding, ordering).unwrap();
+                    let other: MerkleTree<Item, DefaultHasher> =
+                        MerkleTree::try_build(leafs(items as u64 + 1), padding, ordering).unwrap();
+                    assert_ne!(mt.root(), other.root(), "{:?} of {} leafs", padding, items);
+
+                    let mut a = DefaultHasher::new();
+                    for i in zero.saturating_sub(1)..cmp::min(zero + 2, items) {
+                        let p = mt.gen_proof(i);
+                        assert!(p.validate::<DefaultHasher>());
+                        assert_eq!(p.root(), mt.root());
+                        a.reset();
+                        assert_eq!(p.item(), a.leaf(leafs(0).nth(i).unwrap()));
+                    }
+                    let p = mt.gen_proof(zero);
+                    assert!(!p.validate_against::<DefaultHasher>(&other.root()));
+                }
+            }
+        }
+    }
+}
+
+#[test]
+fn test_gen_proof_ref() {
+    use crate::merkle::PaddingStrategy::*;
+    use crate::merkle::PairOrdering;
+
+    for padding in [HashWithSelf, PromoteOdd, EmptyHashPerLevel, DuplicateLast] {
+        for ordering in [PairOrdering::Ordered, PairOrdering::Sorted] {
+            for items in 1..20u64 {
+                let mt: MerkleTree<Item, DefaultHasher> =
+                    MerkleTree::try_build((0..items).map(Item), padding, ordering).unwrap();
+                let root = mt.root();
+
+                for i in 0..mt.leafs() {
+                    let p = mt.gen_proof_ref(i);
+                    let owned = mt.gen_proof(i);
+                    assert_eq!(p.to_owned(), owned);
+                    assert_eq!(p.to_bytes(), owned.to_bytes());
+                    assert_eq!(*p.item(), owned.item());
+                    assert_eq!(p.index(), owned.index());
+                    assert_eq!(
+                        p.validate::<DefaultHasher>(),
+                        owned.validate::<DefaultHasher>()
+                    );
+                    assert!(p.validate_against::<DefaultHasher>(&root));
+                    assert!(!p.validate_against::<DefaultHasher>(&Item(root.0 ^ 1)));
+                }
+            }
+        }
+    }
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..3u64);
+    assert_eq!(
+        mt.try_gen_proof_ref(4).unwrap_err(),
+        MerkleError::IndexOutOfBounds { index: 4, leafs: 3 }
+    );
+}
+
+#[test]
+fn test_validate_data() {
+    let data = [3u64, 1, 4, 1, 5, 9, 2];
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(data);
+    let root = mt.root();
+
+    for (i, x) in data.iter().enumerate() {
+        // only the object and the trusted root
+        let p = mt.gen_proof(i);
+        assert!(p.validate_data::<u64, DefaultHasher>(x));
+        assert!(p.validate_data_against::<u64, DefaultHasher>(x, &root));
+        assert!(!p.validate_data_against::<u64, DefaultHasher>(&(x + 1), &root));
+        assert!(!p.validate_data_against::<u64, DefaultHasher>(x, &Item(root.0 ^ 1)));
+    }
+}
+
+#[test]
+fn test_verify_path() {
+    use crate::proof::verify_path;
+
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..5u64);
+    let root = mt.root();
+
+    // steps of leaf 4 on the stack alone: its duplicate, node 55, node 1234
+    let steps = [(mt[5], true), (mt[9], true), (mt[10], false)];
+    let verify = |leaf, steps: &[(Item, bool)]| {
+        verify_path::<_, DefaultHasher, _>(leaf, steps.iter().cloned(), &root)
+    };
+    assert!(verify(mt[4], &steps));
+    assert!(!verify(mt[3], &steps));
+    assert!(!verify(mt[4], &steps[..2]));
+    assert!(verify(root, &[]));
+
+    let p = mt.gen_proof(4);
+    let lemma = p.lemma()[1..p.lemma().len() - 1].iter().cloned();
+    assert!(lemma.eq(steps.iter().map(|&(h, _)| h)));
+}
+
+#[test]
+fn test_verify_standalone() {
+    use crate::{verify, verify_raw_leaf};
+
+    let mut a = DefaultHasher::new();
+    for items in 1..=17u64 {
+        let hashes: Vec<Item> = (0..items).map(|x| Item(x * 3 + 1)).collect();
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::new(hashes.clone());
+        let root = mt.root();
+        let other = Item(root.0 ^ 1);
+
+        for (i, &raw) in hashes.iter().enumerate() {
+            let p = mt.gen_proof(i);
+            let (lemma, path) = (p.lemma(), p.path());
+            let (leaf, siblings) = (lemma[0], &lemma[1..lemma.len() - 1]);
+            assert!(verify(&root, &leaf, siblings, path, &mut a));
+            assert!(verify_raw_leaf(&root, &raw, siblings, path, &mut a));
+            assert!(p.validate_against::<DefaultHasher>(&root));
+
+            // wrong root, leaf not leaf-hashed, other leaf
+            assert!(!verify(&other, &leaf, siblings, path, &mut a));
+            assert!(!verify(&root, &raw, siblings, path, &mut a));
+            assert!(!verify_raw_leaf(&root, &leaf, siblings, path, &mut a));
+            let next = hashes[(i + 1) % hashes.len()];
+            if next != raw {
+                assert!(!verify_raw_leaf(&root, &next, siblings, path, &mut a));
+            }
+
+            // siblings not of the steps of the path
+            if let Some((_, up)) = path.split_last() {
+                assert!(!verify(&root, &leaf, siblings, up, &mut a));
+            }
+            assert!(!verify(&root, &leaf, lemma, path, &mut a));
+
+            // flipped steps hash the other way around, but of the node and
+            // its own duplicate filling up the odd level
+            let mut node = leaf;
+            for (j, (&sibling, &left)) in siblings.iter().zip(path).enumerate() {
+                let mut flipped = path.to_vec();
+                flipped[j] = !flipped[j];
+                let valid = verify(&root, &leaf, siblings, &flipped, &mut a);
+                assert_eq!(valid, sibling == node, "leaf {} of {}", i, items);
+                a.reset();
+                node = match left {
+                    true => a.node(node, sibling),
+                    false => a.node(sibling, node),
+                };
+            }
+        }
+    }
+
+    // path of no steps of the tree of one leaf
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::new([Item(7)]);
+    assert!(verify_raw_leaf(&mt.root(), &Item(7), &[], &[], &mut a));
+    assert!(!verify_raw_leaf(&mt.root(), &Item(8), &[], &[], &mut a));
+}
+
+#[test]
+fn test_node_proof() {
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..21u64);
+    let root = mt.root();
+
+    for (level, row) in mt.rows().enumerate() {
+        let width = row.len();
+        for (index, node) in row.iter().enumerate() {
+            let p = mt.gen_node_proof(level, index);
+            assert_eq!(p.item(), *node);
+            assert_eq!(p.index(), index);
+            assert_eq!(p.depth() + level + 1, mt.height());
+            assert!(p.validate_against::<DefaultHasher>(&root));
+        }
+        assert_eq!(
+            mt.try_gen_node_proof(level, width),
+            Err(MerkleError::NodeOutOfBounds {
+                level,
+                index: width
+            })
+        );
+    }
+    assert!(mt.try_gen_node_proof(mt.height(), 0).is_err());
+
+    // subtree over the leafs 8..12 built on its own
+    let sub: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(8..12u64);
+    let outer = mt.gen_node_proof(2, 2);
+    assert_eq!(outer.item(), sub.root());
+    for i in 0..sub.leafs() {
+        let p = sub.gen_proof(i).compose(outer.clone()).unwrap();
+        assert!(p.validate_against::<DefaultHasher>(&root));
+        assert_eq!(p, mt.gen_proof(8 + i));
+    }
+    assert_eq!(
+        sub.gen_proof(0).compose(mt.gen_node_proof(2, 1)),
+        Err(ComposeError::Joint)
+    );
+}
+
+#[test]
+fn test_compose_shards() {
+    use crate::merkle::PairOrdering;
+
+    // shards of uneven number of leafs, of the one of one leaf, committed
+    // into the global tree upon their roots
+    let sizes = [5, 1, 8, 3, 13];
+    let mut start = 0;
+    let shards: Vec<MerkleTree<Item, DefaultHasher>> = sizes
+        .iter()
+        .map(|&n| {
+            start += n;
+            MerkleTree::from_data(start - n..start)
+        })
+        .collect();
+    let global: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_leaf_hashes(shards.iter().map(|shard| shard.root()));
+    assert_eq!(global.leafs(), sizes.len());
+    let root = global.root();
+
+    for s in [0, 2, sizes.len() - 1] {
+        let shard = &shards[s];
+        let outer = global.gen_proof(s);
+        assert_eq!(outer.item(), shard.root());
+        for i in 0..shard.leafs() {
+            let inner = shard.gen_proof(i);
+            let item = inner.item();
+            let p = inner.compose(outer.clone()).unwrap();
+            assert_eq!(p.item(), item);
+            assert_eq!(p.root(), root);
+            assert_eq!(p.depth(), shard.height() + global.height() - 2);
+            assert!(p.validate::<DefaultHasher>());
+            assert!(p.validate_against::<DefaultHasher>(&root));
+            assert!(!p.validate_against::<DefaultHasher>(&shard.root()));
+        }
+    }
+
+    // shard proof composed with the proof of another shard root
+    let outer = global.gen_proof(1);
+    assert_eq!(
+        shards[0].gen_proof(0).compose(outer.clone()),
+        Err(ComposeError::Joint)
+    );
+    let sorted = shards[1]
+        .gen_proof(0)
+        .with_pair_ordering(PairOrdering::Sorted);
+    assert_eq!(
+        sorted.compose(outer),
+        Err(ComposeError::Ordering {
+            inner: PairOrdering::Sorted,
+            outer: PairOrdering::Ordered
+        })
+    );
+
+    // roots of the other width than the leaf of the outer proof
+    let inner = Proof::new(vec![vec![1; 4], vec![2; 4], vec![3; 4]], vec![true]);
+    let outer = Proof::new(vec![vec![3; 8], vec![4; 8]], vec![]);
+    let err = inner.compose(outer).unwrap_err();
+    assert_eq!(err, ComposeError::DigestLength { inner: 4, outer: 8 });
+    assert_eq!(
+        err.to_string(),
+        "inner proof root of 4 bytes does not fit outer proof node of 8 bytes"
+    );
+}
+
+/// Algorithm buffering the bytes written into its state, and counting the
+/// hashes it computed, which differs between the trees of the same nodes.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+struct Buffered {
+    bytes: Vec<u8>,
+    hashes: usize,
+}
+
+impl Hasher for Buffered {
+    fn write(&mut self, msg: &[u8]) {
+        self.bytes.extend_from_slice(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        let mut h = DefaultHasher::new();
+        h.write(&self.bytes);
+        h.finish()
+    }
+}
+
+impl Algorithm<Item> for Buffered {
+    fn hash(&mut self) -> Item {
+        self.hashes += 1;
+        Item(self.finish())
+    }
+
+    fn reset(&mut self) {
+        self.bytes.clear();
+    }
+}
+
+#[test]
+fn test_eq_stateful_algorithm() {
+    let items = || (0..7u64).map(Item);
+    let built: MerkleTree<Item, Buffered> = MerkleTree::new(items());
+    let mut pushed: MerkleTree<Item, Buffered> = MerkleTree::new_with(
+        items().take(1),
+        Buffered {
+            bytes: b"left over".to_vec(),
+            hashes: 1000,
+        },
+    );
+    for x in items().skip(1) {
+        pushed.push(x);
+    }
+
+    // trees of the same nodes are equal, whatever state their algorithms
+    // are left in
+    assert_ne!(built.algorithm(), pushed.algorithm());
+    assert_eq!(built.as_slice(), pushed.as_slice());
+    assert_eq!(built, pushed);
+    pushed.set_leaf(3, Item(0)).unwrap();
+    assert_ne!(built, pushed);
+}
+
+#[test]
+fn test_dirty_algorithm() {
+    use crate::hash::{KeyedAlgorithm, ResetChecked};
+
+    // algorithms written to before they are passed to the trees hash the
+    // same as the fresh ones
+    let mut dirty = DefaultHasher::new();
+    dirty.write(b"left over");
+    let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..9u64);
+    let from_dirty: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::from_data_with(0..9u64, dirty.clone());
+    assert_eq!(from_dirty.root(), mt.root());
+    let leafs = leafs(9);
+    assert_eq!(
+        MerkleTree::<Item, DefaultHasher>::new_with(leafs, dirty).as_slice(),
+        mt.as_slice()
+    );
+
+    let mut keyed = KeyedAlgorithm::<DefaultHasher>::new(b"secret");
+    let clean: MerkleTree<Item, _> = MerkleTree::from_data_with(0..9u64, keyed.clone());
+    keyed.write(b"left over");
+    let mt: MerkleTree<Item, _> = MerkleTree::from_data_with(0..9u64, keyed);
+    assert_eq!(mt.root(), clean.root());
+
+    // algorithms of the tests are reset between the hashes
+    let checked: MerkleTree<Item, ResetChecked<DefaultHasher>> = MerkleTree::from_data(0..9u64);
+    assert_eq!(checked.root(), from_dirty.root());
+    let checked: MerkleTree<Item, ResetChecked<Buffered>> = MerkleTree::from_data(0..9u64);
+    let p = checked.gen_proof(8);
+    assert!(p.validate::<ResetChecked<Buffered>>());
+    assert!(p.validate::<Buffered>());
+}
+
+/// Algorithm which forgets to drop the bytes written as it is reset.
+#[derive(Debug, Clone, Default)]
+struct Sticky(Buffered);
+
+impl Hasher for Sticky {
+    fn write(&mut self, msg: &[u8]) {
+        self.0.write(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        self.0.finish()
+    }
+}
+
+impl Algorithm<Item> for Sticky {
+    fn hash(&mut self) -> Item {
+        self.0.hash()
+    }
+
+    fn reset(&mut self) {}
+}
+
+#[test]
+#[should_panic(expected = "leaf hashes differ, algorithm is not reset between them")]
+fn test_reset_checked() {
+    use crate::hash::ResetChecked;
+
+    let _: MerkleTree<Item, ResetChecked<Sticky>> = MerkleTree::new((0..3u64).map(Item));
+}
+
+#[test]
+fn test_keyed_tree() {
+    use crate::hash::KeyedAlgorithm;
+
+    type Keyed = KeyedAlgorithm<DefaultHasher>;
+    let data = [3u64, 1, 4, 1, 5, 9, 2];
+    let keyed = |key: &[u8]| -> MerkleTree<Item, Keyed> {
+        MerkleTree::from_data_with(data, Keyed::new(key))
+    };
+
+    // same leafs, different keys give different roots
+    let (mt, other) = (keyed(b"secret"), keyed(b"other"));
+    assert_ne!(mt.root(), other.root());
+    assert_ne!(mt.root(), keyed(b"").root());
+    assert_eq!(mt, keyed(b"secret"));
+    assert!(mt.verify());
+
+    let root = mt.root();
+    for (i, x) in data.iter().enumerate() {
+        let p = mt.gen_proof(i);
+        assert!(p.validate_with(&mut Keyed::new(b"secret")));
+        assert!(p.validate_data_against_with(&mut Keyed::new(b"secret"), x, &root));
+        assert!(!p.validate_with(&mut Keyed::new(b"other")));
+        assert!(!p.validate_data_against_with(&mut Keyed::new(b"other"), x, &root));
+        assert!(!p.validate::<Keyed>());
+    }
+
+    // proofs of many leafs are validated with the key
+    let secret = || Keyed::new(b"secret");
+    let mp = mt.gen_multiproof(&[1, 4]);
+    assert!(mp.validate_with(&mut secret(), &root));
+    assert!(!mp.validate::<Keyed>(&root));
+    let rp = mt.gen_range_proof(2..5);
+    let leaves: Vec<Item> = (2..5).map(|i| mt[i]).collect();
+    assert!(rp.validate_with(&mut secret(), &leaves, &root));
+    assert!(!rp.validate::<Keyed>(&leaves, &root));
+    let prefix: MerkleTree<Item, Keyed> = MerkleTree::from_data_with(&data[..3], secret());
+    let cp = mt.gen_consistency_proof(3);
+    assert!(cp.validate_with(&mut secret(), &prefix.root(), 3, &root, 7));
+    assert!(!cp.validate::<Keyed>(&prefix.root(), 3, &root, 7));
+    let empty_root = MerkleTree::<Item, Keyed>::from_data_with(Vec::<u64>::new(), secret()).root();
+    let cp = mt.gen_consistency_proof(0);
+    assert!(cp.validate_with(&mut secret(), &empty_root, 0, &root, 7));
+    assert!(!cp.validate::<Keyed>(&empty_root, 0, &root, 7));
+
+    // committed root and compact tree hash with the key
+    let committed = mt.committed_root();
+    assert_ne!(committed, keyed(b"other").committed_root());
+    let p = mt.gen_proof(2);
+    assert!(p.validate_committed_with(&mut secret(), &committed, 7));
+    assert!(!p.validate_committed::<Keyed>(&committed, 7));
+    let ct = mt.clone().compact();
+    assert_eq!(ct.gen_proof_with_leaf(2, mt[2], mt[3]), p);
+
+    // nodes the tree changes are hashed with its key
+    let mut pushed: MerkleTree<Item, Keyed> =
+        MerkleTree::from_data_with(&data[..6], Keyed::new(b"secret"));
+    let mut a = Keyed::new(b"secret");
+    2u64.hash(&mut a);
+    pushed.push(a.hash());
+    assert_eq!(pushed.root(), root);
+    assert!(pushed.verify());
+
+    // empty trees of different keys have different roots
+    let empty = |key: &[u8]| -> MerkleTree<Item, Keyed> {
+        MerkleTree::from_data_with(Vec::<u64>::new(), Keyed::new(key))
+    };
+    assert_ne!(empty(b"secret").root(), empty(b"other").root());
+    assert_ne!(empty(b"secret").root(), DefaultHasher::new().hash());
+    assert_eq!(empty(b"").root(), DefaultHasher::new().hash());
+
+    // key is never printed
+    assert!(!format!("{:?}", mt).contains("secret"));
+}
+
+#[test]
+fn test_find_leaf() {
+    let leaf = |x: u64| DefaultHasher::new().leaf(Item(x));
+    // leafs 3 and 5 are duplicates of 1
+    let items = [0, 1, 2, 1, 4, 1, 6];
+    let mut mt: MerkleTree<Item, DefaultHasher> = items.iter().map(|&x| Item(x)).collect();
+    let root = mt.root();
+
+    for indexed in [false, true] {
+        if indexed {
+            mt.build_index();
+        }
+        assert_eq!(mt.is_indexed(), indexed);
+        for (i, &x) in items.iter().enumerate() {
+            let first = items.iter().position(|&y| y == x).unwrap();
+            assert_eq!(mt.find_leaf(&leaf(x)), Some(first), "leaf {}", i);
+        }
+        // items are not their leaf hashes, nor are the nodes above the leafs
+        for absent in [leaf(3), leaf(7), Item(1), mt.root(), mt[mt.leafs() + 1]] {
+            assert_eq!(mt.find_leaf(&absent), None);
+            assert_eq!(mt.gen_proof_for_hash(&absent), None);
+        }
+
+        let p = mt.gen_proof_for_hash(&leaf(1)).unwrap();
+        assert_eq!(p, mt.gen_proof(1));
+        assert!(p.validate_against::<DefaultHasher>(&root));
+        assert_eq!(mt.gen_proof_for_hash(&leaf(6)), Some(mt.gen_proof(6)));
+    }
+
+    // index is dropped as the leafs change, and found the same built again
+    mt.set_leaf(1, Item(9)).unwrap();
+    assert!(!mt.is_indexed());
+    assert_eq!(mt.find_leaf(&leaf(1)), Some(3));
+    assert_eq!(mt.find_leaf(&leaf(9)), Some(1));
+    mt.build_index();
+    assert_eq!(mt.find_leaf(&leaf(1)), Some(3));
+    mt.push(Item(7));
+    assert!(!mt.is_indexed());
+    assert_eq!(mt.find_leaf(&leaf(7)), Some(7));
+    mt.build_index();
+    mt.truncate(3);
+    assert!(!mt.is_indexed());
+    assert_eq!(mt.find_leaf(&leaf(1)), None);
+    mt.build_index();
+    mt.extend([Item(1)]);
+    assert!(!mt.is_indexed());
+    assert_eq!(mt.find_leaf(&leaf(1)), Some(3));
+
+    // index does not make trees other
+    let mut indexed = mt.clone();
+    indexed.build_index();
+    assert_eq!(indexed, mt);
+
+    let mut empty: MerkleTree<Item, DefaultHasher> = MerkleTree::empty();
+    assert_eq!(empty.find_leaf(&leaf(0)), None);
+    empty.build_index();
+    assert_eq!(empty.gen_proof_for_hash(&leaf(0)), None);
+}
+
+#[test]
+fn test_find_leaf_flat_store() {
+    use crate::store::FlatStore;
+
+    // flat store lends no nodes, which are read to be compared
+    let hashes: Vec<Vec<u8>> = (0..5u8).map(|x| vec![x; 16]).collect();
+    let mut mt: MerkleTree<Vec<u8>, VecSip, FlatStore<Vec<u8>>> =
+        MerkleTree::from_iter(hashes.clone());
+    let leafs: Vec<Vec<u8>> = (0..5).map(|i| mt.node_bytes(i).to_vec()).collect();
+    for indexed in [false, true] {
+        if indexed {
+            mt.build_index();
+        }
+        for (i, leaf) in leafs.iter().enumerate() {
+            assert_eq!(mt.find_leaf(leaf), Some(i));
+        }
+        assert_eq!(mt.find_leaf(&hashes[0]), None);
+    }
+}
+
+#[test]
+fn test_leaf_counts() {
+    for items in 0..40u64 {
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        let leafs = items as usize;
+        assert_eq!(mt.leaf_count(), leafs);
+        assert_eq!(mt.leafs(), mt.leaf_count());
+        assert_eq!(mt.node_count(), mt.len());
+        assert_eq!(mt.leaves().count(), leafs);
+
+        let padded = if leafs == 0 { 0 } else { next_pow2(leafs) };
+        assert_eq!(mt.padded_leaf_count(), padded);
+        assert_eq!(mt.padding_leaf_count(), padded - leafs);
+        assert_eq!(mt.is_balanced(), leafs == padded);
+        assert!(mt.padded_len() <= cmp::max(padded, 1));
+
+        // leaf indices are of no padding
+        assert!(mt.leaf(leafs).is_none());
+        if leafs > 0 {
+            assert!(mt.try_gen_proof(leafs).is_err());
+            assert!(mt.try_gen_proof(leafs - 1).is_ok());
+        }
+        for index in leafs..padded {
+            let e = MerkleError::PaddingIndex { index, leafs };
+            assert_eq!(mt.try_gen_proof(index), Err(e));
+            assert_eq!(mt.try_gen_proof_ref(index).err(), Some(e));
+        }
+        let e = MerkleError::IndexOutOfBounds {
+            index: cmp::max(padded, 1),
+            leafs,
+        };
+        assert_eq!(mt.try_gen_proof(cmp::max(padded, 1)), Err(e));
+    }
+}
+
+#[test]
+fn test_enumerate_leaves() {
+    for n in [0, 1, 2, 3, 4, 5, 7, 8, 9, 16, 17, 31] {
+        let items: Vec<Item> = (0..n).map(|x| Item(x * 7)).collect();
+        let mt: MerkleTree<Item, DefaultHasher> = MerkleTree::new(items.clone());
+
+        let leaves: Vec<(usize, &Item)> = mt.enumerate_leaves().collect();
+        assert_eq!(leaves.len(), n as usize, "tree of {} leafs", n);
+        for (j, (i, leaf)) in leaves.into_iter().enumerate() {
+            assert_eq!(i, j);
+            assert_eq!(*leaf, DefaultHasher::new().leaf(items[i]));
+            assert!(!mt.is_padded_slot(i));
+            assert!(mt.gen_proof(i).validate::<DefaultHasher>());
+        }
+
+        // only the odd rows of more than one leaf are padded, with the
+        // duplicate of the last leaf right after it
+        let padded = n > 1 && n % 2 == 1;
+        assert_eq!(mt.padded_len(), mt.leafs() + padded as usize);
+        assert_eq!(mt.is_padded_slot(mt.leafs()), padded);
+        assert!(!mt.is_padded_slot(mt.padded_len()));
+        if padded {
+            assert_eq!(mt[mt.leafs()], mt[mt.leafs() - 1]);
+            let err = mt.try_gen_proof(mt.leafs()).unwrap_err();
+            assert!(matches!(err, MerkleError::PaddingIndex { .. }));
+        }
+    }
+}
+
+#[test]
+fn test_padding_strategy() {
+    use crate::merkle::PaddingStrategy::{self, *};
+
+    let strategies = [HashWithSelf, PromoteOdd, EmptyHashPerLevel, DuplicateLast];
+    let padded = |n: u64, padding: PaddingStrategy| -> MerkleTree<Item, DefaultHasher> {
+        MerkleTree::new_padded((0..n).map(Item), padding)
+    };
+
+    // roots of 5 leafs as hashed by hand
+    let node = |l: Item, r: Item| DefaultHasher::default().node(l, r);
+    let l: Vec<Item> = (0..5)
+        .map(|x| DefaultHasher::default().leaf(Item(x)))
+        .collect();
+    let abcd = node(node(l[0], l[1]), node(l[2], l[3]));
+    let ee = node(l[4], l[4]);
+    let e0 = DefaultHasher::default().hash();
+    let e1 = node(e0, e0);
+    let roots = [
+        node(abcd, node(ee, ee)),
+        node(abcd, l[4]),
+        node(abcd, node(node(l[4], e0), e1)),
+        node(abcd, ee),
+    ];
+    for (padding, root) in strategies.iter().zip(roots) {
+        let mt = padded(5, *padding);
+        assert_eq!(mt.root(), root, "{:?}", padding);
+        assert_eq!(mt.padding_strategy(), *padding);
+        assert_eq!(mt.len(), padded(5, HashWithSelf).len());
+    }
+    for (i, a) in roots.iter().enumerate() {
+        assert!(roots[i + 1..].iter().all(|b| a != b));
+    }
+    assert_eq!(padded(5, HashWithSelf), MerkleTree::new((0..5).map(Item)));
+
+    // padding strategy is persisted with the tree
+    for padding in strategies {
+        let mt = padded(5, padding);
+        let mut bytes = Vec::new();
+        mt.encode(&mut bytes).unwrap();
+        let decoded = MerkleTree::decode(&bytes[..], DefaultHasher::new()).unwrap();
+        assert_eq!(decoded, mt, "{:?}", padding);
+
+        bytes.clear();
+        mt.write_to(&mut bytes).unwrap();
+        let read = MerkleTree::read_from(&mut &bytes[..], DefaultHasher::new()).unwrap();
+        assert_eq!(read, mt, "{:?}", padding);
+
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&mt).unwrap();
+            let deserialized: MerkleTree<Item, _> = serde_json::from_str(&json).unwrap();
+            assert_eq!(deserialized, mt, "{:?}", padding);
+            let bytes = bincode::serialize(&mt).unwrap();
+            let deserialized: MerkleTree<Item, _> = bincode::deserialize(&bytes).unwrap();
+            assert_eq!(deserialized, mt, "{:?}", padding);
+        }
+
+        #[cfg(feature = "json")]
+        assert_eq!(MerkleTree::from_json(&mt.to_json()).unwrap(), mt);
+
+        match padding {
+            HashWithSelf => assert!(mt.serialize_leaves(Vec::new()).is_ok()),
+            _ => assert!(mt.serialize_leaves(Vec::new()).is_err()),
+        }
+    }
+
+    for padding in strategies {
+        for n in 0..20u64 {
+            let mt = padded(n, padding);
+            assert_eq!(mt.validate(), Ok(()), "{:?} of {} leafs", padding, n);
+            for i in 0..n as usize {
+                let p = mt.gen_proof(i);
+                assert!(p.validate::<DefaultHasher>());
+                assert_eq!(p.root(), mt.root());
+            }
+
+            // changes rehash as the tree is built
+            let mut pushed = padded(n.saturating_sub(3), padding);
+            pushed.extend((n.saturating_sub(3)..n).map(Item));
+            assert_eq!(pushed.root(), mt.root());
+            let mut truncated = padded(n + 3, padding);
+            truncated.truncate(n as usize);
+            assert_eq!(truncated.root(), mt.root());
+            if n > 0 {
+                let mut set = mt.clone();
+                set.set_leaf(n as usize - 1, Item(7)).unwrap();
+                let mut leafs: Vec<Item> = (0..n - 1).map(Item).collect();
+                leafs.push(Item(7));
+                let rebuilt: MerkleTree<Item, DefaultHasher> =
+                    MerkleTree::new_padded(leafs, padding);
+                assert_eq!(set.root(), rebuilt.root());
+                assert_eq!(set.validate(), Ok(()));
+            }
+        }
+    }
+
+    // promoted nodes are left out of the proofs
+    assert_eq!(padded(5, PromoteOdd).gen_proof(4).path().len(), 1);
+    assert_eq!(padded(5, DuplicateLast).gen_proof(4).path().len(), 2);
+
+    // tampered pad node
+    let mut parts = padded(5, EmptyHashPerLevel).into_parts();
+    parts.0[5] = l[4];
+    let mt = MerkleTree::<Item, DefaultHasher>::from_parts(parts.0, parts.1, parts.2).unwrap();
+    assert!(mt.with_padding(EmptyHashPerLevel).validate().is_err());
+
+    let mt = padded(5, PromoteOdd);
+    let unsupported = MerkleError::UnsupportedPadding {
+        padding: PromoteOdd,
+    };
+    assert_eq!(mt.try_gen_range_proof(0..2).err(), Some(unsupported));
+    assert_eq!(mt.try_gen_multiproof(&[0]).err(), Some(unsupported));
+    assert_eq!(mt.try_gen_consistency_proof(3).err(), Some(unsupported));
+    assert!(mt.subtree(1, 0).is_none());
+}
+
+#[test]
+fn test_pair_ordering() {
+    use crate::merkle::PairOrdering::{self, *};
+
+    let sorted = |n: u64| -> MerkleTree<Item, DefaultHasher> {
+        MerkleTree::new_ordered((0..n).map(Item), Sorted)
+    };
+
+    // root of 3 leafs as hashed by hand, pairs sorted by their bytes
+    let node = |l: Item, r: Item| match l.as_ref() <= r.as_ref() {
+        true => DefaultHasher::default().node(l, r),
+        false => DefaultHasher::default().node(r, l),
+    };
+    let l: Vec<Item> = (0..3)
+        .map(|x| DefaultHasher::default().leaf(Item(x)))
+        .collect();
+    let mt = sorted(3);
+    assert_eq!(mt.root(), node(node(l[0], l[1]), node(l[2], l[2])));
+    assert_eq!(mt.pair_ordering(), Sorted);
+    let ordered: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::new_ordered((0..3).map(Item), Ordered);
+    assert_eq!(ordered, MerkleTree::new((0..3).map(Item)));
+
+    for n in 0..20u64 {
+        let mt = sorted(n);
+        assert_eq!(mt.validate(), Ok(()), "{} leafs", n);
+        for i in 0..n as usize {
+            let p = mt.gen_proof(i);
+            assert_eq!(p.pair_ordering(), Sorted);
+            assert_eq!(p.index(), i);
+            assert!(p.validate::<DefaultHasher>());
+
+            // directions are ignored
+            let flipped: Vec<bool> = p.path().iter().map(|&left| !left).collect();
+            let flipped = Proof::new(p.lemma().to_vec(), flipped).with_pair_ordering(Sorted);
+            assert!(flipped.validate::<DefaultHasher>());
+        }
+
+        // changes rehash as the tree is built
+        let mut pushed = sorted(n.saturating_sub(3));
+        pushed.extend((n.saturating_sub(3)..n).map(Item));
+        assert_eq!(pushed.root(), mt.root());
+        if n > 0 {
+            let mut set = mt.clone();
+            set.set_leaf(0, Item(7)).unwrap();
+            let mut leafs: Vec<Item> = (1..n).map(Item).collect();
+            leafs.insert(0, Item(7));
+            let rebuilt: MerkleTree<Item, DefaultHasher> = MerkleTree::new_ordered(leafs, Sorted);
+            assert_eq!(set.root(), rebuilt.root());
+        }
+    }
+
+    // ordering is carried by the proof, not its path
+    let p = sorted(5).gen_proof(3);
+    let unordered = Proof::new(p.lemma().to_vec(), p.path().to_vec());
+    assert_eq!(unordered.pair_ordering(), PairOrdering::default());
+    assert_eq!(unordered.with_pair_ordering(Sorted), p);
+
+    // and is encoded and serialized with it
+    let promoted: MerkleTree<Item, DefaultHasher> =
+        MerkleTree::new_padded((0..5).map(Item), crate::merkle::PaddingStrategy::PromoteOdd);
+    for p in [p, promoted.with_pair_ordering(Sorted).gen_proof(4)] {
+        assert_eq!(Proof::<Item>::from_bytes(&p.to_bytes()), Ok(p.clone()));
+        assert_eq!(Proof::<Item>::from_hex(&p.to_hex()), Ok(p.clone()));
+        #[cfg(feature = "serde")]
+        {
+            let json = serde_json::to_string(&p).unwrap();
+            assert_eq!(serde_json::from_str::<Proof<Item>>(&json).unwrap(), p);
+            let bin = bincode::serialize(&p).unwrap();
+            assert_eq!(bincode::deserialize::<Proof<Item>>(&bin).unwrap(), p);
+        }
+        #[cfg(feature = "json")]
+        assert_eq!(Proof::<Item>::from_json(&p.to_json()).unwrap(), p);
+    }
+
+    // ordering is persisted with the tree
+    let mt = sorted(5);
+    let mut bytes = Vec::new();
+    mt.encode(&mut bytes).unwrap();
+    let decoded = MerkleTree::decode(&bytes[..], DefaultHasher::new()).unwrap();
+    assert_eq!(decoded, mt);
+    assert_eq!(decoded.gen_proof(3), mt.gen_proof(3));
+    bytes.clear();
+    mt.write_to(&mut bytes).unwrap();
+    let read = MerkleTree::read_from(&mut &bytes[..], DefaultHasher::new()).unwrap();
+    assert_eq!(read, mt);
+    #[cfg(feature = "serde")]
+    {
+        let json = serde_json::to_string(&mt).unwrap();
+        let deserialized: MerkleTree<Item, DefaultHasher> = serde_json::from_str(&json).unwrap();
+        assert_eq!(deserialized.pair_ordering(), Sorted);
+        assert_eq!(deserialized, mt);
+        let bytes = bincode::serialize(&mt).unwrap();
+        let deserialized: MerkleTree<Item, DefaultHasher> = bincode::deserialize(&bytes).unwrap();
+        assert_eq!(deserialized, mt);
+    }
+    #[cfg(feature = "json")]
+    assert_eq!(MerkleTree::from_json(&mt.to_json()).unwrap(), mt);
+    assert!(mt.serialize_leaves(Vec::new()).is_err());
+
+    let unsupported = MerkleError::UnsupportedOrdering { ordering: Sorted };
+    assert_eq!(mt.try_gen_range_proof(0..2).err(), Some(unsupported));
+    assert_eq!(mt.try_gen_multiproof(&[0]).err(), Some(unsupported));
+    assert_eq!(mt.try_gen_consistency_proof(3).err(), Some(unsupported));
+    assert!(mt.subtree(1, 0).is_none());
+}
+
+#[test]
+fn test_committed_root() {
+    // CVE-2012-2459: the last leaf duplicated gives the same root
+    let abc: MerkleTree<Item, DefaultHasher> = MerkleTree::new([1, 2, 3].map(leaf));
+    let abcc: MerkleTree<Item, DefaultHasher> = MerkleTree::new([1, 2, 3, 3].map(leaf));
+    assert_eq!(abc.root(), abcc.root());
+    let forged = abcc.gen_proof(3);
+    assert!(forged.validate_against::<DefaultHasher>(&abc.root()));
+
+    // not with the number of leafs committed
+    assert_ne!(abc.committed_root(), abcc.committed_root());
+    let committed = abc.committed_root();
+    assert!(!forged.validate_committed::<DefaultHasher>(&committed, 3));
+    assert!(!forged.validate_committed::<DefaultHasher>(&committed, 4));
+    for i in 0..3 {
+        let p = abc.gen_proof(i);
+        assert!(p.validate_committed::<DefaultHasher>(&committed, 3));
+        assert!(!p.validate_committed::<DefaultHasher>(&committed, 4));
+        assert!(!p.validate_committed::<DefaultHasher>(&abc.root(), 3));
+    }
+
+    // committed root is the root followed by the number of leafs
+    assert_eq!(committed, commit(&abc.root(), 3));
+
+    // leafs deeper than the tree
+    let abcde: MerkleTree<Item, DefaultHasher> = MerkleTree::new((1..6).map(leaf));
+    let p = abcde.gen_proof(0);
+    assert!(!p.validate_committed::<DefaultHasher>(&commit(&p.root(), 4), 4));
+    assert!(p.validate_committed::<DefaultHasher>(&abcde.committed_root(), 5));
+
+    // index the proof claims is checked against its path
+    for (i, j) in [(3, 2), (3, 0), (2, 1)] {
+        let moved = abcc.gen_proof(i).with_position(j, 2);
+        assert!(!moved.validate_committed::<DefaultHasher>(&committed, 3));
+    }
+
+    // proofs of the trees of any padding strategy
+    use crate::merkle::PaddingStrategy::*;
+    for padding in [HashWithSelf, PromoteOdd, EmptyHashPerLevel, DuplicateLast] {
+        for items in 1..40u64 {
+            let mt: MerkleTree<Item, DefaultHasher> =
+                MerkleTree::new_padded((0..items).map(Item), padding);
+            let (committed, leafs) = (mt.committed_root(), mt.leafs());
+            for i in 0..leafs {
+                let p = mt.gen_proof(i);
+                let valid =
+                    |p: &Proof<Item>| p.validate_committed::<DefaultHasher>(&committed, leafs);
+                let at = format!("{} of {} leafs {:?}", i, items, padding);
+                assert!(valid(&p), "{}", at);
+                assert!(!valid(&p.clone().with_position(i ^ 1, p.depth())), "{}", at);
+            }
+        }
+    }
+
+    fn commit(root: &Item, leafs: u64) -> Item {
+        let mut a = DefaultHasher::default();
+        a.write(root.as_ref());
+        a.write(&leafs.to_le_bytes());
+        a.hash()
+    }
+}
@@ -0,0 +1,73 @@
+#![cfg(test)]
+
+use crate::test_xor128::{Item, SIZE, XOR128};
+
+#[test]
+fn test_absence_proof() {
+    use crate::hash::KeyedAlgorithm;
+    use crate::merkle::MerkleError;
+    use crate::sorted::SortedMerkleTree;
+
+    let key = |x: u8| {
+        let mut k = [0; SIZE];
+        k[0] = x;
+        k
+    };
+
+    for items in 0..20u8 {
+        // even keys in the tree, odd ones absent
+        let st: SortedMerkleTree<Item, XOR128> =
+            SortedMerkleTree::new((0..items).rev().map(|x| key(2 * x + 2)));
+        assert_eq!(st.leafs(), items as usize);
+        let root = st.root();
+
+        for x in 0..=2 * items + 2 {
+            if x % 2 == 0 && x > 0 && x <= 2 * items {
+                let index = st.position(&key(x)).unwrap();
+                assert_eq!(
+                    st.try_gen_absence_proof(&key(x)).unwrap_err(),
+                    MerkleError::Present { index }
+                );
+                continue;
+            }
+
+            let ap = st.gen_absence_proof(&key(x));
+            assert!(ap.validate::<XOR128>(&root, &key(x)));
+            assert!(ap.validate_with(&mut XOR128::new(), &root, &key(x)));
+            let mut keyed = KeyedAlgorithm::<XOR128>::new(b"secret");
+            assert!(!ap.validate_with(&mut keyed, &root, &key(x)));
+            assert_eq!(ap.predecessor().is_none(), items == 0 || x < 2);
+            assert_eq!(ap.successor().is_none(), x >= 2 * items);
+
+            // proof is of that gap alone, the one after the last leaf is open
+            let next = ap.validate::<XOR128>(&root, &key(x + 2));
+            assert_eq!(next, x >= 2 * items);
+            if items > 0 {
+                let forged = key(0xff);
+                assert!(!ap.validate::<XOR128>(&forged, &key(x)));
+            }
+        }
+
+        // present keys cannot be proven absent by the proofs of their gaps
+        if items > 1 {
+            let ap = st.gen_absence_proof(&key(3));
+            assert!(!ap.validate::<XOR128>(&root, &key(2)));
+            assert!(!ap.validate::<XOR128>(&root, &key(4)));
+        }
+    }
+}
+
+#[test]
+fn test_sorted_tree() {
+    use crate::merkle::MerkleError;
+    use crate::sorted::SortedMerkleTree;
+
+    let st: SortedMerkleTree<Item, XOR128> =
+        SortedMerkleTree::new([[3; SIZE], [1; SIZE], [3; SIZE]]);
+    assert_eq!(st.keys(), &[[1; SIZE], [3; SIZE]]);
+    assert!(st.gen_proof(&[3; SIZE]).validate::<XOR128>());
+    assert_eq!(st.position(&[2; SIZE]), None);
+
+    let unsorted = SortedMerkleTree::<Item, XOR128>::try_from_sorted(vec![[1; SIZE], [1; SIZE]]);
+    assert_eq!(unsorted.unwrap_err(), MerkleError::Unsorted { index: 1 });
+}
@@ -0,0 +1,151 @@
+#![cfg(test)]
+
+use crate::hash::Algorithm;
+use crate::merkle::MerkleError;
+use crate::test_item::{Item, VecSip};
+use std::collections::hash_map::DefaultHasher;
+
+#[test]
+fn test_sparse_tree() {
+    use crate::sparse::{SparseMerkleTree, SparseProof};
+
+    let mut smt: SparseMerkleTree<Item, DefaultHasher> = SparseMerkleTree::new();
+    assert_eq!(smt.depth(), 64);
+    assert!(smt.is_empty());
+
+    // root of the empty tree is the same of every tree, and of no nodes
+    let empty = smt.root();
+    assert_eq!(empty, smt.empty_root());
+    let default: SparseMerkleTree<Item, DefaultHasher> = Default::default();
+    assert_eq!(default.root(), empty);
+    let mut h = DefaultHasher::new().hash();
+    for _ in 0..64 {
+        h = DefaultHasher::new().node(h, h);
+    }
+    assert_eq!(empty, h);
+    let absent = smt.gen_proof(&Item(7));
+    assert!(absent.siblings().is_empty());
+    assert!(absent.validate_against::<DefaultHasher>(&empty));
+
+    let mut x = 0x2545f4914f6cdd1du64;
+    let mut next = || {
+        x ^= x << 13;
+        x ^= x >> 7;
+        x ^= x << 17;
+        x
+    };
+    // keys of the common prefixes, and at random
+    let mut keys: Vec<u64> = vec![0, 1, 2, 1 << 63, u64::MAX];
+    keys.extend((0..27).map(|_| next()));
+
+    let mut roots = vec![empty];
+    for (i, &k) in keys.iter().enumerate() {
+        assert_eq!(smt.insert(Item(k), Item(i as u64)), None);
+        roots.push(smt.root());
+    }
+    assert_eq!(smt.len(), keys.len());
+    assert_eq!(smt.insert(Item(keys[3]), Item(3)), Some(Item(3)));
+    assert_eq!(smt.root(), roots[keys.len()]);
+
+    let root = smt.root();
+    for (i, &k) in keys.iter().enumerate() {
+        assert_eq!(smt.get(&Item(k)), Some(&Item(i as u64)));
+        let p = smt.gen_proof(&Item(k));
+        assert_eq!(p.value(), Some(&Item(i as u64)));
+        assert!(p.validate_against::<DefaultHasher>(&root), "key {:x}", k);
+        assert!(!p.validate_against::<DefaultHasher>(&roots[i]));
+
+        // proof of the other value, or of no value, does not validate
+        let bitmap = p.bitmap().to_vec();
+        let siblings = p.siblings().to_vec();
+        let forge = |value| SparseProof::new(Item(k), value, bitmap.clone(), siblings.clone());
+        assert!(!forge(Some(Item(i as u64 + 1))).validate_against::<DefaultHasher>(&root));
+        assert!(!forge(None).validate_against::<DefaultHasher>(&root));
+        let moved = SparseProof::new(Item(k ^ 1), p.value().cloned(), bitmap, siblings);
+        assert!(!moved.validate_against::<DefaultHasher>(&root));
+    }
+
+    // keys absent are proven of no value, of the sibling of the leaf if the
+    // key of the last bit of the path flipped is present
+    let last = u64::from_ne_bytes([0, 0, 0, 0, 0, 0, 0, 1]);
+    let next_to = [keys[0] ^ last, keys[4] ^ last];
+    for k in (0..64).map(|_| next()).chain(next_to) {
+        assert_eq!(smt.get(&Item(k)), None);
+        let p = smt.gen_proof(&Item(k));
+        assert_eq!(p.value(), None);
+        assert!(p.validate_against::<DefaultHasher>(&root));
+        assert_eq!(p.bitmap()[0] & 1 == 1, next_to.contains(&k));
+        let (bitmap, siblings) = (p.bitmap().to_vec(), p.siblings().to_vec());
+        let forged = SparseProof::new(Item(k), Some(Item(0)), bitmap, siblings);
+        assert!(!forged.validate_against::<DefaultHasher>(&root));
+    }
+
+    // compressed proofs of the bitmap and the siblings other in number
+    let p = smt.gen_proof(&Item(1));
+    assert_eq!(p.bitmap().len(), 8);
+    assert!(!p.siblings().is_empty());
+    let mut bitmap = p.bitmap().to_vec();
+    bitmap[7] ^= 0x80;
+    let value = p.value().cloned();
+    for (bitmap, siblings) in [
+        (bitmap, p.siblings().to_vec()),
+        (p.bitmap()[..7].to_vec(), p.siblings().to_vec()),
+        (p.bitmap().to_vec(), p.siblings()[1..].to_vec()),
+    ] {
+        let p = SparseProof::new(Item(1), value, bitmap, siblings);
+        assert!(!p.validate_against::<DefaultHasher>(&root));
+    }
+
+    // removing the values in reverse goes back through the roots to the
+    // empty tree
+    assert_eq!(smt.remove(&Item(3)), None);
+    for (i, &k) in keys.iter().enumerate().rev() {
+        assert_eq!(smt.remove(&Item(k)), Some(Item(i as u64)));
+        assert_eq!(smt.root(), roots[i], "key {:x}", k);
+        let p = smt.gen_proof(&Item(k));
+        assert!(p.validate_against::<DefaultHasher>(&roots[i]));
+    }
+    assert!(smt.is_empty());
+    assert_eq!(smt, SparseMerkleTree::new());
+
+    // trees of the same values are the same in any order of the inserts
+    let shuffled: SparseMerkleTree<Item, DefaultHasher> = {
+        let mut t = SparseMerkleTree::new();
+        for (i, &k) in keys.iter().enumerate().rev() {
+            t.insert(Item(k), Item(i as u64));
+        }
+        t
+    };
+    for (i, &k) in keys.iter().enumerate() {
+        smt.insert(Item(k), Item(i as u64));
+    }
+    assert_eq!(shuffled.root(), root);
+    assert_eq!(shuffled, smt);
+}
+
+#[test]
+fn test_sparse_tree_key_length() {
+    use crate::sparse::{SparseMerkleTree, SparseProof};
+
+    // keys of the vectors of the bytes of the hashes, of 16 bytes of VecSip
+    let mut smt: SparseMerkleTree<Vec<u8>, VecSip> = SparseMerkleTree::new();
+    assert_eq!(smt.depth(), 128);
+    let err = smt.try_insert(vec![0; 15], vec![1]).unwrap_err();
+    let expected = MerkleError::KeyLength {
+        len: 15,
+        expected: 16,
+    };
+    assert_eq!(err, expected);
+    assert_eq!(
+        err.to_string(),
+        "key of 15 bytes is not of the 16 bytes of the hashes of the tree"
+    );
+    assert!(smt.try_gen_proof(&vec![0; 17]).is_err());
+    assert!(smt.is_empty());
+
+    smt.insert(vec![0; 16], vec![1]);
+    let p = smt.gen_proof(&vec![0; 16]);
+    assert!(p.validate_against::<VecSip>(&smt.root()));
+    let short = SparseProof::new(vec![0; 15], Some(vec![1]), p.bitmap().to_vec(), vec![]);
+    assert!(!short.validate_against::<VecSip>(&smt.root()));
+}
@@ -0,0 +1,132 @@
+#![cfg(test)]
+
+use crate::merkle::MerkleTree;
+use crate::store::Store;
+use crate::test_item::{Item, VecSip};
+use crate::test_sip::past_leafs;
+use std::collections::hash_map::DefaultHasher;
+use std::iter::FromIterator;
+
+#[test]
+#[should_panic(expected = "bytes overflow capacity of")]
+fn test_flat_store_overflow_panics() {
+    crate::store::FlatStore::<Vec<u8>>::with_width(16, usize::MAX / 8);
+}
+
+/// Store behind no slice, with the default range methods only.
+#[derive(Debug, Clone)]
+struct ListStore(std::collections::LinkedList<Item>);
+
+impl Store<Item> for ListStore {
+    fn new(_: usize) -> Self {
+        ListStore(std::collections::LinkedList::new())
+    }
+
+    fn read(&self, i: usize) -> Item {
+        *self.0.iter().nth(i).unwrap()
+    }
+
+    fn write(&mut self, i: usize, value: Item) {
+        *self.0.iter_mut().nth(i).unwrap() = value;
+    }
+
+    fn push(&mut self, value: Item) {
+        self.0.push_back(value);
+    }
+
+    fn len(&self) -> usize {
+        self.0.len()
+    }
+
+    fn truncate(&mut self, len: usize) {
+        if len < self.0.len() {
+            self.0.split_off(len);
+        }
+    }
+}
+
+#[test]
+fn test_custom_store() {
+    for items in 0..18u64 {
+        let expected: MerkleTree<Item, DefaultHasher> = MerkleTree::from_data(0..items);
+        let mut mt: MerkleTree<Item, DefaultHasher, ListStore> = MerkleTree::from_data(0..items);
+        assert_eq!(mt.len(), expected.len());
+        assert_eq!(mt.root(), expected.root());
+        for i in 0..expected.leafs() {
+            assert_eq!(mt.gen_proof(i), expected.gen_proof(i));
+        }
+
+        mt.push(Item(items));
+        let mut expected = expected;
+        expected.push(Item(items));
+        assert_eq!(mt.root(), expected.root());
+
+        let (data, leafs, height) = mt.into_store();
+        let store: &dyn Store<Item> = &data;
+        assert_eq!(store.read_range(0..store.len()), expected.as_slice());
+
+        let mt = MerkleTree::<Item, DefaultHasher, ListStore>::from_store(data, leafs, height);
+        assert_eq!(mt.unwrap().root(), expected.root());
+    }
+}
+
+#[test]
+fn test_flat_store() {
+    use crate::store::FlatStore;
+
+    type FlatTree = MerkleTree<Vec<u8>, VecSip, FlatStore<Vec<u8>>>;
+    let hashes = |n: u64| (0..n).map(|x| x.to_le_bytes().repeat(2));
+
+    for items in [0, 1, 2, 5, 8, 100, 1025u64] {
+        let expected: MerkleTree<Vec<u8>, VecSip> = MerkleTree::from_iter(hashes(items));
+        let mut mt: FlatTree = MerkleTree::from_iter(hashes(items));
+        assert_eq!(mt.len(), expected.len());
+        assert_eq!(mt.root(), expected.root());
+        assert!(mt.verify());
+        for i in 0..expected.leafs() {
+            let p = mt.gen_proof(i);
+            assert_eq!(p, expected.gen_proof(i));
+            assert!(p.validate::<VecSip>());
+
+            let bytes = mt.gen_proof_bytes(i);
+            assert_eq!(bytes.to_bytes(), p.to_bytes());
+            assert_eq!(mt.node_bytes(i), &p.item()[..]);
+        }
+        assert_eq!(
+            mt.try_gen_proof_bytes(items as usize).unwrap_err(),
+            past_leafs(items as usize)
+        );
+
+        // nodes are 16 bytes each, one after another
+        let (data, _, _) = mt.clone().into_store();
+        assert_eq!(data.as_bytes().len(), 16 * expected.len());
+        assert_eq!(data.width(), if items > 0 { 16 } else { 0 });
+
+        mt.push(vec![7; 16]);
+        let mut expected = expected;
+        expected.push(vec![7; 16]);
+        assert_eq!(mt.root(), expected.root());
+        mt.truncate(1);
+        expected.truncate(1);
+        assert_eq!(mt.root(), expected.root());
+    }
+
+    let store = FlatStore::with_width(16, 3);
+    let mt: FlatTree = MerkleTree::try_from_iter_in(store, hashes(2)).unwrap();
+    assert_eq!(mt.len(), 3);
+
+    // truncating past the end keeps the nodes
+    let mut store = FlatStore::with_width(16, 2);
+    store.push(vec![1u8; 16]);
+    store.truncate(usize::MAX);
+    assert_eq!(store.len(), 1);
+    store.truncate(0);
+    assert_eq!(store.len(), 0);
+}
+
+#[test]
+#[should_panic(expected = "node of 8 bytes in the store of the nodes of 16 bytes")]
+fn test_flat_store_width() {
+    let mut store = crate::store::FlatStore::with_width(16, 1);
+    store.push(vec![0u8; 8]);
+}
@@ -0,0 +1,95 @@
+#![cfg(test)]
+
+use crate::ct::CtTree;
+use crate::sha256::Sha256Hash;
+use crate::tendermint::{proofs_from_byte_vectors, simple_hash_from_byte_vectors, SimpleProof};
+
+fn unhex(s: &str) -> Sha256Hash {
+    s.parse().unwrap()
+}
+
+#[test]
+fn test_simple_hash_from_byte_vectors() {
+    // vectors of tendermint-rs
+    let empty: [&[u8]; 0] = [];
+    assert_eq!(
+        simple_hash_from_byte_vectors(&empty),
+        unhex("e3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855")
+    );
+    assert_eq!(
+        simple_hash_from_byte_vectors(&[b""]),
+        unhex("6e340b9cffb37a989ca544e6bb780a2c78901d3fb33738768511a30617afa01d")
+    );
+    assert_eq!(
+        simple_hash_from_byte_vectors(&[b"L123456"]),
+        unhex("395aa064aa4c29f7010acfe3f25db9485bbd4b91897b6ad7ad547639252b4d56")
+    );
+    assert_eq!(
+        simple_hash_from_byte_vectors(&[b"N123", b"N456"]),
+        unhex("dc9a0536ff2e196d5a628a5bf377ab247bbddf83342be39699461c1e766e6646")
+    );
+}
+
+#[test]
+fn test_simple_proof() {
+    let empty: [&[u8]; 0] = [];
+    let (root, proofs) = proofs_from_byte_vectors(&empty);
+    assert_eq!(root, simple_hash_from_byte_vectors(&empty));
+    assert!(proofs.is_empty());
+
+    // single item is the root, of no aunts
+    let (root, proofs) = proofs_from_byte_vectors(&[b"L123456"]);
+    assert_eq!(proofs.len(), 1);
+    assert_eq!(proofs[0].total, 1);
+    assert_eq!(proofs[0].leaf_hash, root);
+    assert!(proofs[0].aunts.is_empty());
+    assert!(proofs[0].verify(&root, b"L123456"));
+
+    // aunts of the leaf 2 of 3, and of the leafs 0 and 5 of 8, are the
+    // audit paths of RFC 6962
+    let items: Vec<Vec<u8>> = (0..8u8).map(|i| vec![i; i as usize]).collect();
+    let (root, proofs) = proofs_from_byte_vectors(&items[..3]);
+    assert_eq!(
+        proofs[2].aunts,
+        [simple_hash_from_byte_vectors(&items[..2])]
+    );
+    assert!(proofs[2].verify(&root, &items[2]));
+    let (_, proofs) = proofs_from_byte_vectors(&items);
+    assert_eq!(
+        proofs[0].aunts[2],
+        simple_hash_from_byte_vectors(&items[4..])
+    );
+    assert_eq!(
+        proofs[5].aunts[2],
+        simple_hash_from_byte_vectors(&items[..4])
+    );
+
+    for n in 1..=items.len() {
+        let (root, proofs) = proofs_from_byte_vectors(&items[..n]);
+        assert_eq!(root, simple_hash_from_byte_vectors(&items[..n]));
+        let log = CtTree::from_entries(&items[..n]);
+        for (i, p) in proofs.iter().enumerate() {
+            assert_eq!((p.total, p.index), (n as u64, i as u64));
+            assert_eq!(p.aunts, log.audit_path(i).unwrap());
+            assert_eq!(*p, SimpleProof::of(&log, i).unwrap());
+            assert_eq!(p.compute_root_hash(), Some(root));
+            assert!(p.verify(&root, &items[i]));
+            assert!(!p.verify(&root, b"x"));
+
+            let mut bad = p.clone();
+            bad.index = n as u64;
+            assert_eq!(bad.compute_root_hash(), None);
+            let mut bad = p.clone();
+            bad.aunts.push(root);
+            assert!(!bad.verify(&root, &items[i]));
+            if n > 1 {
+                let mut bad = p.clone();
+                bad.aunts.pop();
+                assert!(!bad.verify(&root, &items[i]));
+                let mut bad = p.clone();
+                bad.index ^= 1;
+                assert!(!bad.verify(&root, &items[i]));
+            }
+        }
+    }
+}
@@ -8,18 +8,18 @@ use std::fmt;
 use std::hash::Hasher;
 use std::iter::FromIterator;
 
-const SIZE: usize = 0x10;
+pub(crate) const SIZE: usize = 0x10;
 
-type Item = [u8; SIZE];
+pub(crate) type Item = [u8; SIZE];
 
 #[derive(Debug, Copy, Clone, Default)]
-struct XOR128 {
+pub(crate) struct XOR128 {
     data: Item,
     i: usize,
 }
 
 impl XOR128 {
-    fn new() -> XOR128 {
+    pub(crate) fn new() -> XOR128 {
         XOR128 {
             data: [0; SIZE],
             i: 0,
@@ -55,14 +55,10 @@ impl Algorithm<Item> for XOR128 {
 impl fmt::UpperHex for XOR128 {
     fn fmt(&self, f: &mut fmt::Formatter) -> fmt::Result {
         if f.alternate() {
-            if let Err(e) = f.write_str("0x") {
-                return Err(e);
-            }
+            f.write_str("0x")?;
         }
         for b in self.data.as_ref() {
-            if let Err(e) = write!(f, "{:02X}", b) {
-                return Err(e);
-            }
+            write!(f, "{:02X}", b)?;
         }
         Ok(())
     }
@@ -71,13 +67,13 @@ impl fmt::UpperHex for XOR128 {
 #[test]
 fn test_hasher_light() {
     let mut h = XOR128::new();
-    "1234567812345678".hash(&mut h);
+    h.write("1234567812345678".as_bytes());
     h.reset();
-    String::from("1234567812345678").hash(&mut h);
+    h.write("1234567812345678".as_bytes());
     assert_eq!(format!("{:#X}", h), "0x31323334353637383132333435363738");
-    String::from("1234567812345678").hash(&mut h);
+    h.write("1234567812345678".as_bytes());
     assert_eq!(format!("{:#X}", h), "0x00000000000000000000000000000000");
-    String::from("1234567812345678").hash(&mut h);
+    h.write("1234567812345678".as_bytes());
     assert_eq!(format!("{:#X}", h), "0x31323334353637383132333435363738");
 }
 
@@ -88,9 +84,9 @@ fn test_from_slice() {
     assert_eq!(
         mt.as_slice(),
         [
-            [0, 97, 114, 115, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [0, 122, 120, 99, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
-            [1, 0, 27, 10, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0],
+            [0, 3, 0, 0, 0, 0, 0, 0, 0, 97, 114, 115, 0, 0, 0, 0],
+            [0, 3, 0, 0, 0, 0, 0, 0, 0, 122, 120, 99, 0, 0, 0, 0],
+            [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 27, 10, 16, 0, 0, 0],
         ]
     );
     assert_eq!(mt.len(), 3);
@@ -98,7 +94,7 @@ fn test_from_slice() {
     assert_eq!(mt.height(), 2);
     assert_eq!(
         mt.root(),
-        [1, 0, 27, 10, 16, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0, 0]
+        [1, 0, 0, 0, 0, 0, 0, 0, 0, 0, 27, 10, 16, 0, 0, 0]
     );
 }
 
@@ -212,3 +208,611 @@ fn test_simple_tree() {
         }
     }
 }
+
+#[test]
+fn test_single_leaf_tree() {
+    let mut a = XOR128::new();
+    let mut hash = |x: &str| {
+        a.reset();
+        x.hash(&mut a);
+        a.hash()
+    };
+
+    let one: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_iter(vec![hash("a")]);
+    let two: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_iter(vec![hash("a"), hash("a")]);
+
+    assert_eq!(one.leafs(), 1);
+    assert_eq!(one.height(), 1);
+    assert_eq!(one.len(), 1);
+    assert_eq!(one.root(), one[0]);
+
+    // duplicating the single leaf hashes it with itself one level up
+    let mut a = XOR128::new();
+    assert_eq!(two.root(), a.node(one.root(), one.root()));
+
+    let p = one.gen_proof(0);
+    assert_eq!(p.lemma(), [one.root(), one.root()]);
+    assert!(p.path().is_empty());
+    assert!(p.validate::<XOR128>());
+    assert_eq!(p.root(), one.root());
+
+    for i in 0..two.leafs() {
+        let p = two.gen_proof(i);
+        assert!(p.validate::<XOR128>());
+        assert_eq!(p.item(), one.root());
+        assert_eq!(p.root(), two.root());
+    }
+}
+
+#[test]
+fn test_empty_tree() {
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::empty();
+    assert!(mt.is_empty());
+    assert_eq!(mt.len(), 0);
+    assert_eq!(mt.leafs(), 0);
+    assert_eq!(mt.height(), 0);
+    assert_eq!(mt.root(), XOR128::new().hash());
+
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_iter(Vec::new());
+    assert!(mt.is_empty());
+    assert_eq!(mt.root(), [0; 16]);
+
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_data(Vec::<String>::new());
+    assert!(mt.is_empty());
+    assert_eq!(mt.leafs(), 0);
+}
+
+#[test]
+#[should_panic(expected = "leaf index 0 is out of bounds of the tree with 0 leafs")]
+fn test_empty_tree_proof() {
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::empty();
+    mt.gen_proof(0);
+}
+
+#[test]
+fn test_index() {
+    let mut a = XOR128::new();
+    for items in 1..9 {
+        let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_iter(
+            [1, 2, 3, 4, 5, 6, 7, 8]
+                .iter()
+                .map(|x| {
+                    a.reset();
+                    x.hash(&mut a);
+                    a.hash()
+                })
+                .take(items),
+        );
+
+        assert_eq!(mt[mt.len() - 1], mt.root());
+        assert_eq!(&mt[0..mt.len()], mt.as_slice());
+        assert!(mt[1..1].is_empty());
+        assert_eq!(mt[..mt.leafs()].len(), mt.leafs());
+
+        for i in 0..mt.leafs() {
+            assert_eq!(mt[i], mt.gen_proof(i).item());
+            assert_eq!(mt[i..i + 1], mt.gen_proof(i).lemma()[..1]);
+        }
+    }
+}
+
+#[test]
+#[should_panic(expected = "node index 3 is out of bounds of the tree with 3 nodes")]
+fn test_index_out_of_bounds() {
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_iter(vec![[1; 16], [2; 16]]);
+    let _ = mt[3];
+}
+
+#[test]
+#[should_panic(expected = "node index 2..5 is out of bounds of the tree with 3 nodes")]
+fn test_index_range_out_of_bounds() {
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_iter(vec![[1; 16], [2; 16]]);
+    let _ = &mt[2..5];
+}
+
+#[test]
+fn test_leaves() {
+    let mut a = XOR128::new();
+    for items in 1..10 {
+        let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_data((0..items).map(|x| {
+            a.reset();
+            x.hash(&mut a);
+            a.hash()
+        }));
+        let leafs: Vec<[u8; 16]> = (0..items).map(|i| mt.gen_proof(i).item()).collect();
+
+        assert_eq!(mt.leaves().count(), items);
+        assert_eq!(mt.leaves().cloned().collect::<Vec<_>>(), leafs);
+        assert_eq!((&mt).into_iter().count(), items);
+
+        let mut n = 0;
+        for (i, leaf) in (&mt).into_iter().enumerate() {
+            assert_eq!(*leaf, leafs[i]);
+            n += 1;
+        }
+        assert_eq!(n, items);
+
+        assert_eq!(mt.into_iter().collect::<Vec<_>>(), leafs);
+    }
+}
+
+#[test]
+fn test_deref() {
+    let mut a = XOR128::new();
+    for items in 1..10 {
+        let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_data((0..items).map(|x| {
+            a.reset();
+            x.hash(&mut a);
+            a.hash()
+        }));
+
+        assert_eq!(&*mt, mt.as_slice());
+        assert_eq!(mt.len(), mt.as_slice().len());
+        assert_eq!(mt.iter().count(), mt.len());
+        assert_eq!(mt.first(), Some(&mt.gen_proof(0).item()));
+        assert_eq!(mt.last(), Some(&mt.root()));
+        assert_eq!(
+            mt[..mt.leafs()].to_vec(),
+            mt.leaves().cloned().collect::<Vec<_>>()
+        );
+    }
+}
+
+#[test]
+fn test_rows() {
+    let mut a = XOR128::new();
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_data((0..5).map(|x| {
+        a.reset();
+        x.hash(&mut a);
+        a.hash()
+    }));
+
+    // [l0 l1 l2 l3 l4 l4 | n01 n23 n44 n44 | n0123 n4444 | root]
+    assert_eq!(mt.height(), 4);
+    assert_eq!(mt.row(0), &mt[0..5]);
+    assert_eq!(mt.row(1), &mt[6..9]);
+    assert_eq!(mt.row(2), &mt[10..12]);
+    assert_eq!(mt.row(3), [mt.root()]);
+
+    let rows: Vec<&[[u8; 16]]> = mt.rows().collect();
+    assert_eq!(rows.len(), mt.height());
+    assert_eq!(rows[0], mt.leaves().as_slice());
+    assert_eq!(
+        rows.iter().map(|r| r.len()).collect::<Vec<_>>(),
+        [5, 3, 2, 1]
+    );
+
+    for items in 1..17 {
+        let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_data(0..items);
+        assert_eq!(mt.rows().count(), mt.height());
+        assert_eq!(mt.row(0).len(), items);
+        assert_eq!(mt.row(mt.height() - 1), [mt.root()]);
+
+        // a sibling from the proof is either in the row or fills it up
+        for i in 0..items {
+            let p = mt.gen_proof(i);
+            for (level, row) in mt.rows().take(mt.height() - 1).enumerate() {
+                let j = (i >> level) ^ 1;
+                let sibling = &p.lemma()[level + 1];
+                assert!(row.get(j).unwrap_or_else(|| &row[j - 1]) == sibling);
+            }
+        }
+    }
+
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::empty();
+    assert_eq!(mt.rows().count(), 0);
+}
+
+#[test]
+#[should_panic(expected = "level 2 is out of bounds of the tree of height 2")]
+fn test_row_out_of_bounds() {
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_data(0..2);
+    mt.row(2);
+}
+
+#[test]
+fn test_leaf_and_node() {
+    for items in 1..17 {
+        let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_data(0..items);
+
+        for i in 0..items {
+            assert_eq!(mt.leaf(i), Some(&mt[i]));
+            assert_eq!(mt.leaf(i), Some(&mt.gen_proof(i).item()));
+        }
+        // boundary and the duplicated node of an odd leafs row
+        assert_eq!(mt.leaf(items), None);
+        assert_eq!(mt.leaf(items + 1), None);
+
+        for level in 0..mt.height() {
+            let row = mt.row(level);
+            for (index, node) in row.iter().enumerate() {
+                assert_eq!(mt.node(level, index), Some(node));
+            }
+            assert_eq!(mt.node(level, row.len()), None);
+        }
+
+        let top = mt.height() - 1;
+        assert_eq!(mt.node(top, 0), Some(&mt.root()));
+        assert_eq!(mt.node(top, 1), None);
+        assert_eq!(mt.node(mt.height(), 0), None);
+    }
+
+    let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::empty();
+    assert_eq!(mt.leaf(0), None);
+    assert_eq!(mt.node(0, 0), None);
+}
+
+#[test]
+fn test_layout_indices() {
+    for items in 0..40 {
+        let mt: MerkleTree<[u8; 16], XOR128> = MerkleTree::from_data(0..items);
+
+        for i in 0..mt.len() {
+            let level = mt.level_of(i).unwrap();
+            assert!(level < mt.height());
+
+            match mt.parent_index(i) {
+                Some(parent) => {
+                    assert_eq!(mt.level_of(parent), Some(level + 1));
+                    let (left, right) = mt.children_indices(parent).unwrap();
+                    assert!(i == left || i == right);
+                    assert_eq!(mt.sibling_index(i), Some(left + right - i));
+                    assert_eq!(mt[left + right - i], mt[mt.sibling_index(i).unwrap()]);
+                }
+                None => {
+                    assert_eq!(i, mt.len() - 1);
+                    assert_eq!(mt.sibling_index(i), None);
+                }
+            }
+
+            if let Some((left, right)) = mt.children_indices(i) {
+                assert_eq!(right, left + 1);
+                assert_eq!(mt.parent_index(left), Some(i));
+                assert_eq!(mt.parent_index(right), Some(i));
+                assert_eq!(mt.level_of(left), Some(level - 1));
+            } else {
+                // leafs and the duplicates filling up odd levels
+                assert!(level == 0 || mt[i] == mt[i - 1] && mt.level_of(i + 1) == Some(level + 1));
+            }
+        }
+
+        assert_eq!(mt.level_of(mt.len()), None);
+        assert_eq!(mt.parent_index(mt.len()), None);
+        assert_eq!(mt.sibling_index(mt.len()), None);
+        assert_eq!(mt.children_indices(mt.len()), None);
+    }
+}
+
+#[test]
+fn test_rehash_wide() {
+    use crate::test_item::Item as Word;
+    use std::collections::hash_map::DefaultHasher;
+
+    let mt: MerkleTree<Word, DefaultHasher> = MerkleTree::from_data(0..13u64);
+    let wide = |h: &Word| {
+        let mut x = [0; SIZE];
+        x[..8].copy_from_slice(h.as_ref());
+        x
+    };
+
+    let xt: MerkleTree<Item, XOR128> = mt.rehash(wide, XOR128::new());
+    assert_eq!((xt.leafs(), xt.height()), (mt.leafs(), mt.height()));
+    assert_eq!(xt.len(), mt.len());
+    let leaves: Vec<Item> = mt.leaves().map(wide).collect();
+    assert!(xt.leaves().eq(leaves.iter()));
+    assert!(xt.verify());
+
+    let mut a = XOR128::new();
+    assert_eq!(xt[14], a.node(xt[0], xt[1]));
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde() {
+    for items in 0..18u64 {
+        let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..items);
+
+        let json = serde_json::to_string(&mt).unwrap();
+        let from_json: MerkleTree<Item, XOR128> = serde_json::from_str(&json).unwrap();
+        let bin = bincode::serialize(&mt).unwrap();
+        let from_bin: MerkleTree<Item, XOR128> = bincode::deserialize(&bin).unwrap();
+
+        for t in &[from_json, from_bin] {
+            assert_eq!(t.as_slice(), mt.as_slice());
+            assert_eq!((t.leafs(), t.height()), (mt.leafs(), mt.height()));
+            assert_eq!(t.root(), mt.root());
+            for i in 0..mt.leafs() {
+                assert_eq!(t.gen_proof(i), mt.gen_proof(i));
+            }
+        }
+
+        let p = serde_json::to_string(&mt.try_gen_proof(0).ok()).unwrap();
+        let p: Option<crate::proof::Proof<Item>> = serde_json::from_str(&p).unwrap();
+        assert_eq!(p, mt.try_gen_proof(0).ok());
+    }
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_deserialize_verified() {
+    #[derive(serde::Deserialize)]
+    struct Verified {
+        #[serde(deserialize_with = "MerkleTree::deserialize_verified")]
+        tree: MerkleTree<Item, XOR128>,
+    }
+    let decode = |bytes: &[u8]| bincode::deserialize::<Verified>(bytes).map(|v| v.tree);
+
+    for items in 2..18u64 {
+        let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..items);
+        let bytes = bincode::serialize(&mt).unwrap();
+        assert_eq!(decode(&bytes).unwrap().as_slice(), mt.as_slice());
+
+        // nodes follow the number of leafs, the height and the length of
+        // the nodes, leaf and internal alike
+        for i in 0..mt.len() {
+            let mut tampered = bytes.clone();
+            tampered[24 + SIZE * i] ^= 1;
+
+            let trusted: MerkleTree<Item, XOR128> = bincode::deserialize(&tampered).unwrap();
+            let e = decode(&tampered).unwrap_err();
+            let expected = trusted.validate().unwrap_err();
+            assert_eq!(e.to_string(), expected.to_string());
+        }
+    }
+
+    // length of the nodes far beyond the stream
+    let mut bytes = bincode::serialize(&MerkleTree::<Item, XOR128>::from_data(0..3u64)).unwrap();
+    bytes[16..24].copy_from_slice(&(u64::MAX >> 8).to_le_bytes());
+    assert!(decode(&bytes).is_err());
+
+    // and beyond the 7 nodes of the tree over 3 leafs, failing at the 8th
+    bytes.extend_from_slice(&[0; 4 * SIZE]);
+    let bounded = "invalid length 8, expected sequence of at most 7 elements";
+    assert_eq!(decode(&bytes).unwrap_err().to_string(), bounded);
+    let e = bincode::deserialize::<MerkleTree<Item, XOR128>>(&bytes).unwrap_err();
+    assert_eq!(e.to_string(), bounded);
+
+    // leafs past the address space lay out no tree
+    bytes[..8].copy_from_slice(&u64::MAX.to_le_bytes());
+    assert!(decode(&bytes).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_proof_serde() {
+    use crate::proof::{Proof, MAX_PATH};
+
+    let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..13u64);
+    for i in 0..mt.leafs() {
+        let p = mt.gen_proof(i);
+        let json: Proof<Item> = serde_json::from_str(&serde_json::to_string(&p).unwrap()).unwrap();
+        let bin: Proof<Item> = bincode::deserialize(&bincode::serialize(&p).unwrap()).unwrap();
+        assert_eq!(json, p);
+        assert_eq!(bin, p);
+
+        // proof of the references serializes the same
+        let r = mt.gen_proof_ref(i);
+        assert_eq!(
+            serde_json::to_string(&r).unwrap(),
+            serde_json::to_string(&p).unwrap()
+        );
+        assert_eq!(
+            bincode::serialize(&r).unwrap(),
+            bincode::serialize(&p).unwrap()
+        );
+    }
+
+    let parse = |lemma: usize, path: usize| {
+        let lemma = serde_json::to_string(&vec![[0u8; SIZE]; lemma]).unwrap();
+        let position = format!(r#""index":0,"depth":{}"#, path);
+        let path = serde_json::to_string(&vec![true; path]).unwrap();
+        let json = format!(r#"{{"lemma":{},"path":{},{}}}"#, lemma, path, position);
+        serde_json::from_str::<Proof<Item>>(&json)
+    };
+    assert!(parse(2, 0).is_ok());
+    assert!(parse(MAX_PATH + 2, MAX_PATH).is_ok());
+
+    // would panic in the constructor or in validation
+    let e = parse(3, 2).unwrap_err().to_string();
+    assert!(e.starts_with("lemma of 3 hashes does not fit the path of 2 steps"));
+    assert!(parse(1, 0).is_err());
+    assert!(parse(0, 0).is_err());
+
+    let e = parse(MAX_PATH + 3, MAX_PATH + 1).unwrap_err().to_string();
+    assert!(e.starts_with("invalid length 67, expected sequence of at most 66"));
+
+    // position the path cannot reach
+    let json = serde_json::to_string(&mt.gen_proof(1)).unwrap();
+    let moved = json.replace(r#""index":1,"#, r#""index":16,"#);
+    let e = serde_json::from_str::<Proof<Item>>(&moved).unwrap_err();
+    let invalid = "proof leaf index or depth is invalid";
+    assert!(e.to_string().starts_with(invalid));
+
+    // length of the lemma far beyond the stream
+    let mut bytes = bincode::serialize(&mt.gen_proof(0)).unwrap();
+    bytes[..8].copy_from_slice(&(u64::MAX >> 8).to_le_bytes());
+    assert!(bincode::deserialize::<Proof<Item>>(&bytes).is_err());
+}
+
+#[cfg(feature = "serde")]
+#[test]
+fn test_serde_invalid_layout() {
+    let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..5u64);
+    let (mut data, leafs, height) = mt.into_parts();
+
+    let json = |data: &[Item], leafs: usize, height: usize| {
+        let data = serde_json::to_string(data).unwrap();
+        let layout = format!(r#""leafs":{},"height":{}"#, leafs, height);
+        format!(r#"{{"data":{},{}}}"#, data, layout)
+    };
+    let parse = |s: &str| serde_json::from_str::<MerkleTree<Item, XOR128>>(s);
+
+    assert!(parse(&json(&data, leafs, height)).is_ok());
+    let e = parse(&json(&data, leafs + 3, height)).unwrap_err();
+    assert!(e
+        .to_string()
+        .starts_with("13 nodes of height 4 do not lay out a merkle tree over 8 leafs"));
+
+    // nodes past the layout of the leafs ahead of them fail as they are read
+    let ahead = |data: &[Item], leafs: usize| {
+        let data = serde_json::to_string(data).unwrap();
+        let layout = format!(r#""leafs":{},"height":{}"#, leafs, height);
+        format!(r#"{{{},"data":{}}}"#, layout, data)
+    };
+    assert!(parse(&ahead(&data, leafs)).is_ok());
+    let e = parse(&ahead(&data, 2)).unwrap_err().to_string();
+    assert!(e.starts_with("invalid length 4, expected sequence of at most 3 elements"));
+    assert!(parse(&json(&data, leafs, height + 1)).is_err());
+
+    data.pop();
+    assert!(parse(&json(&data, leafs, height)).is_err());
+    assert!(parse(r#"{"data":[],"leafs":0}"#).is_err());
+}
+
+#[test]
+fn test_serialize_leaves() {
+    for items in 0..18u64 {
+        let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..items);
+        let mut bytes = Vec::new();
+        mt.serialize_leaves(&mut bytes).unwrap();
+        assert_eq!(bytes.len(), 12 + mt.leafs() * SIZE);
+
+        let loaded: MerkleTree<Item, XOR128> = MerkleTree::deserialize_leaves(&bytes[..]).unwrap();
+        assert_eq!(loaded.as_slice(), mt.as_slice());
+        assert_eq!(loaded.root(), mt.root());
+
+        if items > 0 {
+            // nodes are rehashed rather than trusted
+            let mut corrupted = bytes.clone();
+            corrupted[12] ^= 1;
+            let loaded: MerkleTree<Item, XOR128> =
+                MerkleTree::deserialize_leaves(&corrupted[..]).unwrap();
+            assert_ne!(loaded.root(), mt.root());
+
+            // end of stream in the middle of the last leaf
+            let truncated = &bytes[..bytes.len() - 1];
+            let e = MerkleTree::<Item, XOR128>::deserialize_leaves(truncated).unwrap_err();
+            assert_eq!(e.kind(), std::io::ErrorKind::UnexpectedEof);
+        }
+    }
+
+    let mut bytes = Vec::new();
+    bytes.extend_from_slice(&1u64.to_le_bytes());
+    bytes.extend_from_slice(&8u32.to_le_bytes());
+    bytes.extend_from_slice(&[0; 8]);
+    let e = MerkleTree::<Item, XOR128>::deserialize_leaves(&bytes[..]).unwrap_err();
+    assert_eq!(e.kind(), std::io::ErrorKind::InvalidData);
+}
+
+#[test]
+fn test_proof_bytes() {
+    use crate::proof::{Proof, ProofDecodeError};
+
+    for items in 1..18u64 {
+        let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..items);
+        for i in 0..mt.leafs() {
+            let p = mt.gen_proof(i);
+            let bytes = p.to_bytes();
+            let path = p.path().len().div_ceil(8);
+            assert_eq!(bytes.len(), 3 + path + SIZE * p.lemma().len());
+            assert_eq!(Proof::<Item>::from_bytes(&bytes), Ok(p));
+
+            for len in 0..bytes.len() {
+                let e = Proof::<Item>::from_bytes(&bytes[..len]);
+                assert_eq!(e, Err(ProofDecodeError::Truncated));
+            }
+            let mut trailing = bytes.clone();
+            trailing.extend_from_slice(&[0, 0]);
+            let e = Proof::<Item>::from_bytes(&trailing);
+            assert_eq!(e, Err(ProofDecodeError::TrailingBytes(2)));
+        }
+    }
+}
+
+#[test]
+fn test_proof_bytes_golden() {
+    use crate::proof::{Proof, ProofDecodeError};
+
+    let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..3u64);
+    let p = mt.gen_proof(2);
+    assert_eq!(p.path(), [true, false]);
+
+    // digest length 16, no flags, path length 2, path 0b01, lemma of 4 hashes
+    let golden = [
+        "10",
+        "00",
+        "02",
+        "01",
+        "00020000000000000000000000000000",
+        "00020000000000000000000000000000",
+        "01000100000000000000000000000000",
+        "01000001000000000000000000000000",
+    ];
+    let hex: String = p.to_bytes().iter().map(|b| format!("{:02x}", b)).collect();
+    assert_eq!(hex, golden.concat());
+
+    let bytes = p.to_bytes();
+    let decode = |f: &dyn Fn(&mut Vec<u8>)| {
+        let mut bytes = bytes.clone();
+        f(&mut bytes);
+        Proof::<Item>::from_bytes(&bytes).unwrap_err()
+    };
+    assert_eq!(
+        decode(&|b| b[0] = 32),
+        ProofDecodeError::DigestLength {
+            expected: 16,
+            found: 32
+        }
+    );
+    assert_eq!(decode(&|b| b[1] = 0x80), ProofDecodeError::Flags(0x80));
+    assert_eq!(decode(&|b| b[2] = 65), ProofDecodeError::PathLength);
+    let overlong = decode(&|b| b.splice(2..3, [0x82, 0x00]).for_each(drop));
+    assert_eq!(overlong, ProofDecodeError::PathLength);
+    assert_eq!(decode(&|b| b[3] = 0b101), ProofDecodeError::PathPadding);
+
+    // leaf index 8 does not fit the depth of 3
+    let position = |index: u8, depth: u8| {
+        decode(&|b| {
+            b[1] = 0x01;
+            b.splice(3..3, [index, depth]).for_each(drop);
+        })
+    };
+    assert_eq!(position(8, 3), ProofDecodeError::Position);
+    assert_eq!(position(2, 1), ProofDecodeError::Position);
+    let p = p.with_position(4, 3);
+    assert_eq!(Proof::<Item>::from_bytes(&p.to_bytes()), Ok(p));
+}
+
+#[test]
+fn test_proof_hex() {
+    use crate::proof::{Proof, ProofDecodeError};
+
+    for items in 1..18u64 {
+        let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..items);
+        for i in 0..mt.leafs() {
+            let p = mt.gen_proof(i);
+            let hex = p.to_hex();
+            assert_eq!(Proof::<Item>::from_hex(&hex), Ok(p.clone()));
+            assert_eq!(Proof::<Item>::from_hex(&hex[2..].to_uppercase()), Ok(p));
+        }
+    }
+
+    // proof of the last of 3 leafs, as in the compact encoding
+    let mt: MerkleTree<Item, XOR128> = MerkleTree::from_data(0..3u64);
+    let p = mt.gen_proof(2);
+    let lemma = [
+        "0x00020000000000000000000000000000",
+        "0x00020000000000000000000000000000",
+        "0x01000100000000000000000000000000",
+        "0x01000001000000000000000000000000",
+    ];
+    assert_eq!(p.to_hex_lemma(), lemma);
+    let hex = format!("0x10000201{}", lemma.concat().replace("0x", ""));
+    assert_eq!(p.to_hex(), hex);
+    let decode = |s: &str| Proof::<Item>::from_hex(s).unwrap_err();
+    assert_eq!(decode(&hex.replace("0x1", "0X1")), ProofDecodeError::Hex);
+    assert_eq!(decode(&hex[..hex.len() - 1]), ProofDecodeError::Hex);
+    let invalid = hex.replace("01000001", "0100000g");
+    assert_eq!(decode(&invalid), ProofDecodeError::Hex);
+    assert_eq!(decode(&hex[..hex.len() - 2]), ProofDecodeError::Truncated);
+}
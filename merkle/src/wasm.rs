@@ -0,0 +1,104 @@
+// bindings wasm_bindgen generates for wasm32 are of unsafe code
+#![allow(unsafe_code)]
+
+extern crate alloc;
+
+use alloc::format;
+use alloc::string::{String, ToString};
+use alloc::vec::Vec;
+use wasm_bindgen::prelude::wasm_bindgen;
+use crate::fixed_hash::FixedHashError;
+use crate::hash::Algorithm;
+use crate::merkle::MerkleTree;
+use crate::proof::Proof;
+use crate::sha256::{Sha256Algorithm, Sha256Hash};
+
+/// Merkle tree of the [`Sha256Algorithm`] for JavaScript, over the leafs
+/// given in hex, so that its roots and proofs are those of the tree built
+/// natively with `MerkleTree::<Sha256Hash, Sha256Algorithm>::new` of the
+/// same leafs.
+///
+/// Hashes are `0x` prefixed lowercase hex, and proofs are the hex of
+/// [`Proof::to_hex`]. Errors are thrown as the strings of their messages.
+///
+/// ```
+/// use merkle_light::wasm::{verify_hex, WasmTree};
+///
+/// let leafs: Vec<String> = (0..3u8).map(|i| format!("{:02x}", i).repeat(32)).collect();
+/// let tree = WasmTree::from_leaf_hex(leafs.clone()).unwrap();
+/// let proof = tree.proof_hex(1).unwrap();
+/// assert!(verify_hex(&tree.root_hex(), &leafs[1], &proof));
+/// assert!(!verify_hex(&tree.root_hex(), &leafs[0], &proof));
+/// ```
+#[wasm_bindgen]
+#[derive(Debug, Clone)]
+pub struct WasmTree {
+    tree: MerkleTree<Sha256Hash, Sha256Algorithm>,
+}
+
+#[wasm_bindgen]
+impl WasmTree {
+    /// Builds the tree over the leafs of 32 bytes in hex, with or without
+    /// the `0x` prefix.
+    ///
+    /// Fails if a leaf is not the hex of 32 bytes, or the leafs are too
+    /// many for the tree.
+    pub fn from_leaf_hex(leafs: Vec<String>) -> Result<WasmTree, String> {
+        let leafs = leafs
+            .iter()
+            .map(|leaf| parse(leaf))
+            .collect::<Result<Vec<Sha256Hash>, String>>()?;
+        let tree = MerkleTree::try_new(leafs).map_err(|e| e.to_string())?;
+        Ok(WasmTree { tree })
+    }
+
+    /// Returns the root of the tree.
+    pub fn root_hex(&self) -> String {
+        hex(&self.tree.root())
+    }
+
+    /// Returns the number of the leafs of the tree.
+    pub fn leafs(&self) -> usize {
+        self.tree.leafs()
+    }
+
+    /// Returns the inclusion proof of the leaf `i`.
+    ///
+    /// Fails unless `i` is less than the number of the leafs.
+    pub fn proof_hex(&self, i: usize) -> Result<String, String> {
+        self.tree
+            .try_gen_proof(i)
+            .map(|proof| proof.to_hex())
+            .map_err(|e| e.to_string())
+    }
+}
+
+/// Verifies the inclusion proof `proof` of [`WasmTree::proof_hex`] of the
+/// leaf `leaf`, as given to [`WasmTree::from_leaf_hex`], against the
+/// trusted root `root`.
+///
+/// Returns `false` if any of them does not parse.
+#[wasm_bindgen]
+pub fn verify_hex(root: &str, leaf: &str, proof: &str) -> bool {
+    let (root, leaf) = match (parse(root), parse(leaf)) {
+        (Ok(root), Ok(leaf)) => (root, leaf),
+        _ => return false,
+    };
+    match Proof::<Sha256Hash>::from_hex(proof) {
+        Ok(proof) => {
+            let leaf = Sha256Algorithm::new().leaf(leaf);
+            proof.validate_leaf_against::<Sha256Algorithm>(&leaf, &root)
+        }
+        Err(_) => false,
+    }
+}
+
+/// Parses the hash of the hex, with or without the `0x` prefix.
+fn parse(s: &str) -> Result<Sha256Hash, String> {
+    s.parse().map_err(|e: FixedHashError| e.to_string())
+}
+
+/// Returns the `0x` prefixed lowercase hex of the hash.
+fn hex(h: &Sha256Hash) -> String {
+    format!("{:#}", h)
+}
@@ -7,7 +7,7 @@ extern crate merkle_light;
 use std::fmt;
 use std::hash::Hasher;
 use std::iter::FromIterator;
-use merkle_light::hash::{Algorithm, Hashable};
+use merkle_light::hash::Algorithm;
 use merkle_light::merkle::MerkleTree;
 use crypto::sha2::Sha256;
 use crypto::digest::Digest;
@@ -97,7 +97,7 @@ impl<'a> fmt::Display for HexSlice<'a> {
 #[test]
 fn test_crypto_bitcoin_leaf_hash() {
     let mut a = CryptoBitcoinAlgorithm::new();
-    "hello".hash(&mut a);
+    a.write(b"hello");
     let h1 = a.hash();
     assert_eq!(
         format!("{}", HexSlice::new(h1.as_ref())),
@@ -0,0 +1,154 @@
+#![cfg(feature = "ffi")]
+
+extern crate merkle_light;
+
+use merkle_light::ffi::MerkleStatus;
+use merkle_light::merkle::MerkleTree;
+use merkle_light::sha256::{Sha256Algorithm, Sha256Hash};
+use std::ptr;
+
+/// Tree opaque to the callers, as `MerkleTreeHandle` of the header.
+#[repr(C)]
+struct MerkleTreeHandle {
+    _private: [u8; 0],
+}
+
+// functions of the C ABI as the C callers declare them, see
+// `include/merkle_light.h`
+extern "C" {
+    fn merkle_tree_new(leaves: *const u8, leaf_len: usize, count: usize) -> *mut MerkleTreeHandle;
+    fn merkle_tree_root(tree: *const MerkleTreeHandle, out: *mut u8) -> MerkleStatus;
+    fn merkle_tree_proof(
+        tree: *const MerkleTreeHandle,
+        index: usize,
+        out: *mut u8,
+        out_len: *mut usize,
+    ) -> MerkleStatus;
+    fn merkle_proof_verify(
+        root: *const u8,
+        leaf: *const u8,
+        leaf_len: usize,
+        proof: *const u8,
+        proof_len: usize,
+    ) -> MerkleStatus;
+    fn merkle_tree_free(tree: *mut MerkleTreeHandle);
+}
+
+/// Records `[i; 8]` for `i` in `0..n`, one after another.
+fn records(n: u8) -> Vec<u8> {
+    (0..n).flat_map(|i| [i; 8]).collect()
+}
+
+fn root(tree: *const MerkleTreeHandle) -> [u8; 32] {
+    let mut root = [0; 32];
+    assert_eq!(
+        unsafe { merkle_tree_root(tree, root.as_mut_ptr()) },
+        MerkleStatus::Ok
+    );
+    root
+}
+
+/// Returns the proof of the leaf `i`, asking for its length first.
+fn proof(tree: *const MerkleTreeHandle, i: usize) -> Vec<u8> {
+    let mut len = 0;
+    let status = unsafe { merkle_tree_proof(tree, i, ptr::null_mut(), &mut len) };
+    assert_eq!(status, MerkleStatus::BufferTooSmall);
+
+    let mut proof = vec![0; len];
+    let status = unsafe { merkle_tree_proof(tree, i, proof.as_mut_ptr(), &mut len) };
+    assert_eq!(status, MerkleStatus::Ok);
+    assert_eq!(len, proof.len());
+    proof
+}
+
+fn verify(root: &[u8; 32], leaf: &[u8], proof: &[u8]) -> MerkleStatus {
+    unsafe {
+        merkle_proof_verify(
+            root.as_ptr(),
+            leaf.as_ptr(),
+            leaf.len(),
+            proof.as_ptr(),
+            proof.len(),
+        )
+    }
+}
+
+#[test]
+fn test_ffi_roots() {
+    for n in [0, 1, 2, 5, 8] {
+        let data = records(n);
+        let tree = unsafe { merkle_tree_new(data.as_ptr(), 8, n as usize) };
+        assert!(!tree.is_null());
+
+        // roots are of the tree over the records as byte arrays
+        let native: MerkleTree<Sha256Hash, Sha256Algorithm> =
+            MerkleTree::from_data((0..n).map(|i| [i; 8]));
+        assert_eq!(root(tree), native.root().0, "tree of {} leafs", n);
+        unsafe { merkle_tree_free(tree) };
+    }
+
+    // empty tree of no records at all
+    let tree = unsafe { merkle_tree_new(ptr::null(), 8, 0) };
+    assert!(!tree.is_null());
+    unsafe { merkle_tree_free(tree) };
+}
+
+#[test]
+fn test_ffi_prove_verify() {
+    let data = records(7);
+    let tree = unsafe { merkle_tree_new(data.as_ptr(), 8, 7) };
+    let root = root(tree);
+
+    let proofs: Vec<Vec<u8>> = (0..7).map(|i| proof(tree, i)).collect();
+    // the caller owns the bytes of the proofs, the tree is not needed to
+    // verify them
+    unsafe { merkle_tree_free(tree) };
+
+    for (i, (leaf, proof)) in data.chunks(8).zip(&proofs).enumerate() {
+        assert_eq!(verify(&root, leaf, proof), MerkleStatus::Ok);
+        assert_eq!(verify(&root, &[7; 8], proof), MerkleStatus::InvalidProof);
+        assert_eq!(
+            verify(&root, leaf, &proofs[(i + 1) % 7]),
+            MerkleStatus::InvalidProof
+        );
+        assert_eq!(verify(&[0; 32], leaf, proof), MerkleStatus::InvalidProof);
+        assert_eq!(
+            verify(&root, leaf, &proof[..proof.len() - 1]),
+            MerkleStatus::InvalidProof
+        );
+    }
+}
+
+#[test]
+fn test_ffi_errors() {
+    let data = records(3);
+    let tree = unsafe { merkle_tree_new(data.as_ptr(), 8, 3) };
+
+    let mut len = 0;
+    let status = unsafe { merkle_tree_proof(tree, 3, ptr::null_mut(), &mut len) };
+    assert_eq!(status, MerkleStatus::IndexOutOfBounds);
+
+    // short buffer is not written to, and the length is of the proof
+    let mut short = vec![0xff; proof(tree, 0).len() - 1];
+    len = short.len();
+    let status = unsafe { merkle_tree_proof(tree, 0, short.as_mut_ptr(), &mut len) };
+    assert_eq!(status, MerkleStatus::BufferTooSmall);
+    assert_eq!(len, short.len() + 1);
+    assert!(short.iter().all(|&b| b == 0xff));
+
+    let status = unsafe { merkle_tree_proof(tree, 0, short.as_mut_ptr(), ptr::null_mut()) };
+    assert_eq!(status, MerkleStatus::NullPointer);
+    let status = unsafe { merkle_tree_root(tree, ptr::null_mut()) };
+    assert_eq!(status, MerkleStatus::NullPointer);
+    let status = unsafe { merkle_tree_root(ptr::null(), [0; 32].as_mut_ptr()) };
+    assert_eq!(status, MerkleStatus::NullPointer);
+    let status = unsafe { merkle_proof_verify(ptr::null(), data.as_ptr(), 8, data.as_ptr(), 8) };
+    assert_eq!(status, MerkleStatus::NullPointer);
+    unsafe { merkle_tree_free(tree) };
+
+    // null records, and records of more bytes than there are
+    assert!(unsafe { merkle_tree_new(ptr::null(), 8, 1) }.is_null());
+    assert!(unsafe { merkle_tree_new(data.as_ptr(), usize::MAX, 2) }.is_null());
+    // freeing null does nothing
+    unsafe { merkle_tree_free(ptr::null_mut()) };
+}
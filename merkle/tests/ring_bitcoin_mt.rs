@@ -107,7 +107,7 @@ impl<'a> fmt::Display for HexSlice<'a> {
 #[test]
 fn test_ring_bitcoin_leaf_hash() {
     let mut a = RingBitcoinAlgorithm::new();
-    "hello".hash(&mut a);
+    a.write(b"hello");
     let h1 = a.hash();
     assert_eq!(
         format!("{}", HexSlice::new(h1.as_ref())),
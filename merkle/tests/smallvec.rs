@@ -0,0 +1,110 @@
+#![cfg(feature = "smallvec")]
+
+extern crate merkle_light;
+extern crate sha2;
+
+use merkle_light::hash::Algorithm;
+use merkle_light::merkle::MerkleTree;
+use merkle_light::proof::{Lemma, Path, Proof, INLINE_PATH};
+use sha2::{Digest, Sha256};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+use std::hash::Hasher;
+use std::iter::FromIterator;
+
+/// System allocator which counts the allocations of every thread.
+struct Counting;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for Counting {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|a| a.set(a.get() + 1));
+        System.alloc(layout)
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        System.dealloc(ptr, layout)
+    }
+}
+
+#[global_allocator]
+static GLOBAL: Counting = Counting;
+
+/// Returns the result of `f` and the allocations it makes.
+fn allocations<R, F: FnOnce() -> R>(f: F) -> (R, usize) {
+    let before = ALLOCATIONS.with(|a| a.get());
+    let r = f();
+    (r, ALLOCATIONS.with(|a| a.get()) - before)
+}
+
+/// SHA-256 over the hashes of 32 bytes.
+#[derive(Clone, Default)]
+struct A(Sha256);
+
+impl Hasher for A {
+    fn write(&mut self, msg: &[u8]) {
+        self.0.update(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        unimplemented!()
+    }
+}
+
+impl Algorithm<[u8; 32]> for A {
+    fn hash(&mut self) -> [u8; 32] {
+        self.0.finalize_reset().into()
+    }
+}
+
+#[test]
+fn test_tree_proofs_do_not_allocate() {
+    for n in [1u32, 2, 3, 5, 8, 100, 1 << 16] {
+        let mt: MerkleTree<[u8; 32], A> =
+            MerkleTree::from_iter((0..n).map(|i| Sha256::digest(i.to_le_bytes()).into()));
+        for i in [0, n as usize / 2, n as usize - 1] {
+            let (p, count) = allocations(|| mt.gen_proof(i));
+            assert_eq!(count, 0, "proof of {} of {} leafs", i, n);
+            let (valid, count) = allocations(|| p.clone().validate::<A>());
+            assert!(valid);
+            assert_eq!(count, 0);
+        }
+    }
+}
+
+#[test]
+fn test_longest_inline_proof() {
+    // proof of the leaf 0 of the tree of 2^32 leafs of the same hash
+    let mut a = A::default();
+    let mut lemma = Lemma::new();
+    let mut h = a.leaf([7; 32]);
+    lemma.push(h);
+    for _ in 0..INLINE_PATH {
+        lemma.push(h);
+        a.reset();
+        h = a.node(h, h);
+    }
+    lemma.push(h);
+    let path: Path = (0..INLINE_PATH).map(|_| true).collect();
+
+    let (p, count) = allocations(|| Proof::new(lemma.clone(), path.clone()));
+    assert_eq!(count, 0);
+    assert_eq!(p.lemma().len(), INLINE_PATH + 2);
+    assert!(p.validate::<A>());
+
+    // proofs of the vectors are the same
+    assert_eq!(Proof::new(lemma.to_vec(), path.to_vec()), p);
+
+    // one step longer spills to the heap
+    let (_, count) = allocations(|| {
+        let mut lemma = lemma.clone();
+        lemma.push(h);
+        let mut path = path.clone();
+        path.push(true);
+        Proof::new(lemma, path)
+    });
+    assert!(count > 0);
+}
@@ -0,0 +1,79 @@
+#![cfg(feature = "wasm")]
+
+extern crate merkle_light;
+extern crate wasm_bindgen_test;
+
+use merkle_light::fixed_hash::FixedHash;
+use merkle_light::merkle::MerkleTree;
+use merkle_light::sha256::{Sha256Algorithm, Sha256Hash};
+use merkle_light::wasm::{verify_hex, WasmTree};
+use wasm_bindgen_test::wasm_bindgen_test;
+
+// tests run natively as well, and in the browser or node with wasm-pack:
+// `wasm-pack test --node merkle -- --features wasm`
+
+/// Hex of the items `[i; 32]` for `i` in `0..n`.
+fn leafs(n: u8) -> Vec<String> {
+    (0..n).map(|i| format!("{:02x}", i).repeat(32)).collect()
+}
+
+#[wasm_bindgen_test(unsupported = test)]
+fn test_wasm_roots() {
+    // roots of the native tree over the same leafs
+    assert_eq!(
+        WasmTree::from_leaf_hex(leafs(0)).unwrap().root_hex(),
+        "0xe3b0c44298fc1c149afbf4c8996fb92427ae41e4649b934ca495991b7852b855"
+    );
+    assert_eq!(
+        WasmTree::from_leaf_hex(leafs(5)).unwrap().root_hex(),
+        "0x2d331714d5160948ab3e13bed57544502653b85d90b7d71f98580eeb835b9c06"
+    );
+}
+
+#[wasm_bindgen_test(unsupported = test)]
+fn test_wasm_prove_verify() {
+    let leafs = leafs(7);
+    let tree = WasmTree::from_leaf_hex(leafs.clone()).unwrap();
+    let root = tree.root_hex();
+    assert_eq!(tree.leafs(), 7);
+
+    for (i, leaf) in leafs.iter().enumerate() {
+        let proof = tree.proof_hex(i).unwrap();
+        assert!(verify_hex(&root, leaf, &proof));
+        assert!(verify_hex(
+            &root,
+            &format!("0x{}", leaf.to_uppercase()),
+            &proof
+        ));
+        assert!(!verify_hex(&root, &leafs[(i + 1) % 7], &proof));
+        assert!(!verify_hex(&leafs[0], leaf, &proof));
+    }
+    assert!(tree.proof_hex(7).is_err());
+}
+
+#[wasm_bindgen_test(unsupported = test)]
+fn test_wasm_native_proofs() {
+    let tree = WasmTree::from_leaf_hex(leafs(3)).unwrap();
+    let native: MerkleTree<Sha256Hash, Sha256Algorithm> =
+        MerkleTree::new((0..3).map(|i| FixedHash([i; 32])));
+    assert_eq!(tree.root_hex(), format!("{:#}", native.root()));
+    for i in 0..3 {
+        let proof = native.gen_proof(i).to_hex();
+        assert_eq!(tree.proof_hex(i).unwrap(), proof);
+        assert!(verify_hex(&tree.root_hex(), &leafs(3)[i], &proof));
+    }
+}
+
+#[wasm_bindgen_test(unsupported = test)]
+fn test_wasm_malformed() {
+    assert!(WasmTree::from_leaf_hex(vec!["0x00".to_string()]).is_err());
+    assert!(WasmTree::from_leaf_hex(vec!["zz".repeat(32)]).is_err());
+
+    let leafs = leafs(2);
+    let tree = WasmTree::from_leaf_hex(leafs.clone()).unwrap();
+    let (root, proof) = (tree.root_hex(), tree.proof_hex(0).unwrap());
+    assert!(!verify_hex("0x00", &leafs[0], &proof));
+    assert!(!verify_hex(&root, "", &proof));
+    assert!(!verify_hex(&root, &leafs[0], "0x0"));
+    assert!(!verify_hex(&root, &leafs[0], &proof[..proof.len() - 2]));
+}
@@ -1,78 +1,224 @@
+//! `#[derive(Hashable)]` for the `merkle_light` `Hashable`, re-exported by
+//! the `derive` feature of `merkle_light` as `merkle_light::hash::Hashable`.
+//!
+//! Structs hash their fields in declaration order, each with its own
+//! `Hashable`. Enums hash the index of the variant in declaration order as
+//! u32 little endian first, then the fields of the variant. Fields take the
+//! attributes:
+//!
+//! - `#[merkle(skip)]` leaves the field out of the hash;
+//! - `#[merkle(with = "path")]` hashes the field with the function `path`
+//!   of the signature `fn<H: Hasher>(&T, &mut H)` instead.
+//!
+//! ```
+//! use merkle_light::hash::Hashable;
+//! use std::hash::Hasher;
+//!
+//! fn upper<H: Hasher>(s: &String, state: &mut H) {
+//!     s.to_uppercase().hash(state)
+//! }
+//!
+//! #[derive(Hashable)]
+//! struct Account {
+//!     id: u64,
+//!     #[merkle(with = "upper")]
+//!     name: String,
+//!     #[merkle(skip)]
+//!     cached: Option<u64>,
+//! }
+//!
+//! #[derive(Hashable)]
+//! enum Op {
+//!     Open(Account),
+//!     Close { id: u64 },
+//!     Noop,
+//! }
+//! ```
+//!
+//! Unit structs and unions are not supported:
+//!
+//! ```compile_fail
+//! use merkle_light_derive::Hashable;
+//!
+//! #[derive(Hashable)]
+//! struct Unit;
+//! ```
+//!
+//! ```compile_fail
+//! use merkle_light_derive::Hashable;
+//!
+//! #[derive(Hashable)]
+//! union Bits {
+//!     a: u32,
+//!     b: f32,
+//! }
+//! ```
+//!
+//! Nor are the fields which are not `Hashable`, or the unknown attributes:
+//!
+//! ```compile_fail
+//! use merkle_light_derive::Hashable;
+//!
+//! #[derive(Hashable)]
+//! struct Float {
+//!     x: f64,
+//! }
+//! ```
+//!
+//! ```compile_fail
+//! use merkle_light_derive::Hashable;
+//!
+//! #[derive(Hashable)]
+//! struct Unknown {
+//!     #[merkle(rename = "y")]
+//!     x: u64,
+//! }
+//! ```
+
 extern crate proc_macro;
 extern crate syn;
 #[macro_use]
 extern crate quote;
-extern crate merkle_light;
 
 use proc_macro::TokenStream;
 
-#[proc_macro_derive(Hashable)]
+#[proc_macro_derive(Hashable, attributes(merkle))]
 pub fn derive_hashable(input: TokenStream) -> TokenStream {
     let s = input.to_string();
-    let ast = syn::parse_derive_input(&s).unwrap();
+    let ast = match syn::parse_derive_input(&s) {
+        Ok(ast) => ast,
+        Err(_) => panic!("#[derive(Hashable)] is only defined for structs and enums."),
+    };
     let gen = impl_hashable(&ast);
     gen.parse().unwrap()
 }
 
 fn impl_hashable(ast: &syn::DeriveInput) -> quote::Tokens {
-    let body = match ast.body {
-        syn::Body::Struct(ref s) => s,
-        _ => panic!("#[derive(Hashable)] is only defined for structs."),
-    };
+    let name = &ast.ident;
 
-    let stmts: Vec<_> = match *body {
-        syn::VariantData::Struct(ref fields) => {
-            fields.iter().enumerate().map(hash_field_map).collect()
+    let body = match ast.body {
+        syn::Body::Struct(syn::VariantData::Unit) => {
+            panic!("#[derive(Hashable)] is not defined for Unit structs.")
         }
-        syn::VariantData::Tuple(ref fields) => {
-            fields.iter().enumerate().map(hash_field_map).collect()
+        syn::Body::Struct(ref data) => {
+            let (pattern, stmts) = destructure(data);
+            quote! {
+                let #name #pattern = *self;
+                #(#stmts)*
+            }
+        }
+        syn::Body::Enum(ref variants) => {
+            let arms: Vec<_> = variants
+                .iter()
+                .enumerate()
+                .map(|(i, v)| {
+                    let tag = i as u32;
+                    let variant = &v.ident;
+                    let (pattern, stmts) = destructure(&v.data);
+                    quote! {
+                        #name::#variant #pattern => {
+                            Hashable::hash(&#tag, state);
+                            #(#stmts)*
+                        }
+                    }
+                })
+                .collect();
+            quote! {
+                match *self {
+                    #(#arms)*
+                }
+            }
         }
-        syn::VariantData::Unit => panic!("#[derive(Hashable)] is not defined for Unit structs."),
     };
 
-    let name = &ast.ident;
-    let dummy_const = syn::Ident::new(format!("_IMPL_HASHABLE_FOR_{}", name).to_uppercase());
+    // hasher goes last, after the parameters of the type, which all must
+    // be hashable with it
+    let mut generics = ast.generics.clone();
+    let hashable = bound("::merkle_light::hash::Hashable<__H>");
+    for param in &mut generics.ty_params {
+        param.bounds.push(hashable.clone());
+    }
+    let mut hasher = syn::TyParam::from(syn::Ident::new("__H"));
+    hasher.bounds.push(bound("::core::hash::Hasher"));
+    generics.ty_params.push(hasher);
+
+    let (impl_generics, _, _) = generics.split_for_impl();
+    let (_, ty_generics, where_clause) = ast.generics.split_for_impl();
 
     quote! {
-        const #dummy_const: () = {
+        const _: () = {
             extern crate merkle_light;
 
-            use std::hash::Hasher;
             use merkle_light::hash::Hashable;
 
-            impl<H: Hasher> Hashable<H> for #name {
-                fn hash(&self, state: &mut H) {
-                    #(#stmts)*
+            impl #impl_generics Hashable<__H> for #name #ty_generics #where_clause {
+                #[allow(unused_variables)]
+                fn hash(&self, state: &mut __H) {
+                    #body
                 }
             }
         };
     }
 }
 
-fn hash_field_map(tuple: (usize, &syn::Field)) -> quote::Tokens {
-    hash_field(tuple.0, tuple.1)
+fn bound(s: &str) -> syn::TyParamBound {
+    syn::parse_ty_param_bound(s).expect("valid bound")
 }
 
-fn hash_field(index: usize, f: &syn::Field) -> quote::Tokens {
-    let mut ty = f.ty.clone();
+/// Returns the pattern binding the fields by reference, and the statements
+/// hashing them in order.
+fn destructure(data: &syn::VariantData) -> (quote::Tokens, Vec<quote::Tokens>) {
+    let fields = data.fields();
+    let binds: Vec<_> = (0..fields.len())
+        .map(|i| syn::Ident::new(format!("__f{}", i)))
+        .collect();
+    let stmts = fields
+        .iter()
+        .zip(&binds)
+        .filter_map(|(f, bind)| hash_field(f, bind))
+        .collect();
 
-    loop {
-        match ty {
-            syn::Ty::Path(_, ref path) => {
-                path.segments.first().expect(
-                    "there must be at least 1 segment",
-                );
-                break;
-            }
-            syn::Ty::Rptr(_, bty) => {
-                ty = bty.ty.clone();
-            }
-            _ => panic!("hashing not supported: {:?}", ty),
+    let pattern = match *data {
+        syn::VariantData::Struct(_) => {
+            let names = fields.iter().map(|f| &f.ident);
+            quote! { { #(#names: ref #binds),* } }
+        }
+        syn::VariantData::Tuple(_) => quote! { ( #(ref #binds),* ) },
+        syn::VariantData::Unit => quote! {},
+    };
+    (pattern, stmts)
+}
+
+/// Returns the statement hashing the field bound to `bind`, or `None` if
+/// the field is skipped.
+fn hash_field(f: &syn::Field, bind: &syn::Ident) -> Option<quote::Tokens> {
+    let mut with = None;
+
+    for attr in &f.attrs {
+        let items = match attr.value {
+            syn::MetaItem::List(ref ident, ref items) if ident == "merkle" => items,
+            _ => continue,
         };
+        for item in items {
+            match *item {
+                syn::NestedMetaItem::MetaItem(syn::MetaItem::Word(ref w)) if w == "skip" => {
+                    return None
+                }
+                syn::NestedMetaItem::MetaItem(syn::MetaItem::NameValue(
+                    ref w,
+                    syn::Lit::Str(ref path, _),
+                )) if w == "with" => {
+                    let path = syn::parse_path(path)
+                        .unwrap_or_else(|_| panic!("invalid path in #[merkle(with)]: {}", path));
+                    with = Some(path);
+                }
+                _ => panic!("unknown #[merkle] attribute: {}", quote! { #item }),
+            }
+        }
     }
 
-    match f.ident {
-        Some(ref ident) => quote! { self.#ident.hash(state); },
-        None => quote! { self.#index.hash(state); },
-    }
+    Some(match with {
+        Some(path) => quote! { #path(#bind, state); },
+        None => quote! { Hashable::hash(#bind, state); },
+    })
 }
@@ -1,8 +1,5 @@
 extern crate merkle_light;
 
-#[macro_use]
-extern crate merkle_light_derive;
-
 use merkle_light::hash::Hashable;
 
 use std::collections::hash_map::DefaultHasher;
@@ -31,5 +28,81 @@ fn test_foo_hash() {
 
     let hr = &mut DefaultHasher::new();
     foo.hash(hr);
-    assert_eq!(hr.finish(), 8466196983143881409)
+    assert_eq!(hr.finish(), 10831727303769910683)
+}
+
+/// Hasher which records the bytes fed into it.
+#[derive(Default)]
+struct Bytes(Vec<u8>);
+
+impl Hasher for Bytes {
+    fn write(&mut self, msg: &[u8]) {
+        self.0.extend_from_slice(msg)
+    }
+
+    fn finish(&self) -> u64 {
+        unimplemented!()
+    }
+}
+
+fn encode<T: Hashable<Bytes>>(x: &T) -> Vec<u8> {
+    let mut b = Bytes::default();
+    x.hash(&mut b);
+    b.0
+}
+
+fn double<H: Hasher>(x: &u32, state: &mut H) {
+    (x * 2).hash(state)
+}
+
+#[derive(Hashable)]
+struct Attrs {
+    a: u8,
+    #[merkle(skip)]
+    #[allow(dead_code)]
+    b: u64,
+    #[merkle(with = "double")]
+    c: u32,
+}
+
+#[derive(Hashable)]
+struct Pair<'a, T>(T, &'a str);
+
+#[derive(Hashable)]
+enum Shape {
+    Empty,
+    Point(i32, i32),
+    Named { name: String, sides: Option<u8> },
+}
+
+#[test]
+fn test_derive_eq_manual() {
+    let foo = Foo {
+        a: 1,
+        b: 2,
+        c: 3,
+        d: 4,
+        e: String::from("bar"),
+        f: "bar",
+    };
+    let manual = (1u8, 2u16, 3u32, 4u64, "bar", "bar");
+    assert_eq!(encode(&foo), encode(&manual));
+
+    let attrs = Attrs { a: 1, b: 2, c: 3 };
+    assert_eq!(encode(&attrs), encode(&(1u8, 6u32)));
+
+    let pair = Pair(vec![1u16, 2], "x");
+    assert_eq!(encode(&pair), encode(&(vec![1u16, 2], "x")));
+}
+
+#[test]
+fn test_derive_enum() {
+    assert_eq!(encode(&Shape::Empty), [0, 0, 0, 0]);
+    assert_eq!(encode(&Shape::Point(1, -1)), encode(&(1u32, 1i32, -1i32)));
+
+    let named = Shape::Named {
+        name: String::from("square"),
+        sides: Some(4),
+    };
+    assert_eq!(encode(&named), encode(&(2u32, "square", Some(4u8))));
 }
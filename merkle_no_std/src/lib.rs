@@ -0,0 +1,53 @@
+//! Check of `merkle_light` in `no_std` without `alloc`.
+//!
+//! Crate builds for the targets without the standard library and allocator,
+//! such as `thumbv7m-none-eabi`, and verifies the inclusion proofs of the
+//! SHA-256 trees given as slices:
+//!
+//! ```text
+//! cargo build -p merkle_light_no_std --target thumbv7m-none-eabi
+//! ```
+
+#![cfg_attr(not(test), no_std)]
+#![deny(missing_docs, unsafe_code)]
+
+use merkle_light::proof::verify_path;
+use merkle_light::sha256::{Sha256Algorithm, Sha256Hash};
+
+/// Verifies the path from the leaf hash `leaf` up to the trusted root
+/// `root`, of the `siblings` bottom-up, of which `left[i]` tells whether
+/// the path goes through the left child at the level `i`.
+///
+/// Fails unless there are as many `siblings` as `left`.
+pub fn verify(leaf: Sha256Hash, siblings: &[Sha256Hash], left: &[bool], root: &Sha256Hash) -> bool {
+    siblings.len() == left.len()
+        && verify_path::<_, Sha256Algorithm, _>(
+            leaf,
+            siblings.iter().copied().zip(left.iter().copied()),
+            root,
+        )
+}
+
+#[cfg(test)]
+mod tests {
+    use super::verify;
+    use merkle_light::fixed_hash::FixedHash;
+    use merkle_light::hash::Algorithm;
+    use merkle_light::sha256::{Sha256Algorithm, Sha256Hash};
+
+    fn leaf(i: u8) -> Sha256Hash {
+        Sha256Algorithm::new().leaf(FixedHash([i; 32]))
+    }
+
+    #[test]
+    fn test_verify() {
+        // root of the tree over the items `[0; 32]` and `[1; 32]`
+        let root: Sha256Hash = "28fb81e496897e0ce886f08602392e9239b65c659041e5202163e58ad898f444"
+            .parse()
+            .unwrap();
+        assert!(verify(leaf(0), &[leaf(1)], &[true], &root));
+        assert!(verify(leaf(1), &[leaf(0)], &[false], &root));
+        assert!(!verify(leaf(1), &[leaf(0)], &[true], &root));
+        assert!(!verify(leaf(0), &[leaf(1)], &[], &root));
+    }
+}